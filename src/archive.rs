@@ -0,0 +1,84 @@
+//! Support for archiving generated types with rkyv.
+//!
+//! Plain fields are archived natively. SurrealDB-specific types (record ids, datetimes,
+//! geometries, untyped values) are archived as their JSON representation through [AsJson].
+
+use rkyv::{
+    rancor::{Fallible, Source},
+    string::{ArchivedString, StringResolver},
+    with::{ArchiveWith, DeserializeWith, SerializeWith},
+    Place, SerializeUnsized,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// An rkyv wrapper that archives any serde type as a JSON string.
+pub struct AsJson;
+
+impl<T: Serialize> ArchiveWith<T> for AsJson {
+    type Archived = ArchivedString;
+    type Resolver = StringResolver;
+
+    fn resolve_with(field: &T, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        // Serialization already succeeded, and produces the same output again.
+        let json = serde_json::to_string(field).expect("field serialized to JSON during archiving");
+        ArchivedString::resolve_from_str(&json, resolver, out);
+    }
+}
+
+impl<T, S> SerializeWith<T, S> for AsJson
+where
+    T: Serialize,
+    S: Fallible + ?Sized,
+    S::Error: Source,
+    str: SerializeUnsized<S>,
+{
+    fn serialize_with(field: &T, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        let json = serde_json::to_string(field).map_err(S::Error::new)?;
+        ArchivedString::serialize_from_str(&json, serializer)
+    }
+}
+
+impl<T, D> DeserializeWith<ArchivedString, T, D> for AsJson
+where
+    T: DeserializeOwned,
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(field: &ArchivedString, _: &mut D) -> Result<T, D::Error> {
+        serde_json::from_str(field.as_str()).map_err(D::Error::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DateTime;
+    use crate::RecordId;
+
+    #[derive(Debug, PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+    struct Row {
+        name: String,
+        #[rkyv(with = AsJson)]
+        created: DateTime,
+        #[rkyv(with = rkyv::with::Map<AsJson>)]
+        author: Option<RecordId<()>>,
+    }
+
+    #[test]
+    fn archives_surreal_types_as_json() {
+        let row = Row {
+            name: "Ann".to_string(),
+            created: "2024-02-29T12:30:00Z".parse().unwrap(),
+            author: Some(RecordId::new("user", "ann")),
+        };
+
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&row).unwrap();
+        let archived = rkyv::access::<ArchivedRow, rkyv::rancor::Error>(&bytes).unwrap();
+        assert_eq!(archived.name.as_str(), "Ann");
+        assert_eq!(archived.created.as_str(), "\"2024-02-29T12:30:00Z\"");
+        assert_eq!(
+            rkyv::from_bytes::<Row, rkyv::rancor::Error>(&bytes).unwrap(),
+            row
+        );
+    }
+}
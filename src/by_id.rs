@@ -0,0 +1,70 @@
+//! Indexing results by their record ids, e.g. to keep live data in a frontend's state.
+//!
+//! Generated result structs with an `id` field implement [HasId], so their results can be
+//! turned into a map with `results.into_by_id()`.
+
+use std::{collections::HashMap, hash::Hash};
+
+/// A result that carries the id of the record it was selected from.
+pub trait HasId {
+    /// The typed id, e.g. `AdultUsersId`.
+    type Id: Clone + Eq + Hash;
+
+    fn id(&self) -> &Self::Id;
+}
+
+/// Results keyed by their record ids.
+pub type ById<T> = HashMap<<T as HasId>::Id, T>;
+
+/// Collects results into a [ById] map.
+pub trait IntoById<T: HasId> {
+    /// Keys each result by its id. If several results share an id, the last one is kept, so
+    /// applying updates in order leaves the latest version of each record.
+    fn into_by_id(self) -> ById<T>;
+}
+
+impl<T: HasId, I: IntoIterator<Item = T>> IntoById<T> for I {
+    fn into_by_id(self) -> ById<T> {
+        self.into_iter()
+            .map(|result| (result.id().clone(), result))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct User {
+        id: String,
+        name: &'static str,
+    }
+
+    impl HasId for User {
+        type Id = String;
+
+        fn id(&self) -> &String {
+            &self.id
+        }
+    }
+
+    #[test]
+    fn keeps_latest_result_per_id() {
+        let user = |id: &str, name| User {
+            id: id.to_string(),
+            name,
+        };
+        let users = vec![
+            user("user:1", "Ann"),
+            user("user:2", "Bob"),
+            user("user:1", "Anna"),
+        ];
+
+        let by_id = users.into_by_id();
+
+        assert_eq!(by_id.len(), 2);
+        assert_eq!(by_id["user:1"].name, "Anna");
+        assert_eq!(by_id["user:2"].name, "Bob");
+    }
+}
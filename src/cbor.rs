@@ -0,0 +1,252 @@
+//! Decoding of SurrealDB's CBOR protocol into generated types.
+//!
+//! SurrealDB tags values that JSON has no representation for (record ids, datetimes, uuids...).
+//! Those tags are first rewritten into the forms the runtime types accept from JSON, so generated
+//! types deserialize the same from either protocol.
+
+use ciborium::Value;
+use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
+use thiserror::Error;
+
+use crate::types::{DateTime, Duration, RecordId, RecordKey};
+
+#[derive(Error, Debug)]
+pub enum CborError {
+    #[error("Failed to decode CBOR: {0}")]
+    Decode(String),
+    #[error("Invalid value for CBOR tag {0}: {1:?}")]
+    InvalidTag(u64, Value),
+    #[error("Failed to deserialize CBOR value: {0}")]
+    Deserialize(String),
+}
+
+const TAG_DATETIME: u64 = 0;
+const TAG_NONE: u64 = 6;
+const TAG_TABLE: u64 = 7;
+const TAG_RECORD_ID: u64 = 8;
+const TAG_UUID_STRING: u64 = 9;
+const TAG_DECIMAL: u64 = 10;
+const TAG_DATETIME_COMPACT: u64 = 12;
+const TAG_DURATION: u64 = 13;
+const TAG_DURATION_COMPACT: u64 = 14;
+const TAG_UUID: u64 = 37;
+const TAG_GEOMETRY_POINT: u64 = 88;
+const TAG_GEOMETRY_COLLECTION: u64 = 94;
+
+/// Decodes a CBOR document, e.g. the `result` of a query response, into `T`.
+pub fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CborError> {
+    let value: Value =
+        ciborium::from_reader(bytes).map_err(|e| CborError::Decode(e.to_string()))?;
+    from_value(value)
+}
+
+/// Deserializes an already decoded CBOR value into `T`.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, CborError> {
+    normalize(value)?
+        .deserialized()
+        .map_err(|e| CborError::Deserialize(e.to_string()))
+}
+
+/// Rewrites SurrealDB's tagged values into their untagged JSON-compatible forms.
+fn normalize(value: Value) -> Result<Value, CborError> {
+    match value {
+        Value::Tag(tag, inner) => normalize_tag(tag, *inner),
+        Value::Array(items) => Ok(Value::Array(
+            items.into_iter().map(normalize).collect::<Result<_, _>>()?,
+        )),
+        Value::Map(entries) => Ok(Value::Map(
+            entries
+                .into_iter()
+                .map(|(k, v)| Ok((normalize(k)?, normalize(v)?)))
+                .collect::<Result<_, CborError>>()?,
+        )),
+        value => Ok(value),
+    }
+}
+
+fn normalize_tag(tag: u64, inner: Value) -> Result<Value, CborError> {
+    let invalid = |inner: Value| CborError::InvalidTag(tag, inner);
+
+    match (tag, inner) {
+        (TAG_NONE, _) => Ok(Value::Null),
+        (TAG_DATETIME | TAG_TABLE | TAG_DURATION, Value::Text(s)) => Ok(Value::Text(s)),
+        (TAG_DECIMAL, Value::Text(s)) => match s.parse() {
+            Ok(n) => Ok(Value::Float(n)),
+            Err(_) => Err(invalid(Value::Text(s))),
+        },
+        // uuids are decoded from their bytes, as serde formats that aren't human readable expect.
+        (TAG_UUID, Value::Bytes(bytes)) => Ok(Value::Bytes(bytes)),
+        (TAG_UUID_STRING, Value::Text(s)) => match uuid::Uuid::parse_str(&s) {
+            Ok(uuid) => Ok(Value::Bytes(uuid.as_bytes().to_vec())),
+            Err(_) => Err(invalid(Value::Text(s))),
+        },
+        (TAG_DATETIME_COMPACT, inner) => {
+            let [secs, nanos] = compact_parts(&inner).ok_or_else(|| invalid(inner.clone()))?;
+            let datetime = u32::try_from(nanos)
+                .ok()
+                .and_then(|nanos| DateTime::from_timestamp(i64::try_from(secs).ok()?, nanos))
+                .ok_or_else(|| invalid(inner))?;
+            Ok(Value::Text(datetime.to_string()))
+        }
+        (TAG_DURATION_COMPACT, inner) => {
+            let [secs, nanos] = compact_parts(&inner).ok_or_else(|| invalid(inner.clone()))?;
+            match (u64::try_from(secs), u32::try_from(nanos)) {
+                (Ok(secs), Ok(nanos)) => Ok(Value::Text(
+                    Duration(std::time::Duration::new(secs, nanos)).to_string(),
+                )),
+                _ => Err(invalid(inner)),
+            }
+        }
+        (TAG_RECORD_ID, Value::Text(s)) => Ok(Value::Text(s)),
+        (TAG_RECORD_ID, Value::Array(parts)) => match <[Value; 2]>::try_from(parts) {
+            Ok([table, key]) => {
+                let table = match normalize(table)? {
+                    Value::Text(table) => table,
+                    table => return Err(invalid(table)),
+                };
+                let key = record_key(key).map_err(invalid)?;
+                Ok(Value::Text(RecordId::<()>::new(table, key).to_string()))
+            }
+            Err(parts) => Err(invalid(Value::Array(parts))),
+        },
+        (TAG_GEOMETRY_POINT..=TAG_GEOMETRY_COLLECTION, inner) => geometry(tag, inner),
+        // Tags with no special meaning to SurrealDB carry their value unchanged.
+        (_, inner) => normalize(inner),
+    }
+}
+
+/// Reads the `[seconds, nanoseconds]` of a compact datetime or duration, either of which may be omitted.
+fn compact_parts(value: &Value) -> Option<[i128; 2]> {
+    let Value::Array(parts) = value else {
+        return None;
+    };
+    let mut ints = parts.iter().map(|part| part.as_integer().map(i128::from));
+    let secs = ints.next().flatten().unwrap_or(0);
+    let nanos = ints.next().flatten().unwrap_or(0);
+    Some([secs, nanos])
+}
+
+fn record_key(key: Value) -> Result<RecordKey, Value> {
+    match key {
+        Value::Tag(TAG_UUID, inner) => {
+            let uuid = match inner.as_ref() {
+                Value::Bytes(bytes) => uuid::Uuid::from_slice(bytes).ok(),
+                _ => None,
+            };
+            uuid.map(RecordKey::from).ok_or(Value::Tag(TAG_UUID, inner))
+        }
+        Value::Tag(TAG_UUID_STRING, inner) => match *inner {
+            Value::Text(s) => Ok(RecordKey::String(s)),
+            other => Err(Value::Tag(TAG_UUID_STRING, Box::new(other))),
+        },
+        Value::Text(s) => Ok(RecordKey::String(s)),
+        Value::Integer(n) => i64::try_from(n)
+            .map(RecordKey::Number)
+            .map_err(|_| Value::Integer(n)),
+        key => {
+            let normalized = normalize(key.clone()).map_err(|_| key.clone())?;
+            match normalized.deserialized::<JsonValue>() {
+                Ok(JsonValue::Array(arr)) => Ok(RecordKey::Array(arr)),
+                Ok(JsonValue::Object(obj)) => Ok(RecordKey::Object(obj)),
+                _ => Err(key),
+            }
+        }
+    }
+}
+
+/// Converts a tagged geometry into GeoJSON, which is how geometries arrive over JSON.
+fn geometry(tag: u64, inner: Value) -> Result<Value, CborError> {
+    let text = |s: &str| Value::Text(s.to_string());
+
+    if tag == TAG_GEOMETRY_COLLECTION {
+        return Ok(Value::Map(vec![
+            (text("type"), text("GeometryCollection")),
+            (text("geometries"), normalize(inner)?),
+        ]));
+    }
+
+    let kind = match tag - TAG_GEOMETRY_POINT {
+        0 => "Point",
+        1 => "LineString",
+        2 => "Polygon",
+        3 => "MultiPoint",
+        4 => "MultiLineString",
+        _ => "MultiPolygon",
+    };
+    Ok(Value::Map(vec![
+        (text("type"), text(kind)),
+        (text("coordinates"), coordinates(inner)?),
+    ]))
+}
+
+/// Strips the geometry tags from nested coordinates, e.g. the points making up a line.
+fn coordinates(value: Value) -> Result<Value, CborError> {
+    match value {
+        Value::Tag(TAG_GEOMETRY_POINT..=TAG_GEOMETRY_COLLECTION, inner) => coordinates(*inner),
+        Value::Tag(TAG_DECIMAL, inner) => normalize_tag(TAG_DECIMAL, *inner),
+        Value::Array(items) => Ok(Value::Array(
+            items
+                .into_iter()
+                .map(coordinates)
+                .collect::<Result<_, _>>()?,
+        )),
+        value => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Geometry;
+
+    fn tagged(tag: u64, value: Value) -> Value {
+        Value::Tag(tag, Box::new(value))
+    }
+
+    #[test]
+    fn decodes_tagged_values() {
+        let id: RecordId<()> = from_value(tagged(
+            TAG_RECORD_ID,
+            Value::Array(vec![Value::Text("user".into()), Value::Integer(1.into())]),
+        ))
+        .unwrap();
+        assert_eq!(id, RecordId::new("user", 1i64));
+
+        let datetime: DateTime = from_value(tagged(
+            TAG_DATETIME_COMPACT,
+            Value::Array(vec![
+                Value::Integer(0.into()),
+                Value::Integer(5_000_000.into()),
+            ]),
+        ))
+        .unwrap();
+        assert_eq!(datetime, DateTime::from_timestamp(0, 5_000_000).unwrap());
+
+        let duration: Duration = from_value(tagged(
+            TAG_DURATION_COMPACT,
+            Value::Array(vec![Value::Integer(90.into())]),
+        ))
+        .unwrap();
+        assert_eq!(duration, Duration::secs(90));
+
+        let none: Option<String> = from_value(tagged(TAG_NONE, Value::Null)).unwrap();
+        assert_eq!(none, None);
+    }
+
+    #[test]
+    fn decodes_geometry() {
+        let point = |x: f64, y: f64| {
+            tagged(
+                TAG_GEOMETRY_POINT,
+                Value::Array(vec![Value::Float(x), Value::Float(y)]),
+            )
+        };
+        let line: Geometry = from_value(tagged(
+            TAG_GEOMETRY_POINT + 1,
+            Value::Array(vec![point(0.0, 0.0), point(1.0, 1.0)]),
+        ))
+        .unwrap();
+        assert!(matches!(line, Geometry::Line(points) if points.len() == 2));
+    }
+}
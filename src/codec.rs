@@ -0,0 +1,130 @@
+//! Hooks transforming fields between their Rust value and the form stored in the database, e.g.
+//! to encrypt sensitive columns client-side, for fields marked with `build_query!`'s
+//! `encrypt(...)` or `transform(...)` options.
+
+use std::fmt::Display;
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Converts a field between the value generated types hold and the value the database stores.
+///
+/// ```ignore
+/// struct Sealed;
+///
+/// impl FieldCodec for Sealed {
+///     type Value = String;
+///     type Stored = String;
+///     type Error = CryptoError;
+///
+///     fn encode(value: &String) -> Result<String, CryptoError> { encrypt(value) }
+///     fn decode(stored: String) -> Result<String, CryptoError> { decrypt(&stored) }
+/// }
+///
+/// build_query!(GetUser, encrypt(ssn) = "crate::Sealed", "SELECT * FROM user");
+/// ```
+pub trait FieldCodec {
+    /// The type of the field in generated types.
+    type Value;
+    /// The type of the field as stored, matching its type in the schema.
+    type Stored: Serialize + DeserializeOwned;
+    type Error: Display;
+
+    fn encode(value: &Self::Value) -> Result<Self::Stored, Self::Error>;
+    fn decode(stored: Self::Stored) -> Result<Self::Value, Self::Error>;
+}
+
+/// Serializes a field through its codec, for `#[serde(serialize_with)]`.
+pub fn serialize<C: FieldCodec, S: Serializer>(
+    value: &C::Value,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    C::encode(value)
+        .map_err(serde::ser::Error::custom)?
+        .serialize(serializer)
+}
+
+/// Deserializes a field through its codec, for `#[serde(deserialize_with)]`.
+pub fn deserialize<'de, C: FieldCodec, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<C::Value, D::Error> {
+    C::decode(C::Stored::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+}
+
+/// [serialize] for an optional field, leaving NONE as it is.
+pub fn serialize_option<C: FieldCodec, S: Serializer>(
+    value: &Option<C::Value>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    value
+        .as_ref()
+        .map(C::encode)
+        .transpose()
+        .map_err(serde::ser::Error::custom)?
+        .serialize(serializer)
+}
+
+/// [deserialize] for an optional field, leaving NONE as it is.
+pub fn deserialize_option<'de, C: FieldCodec, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<C::Value>, D::Error> {
+    Option::<C::Stored>::deserialize(deserializer)?
+        .map(C::decode)
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reverses strings, standing in for encryption.
+    struct Reversed;
+
+    impl FieldCodec for Reversed {
+        type Value = String;
+        type Stored = String;
+        type Error = &'static str;
+
+        fn encode(value: &String) -> Result<String, &'static str> {
+            Ok(value.chars().rev().collect())
+        }
+
+        fn decode(stored: String) -> Result<String, &'static str> {
+            match stored.is_empty() {
+                true => Err("Nothing to decode"),
+                false => Ok(stored.chars().rev().collect()),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct User {
+        #[serde(
+            serialize_with = "serialize::<Reversed, _>",
+            deserialize_with = "deserialize::<Reversed, _>"
+        )]
+        ssn: String,
+        #[serde(
+            default,
+            serialize_with = "serialize_option::<Reversed, _>",
+            deserialize_with = "deserialize_option::<Reversed, _>"
+        )]
+        card: Option<String>,
+    }
+
+    #[test]
+    fn fields_pass_through_their_codec() {
+        let user = User {
+            ssn: "123".to_string(),
+            card: None,
+        };
+
+        let json = serde_json::to_value(&user).unwrap();
+        assert_eq!(json, serde_json::json!({ "ssn": "321", "card": null }));
+        assert_eq!(serde_json::from_value::<User>(json).unwrap(), user);
+
+        let user: User = serde_json::from_value(serde_json::json!({ "ssn": "321" })).unwrap();
+        assert_eq!(user.card, None);
+        assert!(serde_json::from_value::<User>(serde_json::json!({ "ssn": "" })).is_err());
+    }
+}
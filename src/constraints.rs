@@ -0,0 +1,61 @@
+//! Client-side checks backing the validating newtypes generated from ASSERT clauses.
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Value does not satisfy the schema assertion: {0}")]
+pub struct ConstraintError(pub String);
+
+/// Mirrors SurrealDB's `string::is::*` functions.
+///
+/// Checks that can't be reproduced faithfully client-side pass, leaving the final say to the database.
+pub fn string_is(check: &str, value: &str) -> bool {
+    match check {
+        "alphanum" => value.chars().all(char::is_alphanumeric),
+        "alpha" => value.chars().all(char::is_alphabetic),
+        "ascii" => value.is_ascii(),
+        "numeric" => !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()),
+        "hexadecimal" => !value.is_empty() && value.chars().all(|c| c.is_ascii_hexdigit()),
+        "uuid" => uuid::Uuid::parse_str(value).is_ok(),
+        "datetime" => chrono::DateTime::parse_from_rfc3339(value).is_ok(),
+        "latitude" => value
+            .parse::<f64>()
+            .map_or(false, |v| (-90.0..=90.0).contains(&v)),
+        "longitude" => value
+            .parse::<f64>()
+            .map_or(false, |v| (-180.0..=180.0).contains(&v)),
+        "semver" => is_semver(value),
+        "domain" => is_domain(value),
+        "email" => value.split_once('@').map_or(false, |(local, domain)| {
+            !local.is_empty() && !local.contains(char::is_whitespace) && is_domain(domain)
+        }),
+        "url" => value.split_once("://").map_or(false, |(scheme, rest)| {
+            !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+                && !rest.is_empty()
+                && !rest.contains(char::is_whitespace)
+        }),
+        _ => true,
+    }
+}
+
+fn is_domain(value: &str) -> bool {
+    let labels: Vec<_> = value.split('.').collect();
+    labels.len() > 1
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+}
+
+fn is_semver(value: &str) -> bool {
+    let core = value.split(['-', '+']).next().unwrap_or_default();
+    let parts: Vec<_> = core.split('.').collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+}
@@ -0,0 +1,175 @@
+//! Decoding query results into generated types, with errors that say where decoding failed.
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use serde_path_to_error::Segment;
+use thiserror::Error;
+
+/// How much of the offending value a `DecodeError` quotes.
+const SNIPPET_LEN: usize = 80;
+
+/// A result that doesn't match the type generated for it, e.g. because the schema changed
+/// since the code was generated.
+#[derive(Error, Debug)]
+#[error("Failed to decode {type_name} at '{pointer}': {source} (found {snippet})")]
+pub struct DecodeError {
+    /// The JSON pointer to the offending value within the result, e.g. `/0/address/city`.
+    pub pointer: String,
+    /// The type being decoded, e.g. `my_app::adult_users::User`.
+    pub type_name: &'static str,
+    /// The offending value as JSON, cut short if it is long.
+    pub snippet: String,
+    #[source]
+    pub source: serde_json::Error,
+}
+
+pub fn decode<T: DeserializeOwned>(value: Value) -> Result<T, DecodeError> {
+    serde_path_to_error::deserialize(&value).map_err(|err| {
+        let pointer = pointer(err.path());
+        let offending = value.pointer(&pointer).unwrap_or(&value);
+        DecodeError {
+            snippet: snippet(offending),
+            pointer,
+            type_name: std::any::type_name::<T>(),
+            source: err.into_inner(),
+        }
+    })
+}
+
+/// A schema DEFAULT, embedded as JSON by `build_query!`'s `lenient_defaults` option.
+///
+/// Fails if the default doesn't match the type of its field, which the database would reject
+/// too; the generated types report this as an error deserializing the row.
+pub fn schema_default<T: DeserializeOwned>(json: &str) -> Result<T, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+/// The table of a row of a SELECT from several tables, from its `id`, which `build_query!`
+/// decodes the row by, as rows of tables with the same fields decode as either.
+pub fn row_table(row: &Value) -> Result<String, serde_json::Error> {
+    let id = row
+        .get("id")
+        .ok_or_else(|| serde::de::Error::missing_field("id"))?;
+    Ok(crate::RecordId::<()>::deserialize(id)?.table().to_string())
+}
+
+fn pointer(path: &serde_path_to_error::Path) -> String {
+    path.iter()
+        .map(|segment| match segment {
+            Segment::Seq { index } => format!("/{}", index),
+            Segment::Map { key } => format!("/{}", key.replace('~', "~0").replace('/', "~1")),
+            Segment::Enum { .. } | Segment::Unknown => String::new(),
+        })
+        .collect()
+}
+
+fn snippet(value: &Value) -> String {
+    let json = value.to_string();
+    match json.char_indices().nth(SNIPPET_LEN) {
+        Some((end, _)) => format!("{}…", &json[..end]),
+        None => json,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Address {
+        city: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct User {
+        name: String,
+        address: Address,
+    }
+
+    #[test]
+    fn reports_where_decoding_failed() {
+        let rows = json!([
+            { "name": "Ann", "address": { "city": "Oslo" } },
+            { "name": "Bob", "address": { "city": null } },
+        ]);
+
+        let err = decode::<Vec<User>>(rows).unwrap_err();
+
+        assert_eq!(err.pointer, "/1/address/city");
+        assert_eq!(err.snippet, "null");
+        assert!(err
+            .type_name
+            .ends_with("Vec<surrealix::decode::tests::User>"));
+        assert!(err.to_string().contains("invalid type: null"));
+    }
+
+    #[test]
+    fn decodes_schema_defaults() {
+        let tags: Vec<String> = schema_default(r#"["new","unverified"]"#).unwrap();
+        assert_eq!(tags, vec!["new", "unverified"]);
+        assert!(schema_default::<u32>(r#""soon""#).is_err());
+    }
+
+    /// A row of `SELECT id, name FROM user, post`, as `build_query!` generates it.
+    #[derive(Debug)]
+    enum UserOrPost {
+        User(Named),
+        Post(Named),
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Named {
+        id: crate::RecordId<()>,
+        name: String,
+    }
+
+    impl UserOrPost {
+        fn table(&self) -> &'static str {
+            match self {
+                Self::User(_) => "user",
+                Self::Post(_) => "post",
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for UserOrPost {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let row = <Value as Deserialize>::deserialize(deserializer)?;
+            let decoded = match row_table(&row).map_err(serde::de::Error::custom)?.as_str() {
+                "user" => serde_json::from_value(row).map(Self::User),
+                "post" => serde_json::from_value(row).map(Self::Post),
+                table => {
+                    return Err(serde::de::Error::custom(format_args!(
+                        "a row of the unexpected table '{}'",
+                        table
+                    )))
+                }
+            };
+            decoded.map_err(serde::de::Error::custom)
+        }
+    }
+
+    #[test]
+    fn decodes_rows_by_their_table() {
+        let rows = json!([
+            { "id": "post:first", "name": "Hello" },
+            { "id": { "tb": "user", "id": { "String": "ann" } }, "name": "Ann" },
+        ]);
+
+        let rows = decode::<Vec<UserOrPost>>(rows).unwrap();
+        assert_eq!(rows[0].table(), "post");
+        assert_eq!(rows[1].table(), "user");
+        assert!(decode::<UserOrPost>(json!({ "id": "tag:rust", "name": "Rust" })).is_err());
+        assert!(decode::<UserOrPost>(json!({ "name": "Ann" })).is_err());
+    }
+
+    #[test]
+    fn cuts_long_snippets() {
+        let long = Value::String("x".repeat(200));
+        assert_eq!(snippet(&long).chars().count(), SNIPPET_LEN + 1);
+    }
+}
@@ -0,0 +1,195 @@
+//! Field-level diffs between two results of the same query.
+//!
+//! Records are matched by their `id`, so reordered results don't show up as changes, and each
+//! change is reported relative to the record it belongs to.
+
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+use std::fmt;
+
+/// A single field that differs between two results.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// The id of the record containing the field, if it is within one.
+    pub id: Option<String>,
+    /// Dotted path of the field within its record, e.g. `address.city` or `tags[2]`.
+    /// Empty when a whole record was added or removed.
+    pub path: String,
+    /// The previous value, or `None` if the field was added.
+    pub old: Option<JsonValue>,
+    /// The new value, or `None` if the field was removed.
+    pub new: Option<JsonValue>,
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |value: &Option<JsonValue>| match value {
+            Some(value) => value.to_string(),
+            None => "(none)".to_string(),
+        };
+        match &self.id {
+            Some(id) => write!(f, "{} {}", id, self.path)?,
+            None => write!(f, "{}", self.path)?,
+        }
+        write!(f, ": {} -> {}", show(&self.old), show(&self.new))
+    }
+}
+
+/// Lists the fields that changed from `old` to `new`.
+///
+/// Values that can't be serialized are treated as `null`.
+pub fn diff<T: Serialize + ?Sized>(old: &T, new: &T) -> Vec<FieldChange> {
+    let old = serde_json::to_value(old).unwrap_or_default();
+    let new = serde_json::to_value(new).unwrap_or_default();
+
+    let mut changes = vec![];
+    diff_value(None, String::new(), &old, &new, &mut changes);
+    changes
+}
+
+fn record_id(value: &JsonValue) -> Option<&str> {
+    value.as_object()?.get("id")?.as_str()
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn diff_value(
+    id: Option<&str>,
+    path: String,
+    old: &JsonValue,
+    new: &JsonValue,
+    changes: &mut Vec<FieldChange>,
+) {
+    match (old, new) {
+        (JsonValue::Object(old_obj), JsonValue::Object(new_obj)) => {
+            // Entering a record restarts the path, so changes are relative to that record.
+            let (id, path) = match (record_id(old), record_id(new)) {
+                (Some(old_id), Some(new_id)) if old_id == new_id => (Some(old_id), String::new()),
+                _ => (id, path),
+            };
+            diff_objects(id, &path, old_obj, new_obj, changes);
+        }
+        (JsonValue::Array(old_items), JsonValue::Array(new_items))
+            if is_records(old_items) && is_records(new_items) =>
+        {
+            diff_records(old_items, new_items, changes);
+        }
+        (JsonValue::Array(old_items), JsonValue::Array(new_items))
+            if old_items.len() == new_items.len() =>
+        {
+            for (i, (old, new)) in old_items.iter().zip(new_items).enumerate() {
+                diff_value(id, format!("{}[{}]", path, i), old, new, changes);
+            }
+        }
+        _ if old != new => changes.push(FieldChange {
+            id: id.map(str::to_string),
+            path,
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+fn diff_objects(
+    id: Option<&str>,
+    path: &str,
+    old: &Map<String, JsonValue>,
+    new: &Map<String, JsonValue>,
+    changes: &mut Vec<FieldChange>,
+) {
+    let mut keys: Vec<_> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    for key in keys {
+        let field_path = join(path, key);
+        match (old.get(key), new.get(key)) {
+            (Some(old), Some(new)) => diff_value(id, field_path, old, new, changes),
+            (old, new) => changes.push(FieldChange {
+                id: id.map(str::to_string),
+                path: field_path,
+                old: old.cloned(),
+                new: new.cloned(),
+            }),
+        }
+    }
+}
+
+fn is_records(items: &[JsonValue]) -> bool {
+    !items.is_empty() && items.iter().all(|item| record_id(item).is_some())
+}
+
+/// Matches records by id, reporting removed records, then changed, then added.
+fn diff_records(old: &[JsonValue], new: &[JsonValue], changes: &mut Vec<FieldChange>) {
+    let whole = |id: &str, old: Option<&JsonValue>, new: Option<&JsonValue>| FieldChange {
+        id: Some(id.to_string()),
+        path: String::new(),
+        old: old.cloned(),
+        new: new.cloned(),
+    };
+
+    for old_record in old {
+        let id = record_id(old_record).unwrap_or_default();
+        match new
+            .iter()
+            .find(|new_record| record_id(new_record) == Some(id))
+        {
+            Some(new_record) => {
+                diff_value(Some(id), String::new(), old_record, new_record, changes)
+            }
+            None => changes.push(whole(id, Some(old_record), None)),
+        }
+    }
+
+    for new_record in new {
+        let id = record_id(new_record).unwrap_or_default();
+        if !old
+            .iter()
+            .any(|old_record| record_id(old_record) == Some(id))
+        {
+            changes.push(whole(id, None, Some(new_record)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diffs_records_by_id() {
+        let old = json!([
+            { "id": "user:1", "name": "Ann", "address": { "city": "Oslo" } },
+            { "id": "user:2", "name": "Bob", "address": { "city": "Rome" } },
+        ]);
+        let new = json!([
+            { "id": "user:3", "name": "Cid", "address": { "city": "Lima" } },
+            { "id": "user:1", "name": "Ann", "address": { "city": "Bergen" } },
+        ]);
+
+        let changes = diff(&old, &new);
+
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].id.as_deref(), Some("user:1"));
+        assert_eq!(changes[0].path, "address.city");
+        assert_eq!(changes[0].new, Some(json!("Bergen")));
+        assert_eq!(changes[1].id.as_deref(), Some("user:2"));
+        assert_eq!(changes[1].new, None);
+        assert_eq!(changes[2].id.as_deref(), Some("user:3"));
+        assert_eq!(changes[2].old, None);
+    }
+
+    #[test]
+    fn identical_results_have_no_changes() {
+        let value = json!({ "id": "user:1", "tags": ["a", "b"] });
+        assert!(diff(&value, &value).is_empty());
+    }
+}
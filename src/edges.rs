@@ -0,0 +1,20 @@
+//! Graph traversals selected with the `with_edges` option of `build_query!`, which return each
+//! edge crossed along with the record it leads to.
+
+use serde::{Deserialize, Serialize};
+
+/// An edge record, e.g. a `friend` holding `since`, and the node it leads to.
+///
+/// With `with_edges`, `->friend->user.*` is typed as `Vec<EdgeAndNode<Friend, User>>`, and
+/// `->friend->user`, which only selects the users' ids, as
+/// `Vec<EdgeAndNode<Friend, RecordLink<User>>>`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct EdgeAndNode<E, N> {
+    pub edge: E,
+    pub node: N,
+}
@@ -0,0 +1,167 @@
+//! Running generated queries against a client, bounded by a deadline or a cancellation token.
+
+use std::{future::Future, time::Duration};
+
+use thiserror::Error;
+pub use tokio::time::Instant;
+pub use tokio_util::sync::CancellationToken;
+
+use crate::decode::{decode, DecodeError};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Database error: {0}")]
+    Database(#[from] surrealdb::Error),
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+    /// The query was cancelled by its token or ran past its deadline.
+    #[error("The query was cancelled before it completed")]
+    Cancelled,
+}
+
+/// The point in time by which a query has to complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    pub fn at(instant: Instant) -> Self {
+        Self(instant)
+    }
+
+    pub fn after(timeout: Duration) -> Self {
+        Self(Instant::now() + timeout)
+    }
+
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    pub fn has_passed(&self) -> bool {
+        Instant::now() >= self.0
+    }
+}
+
+/// What may cut a generated `execute` short. The default lets the query run to completion.
+#[derive(Debug, Clone, Default)]
+pub struct ExecuteOptions {
+    pub deadline: Option<Deadline>,
+    pub cancellation: Option<CancellationToken>,
+    /// The most rows `execute_limited` returns. The query asks the database for one more, to
+    /// tell whether there were more rows than that.
+    pub max_rows: Option<usize>,
+}
+
+impl ExecuteOptions {
+    pub fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Deadline::after(timeout))
+    }
+
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    pub fn with_max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = Some(max_rows);
+        self
+    }
+}
+
+/// The rows of a result capped at `ExecuteOptions::max_rows`, and whether any were left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Truncated<T> {
+    pub rows: Vec<T>,
+    pub truncated: bool,
+}
+
+impl From<Deadline> for ExecuteOptions {
+    fn from(deadline: Deadline) -> Self {
+        Self::default().with_deadline(deadline)
+    }
+}
+
+impl From<CancellationToken> for ExecuteOptions {
+    fn from(token: CancellationToken) -> Self {
+        Self::default().with_cancellation(token)
+    }
+}
+
+/// Runs `query` until it completes, its token is cancelled or its deadline passes.
+///
+/// A cancelled query's future is dropped, which drops its request to the client; the client
+/// doesn't wait for the response, though the server may still finish running the query.
+pub async fn run<T, F>(options: &ExecuteOptions, query: F) -> Result<T, Error>
+where
+    F: Future<Output = Result<T, Error>>,
+{
+    let cancelled = async {
+        match &options.cancellation {
+            Some(token) => token.cancelled().await,
+            None => std::future::pending().await,
+        }
+    };
+    let expired = async {
+        match options.deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline.instant()).await,
+            None => std::future::pending().await,
+        }
+    };
+
+    tokio::select! {
+        biased;
+        _ = cancelled => Err(Error::Cancelled),
+        _ = expired => Err(Error::Cancelled),
+        result = query => result,
+    }
+}
+
+/// Takes the result of the statement at `index` from a response, decoded as `T`.
+///
+/// Results are decoded from their JSON form, in which record ids are `table:key` strings. A
+/// result that doesn't match `T` is reported with the path to the offending value.
+pub fn take<T: serde::de::DeserializeOwned>(
+    response: &mut surrealdb::Response,
+    index: usize,
+) -> Result<T, Error> {
+    let value: surrealdb::sql::Value = response.take(index)?;
+    Ok(decode(value.into_json())?)
+}
+
+/// Takes the rows of the statement at `index`, keeping at most `max_rows` of them.
+pub fn take_rows<T: serde::de::DeserializeOwned>(
+    response: &mut surrealdb::Response,
+    index: usize,
+    max_rows: Option<usize>,
+) -> Result<Truncated<T>, Error> {
+    let mut rows: Vec<T> = take(response, index)?;
+    let truncated = max_rows.map_or(false, |max| rows.len() > max);
+    if let Some(max) = max_rows {
+        rows.truncate(max);
+    }
+    Ok(Truncated { rows, truncated })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cancels_queries() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = run(&token.into(), async { Ok(1) }).await;
+        assert!(matches!(result, Err(Error::Cancelled)));
+
+        let options = ExecuteOptions::default().with_timeout(Duration::from_millis(10));
+        let result: Result<(), _> = run(&options, std::future::pending()).await;
+        assert!(matches!(result, Err(Error::Cancelled)));
+
+        let options = ExecuteOptions::default().with_timeout(Duration::from_secs(60));
+        assert_eq!(run(&options, async { Ok(1) }).await.unwrap(), 1);
+    }
+}
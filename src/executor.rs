@@ -0,0 +1,109 @@
+//! Limits on how many queries run on a connection at once.
+
+use std::ops::Deref;
+
+use surrealdb::{Connection, Surreal};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// A client that bounds how many queries run on it at once, so bursts of executions (e.g. a
+/// fan-out per request) don't overwhelm the connection.
+///
+/// Live queries and one-shot queries draw from separate limits, so long-lived subscriptions
+/// can't starve one-shot queries of permits and a burst of one-shot queries can't hold up
+/// subscribing. Within each limit, permits are handed out in the order they were asked for.
+///
+/// ```ignore
+/// let executor = Executor::new(db, 16, 4);
+/// let db = executor.acquire().await;
+/// let users = AdultUsers::execute(&db, ExecuteOptions::default()).await?;
+/// ```
+pub struct Executor<C: Connection> {
+    db: Surreal<C>,
+    queries: Semaphore,
+    live_queries: Semaphore,
+}
+
+/// The client, borrowed from an `Executor` for as long as the permit to query it is held.
+pub struct ExecutorPermit<'a, C: Connection> {
+    db: &'a Surreal<C>,
+    _permit: SemaphorePermit<'a>,
+}
+
+impl<C: Connection> Executor<C> {
+    /// Allows up to `max_queries` one-shot queries and `max_live_queries` live queries at once.
+    pub fn new(db: Surreal<C>, max_queries: usize, max_live_queries: usize) -> Self {
+        Self {
+            db,
+            queries: Semaphore::new(max_queries),
+            live_queries: Semaphore::new(max_live_queries),
+        }
+    }
+
+    /// Waits for a permit to run a one-shot query, which is released when the result is dropped.
+    pub async fn acquire(&self) -> ExecutorPermit<'_, C> {
+        self.permit(&self.queries).await
+    }
+
+    /// Waits for a permit to run a live query. Hold it for as long as the subscription is read
+    /// from, so the limit covers open subscriptions rather than just subscribing.
+    pub async fn acquire_live(&self) -> ExecutorPermit<'_, C> {
+        self.permit(&self.live_queries).await
+    }
+
+    /// The number of one-shot queries that can start without waiting.
+    pub fn available(&self) -> usize {
+        self.queries.available_permits()
+    }
+
+    /// The number of live queries that can start without waiting.
+    pub fn available_live(&self) -> usize {
+        self.live_queries.available_permits()
+    }
+
+    async fn permit<'a>(&'a self, semaphore: &'a Semaphore) -> ExecutorPermit<'a, C> {
+        let permit = semaphore
+            .acquire()
+            .await
+            .expect("The executor's semaphores are never closed");
+        ExecutorPermit {
+            db: &self.db,
+            _permit: permit,
+        }
+    }
+}
+
+impl<C: Connection> Deref for ExecutorPermit<'_, C> {
+    type Target = Surreal<C>;
+
+    fn deref(&self) -> &Self::Target {
+        self.db
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use surrealdb::engine::any::Any;
+
+    #[tokio::test]
+    async fn limits_queries_separately() {
+        let executor = Executor::new(Surreal::<Any>::init(), 2, 1);
+
+        let first = executor.acquire().await;
+        let _second = executor.acquire().await;
+        assert_eq!(executor.available(), 0);
+
+        // Live queries have their own permits.
+        let live = executor.acquire_live().await;
+        assert_eq!(executor.available_live(), 0);
+        drop(live);
+
+        let waiting = tokio::time::timeout(Duration::from_millis(10), executor.acquire()).await;
+        assert!(waiting.is_err());
+
+        drop(first);
+        assert_eq!(executor.available(), 1);
+    }
+}
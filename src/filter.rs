@@ -0,0 +1,54 @@
+//! Filters for the `count` methods of table models, e.g.
+//! `User::count(&db, json!({ "active": true }))`.
+
+use serde::Serialize;
+use surrealdb::sql::{Object, Value};
+
+/// The filter as the object of field values a model's count query matches records against.
+///
+/// `()` and NONE filter nothing, and fields set to NONE or NULL are left out, so filters can be
+/// structs of optional fields. Filters that aren't objects, or that set a field outside
+/// `fields`, fail rather than count records they don't describe.
+pub fn field_filter(fields: &[&str], filter: impl Serialize) -> Result<Value, surrealdb::Error> {
+    let invalid =
+        |message: String| surrealdb::Error::Api(surrealdb::error::Api::InvalidParams(message));
+    let filter = match surrealdb::sql::to_value(filter)? {
+        Value::None | Value::Null => Object::default(),
+        Value::Object(filter) => filter,
+        filter => {
+            return Err(invalid(format!(
+                "The filter {} isn't an object of field values",
+                filter
+            )))
+        }
+    };
+
+    let mut values = Object::default();
+    for (field, value) in filter {
+        if !fields.contains(&field.as_str()) {
+            return Err(invalid(format!("'{}' isn't a field to filter on", field)));
+        }
+        if !value.is_none_or_null() {
+            values.insert(field, value);
+        }
+    }
+    Ok(Value::Object(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_on_set_fields() {
+        let filter = |filter| field_filter(&["name", "active"], filter).map(|f| f.to_string());
+
+        assert_eq!(filter(serde_json::json!(null)).unwrap(), "{  }");
+        assert_eq!(
+            filter(serde_json::json!({ "active": true, "name": null })).unwrap(),
+            "{ active: true }"
+        );
+        assert!(filter(serde_json::json!({ "age": 18 })).is_err());
+        assert!(filter(serde_json::json!([true])).is_err());
+    }
+}
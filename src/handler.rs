@@ -0,0 +1,34 @@
+//! Responding to requests with errors from generated queries, for the Axum handlers
+//! `build_query!` generates with its `axum = "/route/{param}"` option.
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use crate::Error;
+
+/// Responds with the status alone, so clients aren't shown the details of database errors:
+/// 503 for queries that were cancelled or ran past their deadline, and 500 for anything else.
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = match self {
+            Error::Cancelled => StatusCode::SERVICE_UNAVAILABLE,
+            Error::Database(_) | Error::Decode(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        status.into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_respond_with_their_status() {
+        assert_eq!(
+            Error::Cancelled.into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+}
@@ -0,0 +1,13 @@
+//! The index hints of a query, which `build_query!` records in the `INDEX_HINTS` constant of the
+//! query module, e.g. to check in tests that a hot query keeps using its index.
+
+/// The `WITH` clause of a SELECT, which overrides the indexes the query planner picks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexHint {
+    /// The index of the statement within the query.
+    pub statement: usize,
+    /// What the statement selects from, e.g. `user`.
+    pub table: &'static str,
+    /// The indexes the planner is limited to, or `None` for `WITH NOINDEX`.
+    pub indexes: Option<&'static [&'static str]>,
+}
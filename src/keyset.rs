@@ -0,0 +1,202 @@
+//! Keyset pagination, for queries generated with `build_query!`'s `keyset` option.
+//!
+//! Pages continue after the id of the last row of the previous one, so a page far into a large
+//! table is as fast to get as the first:
+//!
+//! ```ignore
+//! let mut after = None;
+//! loop {
+//!     let page = AdultUsers::execute_page(&db, after.as_ref(), 100, ExecuteOptions::default()).await?;
+//!     handle(&page.rows);
+//!     match page.next {
+//!         Some(next) => after = Some(next),
+//!         None => break,
+//!     }
+//! }
+//! ```
+
+use std::{fmt, marker::PhantomData, str::FromStr};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use surrealdb::sql::Thing;
+use thiserror::Error;
+
+use crate::{
+    by_id::HasId,
+    execute::{take_rows, Error, Truncated},
+};
+
+/// Where a page of `T` ended, for the next page to continue after.
+///
+/// Cursors are opaque strings to clients, e.g. to send back in a query string: the URL-safe
+/// base64 of the keys the page ended on. They are typed by the rows they page through, so the
+/// cursor of one query can't continue another.
+pub struct Cursor<T> {
+    after: Thing,
+    _rows: PhantomData<fn() -> T>,
+}
+
+impl<T> Cursor<T> {
+    /// The cursor continuing after `row`.
+    pub fn after(row: &T) -> Result<Self, Error>
+    where
+        T: HasId,
+        T::Id: fmt::Display,
+    {
+        Ok(Self::from_thing(parse_thing(&row.id().to_string())?))
+    }
+
+    /// The id of the record the next page continues after, to bind to the query.
+    pub fn record(&self) -> &Thing {
+        &self.after
+    }
+
+    fn from_thing(after: Thing) -> Self {
+        Self {
+            after,
+            _rows: PhantomData,
+        }
+    }
+}
+
+/// Why a string isn't a cursor.
+#[derive(Error, Debug)]
+pub enum CursorError {
+    #[error("The cursor isn't URL-safe base64: {0}")]
+    Encoding(#[from] base64::DecodeError),
+    #[error("The cursor doesn't hold the keys of a page: {0}")]
+    Keys(#[from] serde_json::Error),
+    #[error("The cursor doesn't hold a record id: {0}")]
+    Record(String),
+}
+
+/// The rows of a page, and the cursor of the next one if the page was full.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub rows: Vec<T>,
+    pub next: Option<Cursor<T>>,
+}
+
+/// Takes a page of at most `page_size` rows from the statement at `index`, which asked for one
+/// more to tell whether there is a next page.
+pub fn take_page<T>(
+    response: &mut surrealdb::Response,
+    index: usize,
+    page_size: usize,
+) -> Result<Page<T>, Error>
+where
+    T: HasId + serde::de::DeserializeOwned,
+    T::Id: fmt::Display,
+{
+    let Truncated { rows, truncated } = take_rows(response, index, Some(page_size))?;
+    let next = match (truncated, rows.last()) {
+        (true, Some(last)) => Some(Cursor::after(last)?),
+        _ => None,
+    };
+    Ok(Page { rows, next })
+}
+
+fn parse_thing(id: &str) -> Result<Thing, Error> {
+    surrealdb::sql::thing(id).map_err(|err| Error::Database(err.into()))
+}
+
+impl<T> Clone for Cursor<T> {
+    fn clone(&self) -> Self {
+        Self::from_thing(self.after.clone())
+    }
+}
+
+impl<T> PartialEq for Cursor<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.after == other.after
+    }
+}
+
+impl<T> fmt::Debug for Cursor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cursor({})", self.after)
+    }
+}
+
+impl<T> fmt::Display for Cursor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys = serde_json::to_vec(&(self.after.to_string(),)).map_err(|_| fmt::Error)?;
+        f.write_str(&URL_SAFE_NO_PAD.encode(keys))
+    }
+}
+
+impl<T> FromStr for Cursor<T> {
+    type Err = CursorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (after,): (String,) = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(s)?)?;
+        surrealdb::sql::thing(&after)
+            .map(Self::from_thing)
+            .map_err(|_| CursorError::Record(after))
+    }
+}
+
+impl<T> Serialize for Cursor<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Cursor<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct User {
+        id: String,
+    }
+
+    impl HasId for User {
+        type Id = String;
+
+        fn id(&self) -> &String {
+            &self.id
+        }
+    }
+
+    #[test]
+    fn cursors_round_trip() {
+        let user = User {
+            id: "user:⟨ann smith⟩".to_string(),
+        };
+        let cursor = Cursor::after(&user).unwrap();
+        assert_eq!(cursor.record().tb, "user");
+
+        let json = serde_json::to_value(&cursor).unwrap();
+        let encoded = json.as_str().unwrap();
+        assert!(!encoded.contains("user"));
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(
+            serde_json::from_value::<Cursor<User>>(json).unwrap(),
+            cursor
+        );
+
+        assert!(matches!(
+            "user:ann".parse::<Cursor<User>>(),
+            Err(CursorError::Encoding(_))
+        ));
+        assert!(matches!(
+            URL_SAFE_NO_PAD.encode("ann").parse::<Cursor<User>>(),
+            Err(CursorError::Keys(_))
+        ));
+        assert!(matches!(
+            URL_SAFE_NO_PAD.encode(r#"["ann"]"#).parse::<Cursor<User>>(),
+            Err(CursorError::Record(key)) if key == "ann"
+        ));
+    }
+}
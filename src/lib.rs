@@ -1 +1,47 @@
+#[cfg(feature = "rkyv")]
+pub mod archive;
+pub mod by_id;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+pub mod codec;
+pub mod constraints;
+pub mod decode;
+#[cfg(feature = "diff")]
+pub mod diff;
+pub mod edges;
+pub mod execute;
+pub mod executor;
+pub mod filter;
+#[cfg(feature = "axum")]
+pub mod handler;
+pub mod hints;
+pub mod keyset;
+pub mod live;
+pub mod memo;
+pub mod routing;
+#[cfg(feature = "cli")]
+pub mod table;
+pub mod type_info;
+pub mod types;
 
+#[cfg(feature = "axum")]
+pub use axum;
+pub use by_id::{ById, HasId, IntoById};
+pub use codec::FieldCodec;
+pub use decode::DecodeError;
+pub use edges::EdgeAndNode;
+pub use execute::{CancellationToken, Deadline, Error, ExecuteOptions, Truncated};
+pub use executor::{Executor, ExecutorPermit};
+pub use hints::IndexHint;
+pub use indexmap::IndexMap;
+pub use keyset::{Cursor, CursorError, Page};
+pub use memo::{Memoizable, Memoized};
+#[cfg(feature = "rkyv")]
+pub use rkyv;
+pub use routing::{QueryAccess, Router};
+#[cfg(feature = "specta")]
+pub use specta;
+pub use type_info::{FieldTypeInfo, TypeInfo};
+pub use surrealdb;
+pub use surrealix_macros::{build_query, schema_fields, table_models};
+pub use types::{Link, RecordId, RecordIdError, RecordKey, RecordLink};
@@ -0,0 +1,176 @@
+//! Streams of typed notifications from LIVE SELECT queries.
+//!
+//! `build_query!` types a LIVE SELECT by the records it sends, so running the query yields a
+//! [LiveStream] of them rather than the id of the live query.
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use surrealdb::{method::QueryStream, sql::Value, Action};
+
+use crate::{by_id::HasId, decode::decode, execute::Error};
+
+/// A change to a record matched by a live query, carrying the record as selected.
+///
+/// For deletions, that is the record as it was before it was deleted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification<T> {
+    Create(T),
+    Update(T),
+    Delete(T),
+}
+
+impl<T> Notification<T> {
+    /// The record the change applies to.
+    pub fn record(&self) -> &T {
+        match self {
+            Notification::Create(record)
+            | Notification::Update(record)
+            | Notification::Delete(record) => record,
+        }
+    }
+
+    pub fn into_record(self) -> T {
+        match self {
+            Notification::Create(record)
+            | Notification::Update(record)
+            | Notification::Delete(record) => record,
+        }
+    }
+}
+
+/// The change a notification makes to the records a live query has matched so far.
+///
+/// Creations and updates both upsert the record, as a record starts matching the query when it
+/// is updated to, while deletions only need its id.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<T: HasId> {
+    Upsert(T),
+    Remove(T::Id),
+}
+
+impl<T: HasId> From<Notification<T>> for Change<T> {
+    fn from(notification: Notification<T>) -> Self {
+        match notification {
+            Notification::Create(record) | Notification::Update(record) => Change::Upsert(record),
+            Notification::Delete(record) => Change::Remove(record.id().clone()),
+        }
+    }
+}
+
+/// Applies a change, or the notification making it, to the records a live query has matched so
+/// far, e.g. the state a frontend renders, which stay in the order they were first seen.
+///
+/// Upserts replace the record with the same id, or add it if there is none.
+pub fn reduce<T: HasId>(state: &mut Vec<T>, change: impl Into<Change<T>>) {
+    match change.into() {
+        Change::Upsert(record) => match state.iter_mut().find(|known| known.id() == record.id()) {
+            Some(known) => *known = record,
+            None => state.push(record),
+        },
+        Change::Remove(id) => state.retain(|known| *known.id() != id),
+    }
+}
+
+/// The notifications of a live query, with each record decoded as `T`.
+///
+/// The live query is killed when the stream is dropped.
+pub struct LiveStream<T> {
+    inner: QueryStream<surrealdb::Notification<Value>>,
+    _marker: std::marker::PhantomData<fn() -> T>,
+}
+
+/// Takes the notifications of the LIVE SELECT at `index` from a response.
+pub fn stream<T: DeserializeOwned>(
+    response: &mut surrealdb::Response,
+    index: usize,
+) -> Result<LiveStream<T>, Error> {
+    Ok(LiveStream {
+        inner: response.stream(index)?,
+        _marker: std::marker::PhantomData,
+    })
+}
+
+impl<T: DeserializeOwned> Stream for LiveStream<T> {
+    type Item = Result<Notification<T>, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let notification = match self.inner.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(notification))) => notification,
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error.into()))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+            // Records are decoded like query results, from their JSON form.
+            let record = match decode(notification.data.into_json()) {
+                Ok(record) => record,
+                Err(error) => return Poll::Ready(Some(Err(error.into()))),
+            };
+            let notification = match notification.action {
+                Action::Create => Notification::Create(record),
+                Action::Update => Notification::Update(record),
+                Action::Delete => Notification::Delete(record),
+                // Actions added by newer servers aren't changes to the record this types.
+                _ => continue,
+            };
+            return Poll::Ready(Some(Ok(notification)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notifications_carry_their_record() {
+        let notification = Notification::Update("user:1");
+        assert_eq!(notification.record(), &"user:1");
+        assert_eq!(Notification::Delete(1).into_record(), 1);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct User {
+        id: u32,
+        name: &'static str,
+    }
+
+    impl HasId for User {
+        type Id = u32;
+
+        fn id(&self) -> &u32 {
+            &self.id
+        }
+    }
+
+    #[test]
+    fn reduces_notifications() {
+        let user = |id, name| User { id, name };
+        let mut state = vec![];
+
+        reduce(&mut state, Notification::Create(user(1, "Ann")));
+        reduce(&mut state, Notification::Create(user(2, "Bob")));
+        reduce(&mut state, Notification::Update(user(1, "Anna")));
+        assert_eq!(state, vec![user(1, "Anna"), user(2, "Bob")]);
+
+        reduce(&mut state, Notification::Update(user(3, "Cy")));
+        reduce(&mut state, Notification::Create(user(3, "Cyd")));
+        reduce(&mut state, Notification::Delete(user(1, "Anna")));
+        assert_eq!(state, vec![user(2, "Bob"), user(3, "Cyd")]);
+
+        reduce(&mut state, Notification::Delete(user(4, "Di")));
+        assert_eq!(state.len(), 2);
+
+        assert_eq!(
+            Change::from(Notification::Delete(user(2, "Bob"))),
+            Change::Remove(2)
+        );
+        reduce(&mut state, Change::Remove(2));
+        assert_eq!(state, vec![user(3, "Cyd")]);
+    }
+}
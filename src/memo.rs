@@ -0,0 +1,218 @@
+//! Caching the results of generated queries per set of bindings, e.g. for dashboards that show
+//! the same results to many viewers.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::{stream, Stream, StreamExt};
+use serde::Serialize;
+use surrealdb::{
+    sql::{Table, Value},
+    Connection, Surreal,
+};
+
+use crate::execute::{Error, Instant};
+
+/// A query generated by `build_query!`, which can be run with any bindings and have its
+/// results cached. Queries with a LIVE SELECT aren't, as their results are streams.
+pub trait Memoizable {
+    /// The results of the query's statements.
+    type Output;
+    /// The query as it is executed.
+    const QUERY: &'static str;
+    /// A hash of the query's canonical form, which queries differing only in formatting share.
+    const QUERY_HASH: u64;
+    /// The tables the query reads from, whose changes may change its results.
+    const TABLES: &'static [&'static str];
+
+    /// Takes the results of the query from its response.
+    fn take(response: surrealdb::Response) -> Result<Self::Output, Error>;
+}
+
+/// The results of the query `Q`, cached per set of bindings until `ttl` has passed or they are
+/// invalidated. Entries are keyed on the query's `QUERY_HASH` along with the bindings.
+///
+/// Executions missing the cache at the same time each run the query, and the last to finish is
+/// the one kept.
+///
+/// ```ignore
+/// let users = Arc::new(Memoized::<AdultUsers>::new(Duration::from_secs(30)));
+/// tokio::spawn({
+///     let users = users.clone();
+///     let db = db.clone();
+///     async move { users.watch(&db).await }
+/// });
+/// let rows = users.execute(&db, ("min_age", 18)).await?;
+/// ```
+pub struct Memoized<Q: Memoizable> {
+    ttl: Duration,
+    entries: Mutex<HashMap<Key, Entry<Q::Output>>>,
+    _query: PhantomData<fn() -> Q>,
+}
+
+/// The hash of a query and the printed form of the bindings it was run with.
+type Key = (u64, String);
+
+struct Entry<T> {
+    results: Arc<T>,
+    expires: Instant,
+}
+
+impl<Q: Memoizable> Memoized<Q> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            _query: PhantomData,
+        }
+    }
+
+    /// The cached results for `bindings`, or else those of running the query with them.
+    pub async fn execute<C: Connection>(
+        &self,
+        db: &Surreal<C>,
+        bindings: impl Serialize,
+    ) -> Result<Arc<Q::Output>, Error> {
+        let bindings = bindings_value(bindings)?;
+        let key = (Q::QUERY_HASH, bindings.to_string());
+        self.cached(key, async {
+            let response = db.query(Q::QUERY).bind(bindings).await?;
+            Q::take(response)
+        })
+        .await
+    }
+
+    /// Drops the results cached for `bindings`, so the next execution with them runs the query.
+    pub fn invalidate(&self, bindings: impl Serialize) -> Result<(), Error> {
+        let key = (Q::QUERY_HASH, bindings_value(bindings)?.to_string());
+        self.entries().remove(&key);
+        Ok(())
+    }
+
+    /// Drops the results cached for every set of bindings.
+    pub fn invalidate_all(&self) {
+        self.entries().clear();
+    }
+
+    /// Drops every cached result whenever `notifications` yields, e.g. with the stream of a
+    /// LIVE SELECT on a table the query reads. Returns once the stream ends.
+    pub async fn invalidate_on<S: Stream>(&self, notifications: S) {
+        let mut notifications = std::pin::pin!(notifications);
+        while notifications.next().await.is_some() {
+            self.invalidate_all();
+        }
+    }
+
+    /// Runs a LIVE SELECT on each of the tables the query reads, dropping every cached result
+    /// whenever one of them changes. Returns once the live queries end, or with the error that
+    /// ended them, e.g. because the connection doesn't support live queries.
+    pub async fn watch<C: Connection>(&self, db: &Surreal<C>) -> Result<(), Error> {
+        let mut streams = vec![];
+        for table in Q::TABLES {
+            let mut response = db
+                .query(format!("LIVE SELECT * FROM {}", Table::from(*table)))
+                .await?;
+            streams.push(crate::live::stream::<serde_json::Value>(&mut response, 0)?);
+        }
+
+        let mut notifications = stream::select_all(streams);
+        while let Some(notification) = notifications.next().await {
+            self.invalidate_all();
+            notification?;
+        }
+        Ok(())
+    }
+
+    async fn cached<F>(&self, key: Key, run: F) -> Result<Arc<Q::Output>, Error>
+    where
+        F: Future<Output = Result<Q::Output, Error>>,
+    {
+        if let Some(entry) = self.entries().get(&key) {
+            if entry.expires > Instant::now() {
+                return Ok(entry.results.clone());
+            }
+        }
+
+        let results = Arc::new(run.await?);
+        let now = Instant::now();
+        let mut entries = self.entries();
+        entries.retain(|_, entry| entry.expires > now);
+        entries.insert(
+            key,
+            Entry {
+                results: results.clone(),
+                expires: now + self.ttl,
+            },
+        );
+        Ok(results)
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<Key, Entry<Q::Output>>> {
+        // The cache is only ever left inconsistent by a panic mid-insert, so it is still usable.
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The bindings as a SurrealQL value, whose printed form keys the cache with the query's hash. Objects print with
+/// their keys sorted, so the same bindings always print the same.
+fn bindings_value(bindings: impl Serialize) -> Result<Value, Error> {
+    surrealdb::sql::to_value(bindings).map_err(|err| Error::Database(surrealdb::Error::from(err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Count;
+
+    impl Memoizable for Count {
+        type Output = usize;
+        const QUERY: &'static str = "RETURN 1";
+        const QUERY_HASH: u64 = 1;
+        const TABLES: &'static [&'static str] = &[];
+
+        fn take(_: surrealdb::Response) -> Result<usize, Error> {
+            Ok(1)
+        }
+    }
+
+    #[tokio::test]
+    async fn caches_until_invalidated() {
+        let memo = Memoized::<Count>::new(Duration::from_secs(60));
+        let key = |min_age: u32| {
+            let bindings = bindings_value(("min_age", min_age)).unwrap();
+            (Count::QUERY_HASH, bindings.to_string())
+        };
+
+        assert_eq!(*memo.cached(key(18), async { Ok(1) }).await.unwrap(), 1);
+        assert_eq!(*memo.cached(key(18), async { Ok(2) }).await.unwrap(), 1);
+        assert_eq!(*memo.cached(key(21), async { Ok(3) }).await.unwrap(), 3);
+
+        memo.invalidate(("min_age", 18)).unwrap();
+        assert_eq!(*memo.cached(key(18), async { Ok(4) }).await.unwrap(), 4);
+        assert_eq!(*memo.cached(key(21), async { Ok(5) }).await.unwrap(), 3);
+
+        memo.invalidate_on(stream::iter([()])).await;
+        assert_eq!(*memo.cached(key(21), async { Ok(6) }).await.unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn results_expire() {
+        let memo = Memoized::<Count>::new(Duration::ZERO);
+
+        let key = || (Count::QUERY_HASH, "a".to_string());
+        assert_eq!(*memo.cached(key(), async { Ok(1) }).await.unwrap(), 1);
+        assert_eq!(*memo.cached(key(), async { Ok(2) }).await.unwrap(), 2);
+        assert!(memo
+            .cached(key(), async { Err(Error::Cancelled) })
+            .await
+            .is_err());
+    }
+}
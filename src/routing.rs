@@ -0,0 +1,51 @@
+//! Sending read-only queries to read replicas, and everything else to the primary.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use surrealdb::{Connection, Surreal};
+
+/// Whether a query only reads, as classified by `build_query!` and exposed as the `ACCESS`
+/// constant of the generated type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryAccess {
+    Read,
+    Write,
+}
+
+/// The primary connection and a pool of read replicas, which read-only queries are spread
+/// across in turn. Without replicas, every query goes to the primary.
+pub struct Router<C: Connection> {
+    primary: Surreal<C>,
+    replicas: Vec<Surreal<C>>,
+    next: AtomicUsize,
+}
+
+impl<C: Connection> Router<C> {
+    pub fn new(primary: Surreal<C>) -> Self {
+        Self {
+            primary,
+            replicas: Vec::new(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn with_replicas(mut self, replicas: impl IntoIterator<Item = Surreal<C>>) -> Self {
+        self.replicas.extend(replicas);
+        self
+    }
+
+    pub fn primary(&self) -> &Surreal<C> {
+        &self.primary
+    }
+
+    /// The connection a query with the given access should run on.
+    pub fn route(&self, access: QueryAccess) -> &Surreal<C> {
+        match access {
+            QueryAccess::Read if !self.replicas.is_empty() => {
+                let next = self.next.fetch_add(1, Ordering::Relaxed);
+                &self.replicas[next % self.replicas.len()]
+            }
+            _ => &self.primary,
+        }
+    }
+}
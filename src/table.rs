@@ -0,0 +1,133 @@
+//! Plain-text tables of query results, for admin and CLI tools.
+//!
+//! Generated result structs implement [Tabled], with a column for each field in the order
+//! they're declared, and display as a table of a single row. Several rows are printed with
+//! [table]:
+//!
+//! ```ignore
+//! let users = AdultUsers::execute(&db, ExecuteOptions::default()).await?;
+//! println!("{}", surrealix::table::table(&users));
+//! ```
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::fmt;
+
+/// A value that can be displayed as a row of a table.
+pub trait Tabled {
+    /// The column headers, one for each cell of a row.
+    const HEADERS: &'static [&'static str];
+
+    /// The cells of this row, rendered with [cell].
+    fn fields(&self) -> Vec<String>;
+}
+
+/// Renders a field as a table cell.
+///
+/// Strings are shown without quotes and missing values as empty cells. Anything else, including
+/// nested objects and arrays, is shown as compact JSON. Line breaks are escaped, so each row
+/// stays on one line.
+pub fn cell<T: Serialize + ?Sized>(value: &T) -> String {
+    let text = match serde_json::to_value(value).unwrap_or_default() {
+        JsonValue::Null => String::new(),
+        JsonValue::String(string) => string,
+        value => value.to_string(),
+    };
+    text.replace('\r', "\\r").replace('\n', "\\n")
+}
+
+/// Rows displayed as a table, with aligned columns.
+pub struct Table<'a, T> {
+    rows: &'a [T],
+}
+
+/// Displays `rows` as a table.
+pub fn table<T: Tabled>(rows: &[T]) -> Table<'_, T> {
+    Table { rows }
+}
+
+impl<T: Tabled> fmt::Display for Table<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<_> = self.rows.iter().map(Tabled::fields).collect();
+        let mut widths: Vec<_> = T::HEADERS.iter().map(|header| width(header)).collect();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(self::width(cell));
+            }
+        }
+
+        let line = |f: &mut fmt::Formatter<'_>, cells: &mut dyn Iterator<Item = &str>| {
+            let mut line = String::new();
+            for (index, (cell, width)) in cells.zip(&widths).enumerate() {
+                if index > 0 {
+                    line.push_str(" | ");
+                }
+                line.push_str(cell);
+                line.extend(std::iter::repeat(' ').take(width - self::width(cell)));
+            }
+            writeln!(f, "{}", line.trim_end())
+        };
+
+        line(f, &mut T::HEADERS.iter().copied())?;
+        let rule: Vec<_> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        writeln!(f, "{}", rule.join("-+-"))?;
+        for row in &rows {
+            line(f, &mut row.iter().map(String::as_str))?;
+        }
+        Ok(())
+    }
+}
+
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Serialize)]
+    struct User {
+        name: String,
+        age: Option<i64>,
+        address: JsonValue,
+    }
+
+    impl Tabled for User {
+        const HEADERS: &'static [&'static str] = &["name", "age", "address"];
+
+        fn fields(&self) -> Vec<String> {
+            vec![cell(&self.name), cell(&self.age), cell(&self.address)]
+        }
+    }
+
+    #[test]
+    fn aligns_columns() {
+        let users = [
+            User {
+                name: "Ann".into(),
+                age: Some(30),
+                address: json!({ "city": "Oslo" }),
+            },
+            User {
+                name: "Bartholomew".into(),
+                age: None,
+                address: JsonValue::Null,
+            },
+        ];
+
+        assert_eq!(
+            table(&users).to_string(),
+            "name        | age | address\n\
+             ------------+-----+----------------\n\
+             Ann         | 30  | {\"city\":\"Oslo\"}\n\
+             Bartholomew |     |\n"
+        );
+    }
+
+    #[test]
+    fn escapes_line_breaks() {
+        assert_eq!(cell("two\nlines"), "two\\nlines");
+    }
+}
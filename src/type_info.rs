@@ -0,0 +1,68 @@
+//! Descriptions of generated structs that are available at runtime, e.g. to build forms or
+//! admin views for query results, as the `TYPE_INFO` constant of each struct.
+
+/// A generated struct and the fields it holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeInfo {
+    /// The name of the struct.
+    pub name: &'static str,
+    /// The table the struct is a record of, if it holds a whole record with its `id`.
+    pub table: Option<&'static str>,
+    /// The fields, sorted by their name in the result.
+    pub fields: &'static [FieldTypeInfo],
+}
+
+/// A field of a generated struct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldTypeInfo {
+    /// The name of the field in the result.
+    pub name: &'static str,
+    /// The name of the Rust field, which may differ from the name in the result.
+    pub rust_name: &'static str,
+    /// The field's type as a SurrealQL kind, e.g. `option<array<string>>`.
+    pub kind: &'static str,
+    /// Where the field was selected from, e.g. `["user", "address", "city"]`.
+    pub source: &'static [&'static str],
+    /// The note of a deprecated field, which may be empty, or `None` if it isn't deprecated.
+    pub deprecated: Option<&'static str>,
+}
+
+impl TypeInfo {
+    /// The field with the given name in the result.
+    pub fn field(&self, name: &str) -> Option<&'static FieldTypeInfo> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: TypeInfo = TypeInfo {
+        name: "AdultUsers",
+        table: Some("user"),
+        fields: &[
+            FieldTypeInfo {
+                name: "bestFriend",
+                rust_name: "best_friend",
+                kind: "option<record<user>>",
+                source: &["user", "bestFriend"],
+                deprecated: None,
+            },
+            FieldTypeInfo {
+                name: "name",
+                rust_name: "name",
+                kind: "string",
+                source: &["user", "name"],
+                deprecated: Some(""),
+            },
+        ],
+    };
+
+    #[test]
+    fn finds_fields_by_result_name() {
+        assert_eq!(USER.field("bestFriend").unwrap().rust_name, "best_friend");
+        assert_eq!(USER.field("name").unwrap().deprecated, Some(""));
+        assert!(USER.field("best_friend").is_none());
+    }
+}
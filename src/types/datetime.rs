@@ -0,0 +1,196 @@
+use super::Duration;
+use chrono::Utc;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt,
+    ops::{Add, Sub},
+    str::FromStr,
+};
+
+/// A SurrealDB datetime, always in UTC.
+///
+/// Deserializes from RFC 3339 strings as well as SurrealDB's own datetime value,
+/// which arrives wrapped in a newtype or as a `d"..."` literal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime(pub chrono::DateTime<Utc>);
+
+impl DateTime {
+    pub fn now() -> Self {
+        Self(Utc::now())
+    }
+
+    /// Seconds since the unix epoch.
+    pub fn timestamp(&self) -> i64 {
+        self.0.timestamp()
+    }
+
+    /// Milliseconds since the unix epoch.
+    pub fn timestamp_millis(&self) -> i64 {
+        self.0.timestamp_millis()
+    }
+
+    /// Microseconds since the unix epoch.
+    pub fn timestamp_micros(&self) -> i64 {
+        self.0.timestamp_micros()
+    }
+
+    /// Nanoseconds since the unix epoch, or `None` outside the years 1677 to 2262.
+    pub fn timestamp_nanos(&self) -> Option<i64> {
+        self.0.timestamp_nanos_opt()
+    }
+
+    /// Creates a datetime from seconds and nanoseconds since the unix epoch.
+    pub fn from_timestamp(secs: i64, nanos: u32) -> Option<Self> {
+        chrono::DateTime::from_timestamp(secs, nanos).map(Self)
+    }
+
+    pub fn from_timestamp_millis(millis: i64) -> Option<Self> {
+        chrono::DateTime::from_timestamp_millis(millis).map(Self)
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.0.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+        )
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = chrono::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Accept SurrealQL datetime literals (`d"..."`, `'...'`) as well as bare strings.
+        let s = s.strip_prefix('d').unwrap_or(s);
+        let s = s.trim_matches(|c| c == '"' || c == '\'');
+        chrono::DateTime::parse_from_rfc3339(s).map(|dt| Self(dt.with_timezone(&Utc)))
+    }
+}
+
+impl From<chrono::DateTime<Utc>> for DateTime {
+    fn from(dt: chrono::DateTime<Utc>) -> Self {
+        Self(dt)
+    }
+}
+
+impl From<DateTime> for chrono::DateTime<Utc> {
+    fn from(dt: DateTime) -> Self {
+        dt.0
+    }
+}
+
+impl From<surrealdb::sql::Datetime> for DateTime {
+    fn from(dt: surrealdb::sql::Datetime) -> Self {
+        Self(dt.0)
+    }
+}
+
+impl From<DateTime> for surrealdb::sql::Datetime {
+    fn from(dt: DateTime) -> Self {
+        surrealdb::sql::Datetime(dt.0)
+    }
+}
+
+/// Like std's arithmetic, panics if the result would overflow.
+fn to_chrono(duration: Duration) -> chrono::Duration {
+    chrono::Duration::try_from(duration).expect("duration is out of range for a datetime")
+}
+
+impl Add<Duration> for DateTime {
+    type Output = DateTime;
+
+    fn add(self, rhs: Duration) -> DateTime {
+        Self(self.0 + to_chrono(rhs))
+    }
+}
+
+impl Sub<Duration> for DateTime {
+    type Output = DateTime;
+
+    fn sub(self, rhs: Duration) -> DateTime {
+        Self(self.0 - to_chrono(rhs))
+    }
+}
+
+impl Serialize for DateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+struct DateTimeVisitor;
+
+impl<'de> de::Visitor<'de> for DateTimeVisitor {
+    type Value = DateTime;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an RFC 3339 datetime or a SurrealDB datetime value")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<DateTime, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, d: D) -> Result<DateTime, D::Error> {
+        d.deserialize_any(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DateTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(DateTimeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_strings_and_literals() {
+        let dt: DateTime = "2024-02-29T12:30:00Z".parse().unwrap();
+        assert_eq!(dt.timestamp(), 1_709_209_800);
+        assert_eq!(dt.to_string(), "2024-02-29T12:30:00Z");
+        assert_eq!("d\"2024-02-29T12:30:00Z\"".parse::<DateTime>().unwrap(), dt);
+        assert_eq!(
+            "'2024-02-29T13:30:00+01:00'".parse::<DateTime>().unwrap(),
+            dt
+        );
+        assert!("2024-02-30T12:30:00Z".parse::<DateTime>().is_err());
+    }
+
+    #[test]
+    fn deserializes_native_values() {
+        let dt = DateTime::from_timestamp_millis(1_709_209_800_250).unwrap();
+        let native = surrealdb::sql::Value::Datetime(dt.into());
+
+        assert_eq!(surrealdb::sql::from_value::<DateTime>(native).unwrap(), dt);
+        assert_eq!(
+            serde_json::from_value::<DateTime>(json!("2024-02-29T12:30:00.250Z")).unwrap(),
+            dt
+        );
+        assert_eq!(
+            serde_json::to_value(dt).unwrap(),
+            json!("2024-02-29T12:30:00.250Z")
+        );
+    }
+
+    #[test]
+    fn compares_and_shifts_by_durations() {
+        let start = DateTime::from_timestamp(1_709_209_800, 0).unwrap();
+        let end = start + Duration::mins(90);
+
+        assert!(start < end);
+        assert_eq!(end.timestamp() - start.timestamp(), 5_400);
+        assert_eq!(end - Duration::mins(90), start);
+        assert_eq!(
+            chrono::DateTime::<Utc>::from(end).timestamp(),
+            end.timestamp()
+        );
+        assert_eq!(start.timestamp_nanos(), Some(1_709_209_800_000_000_000));
+    }
+}
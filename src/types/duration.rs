@@ -0,0 +1,274 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Sub, SubAssign},
+    str::FromStr,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DurationError {
+    #[error("Invalid duration '{0}': {1}")]
+    InvalidSyntax(String, String),
+    #[error("Duration is out of range for the target type")]
+    OutOfRange,
+}
+
+const SECS_PER_MINUTE: u64 = 60;
+const SECS_PER_HOUR: u64 = 60 * SECS_PER_MINUTE;
+const SECS_PER_DAY: u64 = 24 * SECS_PER_HOUR;
+const SECS_PER_WEEK: u64 = 7 * SECS_PER_DAY;
+const SECS_PER_YEAR: u64 = 365 * SECS_PER_DAY;
+
+/// Units in the order SurrealDB writes them, from largest to smallest, in nanoseconds.
+const UNITS: [(&str, u128); 9] = [
+    ("y", SECS_PER_YEAR as u128 * 1_000_000_000),
+    ("w", SECS_PER_WEEK as u128 * 1_000_000_000),
+    ("d", SECS_PER_DAY as u128 * 1_000_000_000),
+    ("h", SECS_PER_HOUR as u128 * 1_000_000_000),
+    ("m", SECS_PER_MINUTE as u128 * 1_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("µs", 1_000),
+    ("ns", 1),
+];
+
+/// A SurrealDB duration, such as `1w2d` or `1h30m`.
+///
+/// (De)serializes using SurrealDB's textual format, and converts to and from
+/// [std::time::Duration] and [chrono::Duration].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Duration(pub std::time::Duration);
+
+impl Duration {
+    pub const ZERO: Duration = Duration(std::time::Duration::ZERO);
+
+    pub const fn nanos(nanos: u64) -> Self {
+        Self(std::time::Duration::from_nanos(nanos))
+    }
+
+    pub const fn micros(micros: u64) -> Self {
+        Self(std::time::Duration::from_micros(micros))
+    }
+
+    pub const fn millis(millis: u64) -> Self {
+        Self(std::time::Duration::from_millis(millis))
+    }
+
+    pub const fn secs(secs: u64) -> Self {
+        Self(std::time::Duration::from_secs(secs))
+    }
+
+    pub const fn mins(mins: u64) -> Self {
+        Self::secs(mins * SECS_PER_MINUTE)
+    }
+
+    pub const fn hours(hours: u64) -> Self {
+        Self::secs(hours * SECS_PER_HOUR)
+    }
+
+    pub const fn days(days: u64) -> Self {
+        Self::secs(days * SECS_PER_DAY)
+    }
+
+    pub const fn weeks(weeks: u64) -> Self {
+        Self::secs(weeks * SECS_PER_WEEK)
+    }
+
+    /// SurrealDB years are always 365 days.
+    pub const fn years(years: u64) -> Self {
+        Self::secs(years * SECS_PER_YEAR)
+    }
+
+    pub fn checked_add(self, rhs: Duration) -> Option<Duration> {
+        self.0.checked_add(rhs.0).map(Duration)
+    }
+
+    pub fn checked_sub(self, rhs: Duration) -> Option<Duration> {
+        self.0.checked_sub(rhs.0).map(Duration)
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut remaining = self.0.as_nanos();
+        if remaining == 0 {
+            return write!(f, "0ns");
+        }
+        // Each unit only takes what is left over after the larger units.
+        for (unit, nanos) in UNITS {
+            let count = remaining / nanos;
+            if count > 0 {
+                write!(f, "{}{}", count, unit)?;
+                remaining %= nanos;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Duration {
+    type Err = DurationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            |reason: &str| DurationError::InvalidSyntax(s.to_string(), reason.to_string());
+        if s.is_empty() {
+            return Err(invalid("duration is empty"));
+        }
+
+        let mut total: u128 = 0;
+        let mut rest = s;
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or_else(|| invalid("missing unit"))?;
+            if digits == 0 {
+                return Err(invalid("expected a number"));
+            }
+            let count: u128 = rest[..digits]
+                .parse()
+                .map_err(|_| invalid("number is too large"))?;
+            rest = &rest[digits..];
+
+            let unit_len = rest
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let nanos = match &rest[..unit_len] {
+                "us" => 1_000,
+                unit => UNITS
+                    .iter()
+                    .find(|(name, _)| *name == unit)
+                    .map(|(_, nanos)| *nanos)
+                    .ok_or_else(|| invalid("unknown unit"))?,
+            };
+            rest = &rest[unit_len..];
+
+            total = count
+                .checked_mul(nanos)
+                .and_then(|n| total.checked_add(n))
+                .ok_or_else(|| invalid("duration is too large"))?;
+        }
+
+        let secs = u64::try_from(total / 1_000_000_000).map_err(|_| DurationError::OutOfRange)?;
+        Ok(Self(std::time::Duration::new(
+            secs,
+            (total % 1_000_000_000) as u32,
+        )))
+    }
+}
+
+impl From<std::time::Duration> for Duration {
+    fn from(duration: std::time::Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<Duration> for std::time::Duration {
+    fn from(duration: Duration) -> Self {
+        duration.0
+    }
+}
+
+impl TryFrom<chrono::Duration> for Duration {
+    type Error = DurationError;
+
+    /// Fails for negative durations, which SurrealDB can't represent.
+    fn try_from(duration: chrono::Duration) -> Result<Self, Self::Error> {
+        duration
+            .to_std()
+            .map(Self)
+            .map_err(|_| DurationError::OutOfRange)
+    }
+}
+
+impl TryFrom<Duration> for chrono::Duration {
+    type Error = DurationError;
+
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        chrono::Duration::from_std(duration.0).map_err(|_| DurationError::OutOfRange)
+    }
+}
+
+impl Add for Duration {
+    type Output = Duration;
+
+    fn add(self, rhs: Duration) -> Duration {
+        Duration(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Duration {
+    fn add_assign(&mut self, rhs: Duration) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Duration {
+    type Output = Duration;
+
+    fn sub(self, rhs: Duration) -> Duration {
+        Duration(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Duration {
+    fn sub_assign(&mut self, rhs: Duration) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Serialize for Duration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// JSON responses carry durations as strings, while SurrealDB's own value type
+/// serializes them as `{ secs, nanos }`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DurationRepr {
+    String(String),
+    Std(std::time::Duration),
+}
+
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match DurationRepr::deserialize(deserializer)? {
+            DurationRepr::String(s) => s.parse().map_err(de::Error::custom),
+            DurationRepr::Std(duration) => Ok(Self(duration)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_nests_units() {
+        let duration = Duration::weeks(1) + Duration::days(2) + Duration::mins(90);
+        assert_eq!(duration.to_string(), "1w2d1h30m");
+        assert_eq!(Duration::millis(1_500).to_string(), "1s500ms");
+        assert_eq!(Duration::ZERO.to_string(), "0ns");
+    }
+
+    #[test]
+    fn parse_round_trips() {
+        for raw in ["1y2w3d4h5m6s7ms8µs9ns", "90m", "1h30m"] {
+            let duration: Duration = raw.parse().unwrap();
+            assert_eq!(duration, duration.to_string().parse().unwrap());
+        }
+        assert_eq!("1h30m".parse::<Duration>().unwrap(), Duration::mins(90));
+        assert_eq!("5us".parse::<Duration>().unwrap(), Duration::micros(5));
+    }
+
+    #[test]
+    fn parse_rejects_invalid() {
+        assert!("".parse::<Duration>().is_err());
+        assert!("10".parse::<Duration>().is_err());
+        assert!("h".parse::<Duration>().is_err());
+        assert!("3x".parse::<Duration>().is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! Conversions to and from the `geo-types` crate.
+use super::{Geometry, Point};
+
+impl From<Point> for geo_types::Point<f64> {
+    fn from(p: Point) -> Self {
+        geo_types::Point::new(p.x, p.y)
+    }
+}
+
+impl From<geo_types::Point<f64>> for Point {
+    fn from(p: geo_types::Point<f64>) -> Self {
+        Point::new(p.x(), p.y())
+    }
+}
+
+impl From<geo_types::Coord<f64>> for Point {
+    fn from(c: geo_types::Coord<f64>) -> Self {
+        Point::new(c.x, c.y)
+    }
+}
+
+fn line_string(points: Vec<Point>) -> geo_types::LineString<f64> {
+    points
+        .into_iter()
+        .map(|p| geo_types::Coord { x: p.x, y: p.y })
+        .collect::<Vec<_>>()
+        .into()
+}
+
+fn polygon(mut rings: Vec<Vec<Point>>) -> geo_types::Polygon<f64> {
+    let exterior = if rings.is_empty() {
+        vec![]
+    } else {
+        rings.remove(0)
+    };
+    geo_types::Polygon::new(
+        line_string(exterior),
+        rings.into_iter().map(line_string).collect(),
+    )
+}
+
+fn points(line: &geo_types::LineString<f64>) -> Vec<Point> {
+    line.0.iter().copied().map(Point::from).collect()
+}
+
+fn rings(polygon: &geo_types::Polygon<f64>) -> Vec<Vec<Point>> {
+    std::iter::once(polygon.exterior())
+        .chain(polygon.interiors())
+        .map(points)
+        .collect()
+}
+
+impl From<Geometry> for geo_types::Geometry<f64> {
+    fn from(geometry: Geometry) -> Self {
+        match geometry {
+            Geometry::Point(p) => geo_types::Geometry::Point(p.into()),
+            Geometry::Line(line) => geo_types::Geometry::LineString(line_string(line)),
+            Geometry::Polygon(rings) => geo_types::Geometry::Polygon(polygon(rings)),
+            Geometry::MultiPoint(ps) => geo_types::Geometry::MultiPoint(geo_types::MultiPoint(
+                ps.into_iter().map(geo_types::Point::from).collect(),
+            )),
+            Geometry::MultiLine(lines) => geo_types::Geometry::MultiLineString(
+                geo_types::MultiLineString(lines.into_iter().map(line_string).collect()),
+            ),
+            Geometry::MultiPolygon(polygons) => geo_types::Geometry::MultiPolygon(
+                geo_types::MultiPolygon(polygons.into_iter().map(polygon).collect()),
+            ),
+            Geometry::Collection(geometries) => geo_types::Geometry::GeometryCollection(
+                geo_types::GeometryCollection(geometries.into_iter().map(Into::into).collect()),
+            ),
+        }
+    }
+}
+
+/// `geo-types` has a few shapes SurrealDB doesn't; lines, rects and triangles are
+/// converted to the equivalent line string or polygon.
+impl From<geo_types::Geometry<f64>> for Geometry {
+    fn from(geometry: geo_types::Geometry<f64>) -> Self {
+        match geometry {
+            geo_types::Geometry::Point(p) => Geometry::Point(p.into()),
+            geo_types::Geometry::Line(line) => {
+                Geometry::Line(vec![line.start.into(), line.end.into()])
+            }
+            geo_types::Geometry::LineString(line) => Geometry::Line(points(&line)),
+            geo_types::Geometry::Polygon(p) => Geometry::Polygon(rings(&p)),
+            geo_types::Geometry::MultiPoint(ps) => {
+                Geometry::MultiPoint(ps.0.into_iter().map(Point::from).collect())
+            }
+            geo_types::Geometry::MultiLineString(lines) => {
+                Geometry::MultiLine(lines.0.iter().map(points).collect())
+            }
+            geo_types::Geometry::MultiPolygon(polygons) => {
+                Geometry::MultiPolygon(polygons.0.iter().map(rings).collect())
+            }
+            geo_types::Geometry::GeometryCollection(geometries) => {
+                Geometry::Collection(geometries.0.into_iter().map(Geometry::from).collect())
+            }
+            geo_types::Geometry::Rect(rect) => Geometry::Polygon(rings(&rect.to_polygon())),
+            geo_types::Geometry::Triangle(triangle) => {
+                Geometry::Polygon(rings(&triangle.to_polygon()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_and_from_geo_types() {
+        let polygon = Geometry::Polygon(vec![
+            vec![
+                Point::new(0.0, 0.0),
+                Point::new(4.0, 0.0),
+                Point::new(0.0, 4.0),
+                Point::new(0.0, 0.0),
+            ],
+            vec![
+                Point::new(1.0, 1.0),
+                Point::new(2.0, 1.0),
+                Point::new(1.0, 2.0),
+                Point::new(1.0, 1.0),
+            ],
+        ]);
+
+        let geo = geo_types::Geometry::from(polygon.clone());
+        let geo_types::Geometry::Polygon(geo_polygon) = &geo else {
+            panic!("Expected a polygon");
+        };
+        assert_eq!(geo_polygon.interiors().len(), 1);
+        assert_eq!(Geometry::from(geo), polygon);
+
+        // Shapes SurrealDB doesn't have become their line string or polygon.
+        let line = geo_types::Line::new((0.0, 0.0), (1.0, 2.0));
+        assert_eq!(
+            Geometry::from(geo_types::Geometry::Line(line)),
+            Geometry::Line(vec![Point::new(0.0, 0.0), Point::new(1.0, 2.0)])
+        );
+        let rect = geo_types::Rect::new((0.0, 0.0), (1.0, 1.0));
+        let Geometry::Polygon(rings) = Geometry::from(geo_types::Geometry::Rect(rect)) else {
+            panic!("Expected a rect to become a polygon");
+        };
+        assert_eq!(rings.len(), 1);
+        assert_eq!(rings[0].len(), 5);
+    }
+}
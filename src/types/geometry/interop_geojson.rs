@@ -0,0 +1,127 @@
+//! Conversions to and from the `geojson` crate.
+use super::{Geometry, GeometryError, Point};
+
+impl From<Point> for geojson::Position {
+    fn from(p: Point) -> Self {
+        vec![p.x, p.y]
+    }
+}
+
+impl TryFrom<geojson::Position> for Point {
+    type Error = GeometryError;
+
+    fn try_from(position: geojson::Position) -> Result<Self, Self::Error> {
+        match position.as_slice() {
+            [x, y, ..] => Ok(Point::new(*x, *y)),
+            _ => Err(GeometryError::InvalidPosition(position.len())),
+        }
+    }
+}
+
+fn positions(points: Vec<Point>) -> Vec<geojson::Position> {
+    points.into_iter().map(Into::into).collect()
+}
+
+fn points(positions: Vec<geojson::Position>) -> Result<Vec<Point>, GeometryError> {
+    positions.into_iter().map(Point::try_from).collect()
+}
+
+fn rings(rings: Vec<Vec<geojson::Position>>) -> Result<Vec<Vec<Point>>, GeometryError> {
+    rings.into_iter().map(points).collect()
+}
+
+impl From<Geometry> for geojson::Value {
+    fn from(geometry: Geometry) -> Self {
+        match geometry {
+            Geometry::Point(p) => geojson::Value::Point(p.into()),
+            Geometry::Line(line) => geojson::Value::LineString(positions(line)),
+            Geometry::Polygon(rings) => {
+                geojson::Value::Polygon(rings.into_iter().map(positions).collect())
+            }
+            Geometry::MultiPoint(ps) => geojson::Value::MultiPoint(positions(ps)),
+            Geometry::MultiLine(lines) => {
+                geojson::Value::MultiLineString(lines.into_iter().map(positions).collect())
+            }
+            Geometry::MultiPolygon(polygons) => geojson::Value::MultiPolygon(
+                polygons
+                    .into_iter()
+                    .map(|rings| rings.into_iter().map(positions).collect())
+                    .collect(),
+            ),
+            Geometry::Collection(geometries) => geojson::Value::GeometryCollection(
+                geometries
+                    .into_iter()
+                    .map(geojson::Geometry::from)
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<Geometry> for geojson::Geometry {
+    fn from(geometry: Geometry) -> Self {
+        geojson::Geometry::new(geometry.into())
+    }
+}
+
+impl TryFrom<geojson::Value> for Geometry {
+    type Error = GeometryError;
+
+    fn try_from(value: geojson::Value) -> Result<Self, Self::Error> {
+        Ok(match value {
+            geojson::Value::Point(p) => Geometry::Point(p.try_into()?),
+            geojson::Value::LineString(line) => Geometry::Line(points(line)?),
+            geojson::Value::Polygon(polygon) => Geometry::Polygon(rings(polygon)?),
+            geojson::Value::MultiPoint(ps) => Geometry::MultiPoint(points(ps)?),
+            geojson::Value::MultiLineString(lines) => Geometry::MultiLine(rings(lines)?),
+            geojson::Value::MultiPolygon(polygons) => {
+                Geometry::MultiPolygon(polygons.into_iter().map(rings).collect::<Result<_, _>>()?)
+            }
+            geojson::Value::GeometryCollection(geometries) => Geometry::Collection(
+                geometries
+                    .into_iter()
+                    .map(Geometry::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
+}
+
+impl TryFrom<geojson::Geometry> for Geometry {
+    type Error = GeometryError;
+
+    fn try_from(geometry: geojson::Geometry) -> Result<Self, Self::Error> {
+        geometry.value.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_and_from_geojson() {
+        let lines = Geometry::MultiLine(vec![
+            vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)],
+            vec![Point::new(2.0, 2.0), Point::new(3.0, 5.0)],
+        ]);
+        let collection = Geometry::Collection(vec![Geometry::Point(Point::new(1.0, 2.0)), lines]);
+
+        let geojson = geojson::Geometry::from(collection.clone());
+        let geojson::Value::GeometryCollection(geometries) = &geojson.value else {
+            panic!("Expected a geometry collection");
+        };
+        assert_eq!(geometries[0].value, geojson::Value::Point(vec![1.0, 2.0]));
+        assert_eq!(Geometry::try_from(geojson).unwrap(), collection);
+
+        // Altitudes are dropped, but positions without both coordinates are rejected.
+        assert_eq!(
+            Geometry::try_from(geojson::Value::Point(vec![1.0, 2.0, 30.0])).unwrap(),
+            Geometry::Point(Point::new(1.0, 2.0))
+        );
+        assert_eq!(
+            Geometry::try_from(geojson::Value::LineString(vec![vec![1.0, 2.0], vec![3.0]])),
+            Err(GeometryError::InvalidPosition(1))
+        );
+    }
+}
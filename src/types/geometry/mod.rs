@@ -0,0 +1,223 @@
+#[cfg(feature = "geo-types")]
+mod interop_geo;
+#[cfg(feature = "geojson")]
+mod interop_geojson;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum GeometryError {
+    #[error("A position must have at least two coordinates, but got {0}")]
+    InvalidPosition(usize),
+}
+
+/// A geographic point, with `x` as longitude and `y` as latitude.
+///
+/// Deserializes from either a GeoJSON point or a bare `[x, y]` pair.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+/// A geometry value as returned by SurrealDB, (de)serialized as GeoJSON.
+///
+/// Polygons are stored as rings, the first being the exterior and the rest holes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    Point(Point),
+    Line(Vec<Point>),
+    Polygon(Vec<Vec<Point>>),
+    MultiPoint(Vec<Point>),
+    MultiLine(Vec<Vec<Point>>),
+    MultiPolygon(Vec<Vec<Vec<Point>>>),
+    Collection(Vec<Geometry>),
+}
+
+/// A position as it appears in GeoJSON coordinates.
+#[derive(Serialize, Deserialize)]
+struct Coord(f64, f64);
+
+impl From<Point> for Coord {
+    fn from(p: Point) -> Self {
+        Coord(p.x, p.y)
+    }
+}
+
+impl From<Coord> for Point {
+    fn from(c: Coord) -> Self {
+        Point::new(c.0, c.1)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum GeoJson {
+    Point { coordinates: Coord },
+    LineString { coordinates: Vec<Coord> },
+    Polygon { coordinates: Vec<Vec<Coord>> },
+    MultiPoint { coordinates: Vec<Coord> },
+    MultiLineString { coordinates: Vec<Vec<Coord>> },
+    MultiPolygon { coordinates: Vec<Vec<Vec<Coord>>> },
+    GeometryCollection { geometries: Vec<Geometry> },
+}
+
+fn coords(points: Vec<Point>) -> Vec<Coord> {
+    points.into_iter().map(Coord::from).collect()
+}
+
+fn points(coords: Vec<Coord>) -> Vec<Point> {
+    coords.into_iter().map(Point::from).collect()
+}
+
+impl From<Geometry> for GeoJson {
+    fn from(geometry: Geometry) -> Self {
+        match geometry {
+            Geometry::Point(p) => GeoJson::Point {
+                coordinates: p.into(),
+            },
+            Geometry::Line(line) => GeoJson::LineString {
+                coordinates: coords(line),
+            },
+            Geometry::Polygon(rings) => GeoJson::Polygon {
+                coordinates: rings.into_iter().map(coords).collect(),
+            },
+            Geometry::MultiPoint(ps) => GeoJson::MultiPoint {
+                coordinates: coords(ps),
+            },
+            Geometry::MultiLine(lines) => GeoJson::MultiLineString {
+                coordinates: lines.into_iter().map(coords).collect(),
+            },
+            Geometry::MultiPolygon(polygons) => GeoJson::MultiPolygon {
+                coordinates: polygons
+                    .into_iter()
+                    .map(|rings| rings.into_iter().map(coords).collect())
+                    .collect(),
+            },
+            Geometry::Collection(geometries) => GeoJson::GeometryCollection { geometries },
+        }
+    }
+}
+
+impl From<GeoJson> for Geometry {
+    fn from(geojson: GeoJson) -> Self {
+        match geojson {
+            GeoJson::Point { coordinates } => Geometry::Point(coordinates.into()),
+            GeoJson::LineString { coordinates } => Geometry::Line(points(coordinates)),
+            GeoJson::Polygon { coordinates } => {
+                Geometry::Polygon(coordinates.into_iter().map(points).collect())
+            }
+            GeoJson::MultiPoint { coordinates } => Geometry::MultiPoint(points(coordinates)),
+            GeoJson::MultiLineString { coordinates } => {
+                Geometry::MultiLine(coordinates.into_iter().map(points).collect())
+            }
+            GeoJson::MultiPolygon { coordinates } => Geometry::MultiPolygon(
+                coordinates
+                    .into_iter()
+                    .map(|rings| rings.into_iter().map(points).collect())
+                    .collect(),
+            ),
+            GeoJson::GeometryCollection { geometries } => Geometry::Collection(geometries),
+        }
+    }
+}
+
+impl Serialize for Geometry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GeoJson::from(self.clone()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Geometry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        GeoJson::deserialize(deserializer).map(Geometry::from)
+    }
+}
+
+impl Serialize for Point {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GeoJson::Point {
+            coordinates: (*self).into(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PointRepr {
+    Coord(Coord),
+    GeoJson(GeoJson),
+}
+
+impl<'de> Deserialize<'de> for Point {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match PointRepr::deserialize(deserializer)? {
+            PointRepr::Coord(coord) => Ok(coord.into()),
+            PointRepr::GeoJson(GeoJson::Point { coordinates }) => Ok(coordinates.into()),
+            PointRepr::GeoJson(_) => Err(serde::de::Error::custom(
+                "expected a point, but got another geometry",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn geometries_are_geojson() {
+        let polygon = Geometry::Polygon(vec![
+            vec![
+                Point::new(0.0, 0.0),
+                Point::new(4.0, 0.0),
+                Point::new(0.0, 4.0),
+            ],
+            vec![
+                Point::new(1.0, 1.0),
+                Point::new(2.0, 1.0),
+                Point::new(1.0, 2.0),
+            ],
+        ]);
+        let collection = Geometry::Collection(vec![Geometry::Point(Point::new(1.5, 2.5)), polygon]);
+
+        let json = serde_json::to_value(&collection).unwrap();
+        assert_eq!(json["type"], "GeometryCollection");
+        assert_eq!(
+            json["geometries"][0],
+            json!({ "type": "Point", "coordinates": [1.5, 2.5] })
+        );
+        assert_eq!(
+            json["geometries"][1]["coordinates"][1][2],
+            json!([1.0, 2.0])
+        );
+        assert_eq!(
+            serde_json::from_value::<Geometry>(json).unwrap(),
+            collection
+        );
+    }
+
+    #[test]
+    fn points_are_pairs_or_geojson() {
+        let pair: Point = serde_json::from_value(json!([-0.12, 51.5])).unwrap();
+        let geojson: Point =
+            serde_json::from_value(json!({ "type": "Point", "coordinates": [-0.12, 51.5] }))
+                .unwrap();
+
+        assert_eq!(pair, Point::new(-0.12, 51.5));
+        assert_eq!(geojson, pair);
+        assert!(serde_json::from_value::<Point>(
+            json!({ "type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]] })
+        )
+        .is_err());
+    }
+}
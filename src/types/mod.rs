@@ -0,0 +1,11 @@
+mod datetime;
+mod duration;
+mod geometry;
+mod record;
+
+pub use datetime::DateTime;
+pub use duration::{Duration, DurationError};
+pub use geometry::{Geometry, GeometryError, Point};
+pub use record::{
+    serialize_thing, serialize_things, Link, RecordId, RecordIdError, RecordKey, RecordLink,
+};
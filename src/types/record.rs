@@ -0,0 +1,397 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value as JsonValue};
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    str::FromStr,
+};
+use surrealdb::sql::{Id, Thing, Value};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RecordIdError {
+    #[error("Record id '{0}' is missing the ':' separating the table from the key")]
+    MissingSeparator(String),
+    #[error("Record id '{0}' belongs to table '{1}', but a record of '{2}' was expected")]
+    WrongTable(String, String, String),
+    #[error("Record id '{0}' does not have a valid {1} key")]
+    InvalidKey(String, String),
+    #[error("Record id '{0}' could not be parsed: {1}")]
+    InvalidSyntax(String, String),
+}
+
+/// The key identifying a record within its table.
+///
+/// SurrealDB allows complex keys, so arrays and objects are kept structured rather than as strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordKey {
+    Number(i64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Map<String, JsonValue>),
+}
+
+impl RecordKey {
+    fn from_id(id: Id) -> Option<Self> {
+        match id {
+            Id::Number(n) => Some(RecordKey::Number(n)),
+            Id::String(s) => Some(RecordKey::String(s)),
+            Id::Array(arr) => match Value::Array(arr).into_json() {
+                JsonValue::Array(arr) => Some(RecordKey::Array(arr)),
+                _ => None,
+            },
+            Id::Object(obj) => match Value::Object(obj).into_json() {
+                JsonValue::Object(obj) => Some(RecordKey::Object(obj)),
+                _ => None,
+            },
+            // Generated ids (`rand()`, `ulid()`) only exist in queries, never in results.
+            Id::Generate(_) => None,
+        }
+    }
+}
+
+/// Displays the raw key, without the escaping used when it is part of a record id.
+impl fmt::Display for RecordKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordKey::Number(n) => write!(f, "{}", n),
+            RecordKey::String(s) => write!(f, "{}", s),
+            RecordKey::Array(arr) => write!(f, "{}", JsonValue::Array(arr.clone())),
+            RecordKey::Object(obj) => write!(f, "{}", JsonValue::Object(obj.clone())),
+        }
+    }
+}
+
+impl Hash for RecordKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_string().hash(state);
+    }
+}
+
+impl From<i64> for RecordKey {
+    fn from(n: i64) -> Self {
+        RecordKey::Number(n)
+    }
+}
+
+/// Numbers beyond `i64::MAX` can't be numeric keys in SurrealDB, so they are kept as strings.
+impl From<u64> for RecordKey {
+    fn from(n: u64) -> Self {
+        i64::try_from(n).map_or_else(|_| RecordKey::String(n.to_string()), RecordKey::Number)
+    }
+}
+
+impl From<String> for RecordKey {
+    fn from(s: String) -> Self {
+        RecordKey::String(s)
+    }
+}
+
+impl From<&str> for RecordKey {
+    fn from(s: &str) -> Self {
+        RecordKey::String(s.to_string())
+    }
+}
+
+impl From<uuid::Uuid> for RecordKey {
+    fn from(uuid: uuid::Uuid) -> Self {
+        RecordKey::String(uuid.to_string())
+    }
+}
+
+/// A typed reference to a record in the table modelled by `T`.
+///
+/// `T` is only a marker, so ids can be passed around without the record itself.
+/// Generated code wraps this in a per-table newtype (`UserId`) so ids of different tables can't be mixed up.
+pub struct RecordId<T> {
+    table: String,
+    key: RecordKey,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// A record link that has not been fetched, as it appears in a selected field.
+pub type RecordLink<T> = RecordId<T>;
+
+/// A record link that may or may not have been fetched, e.g. when the fields to FETCH are a
+/// parameter of the query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Link<T> {
+    Record(T),
+    Id(RecordId<T>),
+}
+
+impl<T> RecordId<T> {
+    pub fn new(table: impl Into<String>, key: impl Into<RecordKey>) -> Self {
+        Self {
+            table: table.into(),
+            key: key.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The name of the table this record belongs to.
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// The key identifying this record within its table.
+    pub fn key(&self) -> &RecordKey {
+        &self.key
+    }
+
+    /// Splits the id into its table and key.
+    pub fn into_parts(self) -> (String, RecordKey) {
+        (self.table, self.key)
+    }
+
+    /// Parses a `table:key` id, ensuring it belongs to `table`.
+    pub fn parse_in(table: &str, s: &str) -> Result<Self, RecordIdError> {
+        s.parse::<Self>()?.in_table(table)
+    }
+
+    /// Ensures the id belongs to `table`, e.g. after deserializing it from either of its shapes.
+    pub fn in_table(self, table: &str) -> Result<Self, RecordIdError> {
+        if self.table != table {
+            return Err(RecordIdError::WrongTable(
+                self.to_string(),
+                self.table,
+                table.to_string(),
+            ));
+        }
+        Ok(self)
+    }
+}
+
+impl<T> TryFrom<Thing> for RecordId<T> {
+    type Error = RecordIdError;
+
+    fn try_from(thing: Thing) -> Result<Self, Self::Error> {
+        let raw = thing.to_string();
+        let key = RecordKey::from_id(thing.id)
+            .ok_or_else(|| RecordIdError::InvalidKey(raw, "concrete".to_string()))?;
+        Ok(Self::new(thing.tb, key))
+    }
+}
+
+/// The id as SurrealDB's `Thing`, e.g. for binding it as a query parameter.
+impl<T> From<&RecordId<T>> for Thing {
+    fn from(id: &RecordId<T>) -> Self {
+        let key = match &id.key {
+            RecordKey::Number(n) => Id::Number(*n),
+            RecordKey::String(s) => Id::String(s.clone()),
+            RecordKey::Array(arr) => match surrealdb::sql::to_value(arr) {
+                Ok(Value::Array(arr)) => Id::Array(arr),
+                _ => Id::String(id.key.to_string()),
+            },
+            RecordKey::Object(obj) => match surrealdb::sql::to_value(obj) {
+                Ok(Value::Object(obj)) => Id::Object(obj),
+                _ => Id::String(id.key.to_string()),
+            },
+        };
+        Thing::from((id.table.clone(), key))
+    }
+}
+
+impl<T> FromStr for RecordId<T> {
+    type Err = RecordIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.contains(':') {
+            return Err(RecordIdError::MissingSeparator(s.to_string()));
+        }
+        let thing = surrealdb::sql::thing(s)
+            .map_err(|e| RecordIdError::InvalidSyntax(s.to_string(), e.to_string()))?;
+        Self::try_from(thing)
+    }
+}
+
+impl<T> fmt::Display for RecordId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            RecordKey::String(key) if !is_plain_key(key) => {
+                write!(f, "{}:⟨{}⟩", self.table, key)
+            }
+            key => write!(f, "{}:{}", self.table, key),
+        }
+    }
+}
+
+/// SurrealDB escapes string keys that aren't plain identifiers (e.g. uuids) with `⟨⟩`.
+fn is_plain_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !key.chars().all(|c| c.is_ascii_digit())
+}
+
+impl<T> fmt::Debug for RecordId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RecordId({})", self)
+    }
+}
+
+impl<T> Clone for RecordId<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.table.clone(), self.key.clone())
+    }
+}
+
+impl<T> PartialEq for RecordId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.table == other.table && self.key == other.key
+    }
+}
+
+impl<T> Eq for RecordId<T> {}
+
+impl<T> Hash for RecordId<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.table.hash(state);
+        self.key.hash(state);
+    }
+}
+
+impl<T> Serialize for RecordId<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Serializes a record id as SurrealDB's `Thing`, for `#[serde(serialize_with)]` on query
+/// parameters, which would otherwise be bound as the string `table:key`.
+pub fn serialize_thing<T, S: Serializer>(
+    id: &RecordId<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    Thing::from(id).serialize(serializer)
+}
+
+/// Serializes record ids as SurrealDB's `Thing`s, like [serialize_thing].
+pub fn serialize_things<T, S: Serializer>(
+    ids: &[RecordId<T>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(ids.iter().map(Thing::from))
+}
+
+/// The shapes a record id can arrive in: the `table:key` string used by JSON responses,
+/// or SurrealDB's `Thing` object, whose key may or may not be tagged with its kind.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ThingRepr {
+    String(String),
+    Thing { tb: String, id: ThingKey },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ThingKey {
+    Tagged(TaggedKey),
+    Plain(JsonValue),
+}
+
+#[derive(Deserialize)]
+enum TaggedKey {
+    Number(i64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Map<String, JsonValue>),
+}
+
+impl<'de, T> Deserialize<'de> for RecordId<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match ThingRepr::deserialize(deserializer)? {
+            ThingRepr::String(s) => s.parse().map_err(de::Error::custom),
+            ThingRepr::Thing { tb, id } => {
+                let key = match id {
+                    ThingKey::Tagged(TaggedKey::Number(n)) => RecordKey::Number(n),
+                    ThingKey::Tagged(TaggedKey::String(s)) => RecordKey::String(s),
+                    ThingKey::Tagged(TaggedKey::Array(arr)) => RecordKey::Array(arr),
+                    ThingKey::Tagged(TaggedKey::Object(obj)) => RecordKey::Object(obj),
+                    ThingKey::Plain(JsonValue::Number(n)) => n
+                        .as_i64()
+                        .map(RecordKey::Number)
+                        .ok_or_else(|| de::Error::custom("record keys must be integers"))?,
+                    ThingKey::Plain(JsonValue::String(s)) => RecordKey::String(s),
+                    ThingKey::Plain(JsonValue::Array(arr)) => RecordKey::Array(arr),
+                    ThingKey::Plain(JsonValue::Object(obj)) => RecordKey::Object(obj),
+                    ThingKey::Plain(other) => {
+                        return Err(de::Error::custom(format!("invalid record key: {}", other)))
+                    }
+                };
+                Ok(Self::new(tb, key))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn deserializes_strings_and_things() {
+        let from_string: RecordId<()> = serde_json::from_value(json!("user:ann")).unwrap();
+        let from_thing: RecordId<()> =
+            serde_json::from_value(json!({ "tb": "user", "id": { "String": "ann" } })).unwrap();
+        let from_plain_thing: RecordId<()> =
+            serde_json::from_value(json!({ "tb": "user", "id": "ann" })).unwrap();
+
+        assert_eq!(from_string, RecordId::new("user", "ann"));
+        assert_eq!(from_thing, from_string);
+        assert_eq!(from_plain_thing, from_string);
+        assert_eq!(Thing::from(&from_string).to_string(), "user:ann");
+        assert!(matches!(
+            from_thing.in_table("post"),
+            Err(RecordIdError::WrongTable(..))
+        ));
+    }
+
+    #[test]
+    fn parses_and_displays_ids() {
+        let ann: RecordId<()> = "user:ann".parse().unwrap();
+        assert_eq!(ann.table(), "user");
+        assert_eq!(ann.key(), &RecordKey::String("ann".to_string()));
+        assert_eq!(ann.to_string(), "user:ann");
+
+        // Keys that aren't plain identifiers are escaped, so they parse back as the same key.
+        let numeric: RecordId<()> = "user:1".parse().unwrap();
+        let digits = RecordId::<()>::new("user", "1");
+        assert_eq!(numeric.key(), &RecordKey::Number(1));
+        assert_eq!(digits.to_string(), "user:⟨1⟩");
+        assert_eq!(digits.to_string().parse::<RecordId<()>>().unwrap(), digits);
+        let uuid = RecordId::<()>::new("user", uuid::Uuid::nil());
+        assert_eq!(uuid.to_string().parse::<RecordId<()>>().unwrap(), uuid);
+
+        assert!(matches!(
+            "ann".parse::<RecordId<()>>(),
+            Err(RecordIdError::MissingSeparator(_))
+        ));
+        assert!(matches!(
+            RecordId::<()>::parse_in("post", "user:ann"),
+            Err(RecordIdError::WrongTable(..))
+        ));
+    }
+
+    #[test]
+    fn links_are_ids_or_records() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct User {
+            name: String,
+        }
+
+        let id: Link<User> = serde_json::from_value(json!("user:ann")).unwrap();
+        let record: Link<User> =
+            serde_json::from_value(json!({ "id": "user:ann", "name": "Ann" })).unwrap();
+
+        assert_eq!(id, Link::Id(RecordId::new("user", "ann")));
+        assert_eq!(
+            record,
+            Link::Record(User {
+                name: "Ann".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Formatting `.surql` files, and the queries of `build_query!` invocations in Rust files, in
+//! place.
+
+use surrealix_core::format::{format_query, FormatError};
+
+/// The macro whose query literals are formatted.
+const MACRO: &str = "build_query!";
+
+/// Formats a `.surql` file, which holds a single query.
+pub fn format_surql(source: &str) -> Result<String, FormatError> {
+    let mut formatted = format_query(source)?;
+    formatted.push('\n');
+    Ok(formatted)
+}
+
+/// Formats the query literal of each `build_query!` invocation in a Rust file, leaving the
+/// rest of the file as it is.
+///
+/// A single-line query stays a plain string. Longer ones become raw strings, indented like
+/// the literal they replace.
+pub fn format_rust(source: &str) -> Result<String, FormatError> {
+    let mut out = String::with_capacity(source.len());
+    let mut rest = 0;
+    for literal in query_literals(source) {
+        let indent = indentation(source, literal.start);
+        let replacement = encode(&format_query(&literal.value)?, indent);
+        out.push_str(&source[rest..literal.start]);
+        out.push_str(&replacement);
+        rest = literal.end;
+    }
+    out.push_str(&source[rest..]);
+    Ok(out)
+}
+
+/// A string literal in Rust source, by its byte range and the string it holds.
+#[derive(Debug, PartialEq)]
+struct Literal {
+    start: usize,
+    end: usize,
+    value: String,
+}
+
+/// The query of each `build_query!` invocation, which is its last string literal, as the
+/// options before it may hold strings too.
+fn query_literals(source: &str) -> Vec<Literal> {
+    let mut literals = vec![];
+    let mut from = 0;
+    while let Some(found) = source[from..].find(MACRO) {
+        let body = from + found + MACRO.len();
+        let (end, mut strings) = invocation_strings(source, body);
+        if let Some(query) = strings.pop() {
+            literals.push(query);
+        }
+        from = end.max(body);
+    }
+    literals
+}
+
+/// The string literals of the invocation whose delimited body starts at or after `start`,
+/// and where the invocation ends.
+fn invocation_strings(source: &str, start: usize) -> (usize, Vec<Literal>) {
+    let bytes = source.as_bytes();
+    let mut strings = vec![];
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'(' | b'[' => depth += 1,
+            b'}' | b')' | b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return (i + 1, strings);
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = source[i..].find('\n').map_or(bytes.len(), |end| i + end);
+                continue;
+            }
+            b'"' | b'r' if depth > 0 => {
+                if let Some(literal) = string_literal(source, i) {
+                    i = literal.end;
+                    strings.push(literal);
+                    continue;
+                }
+            }
+            c if depth == 0 && !c.is_ascii_whitespace() => return (i, strings),
+            _ => {}
+        }
+        i += 1;
+    }
+    (bytes.len(), strings)
+}
+
+/// The plain or raw string literal starting at `start`, if there is one.
+fn string_literal(source: &str, start: usize) -> Option<Literal> {
+    let rest = &source[start..];
+    if let Some(raw) = rest.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let content = raw[hashes..].strip_prefix('"')?;
+        let closing = format!("\"{}", "#".repeat(hashes));
+        let len = content.find(&closing)?;
+        return Some(Literal {
+            start,
+            end: start + 1 + hashes + 1 + len + closing.len(),
+            value: content[..len].to_string(),
+        });
+    }
+
+    let mut value = String::new();
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '"' => {
+                return Some(Literal {
+                    start,
+                    end: start + offset + 1,
+                    value,
+                })
+            }
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                'r' => value.push('\r'),
+                '0' => value.push('\0'),
+                // A line continuation skips the line break and the indentation after it.
+                '\n' => {
+                    let skipped = rest[offset + 2..]
+                        .chars()
+                        .take_while(|c| c.is_whitespace())
+                        .count();
+                    for _ in 0..skipped {
+                        chars.next();
+                    }
+                }
+                escaped => value.push(escaped),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// The whitespace the line containing `offset` starts with.
+fn indentation(source: &str, offset: usize) -> &str {
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = &source[line_start..offset];
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// The literal for a formatted query.
+fn encode(query: &str, indent: &str) -> String {
+    if !query.contains('\n') {
+        return format!("\"{}\"", query.replace('\\', "\\\\").replace('"', "\\\""));
+    }
+
+    let mut hashes = 1;
+    while query.contains(&format!("\"{}", "#".repeat(hashes))) {
+        hashes += 1;
+    }
+    let hashes = "#".repeat(hashes);
+    let mut literal = format!("r{}\"\n", hashes);
+    for line in query.lines() {
+        literal.push_str(indent);
+        literal.push_str(line);
+        literal.push('\n');
+    }
+    literal.push_str(indent);
+    literal.push('"');
+    literal.push_str(&hashes);
+    literal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_query_literals() {
+        let source = r##"
+build_query! {
+    AdultUsers,
+    shared_types = "crate::models",
+    "select name from user where age > 18"
+}
+
+build_query! {
+    Both,
+    r#"select name from user; select * from post"#
+}
+"##;
+
+        let formatted = format_rust(source).unwrap();
+
+        assert_eq!(
+            formatted,
+            r##"
+build_query! {
+    AdultUsers,
+    shared_types = "crate::models",
+    "SELECT name FROM user WHERE age > 18;"
+}
+
+build_query! {
+    Both,
+    r#"
+    SELECT name FROM user;
+    SELECT * FROM post;
+    "#
+}
+"##
+        );
+        assert_eq!(format_rust(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn reads_escaped_strings() {
+        let source = "build_query!(Q, \"SELECT * FROM user \\\n    WHERE name = \\\"a\\\"\");";
+
+        let literals = query_literals(source);
+
+        assert_eq!(literals.len(), 1);
+        assert_eq!(literals[0].value, "SELECT * FROM user WHERE name = \"a\"");
+        assert_eq!(literals[0].end, source.len() - 2);
+    }
+}
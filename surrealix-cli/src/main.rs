@@ -0,0 +1,329 @@
+mod fmt;
+mod stubs;
+
+use std::{
+    env,
+    io::Read,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand};
+use stubs::Lang;
+use surrealdb::sql::{parse, Query};
+use surrealix_core::{
+    analyzer::analyze as analyze_query, errors::SchemaError, json::analyze_json, lint::lint_schema,
+    mixin::expand_mixins, source::embedded_schema, usage::unused,
+};
+
+#[derive(Parser)]
+#[command(version, about = "Tooling for projects using surrealix")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Flags suspicious definitions in the schema.
+    Lint {
+        /// The schema to lint, instead of the one `.env` names, or `-` for stdin.
+        schema: Option<PathBuf>,
+    },
+    /// Lists the tables and fields that no recorded query references.
+    ///
+    /// Queries are recorded by `build_query!` when `SURREALIX_QUERY_REGISTRY` is set.
+    Unused {
+        /// The schema to check, instead of the one `.env` names, or `-` for stdin.
+        schema: Option<PathBuf>,
+        /// The registry of recorded queries, instead of `SURREALIX_QUERY_REGISTRY` from `.env`.
+        #[arg(long)]
+        registry: Option<PathBuf>,
+    },
+    /// Prints the type of each statement of a query as JSON, for tools outside of Rust.
+    Analyze {
+        /// The `.surql` file holding the query.
+        query: PathBuf,
+        /// The schema to analyze against, instead of the one `.env` names, or `-` for stdin.
+        #[arg(long)]
+        schema: Option<PathBuf>,
+    },
+    /// Prints model stubs for the results of a query, for clients in other languages.
+    Stubs {
+        /// The `.surql` file holding the query.
+        query: PathBuf,
+        #[arg(long, value_enum)]
+        lang: Lang,
+        /// The name the models are given, instead of the name of the query file.
+        #[arg(long)]
+        name: Option<String>,
+        /// The schema to analyze against, instead of the one `.env` names, or `-` for stdin.
+        #[arg(long)]
+        schema: Option<PathBuf>,
+    },
+    /// Formats the queries of `.surql` files and of `build_query!` invocations in Rust files.
+    Fmt {
+        /// The files to format, with directories searched for `.surql` and `.rs` files.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Lists the files that would change instead of rewriting them.
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Lint { schema } => lint(schema),
+        Command::Unused { schema, registry } => report_unused(schema, registry),
+        Command::Analyze { query, schema } => analyze(query, schema),
+        Command::Stubs {
+            query,
+            lang,
+            name,
+            schema,
+        } => print_stubs(query, lang, name, schema),
+        Command::Fmt { paths, check } => format_files(paths, check),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn lint(schema: Option<PathBuf>) -> Result<ExitCode, String> {
+    let schema = load_schema(schema)?;
+    let lints = lint_schema(&schema).map_err(|e| e.to_string())?;
+
+    for lint in &lints {
+        println!("warning: {}", lint);
+    }
+
+    if lints.is_empty() {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!("\n{} warning(s) in the schema", lints.len());
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+fn report_unused(schema: Option<PathBuf>, registry: Option<PathBuf>) -> Result<ExitCode, String> {
+    let schema = load_schema(schema)?;
+    let registry = match registry {
+        Some(registry) => registry,
+        None => registry_path(),
+    };
+    let queries = load_queries(&registry)?;
+    if queries.is_empty() {
+        return Err(format!(
+            "no queries recorded in {}; set SURREALIX_QUERY_REGISTRY and rebuild",
+            registry.display()
+        ));
+    }
+
+    let unused = unused(&schema, &queries).map_err(|e| e.to_string())?;
+    for item in &unused {
+        println!("unused: {}", item);
+    }
+
+    if unused.is_empty() {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!(
+            "\n{} unused item(s) across {} recorded queries",
+            unused.len(),
+            queries.len()
+        );
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+fn analyze(query: PathBuf, schema: Option<PathBuf>) -> Result<ExitCode, String> {
+    let schema = load_schema(schema)?;
+    let query = load_query(&query)?;
+
+    let analysis = analyze_json(schema, query).map_err(|e| e.to_string())?;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&analysis).map_err(|e| e.to_string())?
+    );
+
+    // The analysis is printed either way, but a query that couldn't be typed still fails.
+    if analysis.get("error").is_some() {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+fn print_stubs(
+    query: PathBuf,
+    lang: Lang,
+    name: Option<String>,
+    schema: Option<PathBuf>,
+) -> Result<ExitCode, String> {
+    let name = match name {
+        Some(name) => name,
+        None => query
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or_else(|| format!("{} has no file name to name models by", query.display()))?,
+    };
+    let schema = load_schema(schema)?;
+    let query = load_query(&query)?;
+
+    let types = analyze_query(schema, query).map_err(|e| e.to_string())?;
+    print!("{}", stubs::generate(lang, &name, &types));
+    Ok(ExitCode::SUCCESS)
+}
+
+fn format_files(paths: Vec<PathBuf>, check: bool) -> Result<ExitCode, String> {
+    let mut files = vec![];
+    for path in paths {
+        collect_sources(path, &mut files)?;
+    }
+
+    let mut failed = false;
+    for path in &files {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let formatted = if path.extension().is_some_and(|ext| ext == "rs") {
+            fmt::format_rust(&source)
+        } else {
+            fmt::format_surql(&source)
+        };
+        let formatted = match formatted {
+            Ok(formatted) => formatted,
+            // One query that can't be formatted doesn't stop the others from being.
+            Err(e) => {
+                eprintln!("error: {}: {}", path.display(), e);
+                failed = true;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+
+        if check {
+            println!("would reformat: {}", path.display());
+            failed = true;
+        } else {
+            std::fs::write(path, formatted)
+                .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+            println!("formatted: {}", path.display());
+        }
+    }
+
+    if failed {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Collects the `.surql` and `.rs` files under `path`, or `path` itself if it is a file.
+fn collect_sources(path: PathBuf, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    if !path.is_dir() {
+        files.push(path);
+        return Ok(());
+    }
+
+    let entries = std::fs::read_dir(&path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.is_dir() {
+            // Build output holds copies of sources that aren't worth formatting.
+            if path.file_name().map_or(true, |name| name != "target") {
+                collect_sources(path, files)?;
+            }
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == "surql" || ext == "rs")
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn load_query(path: &Path) -> Result<Query, String> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    parse(&source).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Parses every `.surql` file in the registry.
+fn load_queries(registry: &Path) -> Result<Vec<Query>, String> {
+    let entries = std::fs::read_dir(registry)
+        .map_err(|e| format!("failed to read {}: {}", registry.display(), e))?;
+
+    let mut queries = vec![];
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().map_or(true, |ext| ext != "surql") {
+            continue;
+        }
+        let query = std::fs::read_to_string(&path)
+            .map_err(|e| SchemaError::FileReadError(e).to_string())?;
+        queries.push(parse(&query).map_err(|e| format!("{}: {}", path.display(), e))?);
+    }
+    Ok(queries)
+}
+
+/// Reads the schema, found the same way the macros find it when no path is given, and expands
+/// its mixins as they do. A path of `-` reads the schema from stdin.
+fn load_schema(path: Option<PathBuf>) -> Result<Query, String> {
+    let schema = match path {
+        Some(path) if path == Path::new("-") => {
+            let mut schema = String::new();
+            std::io::stdin()
+                .read_to_string(&mut schema)
+                .map_err(|e| SchemaError::FileReadError(e).to_string())?;
+            schema
+        }
+        Some(path) => read_schema(&path)?,
+        None => match embedded() {
+            Some(schema) => schema?,
+            None => read_schema(&schema_path().map_err(|e| e.to_string())?)?,
+        },
+    };
+    let schema = expand_mixins(&schema).map_err(|e| SchemaError::from(e).to_string())?;
+    parse(&schema).map_err(|e| e.to_string())
+}
+
+fn read_schema(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| SchemaError::FileReadError(e).to_string())
+}
+
+/// The schema embedded in `SURREALIX_SCHEMA` or `SURREALIX_SCHEMA_BASE64`, which take
+/// precedence over `SURREALIX_SCHEMA_PATH`.
+fn embedded() -> Option<Result<String, String>> {
+    dotenv::dotenv().ok();
+    embedded_schema("", |var| env::var(var).ok())
+        .map_err(|e| e.to_string())
+        .transpose()
+}
+
+/// The schema named by `SURREALIX_SCHEMA_PATH`, relative to the current directory.
+fn schema_path() -> Result<PathBuf, SchemaError> {
+    // A missing `.env` is fine, as long as the variable is set some other way.
+    dotenv::dotenv().ok();
+    let path = env::var("SURREALIX_SCHEMA_PATH")
+        .map_err(|_| SchemaError::EnvVarNotSet("SURREALIX_SCHEMA_PATH".to_string()))?;
+    Ok(PathBuf::from(path))
+}
+
+/// The registry named by `SURREALIX_QUERY_REGISTRY`, defaulting to `.surrealix`.
+fn registry_path() -> PathBuf {
+    dotenv::dotenv().ok();
+    let path = env::var("SURREALIX_QUERY_REGISTRY").unwrap_or_else(|_| ".surrealix".to_string());
+    PathBuf::from(path)
+}
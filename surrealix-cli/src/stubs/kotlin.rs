@@ -0,0 +1,134 @@
+//! `kotlinx.serialization` data classes.
+
+use super::{property_name, scalar_type, Backend, Class, JsonScalar, Type};
+
+/// Hard keywords, which can only be property names in backticks.
+const KEYWORDS: &[&str] = &[
+    "as",
+    "break",
+    "class",
+    "continue",
+    "do",
+    "else",
+    "false",
+    "for",
+    "fun",
+    "if",
+    "in",
+    "interface",
+    "is",
+    "null",
+    "object",
+    "package",
+    "return",
+    "super",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typealias",
+    "typeof",
+    "val",
+    "var",
+    "when",
+    "while",
+];
+
+pub(super) struct Kotlin;
+
+impl Backend for Kotlin {
+    fn header(&self, uses_json: bool) -> String {
+        let mut header = String::from(
+            "import kotlinx.serialization.SerialName\nimport kotlinx.serialization.Serializable\n",
+        );
+        if uses_json {
+            header.push_str("import kotlinx.serialization.json.JsonElement\n");
+        }
+        header
+    }
+
+    fn class(&self, class: &Class) -> String {
+        let mut out = format!("@Serializable\ndata class {}(\n", class.name);
+        for field in &class.fields {
+            let property = property_name(&field.name);
+            if property != field.name {
+                out.push_str(&format!("    @SerialName(\"{}\")\n", escape(&field.name)));
+            }
+            let property = if KEYWORDS.contains(&property.as_str()) {
+                format!("`{}`", property)
+            } else {
+                property
+            };
+            let default = match field.ty {
+                Type::Optional(_) => " = null",
+                _ => "",
+            };
+            out.push_str(&format!(
+                "    val {}: {}{},\n",
+                property,
+                type_name(&field.ty),
+                default
+            ));
+        }
+        out.push_str(")\n");
+        out
+    }
+
+    fn alias(&self, name: &str, ty: &Type) -> String {
+        format!("typealias {} = {}\n", name, type_name(ty))
+    }
+}
+
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Scalar(scalar) => match scalar_type(scalar) {
+            Some(JsonScalar::String) => "String".to_string(),
+            Some(JsonScalar::Integer) => "Long".to_string(),
+            Some(JsonScalar::Float) => "Double".to_string(),
+            Some(JsonScalar::Boolean) => "Boolean".to_string(),
+            None => "JsonElement".to_string(),
+        },
+        Type::Class(name) => name.clone(),
+        Type::List(inner) => format!("List<{}>", type_name(inner)),
+        Type::Optional(inner) => format!("{}?", type_name(inner)),
+        Type::Json => "JsonElement".to_string(),
+    }
+}
+
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{tests::stubs, Lang};
+
+    #[test]
+    fn data_classes() {
+        let kotlin = stubs(Lang::Kotlin, "SELECT * FROM user");
+
+        assert!(kotlin.contains("import kotlinx.serialization.json.JsonElement\n"));
+        assert!(kotlin
+            .contains("@Serializable\ndata class AdultUsersAddress(\n    val city: String,\n)\n"));
+        assert!(kotlin.contains("@Serializable\ndata class AdultUsers(\n"));
+        assert!(kotlin.contains("    val address: AdultUsersAddress,\n"));
+        assert!(kotlin.contains("    val age: Long,\n"));
+        assert!(kotlin.contains("    @SerialName(\"best_friend\")\n    val bestFriend: String,\n"));
+        assert!(kotlin.contains("    val nickname: String? = null,\n"));
+        assert!(kotlin.contains("    val settings: JsonElement,\n"));
+    }
+
+    #[test]
+    fn aliases_non_objects() {
+        let kotlin = stubs(
+            Lang::Kotlin,
+            "SELECT VALUE name FROM user; SELECT VALUE age FROM ONLY user:john",
+        );
+
+        assert!(!kotlin.contains("JsonElement"));
+        assert!(kotlin.contains("typealias AdultUsers1 = List<String>\n"));
+        assert!(kotlin.contains("typealias AdultUsers2 = Long\n"));
+    }
+}
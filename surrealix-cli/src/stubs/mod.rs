@@ -0,0 +1,217 @@
+//! Model stubs for query results in languages other than Rust, for clients that share the same
+//! database, such as mobile apps.
+//!
+//! Each object in a result becomes a class named after the query and the path to it, e.g.
+//! `AdultUsersAddress` for the `address` of a row of `adult_users`. Results that aren't
+//! objects, like `SELECT VALUE name`, become an alias of their type instead.
+
+mod kotlin;
+mod swift;
+
+use clap::ValueEnum;
+use convert_case::{Case, Casing};
+use surrealix_core::ast::{ScalarType, TypeAST};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    /// `kotlinx.serialization` data classes.
+    Kotlin,
+    /// `Codable` structs.
+    Swift,
+}
+
+/// A class for an object in a result, with its fields sorted by name.
+struct Class {
+    name: String,
+    fields: Vec<Field>,
+}
+
+struct Field {
+    /// The name of the field in the result.
+    name: String,
+    ty: Type,
+}
+
+/// The type of a field, as far as other languages can express it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Type {
+    Scalar(ScalarType),
+    /// A generated class.
+    Class(String),
+    List(Box<Type>),
+    Optional(Box<Type>),
+    /// Any JSON value, for types without an equivalent, like unions.
+    Json,
+}
+
+/// Renders the classes and aliases of one language.
+trait Backend {
+    /// The imports and shared definitions that precede the models.
+    fn header(&self, uses_json: bool) -> String;
+    fn class(&self, class: &Class) -> String;
+    fn alias(&self, name: &str, ty: &Type) -> String;
+}
+
+/// Generates stubs for a query named `name`, whose statements have the given types.
+///
+/// A query with several statements gets a numbered model for each, like `QueryResultN` in Rust.
+pub fn generate(lang: Lang, name: &str, statements: &[TypeAST]) -> String {
+    let backend: &dyn Backend = match lang {
+        Lang::Kotlin => &kotlin::Kotlin,
+        Lang::Swift => &swift::Swift,
+    };
+    let base = name.to_case(Case::Pascal);
+
+    let mut classes = vec![];
+    let mut aliases = vec![];
+    for (index, ast) in statements.iter().enumerate() {
+        let name = match statements.len() {
+            1 => base.clone(),
+            _ => format!("{}{}", base, index + 1),
+        };
+        let ty = collect(&name, ast, &mut classes);
+        if !classes.iter().any(|class| class.name == name) {
+            aliases.push((name, ty));
+        }
+    }
+
+    let uses_json = classes
+        .iter()
+        .flat_map(|class| class.fields.iter().map(|field| &field.ty))
+        .chain(aliases.iter().map(|(_, ty)| ty))
+        .any(uses_json);
+
+    let mut out = backend.header(uses_json);
+    for class in &classes {
+        out.push('\n');
+        out.push_str(&backend.class(class));
+    }
+    for (name, ty) in &aliases {
+        out.push('\n');
+        out.push_str(&backend.alias(name, ty));
+    }
+    out
+}
+
+/// The type of `ast`, adding a class named `name` for it if it is an object.
+fn collect(name: &str, ast: &TypeAST, classes: &mut Vec<Class>) -> Type {
+    match ast {
+        TypeAST::Scalar(scalar) => Type::Scalar(scalar.clone()),
+        TypeAST::Object(obj) if obj.fields.is_empty() => Type::Json,
+        TypeAST::Object(obj) => {
+            let mut names: Vec<_> = obj.fields.keys().collect();
+            names.sort();
+            let fields = names
+                .into_iter()
+                .map(|field| Field {
+                    name: field.clone(),
+                    ty: collect(
+                        &format!("{}{}", name, field.to_case(Case::Pascal)),
+                        &obj.fields[field].ast,
+                        classes,
+                    ),
+                })
+                .collect();
+            classes.push(Class {
+                name: name.to_string(),
+                fields,
+            });
+            Type::Class(name.to_string())
+        }
+        // Array lengths aren't part of the type in either language.
+        TypeAST::Array(inner) => Type::List(Box::new(collect(name, &inner.0, classes))),
+        TypeAST::Option(inner) => Type::Optional(Box::new(collect(name, inner, classes))),
+        // Record ids are `table:key` strings in JSON.
+        TypeAST::Record(_) => Type::Scalar(ScalarType::String),
+        TypeAST::Union(_) => Type::Json,
+    }
+}
+
+fn uses_json(ty: &Type) -> bool {
+    match ty {
+        Type::Json => true,
+        Type::Scalar(scalar) => scalar_type(scalar).is_none(),
+        Type::List(inner) | Type::Optional(inner) => uses_json(inner),
+        Type::Class(_) => false,
+    }
+}
+
+/// The JSON type a scalar has in results, if it is a plain string, number or bool.
+///
+/// Datetimes, durations and uuids are all strings in JSON, and left for the client to parse.
+fn scalar_type(scalar: &ScalarType) -> Option<JsonScalar> {
+    match scalar {
+        ScalarType::String | ScalarType::Datetime | ScalarType::Duration | ScalarType::Uuid => {
+            Some(JsonScalar::String)
+        }
+        ScalarType::Integer => Some(JsonScalar::Integer),
+        ScalarType::Number | ScalarType::Float => Some(JsonScalar::Float),
+        ScalarType::Boolean => Some(JsonScalar::Boolean),
+        ScalarType::Point
+        | ScalarType::Geometry
+        | ScalarType::Set
+        | ScalarType::Bytes
+        | ScalarType::Any
+        | ScalarType::Null => None,
+    }
+}
+
+enum JsonScalar {
+    String,
+    Integer,
+    Float,
+    Boolean,
+}
+
+/// A property name for a field, in camelCase, which isn't necessarily a valid identifier.
+fn property_name(field: &str) -> String {
+    let name = field.to_case(Case::Camel);
+    match name.chars().next() {
+        Some(first) if first.is_alphabetic() || first == '_' => name,
+        _ => format!("_{}", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::sql::parse;
+    use surrealix_core::analyzer::analyze;
+
+    pub(super) fn stubs(lang: Lang, query: &str) -> String {
+        let schema = parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD age ON user TYPE int;
+                DEFINE FIELD nickname ON user TYPE option<string>;
+                DEFINE FIELD address ON user TYPE object;
+                    DEFINE FIELD address.city ON user TYPE string;
+                DEFINE FIELD best_friend ON user TYPE record<user>;
+                DEFINE FIELD settings ON user TYPE any;
+            "#,
+        )
+        .unwrap();
+        let types = analyze(schema, parse(query).unwrap()).unwrap();
+        generate(lang, "adult_users", &types)
+    }
+
+    #[test]
+    fn names_classes_by_path() {
+        let schema = parse("DEFINE TABLE user SCHEMAFULL; DEFINE FIELD address ON user TYPE object; DEFINE FIELD address.city ON user TYPE string;").unwrap();
+        let types = analyze(schema, parse("SELECT * FROM user").unwrap()).unwrap();
+
+        let mut classes = vec![];
+        let ty = collect("AdultUsers", &types[0], &mut classes);
+
+        assert_eq!(ty, Type::List(Box::new(Type::Class("AdultUsers".into()))));
+        let names: Vec<_> = classes.iter().map(|class| class.name.as_str()).collect();
+        assert_eq!(names, vec!["AdultUsersAddress", "AdultUsers"]);
+    }
+
+    #[test]
+    fn names_properties() {
+        assert_eq!(property_name("best_friend"), "bestFriend");
+        assert_eq!(property_name("2fa"), "_2fa");
+    }
+}
@@ -0,0 +1,206 @@
+//! `Codable` structs.
+
+use super::{property_name, scalar_type, Backend, Class, JsonScalar, Type};
+
+/// Keywords, which can only be property names in backticks.
+const KEYWORDS: &[&str] = &[
+    "as",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "continue",
+    "default",
+    "defer",
+    "do",
+    "else",
+    "enum",
+    "extension",
+    "fallthrough",
+    "false",
+    "for",
+    "func",
+    "guard",
+    "if",
+    "import",
+    "in",
+    "init",
+    "inout",
+    "internal",
+    "is",
+    "let",
+    "nil",
+    "operator",
+    "private",
+    "protocol",
+    "public",
+    "repeat",
+    "return",
+    "self",
+    "static",
+    "struct",
+    "subscript",
+    "super",
+    "switch",
+    "throw",
+    "throws",
+    "true",
+    "try",
+    "typealias",
+    "var",
+    "where",
+    "while",
+];
+
+/// Foundation has no type for arbitrary JSON, so one is generated when a result needs it.
+const JSON_VALUE: &str = r#"
+enum JSONValue: Codable {
+    case null
+    case bool(Bool)
+    case number(Double)
+    case string(String)
+    case array([JSONValue])
+    case object([String: JSONValue])
+
+    init(from decoder: Decoder) throws {
+        let container = try decoder.singleValueContainer()
+        if container.decodeNil() {
+            self = .null
+        } else if let value = try? container.decode(Bool.self) {
+            self = .bool(value)
+        } else if let value = try? container.decode(Double.self) {
+            self = .number(value)
+        } else if let value = try? container.decode(String.self) {
+            self = .string(value)
+        } else if let value = try? container.decode([JSONValue].self) {
+            self = .array(value)
+        } else {
+            self = .object(try container.decode([String: JSONValue].self))
+        }
+    }
+
+    func encode(to encoder: Encoder) throws {
+        var container = encoder.singleValueContainer()
+        switch self {
+        case .null: try container.encodeNil()
+        case .bool(let value): try container.encode(value)
+        case .number(let value): try container.encode(value)
+        case .string(let value): try container.encode(value)
+        case .array(let value): try container.encode(value)
+        case .object(let value): try container.encode(value)
+        }
+    }
+}
+"#;
+
+pub(super) struct Swift;
+
+impl Backend for Swift {
+    fn header(&self, uses_json: bool) -> String {
+        let mut header = String::from("import Foundation\n");
+        if uses_json {
+            header.push_str(JSON_VALUE);
+        }
+        header
+    }
+
+    fn class(&self, class: &Class) -> String {
+        let properties: Vec<_> = class
+            .fields
+            .iter()
+            .map(|field| (property_name(&field.name), field))
+            .collect();
+
+        let mut out = format!("struct {}: Codable {{\n", class.name);
+        for (property, field) in &properties {
+            out.push_str(&format!(
+                "    let {}: {}\n",
+                identifier(property),
+                type_name(&field.ty)
+            ));
+        }
+
+        // Coding keys are only needed when a property is named differently from its field.
+        if properties
+            .iter()
+            .any(|(property, field)| *property != field.name)
+        {
+            out.push_str("\n    enum CodingKeys: String, CodingKey {\n");
+            for (property, field) in &properties {
+                if *property == field.name {
+                    out.push_str(&format!("        case {}\n", identifier(property)));
+                } else {
+                    out.push_str(&format!(
+                        "        case {} = \"{}\"\n",
+                        identifier(property),
+                        escape(&field.name)
+                    ));
+                }
+            }
+            out.push_str("    }\n");
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn alias(&self, name: &str, ty: &Type) -> String {
+        format!("typealias {} = {}\n", name, type_name(ty))
+    }
+}
+
+fn identifier(property: &str) -> String {
+    if KEYWORDS.contains(&property) {
+        format!("`{}`", property)
+    } else {
+        property.to_string()
+    }
+}
+
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Scalar(scalar) => match scalar_type(scalar) {
+            Some(JsonScalar::String) => "String".to_string(),
+            Some(JsonScalar::Integer) => "Int64".to_string(),
+            Some(JsonScalar::Float) => "Double".to_string(),
+            Some(JsonScalar::Boolean) => "Bool".to_string(),
+            None => "JSONValue".to_string(),
+        },
+        Type::Class(name) => name.clone(),
+        Type::List(inner) => format!("[{}]", type_name(inner)),
+        Type::Optional(inner) => format!("{}?", type_name(inner)),
+        Type::Json => "JSONValue".to_string(),
+    }
+}
+
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{tests::stubs, Lang};
+
+    #[test]
+    fn codable_structs() {
+        let swift = stubs(Lang::Swift, "SELECT * FROM user");
+
+        assert!(swift.contains("enum JSONValue: Codable {\n"));
+        assert!(swift.contains("struct AdultUsersAddress: Codable {\n    let city: String\n}\n"));
+        assert!(swift.contains("struct AdultUsers: Codable {\n"));
+        assert!(swift.contains("    let address: AdultUsersAddress\n"));
+        assert!(swift.contains("    let age: Int64\n"));
+        assert!(swift.contains("    let bestFriend: String\n"));
+        assert!(swift.contains("    let nickname: String?\n"));
+        assert!(swift.contains("    let settings: JSONValue\n"));
+        assert!(swift.contains("        case bestFriend = \"best_friend\"\n"));
+        assert!(swift.contains("        case age\n"));
+    }
+
+    #[test]
+    fn omits_unneeded_coding_keys() {
+        let swift = stubs(Lang::Swift, "SELECT name, age FROM user");
+
+        assert!(!swift.contains("CodingKeys"));
+        assert!(!swift.contains("JSONValue"));
+    }
+}
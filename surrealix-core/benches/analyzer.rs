@@ -0,0 +1,61 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use surrealdb::sql::parse;
+use surrealix_core::{analyzer::analyze_statement, bench::corpus, schema::analyze_schema};
+
+const SIZES: [(usize, usize); 3] = [(4, 6), (16, 24), (64, 48)];
+
+fn schema(c: &mut Criterion) {
+    let mut group = c.benchmark_group("analyze_schema");
+    for (tables, fields) in SIZES {
+        let corpus = corpus(tables, fields);
+        let parsed = parse(&corpus.schema).unwrap();
+        group.bench_function(format!("{}x{}", tables, fields), |b| {
+            b.iter_batched(
+                || parsed.clone(),
+                |schema| analyze_schema(black_box(schema)).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    // Profile a real schema with `SURREALIX_BENCH_SCHEMA=path/to/schema.surql`.
+    if let Ok(path) = std::env::var("SURREALIX_BENCH_SCHEMA") {
+        let text = std::fs::read_to_string(&path).unwrap();
+        let parsed = parse(&text).unwrap();
+        group.bench_function(path, |b| {
+            b.iter_batched(
+                || parsed.clone(),
+                |schema| analyze_schema(black_box(schema)).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn select(c: &mut Criterion) {
+    let mut group = c.benchmark_group("analyze_select");
+    for (tables, fields) in SIZES {
+        let corpus = corpus(tables, fields);
+        let schema = analyze_schema(parse(&corpus.schema).unwrap()).unwrap();
+        let queries: Vec<_> = corpus
+            .queries
+            .iter()
+            .map(|query| parse(query).unwrap())
+            .collect();
+
+        group.bench_function(format!("{}x{}", tables, fields), |b| {
+            b.iter(|| {
+                for query in &queries {
+                    for statement in query.iter() {
+                        analyze_statement(black_box(&schema), statement).unwrap();
+                    }
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, schema, select);
+criterion_main!(benches);
@@ -0,0 +1,16 @@
+//! Any query that parses is either analyzed against the schema or rejected with an error,
+//! never a panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use surrealix_core::analyzer::analyze;
+
+fuzz_target!(|input: (&str, &str)| {
+    let (schema, query) = input;
+    let (Ok(schema), Ok(query)) = (surrealdb::sql::parse(schema), surrealdb::sql::parse(query))
+    else {
+        return;
+    };
+    let _ = analyze(schema, query);
+});
@@ -0,0 +1,12 @@
+//! Any schema that parses is either analyzed or rejected with an error, never a panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use surrealix_core::schema::analyze_schema;
+
+fuzz_target!(|schema: &str| {
+    if let Ok(schema) = surrealdb::sql::parse(schema) {
+        let _ = analyze_schema(schema);
+    }
+});
@@ -0,0 +1,256 @@
+//! Whether statements only read data, so they can run on a read replica.
+
+use surrealdb::sql::{
+    parse,
+    statements::{IfelseStatement, SelectStatement},
+    Block, Expression, Field, Function, Query, Statement, Subquery, Value,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementAccess {
+    /// A SELECT that reads nothing but tables, records and builtin functions.
+    Read,
+    /// A statement that writes, like CREATE or DEFINE, or that evaluates values not known to
+    /// only read.
+    Write,
+    /// Anything else, e.g. a custom function call or a `RETURN`, which may or may not write.
+    Unknown,
+}
+
+pub fn statement_access(stmt: &Statement) -> StatementAccess {
+    match stmt {
+        Statement::Select(select) => select_access(select),
+        Statement::Create(_)
+        | Statement::Update(_)
+        | Statement::Delete(_)
+        | Statement::Relate(_)
+        | Statement::Insert(_)
+        | Statement::Define(_)
+        | Statement::Remove(_)
+        | Statement::Kill(_)
+        | Statement::Live(_) => StatementAccess::Write,
+        _ => StatementAccess::Unknown,
+    }
+}
+
+/// Whether every statement of the query is known to only read.
+pub fn is_read_only(query: &Query) -> bool {
+    query
+        .iter()
+        .all(|stmt| statement_access(stmt) == StatementAccess::Read)
+}
+
+/// The statements of the query that are known to write.
+pub fn writing_statements(query: &Query) -> Vec<&Statement> {
+    query
+        .iter()
+        .filter(|stmt| statement_access(stmt) == StatementAccess::Write)
+        .collect()
+}
+
+/// The statements of the query that change the schema, like DEFINE or REMOVE.
+pub fn ddl_statements(query: &Query) -> Vec<&Statement> {
+    query
+        .iter()
+        .filter(|stmt| matches!(stmt, Statement::Define(_) | Statement::Remove(_)))
+        .collect()
+}
+
+/// The tables the SELECTs of the query read from, including those of subqueries in FROM, e.g.
+/// to know which changes can affect its results.
+pub fn read_tables(query: &Query) -> Vec<String> {
+    let mut tables = vec![];
+    for stmt in query.iter() {
+        if let Statement::Select(select) = stmt {
+            select_tables(select, &mut tables);
+        }
+    }
+    tables.sort();
+    tables.dedup();
+    tables
+}
+
+fn select_tables(select: &SelectStatement, tables: &mut Vec<String>) {
+    for what in select.what.iter() {
+        match what {
+            Value::Table(table) => tables.push(table.to_string()),
+            Value::Thing(thing) => tables.push(thing.tb.clone()),
+            Value::Subquery(subquery) => {
+                if let Subquery::Select(select) = subquery.as_ref() {
+                    select_tables(select, tables);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn select_access(select: &SelectStatement) -> StatementAccess {
+    let fields = select.expr.0.iter().filter_map(|field| match field {
+        Field::Single { expr, .. } => Some(expr),
+        Field::All => None,
+    });
+    combined(
+        fields
+            .chain(select.what.iter())
+            .chain(select.cond.iter().map(|cond| &cond.0))
+            .map(value_access),
+    )
+}
+
+/// How evaluating the value accesses the database. Values not known to only read are taken to
+/// write, so they never run on a read replica.
+fn value_access(value: &Value) -> StatementAccess {
+    match value {
+        Value::None
+        | Value::Null
+        | Value::Bool(_)
+        | Value::Number(_)
+        | Value::Strand(_)
+        | Value::Duration(_)
+        | Value::Datetime(_)
+        | Value::Uuid(_)
+        | Value::Geometry(_)
+        | Value::Bytes(_)
+        | Value::Thing(_)
+        | Value::Param(_)
+        | Value::Idiom(_)
+        | Value::Table(_)
+        | Value::Mock(_)
+        | Value::Regex(_)
+        | Value::Range(_)
+        | Value::Edges(_)
+        | Value::Constant(_) => StatementAccess::Read,
+        Value::Array(array) => combined(array.0.iter().map(value_access)),
+        Value::Object(object) => combined(object.0.values().map(value_access)),
+        Value::Cast(cast) => value_access(&cast.1),
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Unary { v, .. } => value_access(v),
+            Expression::Binary { l, r, .. } => combined([value_access(l), value_access(r)]),
+        },
+        // Custom functions and scripts may run any statement.
+        Value::Function(function) => match function.as_ref() {
+            Function::Normal(_, args) => combined(args.iter().map(value_access)),
+            _ => StatementAccess::Unknown,
+        },
+        Value::Model(model) => combined(model.args.iter().map(value_access)),
+        Value::Subquery(subquery) => match subquery.as_ref() {
+            Subquery::Value(value) => value_access(value),
+            Subquery::Select(select) => select_access(select),
+            Subquery::Ifelse(ifelse) => ifelse_access(ifelse),
+            Subquery::Output(output) => value_access(&output.what),
+            _ => StatementAccess::Write,
+        },
+        Value::Block(block) => block_access(block),
+        Value::Future(future) => block_access(&future.0),
+        _ => StatementAccess::Write,
+    }
+}
+
+fn ifelse_access(ifelse: &IfelseStatement) -> StatementAccess {
+    combined(
+        ifelse
+            .exprs
+            .iter()
+            .flat_map(|(cond, then)| [cond, then])
+            .chain(ifelse.close.iter())
+            .map(value_access),
+    )
+}
+
+/// How the statements of a block access the database.
+fn block_access(block: &Block) -> StatementAccess {
+    // The entries of a block can't be named outside of surrealdb, so they are classified as
+    // the statements they print as.
+    let source: String = block.iter().map(|entry| format!("{};", entry)).collect();
+    let Ok(statements) = parse(&source) else {
+        return StatementAccess::Write;
+    };
+    combined(statements.iter().map(|stmt| match stmt {
+        Statement::Value(value) => value_access(value),
+        Statement::Set(set) => value_access(&set.what),
+        Statement::Ifelse(ifelse) => ifelse_access(ifelse),
+        Statement::Output(output) => value_access(&output.what),
+        Statement::Throw(throw) => value_access(&throw.error),
+        Statement::Foreach(foreach) => {
+            combined([value_access(&foreach.range), block_access(&foreach.block)])
+        }
+        Statement::Break(_) | Statement::Continue(_) => StatementAccess::Read,
+        stmt => match statement_access(stmt) {
+            StatementAccess::Unknown => StatementAccess::Write,
+            access => access,
+        },
+    }))
+}
+
+/// The access of evaluating all of `accesses`: writing if any of them writes, and otherwise
+/// unknown if any of them may write.
+fn combined(accesses: impl IntoIterator<Item = StatementAccess>) -> StatementAccess {
+    accesses
+        .into_iter()
+        .fold(StatementAccess::Read, |all, access| match (all, access) {
+            (StatementAccess::Write, _) | (_, StatementAccess::Write) => StatementAccess::Write,
+            (StatementAccess::Unknown, _) | (_, StatementAccess::Unknown) => {
+                StatementAccess::Unknown
+            }
+            _ => StatementAccess::Read,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::sql::parse;
+
+    #[test]
+    fn classifies_statements() {
+        let access = |sql: &str| statement_access(&parse(sql).unwrap().0 .0[0]);
+
+        assert_eq!(
+            access("SELECT * FROM user WHERE age > 18"),
+            StatementAccess::Read
+        );
+        assert_eq!(
+            access("SELECT count() FROM (SELECT * FROM user) GROUP ALL"),
+            StatementAccess::Read
+        );
+        assert_eq!(
+            access("SELECT fn::touch(id) FROM user"),
+            StatementAccess::Unknown
+        );
+        assert_eq!(
+            access("SELECT * FROM (CREATE user SET name = 'a')"),
+            StatementAccess::Write
+        );
+        assert_eq!(
+            access("SELECT <future> { LET $n = 1; RETURN $n + 1 } AS n FROM user"),
+            StatementAccess::Read
+        );
+        assert_eq!(
+            access("SELECT <future> { CREATE log SET at = time::now() } AS logged FROM user"),
+            StatementAccess::Write
+        );
+        assert_eq!(access("RETURN 1"), StatementAccess::Unknown);
+        assert_eq!(access("CREATE user SET name = 'a'"), StatementAccess::Write);
+
+        assert!(is_read_only(
+            &parse("SELECT * FROM user; SELECT * FROM post").unwrap()
+        ));
+        assert!(!is_read_only(
+            &parse("SELECT * FROM user; DELETE post").unwrap()
+        ));
+
+        let query = parse("DEFINE FIELD age ON user TYPE int; SELECT * FROM user").unwrap();
+        assert_eq!(ddl_statements(&query).len(), 1);
+    }
+
+    #[test]
+    fn tables_read() {
+        let query = parse(
+            "SELECT * FROM user:john, post; SELECT count() FROM (SELECT * FROM user) GROUP ALL; \
+             CREATE tag SET name = 'a'",
+        )
+        .unwrap();
+        assert_eq!(read_tables(&query), vec!["post", "user"]);
+    }
+}
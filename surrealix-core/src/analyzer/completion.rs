@@ -0,0 +1,307 @@
+use crate::ast::{ObjectType, TypeAST};
+
+/// What a [Completion] would insert at the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// A field of the object at the cursor, e.g. `address` in `user.address`.
+    Field,
+    /// A relation table that can be traversed, e.g. `friend` in `->friend`.
+    Edge,
+    /// The table at the far side of a relation, e.g. `user` in `->friend->user`.
+    Table,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Separator {
+    Start,
+    Dot,
+    Out,
+    In,
+}
+
+enum Position<'a> {
+    /// A value of a known type, along with the table it is a record of, if any.
+    Value(&'a TypeAST, Option<&'a str>),
+    /// Part way through a graph traversal, after `->edge`.
+    Edge(&'a ObjectType, Separator),
+}
+
+/// Suggests what can follow the partial idiom before `cursor` when selecting from `table`.
+///
+/// Only the idiom directly before the cursor is considered, so `input` may be a whole query.
+/// Returns nothing if the idiom doesn't resolve against the schema.
+pub fn complete(schema: &TypeAST, table: &str, input: &str, cursor: usize) -> Vec<Completion> {
+    let TypeAST::Object(tables) = schema else {
+        return vec![];
+    };
+    let Some(root) = tables.fields.get(table) else {
+        return vec![];
+    };
+
+    let mut cursor = cursor.min(input.len());
+    while !input.is_char_boundary(cursor) {
+        cursor -= 1;
+    }
+
+    let mut steps = split_idiom(current_idiom(&input[..cursor]));
+    let Some((separator, prefix)) = steps.pop() else {
+        return vec![];
+    };
+
+    let mut position = Position::Value(&root.ast, Some(table));
+    for (step, name) in steps {
+        let Some(next) = advance(tables, position, step, name) else {
+            return vec![];
+        };
+        position = next;
+    }
+
+    let mut completions: Vec<_> = candidates(tables, &position, separator)
+        .into_iter()
+        .filter(|completion| completion.label.starts_with(prefix))
+        .collect();
+    completions.sort_by(|a, b| a.label.cmp(&b.label));
+    completions
+}
+
+/// The trailing run of idiom characters, e.g. `->friend->user.na` in `SELECT ->friend->user.na`.
+fn current_idiom(text: &str) -> &str {
+    let start = text
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '<' | '>')))
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    &text[start..]
+}
+
+fn split_idiom(idiom: &str) -> Vec<(Separator, &str)> {
+    let mut steps = vec![];
+    let mut separator = Separator::Start;
+    let mut rest = idiom;
+
+    loop {
+        let next = ["->", "<-", "."]
+            .iter()
+            .filter_map(|sep| rest.find(sep).map(|i| (i, *sep)))
+            .min_by_key(|(i, _)| *i);
+
+        match next {
+            Some((i, sep)) => {
+                // A leading separator has no name before it, e.g. `->friend`.
+                if i > 0 || separator != Separator::Start {
+                    steps.push((separator, &rest[..i]));
+                }
+                separator = match sep {
+                    "->" => Separator::Out,
+                    "<-" => Separator::In,
+                    _ => Separator::Dot,
+                };
+                rest = &rest[i + sep.len()..];
+            }
+            None => {
+                steps.push((separator, rest));
+                return steps;
+            }
+        }
+    }
+}
+
+fn advance<'a>(
+    tables: &'a ObjectType,
+    position: Position<'a>,
+    separator: Separator,
+    name: &str,
+) -> Option<Position<'a>> {
+    match (position, separator) {
+        (Position::Value(ast, _), Separator::Start | Separator::Dot) => {
+            let field = object_of(tables, ast)?.fields.get(name)?;
+            let table = match unwrap_collections(&field.ast) {
+                TypeAST::Record(table) => Some(table.as_str()),
+                _ => None,
+            };
+            Some(Position::Value(&field.ast, table))
+        }
+        (Position::Value(..), Separator::Out | Separator::In) => {
+            match &tables.fields.get(name)?.ast {
+                TypeAST::Object(edge) => Some(Position::Edge(edge, separator)),
+                _ => None,
+            }
+        }
+        (Position::Edge(..), Separator::Out | Separator::In) => {
+            let (table, info) = tables.fields.get_key_value(name)?;
+            Some(Position::Value(&info.ast, Some(table.as_str())))
+        }
+        (Position::Edge(..), _) => None,
+    }
+}
+
+fn candidates(tables: &ObjectType, position: &Position, separator: Separator) -> Vec<Completion> {
+    match (position, separator) {
+        (Position::Value(ast, _), Separator::Start | Separator::Dot) => object_of(tables, ast)
+            .map(|obj| {
+                obj.fields
+                    .keys()
+                    .map(|name| completion(name, CompletionKind::Field))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        (Position::Value(_, table), Separator::Out | Separator::In) => {
+            let side = if separator == Separator::Out {
+                "in"
+            } else {
+                "out"
+            };
+            tables
+                .fields
+                .iter()
+                .filter(|(_, info)| match &info.ast {
+                    TypeAST::Object(edge) => edge
+                        .fields
+                        .get(side)
+                        .map_or(false, |field| links_to(&field.ast, *table)),
+                    _ => false,
+                })
+                .map(|(name, _)| completion(name, CompletionKind::Edge))
+                .collect()
+        }
+        (Position::Edge(edge, _), Separator::Out | Separator::In) => {
+            let side = if separator == Separator::Out {
+                "out"
+            } else {
+                "in"
+            };
+            match edge
+                .fields
+                .get(side)
+                .map(|field| unwrap_collections(&field.ast))
+            {
+                Some(TypeAST::Record(table)) => vec![completion(table, CompletionKind::Table)],
+                _ => tables
+                    .fields
+                    .keys()
+                    .map(|name| completion(name, CompletionKind::Table))
+                    .collect(),
+            }
+        }
+        (Position::Edge(..), _) => vec![],
+    }
+}
+
+fn completion(label: &str, kind: CompletionKind) -> Completion {
+    Completion {
+        label: label.to_string(),
+        kind,
+    }
+}
+
+/// Whether a relation's `in`/`out` field can point at `table`. Untyped fields match anything.
+fn links_to(ast: &TypeAST, table: Option<&str>) -> bool {
+    match (unwrap_collections(ast), table) {
+        (TypeAST::Record(linked), Some(table)) => linked == table,
+        (TypeAST::Record(_), None) => false,
+        _ => true,
+    }
+}
+
+fn unwrap_collections(ast: &TypeAST) -> &TypeAST {
+    match ast {
+        TypeAST::Option(inner) => unwrap_collections(inner),
+        TypeAST::Array(inner) => unwrap_collections(&inner.0),
+        ast => ast,
+    }
+}
+
+/// The object whose fields can follow `ast`, following record links into their table.
+fn object_of<'a>(tables: &'a ObjectType, ast: &'a TypeAST) -> Option<&'a ObjectType> {
+    match unwrap_collections(ast) {
+        TypeAST::Object(obj) => Some(obj),
+        TypeAST::Record(table) => match &tables.fields.get(table)?.ast {
+            TypeAST::Object(obj) => Some(obj),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::analyze_schema;
+    use surrealdb::sql::parse;
+
+    fn create_test_schema() -> TypeAST {
+        let schema = parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD address ON user TYPE object;
+                    DEFINE FIELD address.city ON user TYPE string;
+                    DEFINE FIELD address.zip ON user TYPE number;
+                DEFINE FIELD best_friend ON user TYPE option<record<user>>;
+            DEFINE TABLE friend SCHEMAFULL;
+                DEFINE FIELD in ON friend TYPE record<user>;
+                DEFINE FIELD out ON friend TYPE record<user>;
+            DEFINE TABLE post SCHEMAFULL;
+                DEFINE FIELD title ON post TYPE string;
+            DEFINE TABLE wrote SCHEMAFULL;
+                DEFINE FIELD in ON wrote TYPE record<user>;
+                DEFINE FIELD out ON wrote TYPE record<post>;
+            "#,
+        )
+        .unwrap();
+        analyze_schema(schema).unwrap()
+    }
+
+    fn labels(completions: Vec<Completion>) -> Vec<String> {
+        completions.into_iter().map(|c| c.label).collect()
+    }
+
+    #[test]
+    fn completes_fields() {
+        let schema = create_test_schema();
+        let input = "SELECT address.c";
+
+        let completions = complete(&schema, "user", input, input.len());
+        assert_eq!(labels(completions), vec!["city"]);
+
+        let completions = complete(&schema, "user", "SELECT best_friend.", 19);
+        assert!(labels(completions).contains(&"address".to_string()));
+    }
+
+    #[test]
+    fn completes_edges_and_tables() {
+        let schema = create_test_schema();
+
+        let completions = complete(&schema, "user", "SELECT ->", 9);
+        assert_eq!(labels(completions), vec!["friend", "wrote"]);
+        assert!(complete(&schema, "post", "SELECT ->", 9).is_empty());
+
+        let input = "SELECT ->wrote->";
+        let completions = complete(&schema, "user", input, input.len());
+        assert_eq!(
+            completions,
+            vec![Completion {
+                label: "post".to_string(),
+                kind: CompletionKind::Table,
+            }]
+        );
+
+        let input = "SELECT ->wrote->post.t";
+        let completions = complete(&schema, "user", input, input.len());
+        assert_eq!(labels(completions), vec!["title"]);
+    }
+
+    #[test]
+    fn unresolved_paths_complete_nothing() {
+        let schema = create_test_schema();
+        let input = "SELECT nope.";
+        assert!(complete(&schema, "user", input, input.len()).is_empty());
+        assert!(complete(&schema, "missing", input, input.len()).is_empty());
+    }
+}
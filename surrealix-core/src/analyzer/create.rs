@@ -1,8 +1,8 @@
 use super::Tables;
-use crate::types::TypedQuery;
+use crate::ast::TypeAST;
 use surrealdb::sql::statements::CreateStatement;
 
-pub fn analyze_create(tbls: &Tables, create: &CreateStatement) -> TypedQuery {
+pub fn analyze_create(tbls: &Tables, create: &CreateStatement) -> TypeAST {
     // Implement create analysis logic here
     todo!("Implement create analysis")
 }
@@ -1,8 +1,8 @@
 use super::Tables;
-use crate::types::TypedQuery;
+use crate::ast::TypeAST;
 use surrealdb::sql::statements::DeleteStatement;
 
-pub fn analyze_delete(tbls: &Tables, delete: &DeleteStatement) -> TypedQuery {
+pub fn analyze_delete(tbls: &Tables, delete: &DeleteStatement) -> TypeAST {
     // Implement delete analysis logic here
     todo!("Implement delete analysis")
 }
@@ -1,100 +1,50 @@
-use crate::types::{QueryType, TypedQuery};
-use surrealdb::sql::{Function, Kind, Permissions};
+use super::array_of;
+use crate::ast::{ScalarType, TypeAST};
+use surrealdb::sql::Function;
 
 // Helper function to get the inner type of an array
-fn get_array_inner_type(query_type: &QueryType) -> Option<Box<QueryType>> {
-    if let QueryType::Array(Some(inner), _) = query_type {
-        Some(Box::new(inner.clone().query_type))
+fn get_array_inner_type(ast: &TypeAST) -> Option<&TypeAST> {
+    if let TypeAST::Array(inner) = ast {
+        Some(&inner.0)
     } else {
         None
     }
 }
 
 // Functions that don't change the array type
-fn array_identity(args: &[TypedQuery]) -> TypedQuery {
-    args.first().cloned().unwrap_or(TypedQuery {
-        query_type: QueryType::Array(None, None),
-        perms: Permissions::none(),
-    })
-}
-
-// Functions that return a boolean
-fn array_to_bool(_args: &[TypedQuery]) -> TypedQuery {
-    TypedQuery {
-        query_type: QueryType::Scalar(Kind::Bool),
-        perms: Permissions::none(),
-    }
-}
-
-// Functions that return a number
-fn array_to_number(_args: &[TypedQuery]) -> TypedQuery {
-    TypedQuery {
-        query_type: QueryType::Scalar(Kind::Int),
-        perms: Permissions::none(),
-    }
-}
-
-// Functions that return a string
-fn array_to_string(_args: &[TypedQuery]) -> TypedQuery {
-    TypedQuery {
-        query_type: QueryType::Scalar(Kind::String),
-        perms: Permissions::none(),
-    }
+fn array_identity(args: &[TypeAST]) -> TypeAST {
+    args.first()
+        .cloned()
+        .unwrap_or_else(|| array_of(TypeAST::Scalar(ScalarType::Any)))
 }
 
 // Special cases
-fn array_at(args: &[TypedQuery]) -> TypedQuery {
-    if let Some(arg) = args.first() {
-        if let Some(inner) = get_array_inner_type(&arg.query_type) {
-            return TypedQuery {
-                query_type: *inner,
-                perms: Permissions::none(),
-            };
-        }
-    }
-    TypedQuery {
-        query_type: QueryType::Scalar(Kind::Any),
-        perms: Permissions::none(),
-    }
+fn array_at(args: &[TypeAST]) -> TypeAST {
+    args.first()
+        .and_then(get_array_inner_type)
+        .cloned()
+        .unwrap_or(TypeAST::Scalar(ScalarType::Any))
 }
 
-fn array_clump(args: &[TypedQuery]) -> TypedQuery {
-    if let Some(arg) = args.first() {
-        return TypedQuery {
-            query_type: QueryType::Array(Some(Box::new(arg.clone())), None),
-            perms: Permissions::none(),
-        };
-    }
-    TypedQuery {
-        query_type: QueryType::Array(None, None),
-        perms: Permissions::none(),
-    }
+fn array_clump(args: &[TypeAST]) -> TypeAST {
+    array_of(
+        args.first()
+            .cloned()
+            .unwrap_or(TypeAST::Scalar(ScalarType::Any)),
+    )
 }
 
-fn array_flatten(args: &[TypedQuery]) -> TypedQuery {
-    if let Some(arg) = args.first() {
-        if let Some(inner) = get_array_inner_type(&arg.query_type) {
-            if let Some(inner_inner) = get_array_inner_type(&inner) {
-                return TypedQuery {
-                    query_type: QueryType::Array(
-                        Some(Box::new(TypedQuery {
-                            query_type: *inner_inner,
-                            perms: Permissions::none(),
-                        })),
-                        None,
-                    ),
-                    perms: Permissions::none(),
-                };
-            }
-        }
-    }
-    TypedQuery {
-        query_type: QueryType::Array(None, None),
-        perms: Permissions::none(),
-    }
+fn array_flatten(args: &[TypeAST]) -> TypeAST {
+    let items = args
+        .first()
+        .and_then(get_array_inner_type)
+        .and_then(get_array_inner_type)
+        .cloned()
+        .unwrap_or(TypeAST::Scalar(ScalarType::Any));
+    array_of(items)
 }
 
-pub fn analyze_array(func: &Function, args: Vec<TypedQuery>) -> TypedQuery {
+pub fn analyze_array(func: &Function, args: Vec<TypeAST>) -> TypeAST {
     match func.name().unwrap() {
         // Functions that don't change the array type
         "array::add" | "array::append" | "array::combine" | "array::concat"
@@ -104,13 +54,13 @@ pub fn analyze_array(func: &Function, args: Vec<TypedQuery>) -> TypedQuery {
         | "array::slice" | "array::transpose" | "array::union" => array_identity(&args),
 
         // Functions that return a boolean
-        "array::all" | "array::any" => array_to_bool(&args),
+        "array::all" | "array::any" => TypeAST::Scalar(ScalarType::Boolean),
 
         // Functions that return a number
-        "array::len" | "array::find_index" => array_to_number(&args),
+        "array::len" | "array::find_index" => TypeAST::Scalar(ScalarType::Integer),
 
         // Functions that return a string
-        "array::join" => array_to_string(&args),
+        "array::join" => TypeAST::Scalar(ScalarType::String),
 
         // Special cases
         "array::at" => array_at(&args),
@@ -118,25 +68,9 @@ pub fn analyze_array(func: &Function, args: Vec<TypedQuery>) -> TypedQuery {
         "array::flatten" => array_flatten(&args),
 
         // Functions that might return the type of the array elements
-        "array::first" | "array::last" | "array::max" | "array::min" => {
-            if let Some(arg) = args.first() {
-                if let Some(inner) = get_array_inner_type(&arg.query_type) {
-                    return TypedQuery {
-                        query_type: *inner,
-                        perms: Permissions::none(),
-                    };
-                }
-            }
-            TypedQuery {
-                query_type: QueryType::Scalar(Kind::Any),
-                perms: Permissions::none(),
-            }
-        }
+        "array::first" | "array::last" | "array::max" | "array::min" => array_at(&args),
 
         // Default case for unknown functions
-        _ => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Any),
-            perms: Permissions::none(),
-        },
+        _ => TypeAST::Scalar(ScalarType::Any),
     }
 }
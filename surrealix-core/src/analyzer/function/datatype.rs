@@ -1,69 +1,26 @@
-use crate::types::{QueryType, TypedQuery};
-use surrealdb::sql::{Function, Kind, Permissions};
+use crate::ast::{ScalarType, TypeAST};
+use surrealdb::sql::Function;
 
-pub fn analyze_datatype(func: &Function, args: Vec<TypedQuery>) -> TypedQuery {
+pub fn analyze_datatype(func: &Function, _args: Vec<TypeAST>) -> TypeAST {
     let parts: Vec<&str> = func.name().unwrap().split("::").collect();
 
     match parts.get(1) {
-        Some(&"bool") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Bool),
-            perms: Permissions::none(),
-        },
-        Some(&"datetime") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Datetime),
-            perms: Permissions::none(),
-        },
-        Some(&"decimal") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Decimal),
-            perms: Permissions::none(),
-        },
-        Some(&"duration") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Duration),
-            perms: Permissions::none(),
-        },
-        Some(&"float") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Float),
-            perms: Permissions::none(),
-        },
-        Some(&"int") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Int),
-            perms: Permissions::none(),
-        },
-        Some(&"number") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Number),
-            perms: Permissions::none(),
-        },
-        Some(&"point") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Geometry(vec![])),
-            perms: Permissions::none(),
-        },
-        Some(&"string") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::String),
-            perms: Permissions::none(),
-        },
-        Some(&"table") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::String),
-            perms: Permissions::none(),
-        },
-        Some(&"thing") => todo!("Implement 'thing'"),
-        Some(&"range") => todo!("Implement range"),
-        Some(&"field") | Some(&"fields") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Any),
-            perms: Permissions::none(),
-        },
+        Some(&"bool") => TypeAST::Scalar(ScalarType::Boolean),
+        Some(&"datetime") => TypeAST::Scalar(ScalarType::Datetime),
+        Some(&"decimal") => TypeAST::Scalar(ScalarType::Number),
+        Some(&"duration") => TypeAST::Scalar(ScalarType::Duration),
+        Some(&"float") => TypeAST::Scalar(ScalarType::Float),
+        Some(&"int") => TypeAST::Scalar(ScalarType::Integer),
+        Some(&"number") => TypeAST::Scalar(ScalarType::Number),
+        Some(&"point") => TypeAST::Scalar(ScalarType::Geometry),
+        Some(&"string") => TypeAST::Scalar(ScalarType::String),
+        Some(&"table") => TypeAST::Scalar(ScalarType::String),
+        // The table of a record id built at runtime isn't known.
+        Some(&"thing") | Some(&"field") | Some(&"fields") => TypeAST::Scalar(ScalarType::Any),
         Some(&"is") => match parts.get(2) {
-            Some(_) => TypedQuery {
-                query_type: QueryType::Scalar(Kind::Bool),
-                perms: Permissions::none(),
-            },
-            None => TypedQuery {
-                query_type: QueryType::Scalar(Kind::Any),
-                perms: Permissions::none(),
-            },
-        },
-        _ => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Any),
-            perms: Permissions::none(),
+            Some(_) => TypeAST::Scalar(ScalarType::Boolean),
+            None => TypeAST::Scalar(ScalarType::Any),
         },
+        _ => TypeAST::Scalar(ScalarType::Any),
     }
 }
@@ -1,31 +1,19 @@
-use crate::types::{QueryType, TypedQuery};
-use surrealdb::sql::{Function, Kind, Permissions};
+use crate::ast::{ScalarType, TypeAST};
+use surrealdb::sql::Function;
 
-pub fn analyze_duration(func: &Function, args: Vec<TypedQuery>) -> TypedQuery {
+pub fn analyze_duration(func: &Function, _args: Vec<TypeAST>) -> TypeAST {
     let parts: Vec<&str> = func.name().unwrap().split("::").collect();
 
     match parts[1] {
         "days" | "hours" | "micros" | "millis" | "mins" | "nanos" | "secs" | "weeks" | "years" => {
-            TypedQuery {
-                query_type: QueryType::Scalar(Kind::Number),
-                perms: Permissions::none(),
-            }
+            TypeAST::Scalar(ScalarType::Number)
         }
         "from" => match parts[2] {
             "days" | "hours" | "micros" | "millis" | "mins" | "nanos" | "secs" | "weeks" => {
-                TypedQuery {
-                    query_type: QueryType::Scalar(Kind::Duration),
-                    perms: Permissions::none(),
-                }
+                TypeAST::Scalar(ScalarType::Duration)
             }
-            _ => TypedQuery {
-                query_type: QueryType::Scalar(Kind::Any),
-                perms: Permissions::none(),
-            },
-        },
-        _ => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Any),
-            perms: Permissions::none(),
+            _ => TypeAST::Scalar(ScalarType::Any),
         },
+        _ => TypeAST::Scalar(ScalarType::Any),
     }
 }
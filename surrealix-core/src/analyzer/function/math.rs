@@ -1,51 +1,43 @@
-use crate::types::{QueryType, TypedQuery};
-use surrealdb::sql::{Function, Kind, Permissions};
+use super::array_of;
+use crate::ast::{ScalarType, TypeAST};
+use surrealdb::sql::Function;
 
-pub fn analyze_math(func: &Function, args: Vec<TypedQuery>) -> TypedQuery {
+pub fn analyze_math(func: &Function, args: Vec<TypeAST>) -> TypeAST {
     let parts: Vec<&str> = func.name().unwrap().split("::").collect();
 
+    // Integers stay integers through rounding, and through picking or summing them from an array.
+    let integer = matches!(args.first(), Some(TypeAST::Scalar(ScalarType::Integer)));
+    let integers = matches!(
+        args.first(),
+        Some(TypeAST::Array(items)) if items.0 == TypeAST::Scalar(ScalarType::Integer)
+    );
+    let number = |integer: bool| match integer {
+        true => TypeAST::Scalar(ScalarType::Integer),
+        false => TypeAST::Scalar(ScalarType::Number),
+    };
+
     match parts[1] {
         // Constants
-        "e" | "pi" | "tau" | "inf" => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Number),
-            perms: Permissions::none(),
-        },
+        "e" | "pi" | "tau" | "inf" => TypeAST::Scalar(ScalarType::Number),
 
         // Functions that return a number
-        "abs" | "ceil" | "floor" | "round" | "sqrt" | "fixed" => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Number),
-            perms: Permissions::none(),
-        },
-
-        // Functions that take an array and return a number
-        "max" | "min" | "mean" | "median" | "mode" | "product" | "sum" | "interquartile"
-        | "midhinge" | "spread" | "stddev" | "trimean" | "variance" => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Number),
-            perms: Permissions::none(),
-        },
+        "abs" | "ceil" | "floor" | "round" => number(integer),
+        "sqrt" | "fixed" => TypeAST::Scalar(ScalarType::Number),
+
+        // Functions that take an array and return a number. Those picking one of its numbers
+        // return NONE for an empty array.
+        "max" | "min" | "mode" => TypeAST::Option(Box::new(number(integers))),
+        "product" | "sum" => number(integers),
+        "mean" | "median" | "interquartile" | "midhinge" | "spread" | "stddev" | "trimean"
+        | "variance" => TypeAST::Scalar(ScalarType::Number),
 
         // Functions that take an array and a number and return a number
-        "percentile" | "nearestrank" => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Number),
-            perms: Permissions::none(),
-        },
+        "percentile" | "nearestrank" => TypeAST::Scalar(ScalarType::Number),
 
         // Functions that return an array
-        "bottom" | "top" => TypedQuery {
-            query_type: QueryType::Array(
-                Some(Box::new(TypedQuery {
-                    query_type: QueryType::Scalar(Kind::Number),
-                    perms: Permissions::none(),
-                })),
-                None,
-            ),
-            perms: Permissions::none(),
-        },
+        "bottom" | "top" => array_of(number(integers)),
 
         // Default case
-        _ => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Any),
-            perms: Permissions::none(),
-        },
+        _ => TypeAST::Scalar(ScalarType::Any),
     }
 }
@@ -0,0 +1,22 @@
+use crate::ast::{ScalarType, TypeAST};
+use surrealdb::sql::Model;
+
+/// How many arguments SurrealDB passes to a model: a number, an array of numbers, or an object
+/// of named inputs.
+pub const MODEL_ARGS: usize = 1;
+
+/// The names a signature for `model` can be registered under, most specific first: with its
+/// version, e.g. `ml::house_price<1.0.0>`, or for every version, e.g. `ml::house_price`.
+pub fn model_names(model: &Model) -> [String; 2] {
+    [
+        format!("ml::{}<{}>", model.name, model.version),
+        format!("ml::{}", model.name),
+    ]
+}
+
+/// Types a call to a SurrealML model without a registered signature, e.g.
+/// `ml::house_price<1.0.0>({ size: 120 })`, which SurrealDB computes to the first of the
+/// model's outputs, a float, whatever the model and its inputs.
+pub fn analyze_model() -> TypeAST {
+    TypeAST::Scalar(ScalarType::Float)
+}
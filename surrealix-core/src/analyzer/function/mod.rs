@@ -1,11 +1,12 @@
-use crate::types::{QueryType, TypedQuery};
-use surrealdb::sql::{Function, Kind, Permissions};
+use crate::ast::{ScalarType, TypeAST};
+use surrealdb::sql::Function;
 
 mod array;
 mod crypto;
 mod datatype;
 mod duration;
 mod math;
+pub(crate) mod ml;
 mod object;
 mod parse;
 mod rand;
@@ -14,8 +15,21 @@ mod string;
 mod time;
 mod vector;
 
-pub fn analyze_function(func: &Function, args: Vec<TypedQuery>) -> TypedQuery {
-    let parts: Vec<&str> = func.name().unwrap().split("::").collect();
+/// An array of `items`, of no fixed length.
+fn array_of(items: TypeAST) -> TypeAST {
+    TypeAST::Array(Box::new((items, None)))
+}
+
+pub fn analyze_function(func: &Function, args: Vec<TypeAST>) -> TypeAST {
+    // Scripts have no name, and return whatever they like.
+    let Some(name) = func.name() else {
+        return TypeAST::Scalar(ScalarType::Any);
+    };
+    let parts: Vec<&str> = name.split("::").collect();
+    // Every family below is namespaced, except `sleep`.
+    if parts.len() < 2 && name != "sleep" {
+        return TypeAST::Scalar(ScalarType::Any);
+    }
 
     match parts[0] {
         "array" => array::analyze_array(func, args),
@@ -29,68 +43,92 @@ pub fn analyze_function(func: &Function, args: Vec<TypedQuery>) -> TypedQuery {
         "type" => datatype::analyze_datatype(func, args),
         "vector" => vector::analyze_vector(func, args),
         // as of now, all possible 'session' fns return a string always.
-        "session" => TypedQuery {
-            query_type: QueryType::Scalar(Kind::String),
-            perms: Permissions::full(),
-        },
-        "sleep" => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Null),
-            perms: Permissions::none(),
-        },
+        "session" => TypeAST::Scalar(ScalarType::String),
+        "sleep" => TypeAST::Scalar(ScalarType::Null),
         "string" => string::analyze_string(func, args),
         "time" => time::analyze_time(func, args),
-        "meta" => match parts[1] {
-            "id" => TypedQuery {
-                query_type: QueryType::Scalar(Kind::String),
-                perms: Permissions::none(),
-            },
-            "tb" => TypedQuery {
-                query_type: QueryType::Scalar(Kind::String),
-                perms: Permissions::none(),
-            },
-            _ => todo!("Got invalid query! Replace with proper error handling."),
+        // `meta::*` is the older name for the `record::*` functions.
+        "record" | "meta" => match parts.get(1) {
+            Some(&"id") | Some(&"tb") => TypeAST::Scalar(ScalarType::String),
+            Some(&"exists") => TypeAST::Scalar(ScalarType::Boolean),
+            _ => TypeAST::Scalar(ScalarType::Any),
         },
-        "encoding" => match parts[1] {
-            "base64" => match parts[2] {
-                "encode" => TypedQuery {
-                    query_type: QueryType::Scalar(Kind::String),
-                    perms: Permissions::none(),
-                },
-                "decode" => TypedQuery {
-                    query_type: QueryType::Scalar(Kind::Bytes),
-                    perms: Permissions::none(),
-                },
-                _ => TypedQuery {
-                    query_type: QueryType::Scalar(Kind::Any),
-                    perms: Permissions::none(),
-                },
-            },
-            _ => TypedQuery {
-                query_type: QueryType::Scalar(Kind::Any),
-                perms: Permissions::none(),
-            },
+        "encoding" => match (parts[1], parts.get(2)) {
+            ("base64", Some(&"encode")) => TypeAST::Scalar(ScalarType::String),
+            ("base64", Some(&"decode")) => TypeAST::Scalar(ScalarType::Bytes),
+            _ => TypeAST::Scalar(ScalarType::Any),
         },
         "http" => match parts[1] {
-            "head" => TypedQuery {
-                query_type: QueryType::Scalar(Kind::Null),
-                perms: Permissions::none(),
-            },
-            "get" | "put" | "post" | "patch" | "delete" => TypedQuery {
-                query_type: QueryType::Scalar(Kind::Any),
-                perms: Permissions::none(),
-            },
-            _ => TypedQuery {
-                query_type: QueryType::Scalar(Kind::Any),
-                perms: Permissions::none(),
-            },
-        },
-        "count" => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Int),
-            perms: Permissions::full(),
-        },
-        _ => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Any),
-            perms: Permissions::full(),
+            "head" => TypeAST::Scalar(ScalarType::Null),
+            _ => TypeAST::Scalar(ScalarType::Any),
         },
+        "count" => TypeAST::Scalar(ScalarType::Integer),
+        _ => TypeAST::Scalar(ScalarType::Any),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::sql::{parse, Statement, Value};
+
+    fn function_type(call: &str, args: Vec<TypeAST>) -> TypeAST {
+        let query = parse(&format!("RETURN {}", call)).unwrap();
+        let Some(Statement::Output(output)) = query.0 .0.first() else {
+            panic!("Expected a RETURN");
+        };
+        let Value::Function(func) = &output.what else {
+            panic!("Expected a function call");
+        };
+        analyze_function(func, args)
+    }
+
+    #[test]
+    fn function_types() {
+        let strings = array_of(TypeAST::Scalar(ScalarType::String));
+
+        assert_eq!(
+            function_type("array::first($names)", vec![strings.clone()]),
+            TypeAST::Scalar(ScalarType::String)
+        );
+        assert_eq!(
+            function_type("array::flatten($names)", vec![array_of(strings.clone())]),
+            strings
+        );
+        assert_eq!(
+            function_type("object::entries($user)", vec![]),
+            array_of(TypeAST::Array(Box::new((
+                TypeAST::Scalar(ScalarType::Any),
+                std::num::NonZeroU64::new(2)
+            ))))
+        );
+        assert_eq!(
+            function_type("parse::url::port($url)", vec![]),
+            TypeAST::Option(Box::new(TypeAST::Scalar(ScalarType::Integer)))
+        );
+        assert_eq!(
+            function_type("encoding::base64::decode($data)", vec![]),
+            TypeAST::Scalar(ScalarType::Bytes)
+        );
+        let ints = array_of(TypeAST::Scalar(ScalarType::Integer));
+        assert_eq!(
+            function_type("math::sum($scores)", vec![ints.clone()]),
+            TypeAST::Scalar(ScalarType::Integer)
+        );
+        assert_eq!(
+            function_type("math::max($scores)", vec![ints.clone()]),
+            TypeAST::Option(Box::new(TypeAST::Scalar(ScalarType::Integer)))
+        );
+        assert_eq!(
+            function_type("math::top($scores, 3)", vec![ints]),
+            array_of(TypeAST::Scalar(ScalarType::Integer))
+        );
+        assert_eq!(
+            function_type(
+                "math::round($balance)",
+                vec![TypeAST::Scalar(ScalarType::Float)]
+            ),
+            TypeAST::Scalar(ScalarType::Number)
+        );
     }
 }
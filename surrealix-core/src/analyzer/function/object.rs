@@ -1,59 +1,21 @@
-use std::{collections::HashMap, num::NonZeroU64};
+use std::num::NonZeroU64;
 
-use crate::types::{QueryType, TypedQuery};
-use surrealdb::sql::{Function, Kind, Permissions};
+use super::array_of;
+use crate::ast::{ObjectType, ScalarType, TypeAST};
+use surrealdb::sql::Function;
 
-pub fn analyze_object(func: &Function, args: Vec<TypedQuery>) -> TypedQuery {
+pub fn analyze_object(func: &Function, _args: Vec<TypeAST>) -> TypeAST {
     let parts: Vec<&str> = func.name().unwrap().split("::").collect();
 
     match parts[1] {
-        "entries" => TypedQuery {
-            query_type: QueryType::Array(
-                Some(Box::new(TypedQuery {
-                    query_type: QueryType::Array(
-                        Some(Box::new(TypedQuery {
-                            query_type: QueryType::Scalar(Kind::Any),
-                            perms: Permissions::none(),
-                        })),
-                        NonZeroU64::new(2),
-                    ),
-                    perms: Permissions::none(),
-                })),
-                None,
-            ),
-            perms: Permissions::none(),
-        },
-        "from_entries" => TypedQuery {
-            query_type: QueryType::Object(HashMap::new()),
-            perms: Permissions::none(),
-        },
-        "keys" => TypedQuery {
-            query_type: QueryType::Array(
-                Some(Box::new(TypedQuery {
-                    query_type: QueryType::Scalar(Kind::String),
-                    perms: Permissions::none(),
-                })),
-                None,
-            ),
-            perms: Permissions::none(),
-        },
-        "len" => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Int),
-            perms: Permissions::none(),
-        },
-        "values" => TypedQuery {
-            query_type: QueryType::Array(
-                Some(Box::new(TypedQuery {
-                    query_type: QueryType::Scalar(Kind::Any),
-                    perms: Permissions::none(),
-                })),
-                None,
-            ),
-            perms: Permissions::none(),
-        },
-        _ => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Any),
-            perms: Permissions::none(),
-        },
+        "entries" => array_of(TypeAST::Array(Box::new((
+            TypeAST::Scalar(ScalarType::Any),
+            NonZeroU64::new(2),
+        )))),
+        "from_entries" => TypeAST::Object(ObjectType::default()),
+        "keys" => array_of(TypeAST::Scalar(ScalarType::String)),
+        "len" => TypeAST::Scalar(ScalarType::Integer),
+        "values" => array_of(TypeAST::Scalar(ScalarType::Any)),
+        _ => TypeAST::Scalar(ScalarType::Any),
     }
 }
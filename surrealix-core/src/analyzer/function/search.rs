@@ -1,51 +1,14 @@
-use std::collections::HashMap;
+use super::array_of;
+use crate::ast::{ObjectType, ScalarType, TypeAST};
+use surrealdb::sql::Function;
 
-use crate::types::{QueryType, TypedQuery};
-use surrealdb::sql::{Function, Kind, Permissions};
-
-pub fn analyze_search(func: &Function, args: Vec<TypedQuery>) -> TypedQuery {
+pub fn analyze_search(func: &Function, _args: Vec<TypeAST>) -> TypeAST {
     let parts: Vec<&str> = func.name().unwrap().split("::").collect();
 
     match parts.get(1) {
-        Some(&"score") => TypedQuery {
-            query_type: QueryType::Array(
-                Some(Box::new(TypedQuery {
-                    query_type: QueryType::Scalar(Kind::Float),
-                    perms: Permissions::none(),
-                })),
-                None,
-            ),
-            perms: Permissions::none(),
-        },
-        Some(&"highlight") => TypedQuery {
-            query_type: QueryType::Array(
-                Some(Box::new(TypedQuery {
-                    query_type: QueryType::Scalar(Kind::String),
-                    perms: Permissions::none(),
-                })),
-                None,
-            ),
-            perms: Permissions::none(),
-        },
-        Some(&"offsets") => TypedQuery {
-            query_type: QueryType::Array(
-                Some(Box::new(TypedQuery {
-                    query_type: QueryType::Object(HashMap::new()),
-                    perms: Permissions::none(),
-                })),
-                None,
-            ),
-            perms: Permissions::none(),
-        },
-        _ => TypedQuery {
-            query_type: QueryType::Array(
-                Some(Box::new(TypedQuery {
-                    query_type: QueryType::Scalar(Kind::Any),
-                    perms: Permissions::none(),
-                })),
-                None,
-            ),
-            perms: Permissions::none(),
-        },
+        Some(&"score") => array_of(TypeAST::Scalar(ScalarType::Float)),
+        Some(&"highlight") => array_of(TypeAST::Scalar(ScalarType::String)),
+        Some(&"offsets") => array_of(TypeAST::Object(ObjectType::default())),
+        _ => array_of(TypeAST::Scalar(ScalarType::Any)),
     }
 }
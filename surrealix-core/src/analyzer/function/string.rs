@@ -1,68 +1,48 @@
-use crate::types::{QueryType, TypedQuery};
-use surrealdb::sql::{Function, Kind, Permissions};
+use super::array_of;
+use crate::ast::{ScalarType, TypeAST};
+use surrealdb::sql::Function;
 
-pub fn analyze_string(func: &Function, args: Vec<TypedQuery>) -> TypedQuery {
+pub fn analyze_string(func: &Function, _args: Vec<TypeAST>) -> TypeAST {
     let parts: Vec<&str> = func.name().unwrap().split("::").collect();
 
     match parts.get(1) {
         Some(&"concat") | Some(&"join") | Some(&"lowercase") | Some(&"repeat")
         | Some(&"replace") | Some(&"reverse") | Some(&"slice") | Some(&"slug") | Some(&"trim")
-        | Some(&"uppercase") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::String),
-            perms: Permissions::none(),
+        | Some(&"uppercase") => TypeAST::Scalar(ScalarType::String),
+        Some(&"contains") | Some(&"endsWith") | Some(&"matches") | Some(&"startsWith") => {
+            TypeAST::Scalar(ScalarType::Boolean)
+        }
+        Some(&"html") => match parts.get(2) {
+            Some(&"encode") | Some(&"sanitize") => TypeAST::Scalar(ScalarType::String),
+            _ => TypeAST::Scalar(ScalarType::Any),
         },
-        Some(&"contains") | Some(&"endsWith") | Some(&"startsWith") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Bool),
-            perms: Permissions::none(),
+        Some(&"similarity") => match parts.get(2) {
+            Some(&"fuzzy") | Some(&"jaro") | Some(&"smithwaterman") => {
+                TypeAST::Scalar(ScalarType::Float)
+            }
+            _ => TypeAST::Scalar(ScalarType::Any),
         },
-        Some(&"len") => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Int),
-            perms: Permissions::none(),
-        },
-        Some(&"split") | Some(&"words") => TypedQuery {
-            query_type: QueryType::Array(
-                Some(Box::new(TypedQuery {
-                    query_type: QueryType::Scalar(Kind::String),
-                    perms: Permissions::none(),
-                })),
-                None,
-            ),
-            perms: Permissions::none(),
+        Some(&"distance") => match parts.get(2) {
+            Some(&"hamming") | Some(&"levenshtein") => TypeAST::Scalar(ScalarType::Integer),
+            _ => TypeAST::Scalar(ScalarType::Any),
         },
+        Some(&"len") => TypeAST::Scalar(ScalarType::Integer),
+        Some(&"split") | Some(&"words") => array_of(TypeAST::Scalar(ScalarType::String)),
         Some(&"is") => match parts.get(2) {
             Some(&"alphanum") | Some(&"alpha") | Some(&"ascii") | Some(&"datetime")
             | Some(&"domain") | Some(&"email") | Some(&"hexadecimal") | Some(&"latitude")
             | Some(&"longitude") | Some(&"numeric") | Some(&"semver") | Some(&"url")
-            | Some(&"uuid") => TypedQuery {
-                query_type: QueryType::Scalar(Kind::Bool),
-                perms: Permissions::none(),
-            },
-            _ => TypedQuery {
-                query_type: QueryType::Scalar(Kind::Any),
-                perms: Permissions::none(),
-            },
+            | Some(&"uuid") => TypeAST::Scalar(ScalarType::Boolean),
+            _ => TypeAST::Scalar(ScalarType::Any),
         },
         Some(&"semver") => match parts.get(2) {
-            Some(&"compare") => TypedQuery {
-                query_type: QueryType::Scalar(Kind::Int),
-                perms: Permissions::none(),
-            },
-            Some(&"major") | Some(&"minor") | Some(&"patch") => TypedQuery {
-                query_type: QueryType::Scalar(Kind::Int),
-                perms: Permissions::none(),
-            },
-            Some(&"inc") | Some(&"set") => TypedQuery {
-                query_type: QueryType::Scalar(Kind::String),
-                perms: Permissions::none(),
-            },
-            _ => TypedQuery {
-                query_type: QueryType::Scalar(Kind::Any),
-                perms: Permissions::none(),
-            },
-        },
-        _ => TypedQuery {
-            query_type: QueryType::Scalar(Kind::Any),
-            perms: Permissions::none(),
+            Some(&"compare") => TypeAST::Scalar(ScalarType::Integer),
+            Some(&"major") | Some(&"minor") | Some(&"patch") => {
+                TypeAST::Scalar(ScalarType::Integer)
+            }
+            Some(&"inc") | Some(&"set") => TypeAST::Scalar(ScalarType::String),
+            _ => TypeAST::Scalar(ScalarType::Any),
         },
+        _ => TypeAST::Scalar(ScalarType::Any),
     }
 }
@@ -0,0 +1,139 @@
+use crate::{
+    ast::{FieldInfo, FieldMetadata, ObjectType, ScalarType, TypeAST},
+    errors::AnalysisError,
+};
+use surrealdb::sql::{statements::InfoStatement, Permissions};
+
+/// Types the result of an INFO statement: an object with a map for each kind of definition,
+/// from the name of each definition to the statement defining it.
+///
+/// The tables of `INFO FOR DB` and the fields of `INFO FOR TABLE` are typed from the schema,
+/// while the other maps can hold any names. Only top-level fields are typed, so nested ones
+/// like `address.city` are still in the map but not part of its type, and neither is the `id`
+/// every table's records carry, which is only in the map if the schema defines it.
+pub fn analyze_info(schema: &TypeAST, stmt: &InfoStatement) -> Result<TypeAST, AnalysisError> {
+    let TypeAST::Object(tables) = schema else {
+        return Err(AnalysisError::UnsupportedType(format!(
+            "Schema was not an object! This should not be possible. Please file a bug report."
+        )));
+    };
+
+    let ast = match stmt {
+        InfoStatement::Root => sections([("namespaces", any()), ("users", any())]),
+        InfoStatement::Ns => sections([("databases", any()), ("tokens", any()), ("users", any())]),
+        InfoStatement::Db => sections([
+            ("analyzers", any()),
+            ("functions", any()),
+            ("models", any()),
+            ("params", any()),
+            ("scopes", any()),
+            ("tables", definitions(tables.fields.keys())),
+            ("tokens", any()),
+            ("users", any()),
+        ]),
+        InfoStatement::Sc(_) => sections([("tokens", any())]),
+        InfoStatement::Tb(table) => {
+            let Some(TypeAST::Object(fields)) = tables
+                .fields
+                .get(&table.to_lowercase())
+                .map(|table| &table.ast)
+            else {
+                return Err(AnalysisError::UnknownField(table.to_string()));
+            };
+            sections([
+                ("events", any()),
+                (
+                    "fields",
+                    definitions(fields.fields.keys().filter(|name| *name != "id")),
+                ),
+                ("indexes", any()),
+                ("lives", any()),
+                ("tables", any()),
+            ])
+        }
+        InfoStatement::User(_, _) => TypeAST::Scalar(ScalarType::String),
+    };
+    Ok(ast)
+}
+
+/// A map from the names of any definitions to the statements defining them.
+fn any() -> TypeAST {
+    TypeAST::Object(ObjectType::default())
+}
+
+/// A map from each of `names` to the statement defining it.
+fn definitions<'a>(names: impl Iterator<Item = &'a String>) -> TypeAST {
+    sections(names.map(|name| (name.as_str(), TypeAST::Scalar(ScalarType::String))))
+}
+
+fn sections<'a>(sections: impl IntoIterator<Item = (&'a str, TypeAST)>) -> TypeAST {
+    TypeAST::Object(ObjectType {
+        fields: sections
+            .into_iter()
+            .map(|(name, ast)| {
+                let info = FieldInfo {
+                    ast,
+                    meta: FieldMetadata {
+                        original_name: name.to_string(),
+                        original_path: vec![name.to_string()],
+                        permissions: Permissions::default(),
+                        constraints: vec![],
+                        default: None,
+                        deprecated: None,
+                    },
+                };
+                (name.to_string(), info)
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schema::analyze_schema, typing::shape};
+    use surrealdb::sql::{parse, Statement};
+
+    fn info(sql: &str) -> Result<String, AnalysisError> {
+        let schema = analyze_schema(
+            parse(
+                r#"
+                DEFINE TABLE user SCHEMAFULL;
+                    DEFINE FIELD name ON user TYPE string;
+                    DEFINE FIELD address ON user TYPE object;
+                        DEFINE FIELD address.city ON user TYPE string;
+                DEFINE TABLE post SCHEMALESS;
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let query = parse(sql).unwrap();
+        let Some(Statement::Info(stmt)) = query.first() else {
+            panic!("Expected an INFO statement");
+        };
+        analyze_info(&schema, stmt).map(|ast| shape(&ast))
+    }
+
+    #[test]
+    fn info_for_db() {
+        assert_eq!(
+            info("INFO FOR DB").unwrap(),
+            "{ analyzers: {}, functions: {}, models: {}, params: {}, scopes: {}, \
+             tables: { post: String, user: String }, tokens: {}, users: {} }"
+        );
+    }
+
+    #[test]
+    fn info_for_table() {
+        assert_eq!(
+            info("INFO FOR TABLE user").unwrap(),
+            "{ events: {}, fields: { address: String, name: String }, indexes: {}, lives: {}, \
+             tables: {} }"
+        );
+        assert!(matches!(
+            info("INFO FOR TABLE missing"),
+            Err(AnalysisError::UnknownField(_))
+        ));
+    }
+}
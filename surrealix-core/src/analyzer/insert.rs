@@ -1,8 +1,297 @@
-use super::Tables;
-use crate::types::TypedQuery;
-use surrealdb::sql::statements::InsertStatement;
+use super::{select::apply_field_selection, Analyzer};
+use crate::{
+    ast::{ScalarType, TypeAST},
+    errors::AnalysisError,
+    typing::shape,
+};
+use surrealdb::sql::{
+    statements::InsertStatement, Data, Idiom, Number, Operator, Output, Part, Value,
+};
 
-pub fn analyze_insert(tbls: &Tables, insert: &InsertStatement) -> TypedQuery {
-    // Implement insert analysis logic here
-    todo!("Implement insert analysis")
+/// Types an INSERT by the table it inserts into, after checking the inserted values against
+/// the table's fields.
+///
+/// Values only known at runtime, like parameters and function calls, aren't checked.
+pub fn analyze_insert(
+    analyzer: &Analyzer,
+    schema: &TypeAST,
+    stmt: &InsertStatement,
+) -> Result<TypeAST, AnalysisError> {
+    let TypeAST::Object(schema_obj) = schema else {
+        return Err(AnalysisError::UnsupportedType(format!(
+            "Schema was not an object! This should not be possible. Please file a bug report."
+        )));
+    };
+    let Value::Table(table) = &stmt.into else {
+        return Err(AnalysisError::UnsupportedOperation(format!(
+            "Inserting into {}, rather than a table",
+            stmt.into
+        )));
+    };
+    let table = table.to_string();
+    let record = schema_obj
+        .fields
+        .get(&table.to_lowercase())
+        .map(|field_info| field_info.ast.clone())
+        .ok_or_else(|| AnalysisError::UnknownField(table.clone()))?;
+
+    match &stmt.data {
+        // `INSERT INTO user { ... }`, or `[{ ... }, { ... }]` for several records.
+        Data::SingleExpression(Value::Object(object)) => check_object(&table, &record, object)?,
+        Data::SingleExpression(Value::Array(rows)) => {
+            for row in rows.iter() {
+                check_value(&table, &record, row)?;
+            }
+        }
+        // `INSERT INTO user (name, age) VALUES ("Ann", 30), ("Bob", 31)`
+        Data::ValuesExpression(rows) => {
+            for (idiom, value) in rows.iter().flatten() {
+                check_field(&table, &record, idiom, value)?;
+            }
+        }
+        _ => {}
+    }
+    // `ON DUPLICATE KEY UPDATE age += 1` only assigns with `=` checkably.
+    if let Some(Data::UpdateExpression(updates)) = &stmt.update {
+        for (idiom, operator, value) in updates {
+            match operator {
+                Operator::Equal => check_field(&table, &record, idiom, value)?,
+                _ => {
+                    field_type(&table, &record, idiom)?;
+                }
+            }
+        }
+    }
+
+    // An INSERT returns the records it inserted, even if it inserted just one.
+    let rows = |row: TypeAST| TypeAST::Array(Box::new((row, None)));
+    match &stmt.output {
+        None | Some(Output::After) => Ok(rows(record)),
+        Some(Output::Before) => Ok(rows(TypeAST::Option(Box::new(record)))),
+        Some(Output::None) => Ok(rows(TypeAST::Scalar(ScalarType::Null))),
+        Some(Output::Null) => Ok(TypeAST::Scalar(ScalarType::Null)),
+        Some(Output::Fields(fields)) => {
+            let (selected, _) = apply_field_selection(analyzer, schema, &record, fields, false)?;
+            Ok(rows(selected))
+        }
+        Some(_) => Ok(rows(TypeAST::Scalar(ScalarType::Any))),
+    }
+}
+
+/// The type of the field at `idiom`, e.g. `address.city`, in a record of `table`.
+fn field_type(table: &str, record: &TypeAST, idiom: &Idiom) -> Result<TypeAST, AnalysisError> {
+    let mut current = record;
+    for part in idiom.iter() {
+        let Part::Field(name) = part else {
+            return Ok(TypeAST::Scalar(ScalarType::Any));
+        };
+        current = match current {
+            TypeAST::Option(inner) => &**inner,
+            current => current,
+        };
+        match current {
+            // The record id doesn't need to be defined, and a bare object holds anything.
+            _ if name.as_str() == "id" => return Ok(TypeAST::Scalar(ScalarType::Any)),
+            TypeAST::Object(obj) if obj.fields.is_empty() => {
+                return Ok(TypeAST::Scalar(ScalarType::Any))
+            }
+            TypeAST::Object(obj) => match obj.fields.get(name.as_str()) {
+                Some(field) => current = &field.ast,
+                None => return Err(AnalysisError::UnknownField(format!("{}.{}", table, idiom))),
+            },
+            _ => return Ok(TypeAST::Scalar(ScalarType::Any)),
+        }
+    }
+    Ok(current.clone())
+}
+
+fn check_field(
+    table: &str,
+    record: &TypeAST,
+    idiom: &Idiom,
+    value: &Value,
+) -> Result<(), AnalysisError> {
+    let expected = field_type(table, record, idiom)?;
+    check_value(&format!("{}.{}", table, idiom), &expected, value)
+}
+
+fn check_object(
+    path: &str,
+    expected: &TypeAST,
+    object: &surrealdb::sql::Object,
+) -> Result<(), AnalysisError> {
+    for (key, value) in object.iter() {
+        let idiom = Idiom::from(key.clone());
+        check_field(path, expected, &idiom, value)?;
+    }
+    Ok(())
+}
+
+/// Checks a value given for the field at `path` against the field's type.
+fn check_value(path: &str, expected: &TypeAST, value: &Value) -> Result<(), AnalysisError> {
+    if fits(path, expected, value)? {
+        Ok(())
+    } else {
+        Err(AnalysisError::TypeMismatch(
+            path.to_string(),
+            shape(expected),
+            value.to_string(),
+        ))
+    }
+}
+
+/// Whether `value` fits `expected`, or could at runtime. Errors in nested values are returned
+/// as they are, so they name the nested field.
+fn fits(path: &str, expected: &TypeAST, value: &Value) -> Result<bool, AnalysisError> {
+    let fits = match (expected, value) {
+        // Values computed at runtime can't be checked.
+        (
+            _,
+            Value::Param(_)
+            | Value::Function(_)
+            | Value::Expression(_)
+            | Value::Subquery(_)
+            | Value::Idiom(_)
+            | Value::Future(_)
+            | Value::Cast(_),
+        ) => true,
+        (TypeAST::Scalar(ScalarType::Any), _) => true,
+        (TypeAST::Option(_), Value::None | Value::Null) => true,
+        (TypeAST::Option(inner), value) => fits(path, inner, value)?,
+        (TypeAST::Union(variants), value) => {
+            for variant in variants {
+                if fits(path, variant, value).unwrap_or(false) {
+                    return Ok(true);
+                }
+            }
+            false
+        }
+        (TypeAST::Scalar(scalar), value) => scalar_fits(scalar, value),
+        (TypeAST::Record(table), Value::Thing(thing)) => thing.tb.eq_ignore_ascii_case(table),
+        (TypeAST::Record(_), _) => false,
+        (TypeAST::Array(inner), Value::Array(items)) => {
+            if inner.1.map_or(false, |len| items.len() as u64 > len.get()) {
+                return Ok(false);
+            }
+            for (index, item) in items.iter().enumerate() {
+                check_value(&format!("{}[{}]", path, index), &inner.0, item)?;
+            }
+            true
+        }
+        (TypeAST::Array(_), _) => false,
+        (TypeAST::Object(obj), Value::Object(object)) => {
+            if !obj.fields.is_empty() {
+                check_object(path, expected, object)?;
+            }
+            true
+        }
+        (TypeAST::Object(_), _) => false,
+    };
+    Ok(fits)
+}
+
+fn scalar_fits(scalar: &ScalarType, value: &Value) -> bool {
+    match (scalar, value) {
+        (ScalarType::Any, _) => true,
+        (ScalarType::Null, value) => matches!(value, Value::None | Value::Null),
+        (ScalarType::String, value) => matches!(value, Value::Strand(_)),
+        (ScalarType::Boolean, value) => matches!(value, Value::Bool(_)),
+        (ScalarType::Number, value) => matches!(value, Value::Number(_)),
+        // Whole floats are accepted as ints, as the database does.
+        (ScalarType::Integer, Value::Number(Number::Float(float))) => float.fract() == 0.0,
+        (ScalarType::Integer, value) => matches!(value, Value::Number(_)),
+        (ScalarType::Float, value) => matches!(value, Value::Number(_)),
+        // Strings are accepted wherever the database parses them into the type.
+        (ScalarType::Datetime, value) => matches!(value, Value::Datetime(_) | Value::Strand(_)),
+        (ScalarType::Duration, value) => matches!(value, Value::Duration(_) | Value::Strand(_)),
+        (ScalarType::Uuid, value) => matches!(value, Value::Uuid(_) | Value::Strand(_)),
+        // Geometries and bytes take too many forms to check.
+        (ScalarType::Point | ScalarType::Geometry | ScalarType::Bytes | ScalarType::Set, _) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::analyze_schema;
+    use surrealdb::sql::{parse, Statement};
+
+    fn analyze(sql: &str) -> Result<String, AnalysisError> {
+        let schema = analyze_schema(
+            parse(
+                r#"
+                DEFINE TABLE user SCHEMAFULL;
+                    DEFINE FIELD name ON user TYPE string;
+                    DEFINE FIELD age ON user TYPE int;
+                    DEFINE FIELD nickname ON user TYPE option<string>;
+                    DEFINE FIELD address ON user TYPE object;
+                        DEFINE FIELD address.city ON user TYPE string;
+                    DEFINE FIELD best_friend ON user TYPE option<record<user>>;
+                    DEFINE FIELD tags ON user TYPE array;
+                        DEFINE FIELD tags.* ON user TYPE string;
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let query = parse(sql).unwrap();
+        let Some(Statement::Insert(stmt)) = query.first() else {
+            panic!("Expected an INSERT");
+        };
+        analyze_insert(&Analyzer::default(), &schema, stmt).map(|ast| shape(&ast))
+    }
+
+    #[test]
+    fn insert_content() {
+        let user = "{ address: { city: String }, age: Integer, best_friend: Option<Record(user)>, id: Record(user), name: String, nickname: Option<String>, tags: [String] }";
+
+        assert_eq!(
+            analyze(r#"INSERT INTO user { name: "Ann", age: 30, address: { city: "Oslo" } }"#)
+                .unwrap(),
+            format!("[{}]", user)
+        );
+        assert_eq!(
+            analyze(r#"INSERT INTO user [{ name: "Ann", tags: ["a"] }, { name: $name, best_friend: user:bob }]"#)
+                .unwrap(),
+            format!("[{}]", user)
+        );
+        assert_eq!(
+            analyze(r#"INSERT INTO user { id: "ann", name: "Ann" } RETURN name"#).unwrap(),
+            "[{ name: String }]"
+        );
+    }
+
+    #[test]
+    fn insert_values() {
+        assert!(analyze(r#"INSERT INTO user (name, age) VALUES ("Ann", 30), ("Bob", 31)"#).is_ok());
+        assert!(matches!(
+            analyze(r#"INSERT INTO user (name, age) VALUES ("Ann", 30), ("Bob", "old")"#),
+            Err(AnalysisError::TypeMismatch(field, expected, _))
+                if field == "user.age" && expected == "Integer"
+        ));
+    }
+
+    #[test]
+    fn insert_invalid_content() {
+        assert!(matches!(
+            analyze(r#"INSERT INTO user { name: "Ann", email: "ann@example.com" }"#),
+            Err(AnalysisError::UnknownField(field)) if field == "user.email"
+        ));
+        assert!(matches!(
+            analyze(r#"INSERT INTO user { address: { city: 7 } }"#),
+            Err(AnalysisError::TypeMismatch(field, _, _)) if field == "user.address.city"
+        ));
+        assert!(matches!(
+            analyze(r#"INSERT INTO user { tags: ["a", 1] }"#),
+            Err(AnalysisError::TypeMismatch(field, _, _)) if field == "user.tags[1]"
+        ));
+        assert!(matches!(
+            analyze(r#"INSERT INTO user { best_friend: post:1 }"#),
+            Err(AnalysisError::TypeMismatch(field, _, _)) if field == "user.best_friend"
+        ));
+        assert!(matches!(
+            analyze(r#"INSERT INTO post { title: "Hi" }"#),
+            Err(AnalysisError::UnknownField(table)) if table == "post"
+        ));
+    }
 }
@@ -0,0 +1,88 @@
+use super::{select::analyze_select, Analyzer};
+use crate::{
+    ast::{ScalarType, TypeAST},
+    errors::AnalysisError,
+};
+use surrealdb::sql::{
+    statements::{LiveStatement, SelectStatement},
+    Values,
+};
+
+/// Types the record carried by each notification of a LIVE SELECT.
+///
+/// Notifications carry one record at a time, as selected by the statement's fields, or the
+/// JSON patches describing the change for `LIVE SELECT DIFF`.
+pub fn analyze_live(
+    analyzer: &Analyzer,
+    schema: &TypeAST,
+    stmt: &LiveStatement,
+) -> Result<TypeAST, AnalysisError> {
+    // DIFF is parsed as selecting no fields at all.
+    if stmt.expr.0.is_empty() {
+        return Ok(TypeAST::Array(Box::new((
+            TypeAST::Scalar(ScalarType::Any),
+            None,
+        ))));
+    }
+
+    // A record is typed as it would be selected on its own.
+    let mut select = SelectStatement::default();
+    select.expr = stmt.expr.clone();
+    select.what = Values(vec![stmt.what.clone()]);
+    select.only = true;
+    select.cond = stmt.cond.clone();
+    select.fetch = stmt.fetch.clone();
+    analyze_select(analyzer, schema, &select)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schema::analyze_schema, typing::shape};
+    use surrealdb::sql::{parse, Statement};
+
+    fn notification(sql: &str) -> Result<String, AnalysisError> {
+        let schema = analyze_schema(
+            parse(
+                r#"
+                DEFINE TABLE user SCHEMAFULL;
+                    DEFINE FIELD name ON user TYPE string;
+                    DEFINE FIELD best_friend ON user TYPE record<user>;
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let query = parse(sql).unwrap();
+        let Some(Statement::Live(stmt)) = query.first() else {
+            panic!("Expected a LIVE SELECT");
+        };
+        analyze_live(&Analyzer::default(), &schema, stmt).map(|ast| shape(&ast))
+    }
+
+    #[test]
+    fn live_select_records() {
+        assert_eq!(
+            notification("LIVE SELECT * FROM user").unwrap(),
+            "{ best_friend: Record(user), id: Record(user), name: String }"
+        );
+        assert_eq!(
+            notification("LIVE SELECT name FROM user WHERE name != NONE").unwrap(),
+            "{ name: String }"
+        );
+        assert_eq!(
+            notification("LIVE SELECT * FROM user FETCH best_friend").unwrap(),
+            "{ best_friend: { best_friend: Record(user), id: Record(user), name: String }, \
+             id: Record(user), name: String }"
+        );
+    }
+
+    #[test]
+    fn live_select_diff() {
+        assert_eq!(notification("LIVE SELECT DIFF FROM user").unwrap(), "[Any]");
+        assert!(matches!(
+            notification("LIVE SELECT * FROM post"),
+            Err(AnalysisError::UnknownField(table)) if table == "post"
+        ));
+    }
+}
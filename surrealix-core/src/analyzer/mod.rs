@@ -1,16 +1,38 @@
+pub mod completion;
 // mod create;
 // mod delete;
-// mod function;
-// mod insert;
+pub(crate) mod function;
+mod info;
+mod insert;
+mod live;
+pub(crate) mod operators;
+mod params;
 // mod relate;
-mod select;
+pub(crate) mod select;
 // mod update;
 
 use crate::errors::AnalysisError;
-use crate::{ast::TypeAST, errors, schema::analyze_schema};
-use select::analyze_select;
-use std::collections::HashMap;
-use surrealdb::sql::{Query, Statement};
+use crate::{
+    ast::{ScalarType, TypeAST},
+    errors,
+    lint::{lint_query_with, Lint},
+    schema::{analyze_schema_with, value_type, SchemaParseError},
+};
+use function::{
+    analyze_function,
+    ml::{analyze_model, model_names, MODEL_ARGS},
+};
+use info::analyze_info;
+use insert::analyze_insert;
+use live::analyze_live;
+use params::{builtin_params, path_type};
+use select::{analyze_select, analyze_select_tables, selects_dynamic_table};
+use std::{collections::HashMap, num::NonZeroU64};
+use surrealdb::sql::{
+    parse,
+    statements::{DefineStatement, ForeachStatement, IfelseStatement},
+    Block, Function, Kind, Model, Param, Part, Query, Statement, Subquery, Value,
+};
 
 pub type Tables = HashMap<String, TypeAST>;
 
@@ -20,21 +42,771 @@ pub type Tables = HashMap<String, TypeAST>;
 /// This TypeAST encompasses all transformations performed by the query on the base schema.
 /// There may be gaps in the analysis, represented by [ScalarType::Any].
 pub fn analyze(schema: Query, query: Query) -> Result<Vec<TypeAST>, AnalysisError> {
-    let parsed = analyze_schema(schema)?;
+    Analyzer::default().analyze(schema, query)
+}
+
+/// The result type of a statement.
+pub enum StatementType {
+    Fixed(TypeAST),
+    /// A SELECT from `type::table($param)`, with the result for each candidate table.
+    PerTable(Vec<(String, TypeAST)>),
+    /// A LIVE SELECT, with the record carried by each of its notifications.
+    Live(TypeAST),
+}
 
-    query
-        .iter()
-        .map(|q| analyze_statement(&parsed, q))
-        .collect()
+/// Analyzes the query like [analyze], resolving `type::table($param)` against the candidate `tables`.
+pub fn analyze_tables(
+    schema: Query,
+    query: Query,
+    tables: &[String],
+) -> Result<Vec<StatementType>, AnalysisError> {
+    Analyzer::default().analyze_tables(schema, query, tables)
 }
 
 /// Computes statement transforms over a base AST.
 ///
 /// For top level statements, 'base_type' should contain an object for each table.
 /// For other statements, base_type is the type a statement is transforming.
-fn analyze_statement(base_type: &TypeAST, stmt: &Statement) -> Result<TypeAST, AnalysisError> {
-    match stmt {
-        Statement::Select(sel_stmt) => analyze_select(base_type, sel_stmt),
-        _ => todo!("Statement: {:?} is not supported", stmt),
+pub fn analyze_statement(base_type: &TypeAST, stmt: &Statement) -> Result<TypeAST, AnalysisError> {
+    Analyzer::default().analyze_statement(base_type, stmt)
+}
+
+/// Computes the type of an expression, such as an IF or a subquery, against the tables of the
+/// schema.
+///
+/// Expressions the analyzer has no rule for are typed as [ScalarType::Any].
+pub fn analyze_expression(schema: &TypeAST, value: &Value) -> Result<TypeAST, AnalysisError> {
+    Analyzer::default().analyze_expression(schema, value)
+}
+
+/// The arguments and result of a function the analyzer has no built-in rule for, like one from
+/// a `DEFINE FUNCTION`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSignature {
+    pub args: Vec<Kind>,
+    pub returns: Kind,
+}
+
+impl FunctionSignature {
+    pub fn new(args: impl IntoIterator<Item = Kind>, returns: Kind) -> Self {
+        Self {
+            args: args.into_iter().collect(),
+            returns,
+        }
+    }
+}
+
+/// The analyzer, extended with typing rules for custom functions and kinds.
+///
+/// The free functions of this module analyze with the built-in rules only.
+///
+/// ```
+/// use surrealdb::sql::Kind;
+/// use surrealix_core::analyzer::{Analyzer, FunctionSignature};
+///
+/// let analyzer = Analyzer::new()
+///     .with_function("fn::geohash", FunctionSignature::new([Kind::Point], Kind::String));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Analyzer {
+    functions: HashMap<String, FunctionSignature>,
+    kinds: HashMap<Kind, TypeAST>,
+    /// The types of parameters, keyed by their name without the `$`.
+    params: HashMap<String, TypeAST>,
+    /// Whether statements are within the body of a FOR loop, where BREAK and CONTINUE can be used.
+    in_loop: bool,
+}
+
+impl Analyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Types calls to the function `name`, e.g. `fn::geohash`, by its signature.
+    ///
+    /// Built-in functions can be registered too, which replaces their built-in rule. SurrealML
+    /// models are registered as `ml::name<version>` for one version or `ml::name` for all of
+    /// them, e.g. when a model outputs an integer rather than a float.
+    pub fn with_function(mut self, name: impl Into<String>, signature: FunctionSignature) -> Self {
+        self.functions.insert(name.into(), signature);
+        self
+    }
+
+    /// Types schema fields of `kind` as `ast`, e.g. `decimal` as a string.
+    ///
+    /// Kinds are matched exactly, wherever they appear in a field's type, so mapping `decimal`
+    /// also applies to `option<decimal>`.
+    pub fn with_kind(mut self, kind: Kind, ast: TypeAST) -> Self {
+        self.kinds.insert(kind, ast);
+        self
+    }
+
+    /// Types the parameter `$name` as `ast`, e.g. one the application always binds.
+    ///
+    /// Parameters bound by a LET in the query are typed by their value instead.
+    pub fn with_param(mut self, name: impl Into<String>, ast: TypeAST) -> Self {
+        self.params.insert(name.into(), ast);
+        self
+    }
+
+    /// Lints a query like [lint_query](crate::lint::lint_query), knowing the types of the
+    /// registered parameters.
+    pub fn lint_query(&self, schema: &Query, query: &Query, large_tables: &[String]) -> Vec<Lint> {
+        lint_query_with(self, schema, query, large_tables)
+    }
+
+    /// Analyzes a schema like [analyze_schema](crate::schema::analyze_schema).
+    pub fn analyze_schema(&self, schema: Query) -> Result<TypeAST, SchemaParseError> {
+        analyze_schema_with(self, schema)
+    }
+
+    /// Analyzes a query like [analyze].
+    pub fn analyze(&self, schema: Query, query: Query) -> Result<Vec<TypeAST>, AnalysisError> {
+        let mut schema = schema;
+        let mut parsed = self.analyze_schema(schema.clone())?;
+
+        let mut scope = self.clone().with_schema_params(&schema, &parsed);
+        query
+            .iter()
+            .map(|q| {
+                scope.bind(&parsed, q)?;
+                let ast = scope.analyze_statement(&parsed, q)?;
+                self.apply_ddl(&mut schema, &mut parsed, q)?;
+                Ok(ast)
+            })
+            .collect()
+    }
+
+    /// Analyzes a query like [analyze_tables].
+    pub fn analyze_tables(
+        &self,
+        schema: Query,
+        query: Query,
+        tables: &[String],
+    ) -> Result<Vec<StatementType>, AnalysisError> {
+        let mut schema = schema;
+        let mut parsed = self.analyze_schema(schema.clone())?;
+
+        let mut scope = self.clone().with_schema_params(&schema, &parsed);
+        query
+            .iter()
+            .map(|stmt| {
+                scope.bind(&parsed, stmt)?;
+                let analyzed = match stmt {
+                    Statement::Select(sel_stmt) if selects_dynamic_table(sel_stmt) => {
+                        analyze_select_tables(&scope, &parsed, sel_stmt, tables)
+                            .map(StatementType::PerTable)
+                    }
+                    Statement::Live(live) => {
+                        analyze_live(&scope, &parsed, live).map(StatementType::Live)
+                    }
+                    stmt => scope
+                        .analyze_statement(&parsed, stmt)
+                        .map(StatementType::Fixed),
+                }?;
+                self.apply_ddl(&mut schema, &mut parsed, stmt)?;
+                Ok(analyzed)
+            })
+            .collect()
+    }
+
+    /// Analyzes a statement like [analyze_statement].
+    pub fn analyze_statement(
+        &self,
+        base_type: &TypeAST,
+        stmt: &Statement,
+    ) -> Result<TypeAST, AnalysisError> {
+        match stmt {
+            Statement::Select(sel_stmt) => analyze_select(self, base_type, sel_stmt),
+            Statement::Insert(insert) => analyze_insert(self, base_type, insert),
+            Statement::Ifelse(ifelse) => self.analyze_ifelse(base_type, ifelse),
+            Statement::Output(output) => self.analyze_expression(base_type, &output.what),
+            Statement::Value(value) => self.analyze_expression(base_type, value),
+            // The result of a LIVE SELECT is its id, with records sent in notifications.
+            Statement::Live(live) => {
+                analyze_live(self, base_type, live)?;
+                Ok(TypeAST::Scalar(ScalarType::Uuid))
+            }
+            Statement::Foreach(foreach) => self.analyze_foreach(base_type, foreach),
+            Statement::Info(info) => analyze_info(base_type, info),
+            Statement::Break(_) | Statement::Continue(_) if !self.in_loop => {
+                Err(AnalysisError::UnsupportedOperation(format!(
+                    "{} can only be used within a FOR loop",
+                    stmt
+                )))
+            }
+            // Schema changes, LET and the statements controlling a loop return NONE. A THROW
+            // never returns, so it has no result of its own either.
+            Statement::Define(_)
+            | Statement::Remove(_)
+            | Statement::Set(_)
+            | Statement::Break(_)
+            | Statement::Continue(_)
+            | Statement::Throw(_) => Ok(TypeAST::Scalar(ScalarType::Null)),
+            _ => Err(AnalysisError::UnsupportedOperation(stmt.to_string())),
+        }
+    }
+
+    /// Applies a DEFINE or REMOVE in the query to the schema, so the statements after it are
+    /// analyzed against the schema as it changed.
+    fn apply_ddl(
+        &self,
+        schema: &mut Query,
+        parsed: &mut TypeAST,
+        stmt: &Statement,
+    ) -> Result<(), AnalysisError> {
+        if let Statement::Define(_) | Statement::Remove(_) = stmt {
+            schema.0 .0.push(stmt.clone());
+            *parsed = self.analyze_schema(schema.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Types `$auth`, `$session` and `$token`, which SurrealDB sets for every query, and the
+    /// parameters the schema defines by their values, unless they are registered.
+    fn with_schema_params(mut self, schema: &Query, parsed: &TypeAST) -> Self {
+        for (name, ast) in builtin_params(schema) {
+            self.params.entry(name).or_insert(ast);
+        }
+        for stmt in schema.iter() {
+            if let Statement::Define(DefineStatement::Param(param)) = stmt {
+                if let Ok(ast) = self.analyze_expression(parsed, &param.value) {
+                    self.params.entry(param.name.0.clone()).or_insert(ast);
+                }
+            }
+        }
+        self
+    }
+
+    /// Binds the parameter of a LET, so the statements after it can refer to it.
+    fn bind(&mut self, schema: &TypeAST, stmt: &Statement) -> Result<(), AnalysisError> {
+        if let Statement::Set(set) = stmt {
+            let ast = self.analyze_expression(schema, &set.what)?;
+            self.params.insert(set.name.clone(), ast);
+        }
+        Ok(())
+    }
+
+    /// The type of a parameter, if it is registered or bound by a LET.
+    pub(crate) fn param_type(&self, param: &Param) -> Option<&TypeAST> {
+        self.params.get(param.0.as_str())
+    }
+
+    /// Analyzes an expression like [analyze_expression].
+    pub fn analyze_expression(
+        &self,
+        schema: &TypeAST,
+        value: &Value,
+    ) -> Result<TypeAST, AnalysisError> {
+        match value {
+            Value::Subquery(subquery) => match subquery.as_ref() {
+                Subquery::Select(select) => analyze_select(self, schema, select),
+                Subquery::Insert(insert) => analyze_insert(self, schema, insert),
+                Subquery::Ifelse(ifelse) => self.analyze_ifelse(schema, ifelse),
+                Subquery::Output(output) => self.analyze_expression(schema, &output.what),
+                Subquery::Value(value) => self.analyze_expression(schema, value),
+                _ => Ok(TypeAST::Scalar(ScalarType::Any)),
+            },
+            Value::Block(block) => self.analyze_block(schema, block),
+            Value::Param(param) => Ok(self
+                .param_type(param)
+                .cloned()
+                .unwrap_or(TypeAST::Scalar(ScalarType::Any))),
+            // A path into a parameter, e.g. `$auth.name`.
+            Value::Idiom(idiom) => match idiom.0.split_first() {
+                Some((Part::Start(Value::Param(param)), path)) => match self.param_type(param) {
+                    Some(ast) => path_type(schema, ast, path),
+                    None => Ok(TypeAST::Scalar(ScalarType::Any)),
+                },
+                _ => Ok(value_type(self, value)),
+            },
+            value => Ok(value_type(self, value)),
+        }
+    }
+
+    /// The type of an IF, which is any of the types of its branches.
+    fn analyze_ifelse(
+        &self,
+        schema: &TypeAST,
+        stmt: &IfelseStatement,
+    ) -> Result<TypeAST, AnalysisError> {
+        let mut branches = stmt
+            .exprs
+            .iter()
+            .map(|(_, then)| self.analyze_expression(schema, then))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Without an ELSE, an IF that matches no condition returns NONE.
+        branches.push(match &stmt.close {
+            Some(close) => self.analyze_expression(schema, close)?,
+            None => TypeAST::Scalar(ScalarType::Null),
+        });
+        Ok(TypeAST::union(branches))
+    }
+
+    /// Checks the body of a FOR loop, with the loop's parameter typed as an item of what it
+    /// iterates over. The loop itself returns NONE.
+    fn analyze_foreach(
+        &self,
+        schema: &TypeAST,
+        stmt: &ForeachStatement,
+    ) -> Result<TypeAST, AnalysisError> {
+        let item = match self.analyze_expression(schema, &stmt.range)? {
+            TypeAST::Array(items) => items.0,
+            TypeAST::Option(inner) => match *inner {
+                TypeAST::Array(items) => items.0,
+                _ => TypeAST::Scalar(ScalarType::Any),
+            },
+            _ => TypeAST::Scalar(ScalarType::Any),
+        };
+        let mut body = self.clone().with_param(stmt.param.0.as_str(), item);
+        body.in_loop = true;
+        body.analyze_block(schema, &stmt.block)?;
+        Ok(TypeAST::Scalar(ScalarType::Null))
+    }
+
+    /// The type of a block, which is that of its first RETURN, or else its last statement.
+    ///
+    /// Statements after a THROW, BREAK or CONTINUE never run, so they aren't analyzed.
+    fn analyze_block(&self, schema: &TypeAST, block: &Block) -> Result<TypeAST, AnalysisError> {
+        // The entries of a block can't be named outside of surrealdb, so they are analyzed as
+        // the statements they print as.
+        let source: String = block.iter().map(|entry| format!("{};", entry)).collect();
+        let Ok(statements) = parse(&source) else {
+            return Ok(TypeAST::Scalar(ScalarType::Any));
+        };
+
+        let mut scope = self.clone();
+        let mut ast = TypeAST::Scalar(ScalarType::Null);
+        for stmt in statements.iter() {
+            scope.bind(schema, stmt)?;
+            ast = scope.analyze_statement(schema, stmt)?;
+            if let Statement::Output(_)
+            | Statement::Throw(_)
+            | Statement::Break(_)
+            | Statement::Continue(_) = stmt
+            {
+                break;
+            }
+        }
+        Ok(ast)
+    }
+
+    /// The type of a field of `kind`, with registered kinds in place of the built-in ones.
+    pub(crate) fn kind_type(&self, kind: &Kind) -> TypeAST {
+        if let Some(ast) = self.kinds.get(kind) {
+            return ast.clone();
+        }
+        if self.kinds.is_empty() {
+            return TypeAST::from(kind.clone());
+        }
+        match kind {
+            Kind::Option(inner) => TypeAST::Option(Box::new(self.kind_type(inner))),
+            Kind::Set(inner, len) | Kind::Array(inner, len) => TypeAST::Array(Box::new((
+                self.kind_type(inner),
+                len.and_then(NonZeroU64::new),
+            ))),
+            Kind::Either(kinds) => {
+                TypeAST::Union(kinds.iter().map(|kind| self.kind_type(kind)).collect())
+            }
+            kind => TypeAST::from(kind.clone()),
+        }
+    }
+
+    /// Whether `kind` itself is registered, rather than only kinds within it.
+    pub(crate) fn maps_kind(&self, kind: &Kind) -> bool {
+        self.kinds.contains_key(kind)
+    }
+
+    /// The type of a call to `func`, by its registered signature or the built-in rules.
+    pub(crate) fn function_type(&self, func: &Function, args: Vec<TypeAST>) -> TypeAST {
+        match self.signature(func) {
+            Some((_, signature)) => TypeAST::from(signature.returns.clone()),
+            None => analyze_function(func, args),
+        }
+    }
+
+    /// The type of a call to a registered function, if `func` is one, after checking it is
+    /// given as many arguments as it takes.
+    pub(crate) fn call_type(&self, func: &Function) -> Option<Result<TypeAST, AnalysisError>> {
+        let (name, signature) = self.signature(func)?;
+        let given = func.args().len();
+        if given != signature.args.len() {
+            return Some(Err(AnalysisError::FunctionArity(
+                name,
+                signature.args.len(),
+                given,
+            )));
+        }
+        Some(Ok(self.kind_type(&signature.returns)))
+    }
+
+    /// The type of a call to a SurrealML model, by the signature registered for it or the
+    /// built-in rule.
+    pub(crate) fn model_type(&self, model: &Model) -> TypeAST {
+        match self.model_signature(model) {
+            Some((_, signature)) => self.kind_type(&signature.returns),
+            None => analyze_model(),
+        }
+    }
+
+    /// The type of a call to a SurrealML model, after checking it is given as many arguments as
+    /// its registered signature takes, or otherwise the one argument SurrealDB passes to models.
+    pub(crate) fn model_call_type(&self, model: &Model) -> Result<TypeAST, AnalysisError> {
+        let (name, takes, returns) = match self.model_signature(model) {
+            Some((name, signature)) => (
+                name,
+                signature.args.len(),
+                self.kind_type(&signature.returns),
+            ),
+            None => (model_names(model)[0].clone(), MODEL_ARGS, analyze_model()),
+        };
+        if model.args.len() != takes {
+            return Err(AnalysisError::FunctionArity(name, takes, model.args.len()));
+        }
+        Ok(returns)
+    }
+
+    /// The signature registered for the version of `model`, or else for all of its versions.
+    fn model_signature(&self, model: &Model) -> Option<(String, &FunctionSignature)> {
+        model_names(model).into_iter().find_map(|name| {
+            let signature = self.functions.get(&name)?;
+            Some((name, signature))
+        })
+    }
+
+    fn signature(&self, func: &Function) -> Option<(String, &FunctionSignature)> {
+        let name = match func {
+            Function::Normal(name, _) => name.clone(),
+            Function::Custom(name, _) => format!("fn::{}", name),
+            _ => return None,
+        };
+        let signature = self.functions.get(&name)?;
+        Some((name, signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::typing::{assert_typing, shape};
+    use surrealdb::sql::parse;
+
+    const SCHEMA: &str = r#"
+        DEFINE TABLE place SCHEMAFULL;
+            DEFINE FIELD location ON place TYPE geometry<point>;
+            DEFINE FIELD hash ON place VALUE fn::geohash(location);
+            DEFINE FIELD price ON place TYPE option<decimal>;
+    "#;
+
+    fn analyzer() -> Analyzer {
+        Analyzer::new()
+            .with_function(
+                "fn::geohash",
+                FunctionSignature::new([Kind::Point], Kind::String),
+            )
+            .with_kind(Kind::Decimal, TypeAST::Scalar(ScalarType::String))
+    }
+
+    #[test]
+    fn custom_functions_and_kinds() {
+        let analyze = |analyzer: &Analyzer, query: &str| {
+            analyzer
+                .analyze(parse(SCHEMA).unwrap(), parse(query).unwrap())
+                .map(|types| shape(&types[0]))
+        };
+
+        assert_eq!(
+            analyze(&analyzer(), "SELECT hash, price FROM place").unwrap(),
+            "[{ hash: String, price: Option<String> }]"
+        );
+        assert_eq!(
+            analyze(&Analyzer::new(), "SELECT hash, price FROM place").unwrap(),
+            "[{ hash: Any, price: Option<Number> }]"
+        );
+
+        assert_eq!(
+            analyze(
+                &analyzer(),
+                "SELECT fn::geohash(location) AS hash FROM place"
+            )
+            .unwrap(),
+            "[{ hash: String }]"
+        );
+        assert!(matches!(
+            analyze(&analyzer(), "SELECT fn::geohash(location, 5) AS hash FROM place"),
+            Err(AnalysisError::FunctionArity(name, 1, 2)) if name == "fn::geohash"
+        ));
+        assert!(matches!(
+            analyze(
+                &Analyzer::new(),
+                "SELECT fn::geohash(location) AS hash FROM place"
+            ),
+            Err(AnalysisError::UnsupportedOperation(_))
+        ));
+    }
+
+    #[test]
+    fn ml_models() {
+        let analyze = |analyzer: &Analyzer, query: &str| {
+            analyzer
+                .analyze(parse(SCHEMA).unwrap(), parse(query).unwrap())
+                .map(|types| shape(&types[0]))
+        };
+        let query = "SELECT ml::house::price<1.0.0>({ hash: hash }) AS price FROM place";
+
+        assert_eq!(
+            analyze(&Analyzer::new(), query).unwrap(),
+            "[{ price: Float }]"
+        );
+        // A signature for one version of a model takes precedence over one for all of them.
+        let analyzer = Analyzer::new()
+            .with_function(
+                "ml::house::price",
+                FunctionSignature::new([Kind::Object], Kind::Int),
+            )
+            .with_function(
+                "ml::house::price<2.0.0>",
+                FunctionSignature::new([Kind::Object], Kind::Decimal),
+            );
+        assert_eq!(analyze(&analyzer, query).unwrap(), "[{ price: Integer }]");
+        assert_eq!(
+            analyze(&analyzer, &query.replace("1.0.0", "2.0.0")).unwrap(),
+            "[{ price: Number }]"
+        );
+        // As are the values the schema computes with a model.
+        let Value::Model(model) = surrealdb::sql::value("ml::house::price<2.0.0>(1)").unwrap()
+        else {
+            panic!("Expected a model call");
+        };
+        assert_eq!(
+            analyzer.model_type(&model),
+            TypeAST::Scalar(ScalarType::Number)
+        );
+        assert_eq!(
+            Analyzer::new().model_type(&model),
+            TypeAST::Scalar(ScalarType::Float)
+        );
+
+        assert!(matches!(
+            analyze(
+                &Analyzer::new(),
+                "SELECT ml::house::price<1.0.0>(1, 2) AS price FROM place"
+            ),
+            Err(AnalysisError::FunctionArity(name, 1, 2)) if name == "ml::house::price<1.0.0>"
+        ));
+        assert_typing(SCHEMA, "RETURN ml::house::price<1.0.0>(3) * 2", "Number");
+    }
+
+    #[test]
+    fn let_bindings() {
+        const USERS: &str = r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD age ON user TYPE int;
+                DEFINE FIELD best_friend ON user TYPE record<user>;
+        "#;
+
+        assert_typing(
+            USERS,
+            "LET $adults = (SELECT * FROM user WHERE age > 18); SELECT name FROM $adults",
+            "Null; [{ name: String }]",
+        );
+        assert_typing(
+            USERS,
+            "LET $friend = (SELECT VALUE best_friend FROM ONLY user:john); SELECT age FROM $friend",
+            "Null; [{ age: Integer }]",
+        );
+        assert_typing(
+            USERS,
+            "LET $max = 10; SELECT name, $max AS max FROM user",
+            "Null; [{ max: Integer, name: String }]",
+        );
+
+        let analyze = |analyzer: Analyzer, query: &str| {
+            analyzer.analyze(parse(USERS).unwrap(), parse(query).unwrap())
+        };
+        // Rows of an untyped parameter may be anything.
+        assert_eq!(
+            analyze(
+                Analyzer::new(),
+                "SELECT name FROM $friends; SELECT * FROM ONLY $friend"
+            )
+            .unwrap(),
+            vec![
+                TypeAST::Array(Box::new((TypeAST::Scalar(ScalarType::Any), None))),
+                TypeAST::Scalar(ScalarType::Any),
+            ]
+        );
+        // Parameters the schema defines are typed by their values.
+        assert_typing(
+            &format!("{} DEFINE PARAM $admins VALUE (SELECT * FROM user);", USERS),
+            "SELECT name FROM $admins",
+            "[{ name: String }]",
+        );
+        // The clauses of a SELECT from an untyped parameter are still checked.
+        assert!(matches!(
+            analyze(
+                Analyzer::new(),
+                "SELECT * FROM $friends WHERE name = 'a' - 1"
+            ),
+            Err(AnalysisError::InvalidOperands(..))
+        ));
+        assert!(matches!(
+            analyze(Analyzer::new(), "SELECT * FROM $friends LIMIT 'ten'"),
+            Err(AnalysisError::InvalidCount(..))
+        ));
+        assert!(matches!(
+            analyze(Analyzer::new(), "LET $max = 10; SELECT name FROM $max"),
+            Err(AnalysisError::UnsupportedOperation(_))
+        ));
+        let friends = TypeAST::Array(Box::new((TypeAST::Record("user".into()), None)));
+        assert!(analyze(
+            Analyzer::new().with_param("friends", friends),
+            "SELECT name FROM $friends"
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn ifelse_branches() {
+        const USERS: &str = r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD age ON user TYPE int;
+        "#;
+
+        assert_typing(USERS, "IF $admin THEN 'all' ELSE 'some' END", "String");
+        assert_typing(
+            USERS,
+            "IF $admin THEN 'all' ELSE IF $guest THEN 0 ELSE false END",
+            "Union(String | Integer | Boolean)",
+        );
+        assert_typing(USERS, "IF $admin THEN 'all' END", "Option<String>");
+        assert_typing(
+            USERS,
+            "IF $admin { LET $all = (SELECT name FROM user); RETURN $all; } ELSE { 'nobody' }",
+            "Union([{ name: String }] | String)",
+        );
+        assert_typing(
+            USERS,
+            "SELECT IF age >= 18 THEN 'adult' ELSE 'minor' END AS category FROM user",
+            "[{ category: String }]",
+        );
+    }
+
+    #[test]
+    fn for_loops() {
+        const USERS: &str = r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD best_friend ON user TYPE record<user>;
+        "#;
+
+        assert_typing(
+            USERS,
+            "FOR $user IN (SELECT * FROM user) { LET $friend = $user.best_friend; }; RETURN 1",
+            "Null; Integer",
+        );
+        assert_typing(
+            USERS,
+            "FOR $friend IN (SELECT VALUE best_friend FROM user) { SELECT name FROM $friend; }",
+            "Null",
+        );
+
+        let analyze_query = |query: &str| analyze(parse(USERS).unwrap(), parse(query).unwrap());
+        assert!(matches!(
+            analyze_query("FOR $user IN (SELECT * FROM user) { SELECT nickname FROM $user; }"),
+            Err(AnalysisError::UnknownField(_))
+        ));
+    }
+
+    #[test]
+    fn control_flow() {
+        const USERS: &str = r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD banned ON user TYPE bool;
+        "#;
+
+        assert_typing(
+            USERS,
+            "FOR $user IN (SELECT * FROM user) { \
+                IF $user.banned { CONTINUE } ELSE IF $user.name = 'admin' { BREAK }; \
+                IF $user.name = '' { THROW 'A user has no name' }; \
+             }",
+            "Null",
+        );
+        assert_typing(USERS, "THROW 'Not allowed'; RETURN 1", "Null; Integer");
+
+        let analyze_query = |query: &str| analyze(parse(USERS).unwrap(), parse(query).unwrap());
+        assert!(matches!(
+            analyze_query("BREAK"),
+            Err(AnalysisError::UnsupportedOperation(_))
+        ));
+        assert!(matches!(
+            analyze_query("IF true { CONTINUE }"),
+            Err(AnalysisError::UnsupportedOperation(_))
+        ));
+    }
+
+    #[test]
+    fn session_params() {
+        const ACCOUNTS: &str = r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD email ON user TYPE string;
+            DEFINE SCOPE account SESSION 24h
+                SIGNIN ( SELECT * FROM user WHERE email = $email )
+                SIGNUP ( CREATE user SET email = $email );
+        "#;
+
+        assert_typing(
+            ACCOUNTS,
+            "RETURN $auth; RETURN $auth.id; RETURN $auth.name; RETURN $session.ns",
+            "Option<Record(user)>; Option<Record(user)>; Option<String>; Option<String>",
+        );
+        assert_typing(
+            ACCOUNTS,
+            "SELECT name, $auth.id AS me FROM user; SELECT * FROM $auth",
+            "[{ me: Option<Record(user)>, name: String }]; [{ email: String, id: Record(user), name: String }]",
+        );
+        assert_typing(ACCOUNTS, "RETURN $token.email", "Any");
+        assert_typing("DEFINE TABLE user SCHEMALESS;", "RETURN $auth", "Any");
+
+        let analyze_query = |query: &str| analyze(parse(ACCOUNTS).unwrap(), parse(query).unwrap());
+        assert!(matches!(
+            analyze_query("RETURN $auth.nickname"),
+            Err(AnalysisError::UnknownField(_))
+        ));
+    }
+
+    #[test]
+    fn ddl_in_queries() {
+        const USERS: &str = r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+        "#;
+
+        assert_typing(
+            USERS,
+            "DEFINE FIELD email ON user TYPE option<string>; SELECT name, email FROM user",
+            "Null; [{ email: Option<String>, name: String }]",
+        );
+        assert_typing(
+            USERS,
+            "DEFINE TABLE post SCHEMAFULL; DEFINE FIELD title ON post TYPE string; \
+             DEFINE INDEX title ON post FIELDS title; SELECT title FROM post",
+            "Null; Null; Null; [{ title: String }]",
+        );
+
+        let analyze_query = |query: &str| analyze(parse(USERS).unwrap(), parse(query).unwrap());
+        assert!(matches!(
+            analyze_query("REMOVE FIELD name ON user; SELECT name FROM user"),
+            Err(AnalysisError::UnknownField(field)) if field == "name"
+        ));
+        assert!(matches!(
+            analyze_query("REMOVE TABLE user; SELECT * FROM user"),
+            Err(AnalysisError::UnknownField(table)) if table == "user"
+        ));
     }
 }
@@ -0,0 +1,340 @@
+use super::{params::path_type, Analyzer};
+use crate::{
+    ast::{ScalarType, TypeAST},
+    errors::AnalysisError,
+    schema::value_type,
+};
+use surrealdb::sql::{Expression, Operator, Part, Subquery, Value};
+
+/// The types operators tell apart, with NONE, NULL and values of unknown type left out, as any
+/// operator may be applied to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operand {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Datetime,
+    Duration,
+    Uuid,
+    Bytes,
+    Geometry,
+    Record,
+    Array,
+    Object,
+}
+
+impl Operand {
+    fn of(ast: &TypeAST) -> Option<Operand> {
+        match ast {
+            TypeAST::Option(inner) => Operand::of(inner),
+            // A union is only known to be one of them if all of them are.
+            TypeAST::Union(variants) => {
+                let mut operands = variants
+                    .iter()
+                    .filter(|variant| **variant != TypeAST::Scalar(ScalarType::Null))
+                    .map(Operand::of);
+                let first = operands.next()??;
+                operands
+                    .all(|operand| operand == Some(first))
+                    .then_some(first)
+            }
+            TypeAST::Record(_) => Some(Operand::Record),
+            TypeAST::Array(_) => Some(Operand::Array),
+            TypeAST::Object(_) => Some(Operand::Object),
+            TypeAST::Scalar(scalar) => match scalar {
+                ScalarType::String => Some(Operand::String),
+                ScalarType::Integer => Some(Operand::Integer),
+                ScalarType::Number | ScalarType::Float => Some(Operand::Number),
+                ScalarType::Boolean => Some(Operand::Boolean),
+                ScalarType::Datetime => Some(Operand::Datetime),
+                ScalarType::Duration => Some(Operand::Duration),
+                ScalarType::Uuid => Some(Operand::Uuid),
+                ScalarType::Bytes => Some(Operand::Bytes),
+                ScalarType::Point | ScalarType::Geometry => Some(Operand::Geometry),
+                ScalarType::Set => Some(Operand::Array),
+                ScalarType::Any | ScalarType::Null => None,
+            },
+        }
+    }
+
+    fn is_number(self) -> bool {
+        matches!(self, Operand::Integer | Operand::Number)
+    }
+
+    /// Whether values of both can be equal, or be ordered against each other meaningfully.
+    fn comparable(self, other: Operand) -> bool {
+        self == other || (self.is_number() && other.is_number())
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Operand::String => "string",
+            Operand::Integer => "int",
+            Operand::Number => "number",
+            Operand::Boolean => "bool",
+            Operand::Datetime => "datetime",
+            Operand::Duration => "duration",
+            Operand::Uuid => "uuid",
+            Operand::Bytes => "bytes",
+            Operand::Geometry => "geometry",
+            Operand::Record => "record",
+            Operand::Array => "array",
+            Operand::Object => "object",
+        }
+    }
+}
+
+/// Types an expression over a row of `base_type`, e.g. `age + 1` or `name > 18`, checking that
+/// each operator is applied to operands it is defined for.
+///
+/// Operands whose type isn't known, like untyped parameters, are allowed with any operator.
+/// Subqueries are left to their own analysis.
+pub(super) fn expression_type(
+    analyzer: &Analyzer,
+    schema: &TypeAST,
+    base_type: &TypeAST,
+    value: &Value,
+) -> Result<TypeAST, AnalysisError> {
+    let operand = |value: &Value| expression_type(analyzer, schema, base_type, value);
+    match value {
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Unary { o, v } => unary_type(value, o, &operand(v)?),
+            Expression::Binary { l, o, r } => binary_type(value, &operand(l)?, o, &operand(r)?),
+            _ => Ok(TypeAST::Scalar(ScalarType::Any)),
+        },
+        Value::Idiom(idiom) => match idiom.first() {
+            Some(Part::Field(ident))
+                if ident.as_str() == "id"
+                    && !matches!(base_type, TypeAST::Object(obj) if obj.fields.contains_key("id")) =>
+            {
+                Ok(TypeAST::Scalar(ScalarType::Any))
+            }
+            Some(Part::Field(_)) => path_type(schema, base_type, &idiom.0),
+            _ => analyzer.analyze_expression(schema, value),
+        },
+        Value::Subquery(subquery) => match subquery.as_ref() {
+            Subquery::Value(value) => operand(value),
+            _ => Ok(TypeAST::Scalar(ScalarType::Any)),
+        },
+        // Built-in functions are typed by their arguments as the row types them, e.g.
+        // `array::first(tags)` by the elements of `tags`.
+        Value::Function(function) => {
+            let args = function
+                .args()
+                .iter()
+                .map(operand)
+                .collect::<Result<Vec<_>, _>>()?;
+            match analyzer.call_type(function) {
+                Some(ast) => ast,
+                None => Ok(analyzer.function_type(function, args)),
+            }
+        }
+        Value::Model(model) => {
+            for arg in &model.args {
+                operand(arg)?;
+            }
+            analyzer.model_call_type(model)
+        }
+        Value::Param(_) => analyzer.analyze_expression(schema, value),
+        Value::Thing(thing) => Ok(TypeAST::Record(thing.tb.clone())),
+        Value::Array(_) => Ok(TypeAST::Array(Box::new((
+            TypeAST::Scalar(ScalarType::Any),
+            None,
+        )))),
+        Value::None | Value::Null => Ok(TypeAST::Scalar(ScalarType::Null)),
+        value => Ok(value_type(analyzer, value)),
+    }
+}
+
+fn unary_type(expr: &Value, o: &Operator, v: &TypeAST) -> Result<TypeAST, AnalysisError> {
+    match (o, Operand::of(v)) {
+        (Operator::Not, _) => Ok(TypeAST::Scalar(ScalarType::Boolean)),
+        (Operator::Neg, Some(operand)) if !operand.is_number() => Err(
+            AnalysisError::InvalidOperands(expr.to_string(), o.to_string(), operand.name().into()),
+        ),
+        _ => Ok(v.clone()),
+    }
+}
+
+/// The type of a binary expression, following SurrealDB's rules for each operator.
+pub(crate) fn binary_type(
+    expr: &Value,
+    l: &TypeAST,
+    o: &Operator,
+    r: &TypeAST,
+) -> Result<TypeAST, AnalysisError> {
+    let scalar = |scalar| Ok(TypeAST::Scalar(scalar));
+    let (Some(lo), Some(ro)) = (Operand::of(l), Operand::of(r)) else {
+        return match o {
+            Operator::Or | Operator::And | Operator::Tco | Operator::Nco => {
+                Ok(TypeAST::union([l.clone(), r.clone()]))
+            }
+            Operator::Add | Operator::Sub | Operator::Mul | Operator::Div | Operator::Pow => {
+                scalar(ScalarType::Any)
+            }
+            _ => scalar(ScalarType::Boolean),
+        };
+    };
+    let invalid = || {
+        Err(AnalysisError::InvalidOperands(
+            expr.to_string(),
+            o.to_string(),
+            format!("{} and {}", lo.name(), ro.name()),
+        ))
+    };
+
+    match o {
+        // Either operand may be the result, depending on which is truthy.
+        Operator::Or | Operator::And | Operator::Tco | Operator::Nco => {
+            Ok(TypeAST::union([l.clone(), r.clone()]))
+        }
+        Operator::Add => match (lo, ro) {
+            (Operand::Integer, Operand::Integer) => scalar(ScalarType::Integer),
+            (lo, ro) if lo.is_number() && ro.is_number() => scalar(ScalarType::Number),
+            (Operand::String, Operand::String) => scalar(ScalarType::String),
+            (Operand::Datetime, Operand::Duration) | (Operand::Duration, Operand::Datetime) => {
+                scalar(ScalarType::Datetime)
+            }
+            (Operand::Duration, Operand::Duration) => scalar(ScalarType::Duration),
+            _ => invalid(),
+        },
+        Operator::Sub => match (lo, ro) {
+            (Operand::Integer, Operand::Integer) => scalar(ScalarType::Integer),
+            (lo, ro) if lo.is_number() && ro.is_number() => scalar(ScalarType::Number),
+            (Operand::Datetime, Operand::Duration) | (Operand::Duration, Operand::Datetime) => {
+                scalar(ScalarType::Datetime)
+            }
+            (Operand::Datetime, Operand::Datetime) | (Operand::Duration, Operand::Duration) => {
+                scalar(ScalarType::Duration)
+            }
+            _ => invalid(),
+        },
+        Operator::Mul | Operator::Pow => match (lo, ro) {
+            (Operand::Integer, Operand::Integer) => scalar(ScalarType::Integer),
+            (lo, ro) if lo.is_number() && ro.is_number() => scalar(ScalarType::Number),
+            _ => invalid(),
+        },
+        Operator::Div if lo.is_number() && ro.is_number() => scalar(ScalarType::Number),
+        Operator::Div => invalid(),
+        // Values of different types are never equal, and are ordered by their type alone.
+        Operator::Equal
+        | Operator::Exact
+        | Operator::NotEqual
+        | Operator::LessThan
+        | Operator::LessThanOrEqual
+        | Operator::MoreThan
+        | Operator::MoreThanOrEqual
+            if !lo.comparable(ro) =>
+        {
+            invalid()
+        }
+        Operator::Contain
+        | Operator::NotContain
+        | Operator::ContainAll
+        | Operator::ContainAny
+        | Operator::ContainNone
+            if !matches!(lo, Operand::String | Operand::Array | Operand::Geometry) =>
+        {
+            invalid()
+        }
+        Operator::Inside
+        | Operator::NotInside
+        | Operator::AllInside
+        | Operator::AnyInside
+        | Operator::NoneInside
+            if !matches!(ro, Operand::String | Operand::Array | Operand::Geometry) =>
+        {
+            invalid()
+        }
+        // Fuzzy matches compare strings, or with `*~` and `?~` each string of an array, and
+        // full-text matches search a string, or the strings of an array, for a string's terms.
+        Operator::Like | Operator::NotLike if (lo, ro) != (Operand::String, Operand::String) => {
+            invalid()
+        }
+        Operator::AllLike | Operator::AnyLike | Operator::Matches(_)
+            if !matches!(lo, Operand::String | Operand::Array) || ro != Operand::String =>
+        {
+            invalid()
+        }
+        _ => scalar(ScalarType::Boolean),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schema::analyze_schema, typing::shape};
+    use surrealdb::sql::{parse, Statement};
+
+    fn expression(sql: &str) -> Result<String, AnalysisError> {
+        let schema = analyze_schema(
+            parse(
+                r#"
+                DEFINE TABLE event SCHEMAFULL;
+                    DEFINE FIELD name ON event TYPE string;
+                    DEFINE FIELD seats ON event TYPE int;
+                    DEFINE FIELD price ON event TYPE option<float>;
+                    DEFINE FIELD starts ON event TYPE datetime;
+                    DEFINE FIELD length ON event TYPE duration;
+                    DEFINE FIELD tags ON event TYPE array<string>;
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let TypeAST::Object(tables) = &schema else {
+            panic!("Expected the tables of the schema");
+        };
+        let row = &tables.fields["event"].ast;
+        let query = parse(&format!("RETURN {}", sql)).unwrap();
+        let Some(Statement::Output(output)) = query.first() else {
+            panic!("Expected a RETURN statement");
+        };
+        expression_type(&Analyzer::default(), &schema, row, &output.what).map(|ast| shape(&ast))
+    }
+
+    #[test]
+    fn operator_types() {
+        assert_eq!(expression("seats + 1").unwrap(), "Integer");
+        assert_eq!(expression("seats / 2").unwrap(), "Number");
+        assert_eq!(expression("price * seats").unwrap(), "Number");
+        assert_eq!(expression("name + '!'").unwrap(), "String");
+        assert_eq!(expression("starts + length").unwrap(), "Datetime");
+        assert_eq!(expression("starts - starts").unwrap(), "Duration");
+        assert_eq!(
+            expression("seats > 10 AND 'music' IN tags").unwrap(),
+            "Boolean"
+        );
+        assert_eq!(expression("price != NONE").unwrap(), "Boolean");
+        assert_eq!(
+            expression("name ~ 'gala' OR tags ?~ 'jazz' OR name @@ 'opera'").unwrap(),
+            "Boolean"
+        );
+        assert_eq!(expression("-(seats + $extra)").unwrap(), "Any");
+        assert_eq!(expression("array::first(tags)").unwrap(), "String");
+    }
+
+    #[test]
+    fn invalid_operands() {
+        for sql in [
+            "name > 18",
+            "seats = 'ten'",
+            "length + starts + name",
+            "starts * 2",
+            "-name",
+            "seats CONTAINS 1",
+            "'music' IN name + seats",
+            "seats ~ 'ten'",
+            "name !~ 10",
+            "tags *~ starts",
+            "seats @@ 'ten'",
+        ] {
+            assert!(
+                matches!(expression(sql), Err(AnalysisError::InvalidOperands(..))),
+                "{}",
+                sql
+            );
+        }
+    }
+}
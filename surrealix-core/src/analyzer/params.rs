@@ -0,0 +1,145 @@
+use crate::{
+    ast::{FieldInfo, FieldMetadata, ObjectType, ScalarType, TypeAST},
+    errors::AnalysisError,
+};
+use surrealdb::sql::{
+    statements::DefineStatement, Part, Permissions, Query, Statement, Subquery, Value,
+};
+
+/// The parameters SurrealDB sets for every query, typed against the schema:
+///
+/// - `$auth`, the record signed in to a scope, of a table one of the scopes signs in or up to.
+/// - `$session`, the namespace, database, scope and other details of the session.
+/// - `$token`, the claims of the token the session was authenticated with.
+///
+/// Each is NONE when the session isn't authenticated that way.
+pub(super) fn builtin_params(schema: &Query) -> Vec<(String, TypeAST)> {
+    let mut tables: Vec<TypeAST> = vec![];
+    for stmt in schema.iter() {
+        if let Statement::Define(DefineStatement::Scope(scope)) = stmt {
+            for access in [&scope.signin, &scope.signup].into_iter().flatten() {
+                if let Some(table) = access_table(access) {
+                    tables.push(TypeAST::Record(table));
+                }
+            }
+        }
+    }
+    let record = match tables.is_empty() {
+        true => TypeAST::Scalar(ScalarType::Any),
+        false => TypeAST::union(tables),
+    };
+    let auth = TypeAST::union([record, TypeAST::Scalar(ScalarType::Null)]);
+
+    let string = || TypeAST::Option(Box::new(TypeAST::Scalar(ScalarType::String)));
+    let session = object([
+        ("db", string()),
+        ("id", string()),
+        ("ip", string()),
+        ("ns", string()),
+        ("or", string()),
+        ("sc", string()),
+        ("sd", auth.clone()),
+        (
+            "tk",
+            TypeAST::Option(Box::new(TypeAST::Object(ObjectType::default()))),
+        ),
+    ]);
+    let token = TypeAST::Option(Box::new(TypeAST::Object(ObjectType::default())));
+
+    vec![
+        ("auth".to_string(), auth),
+        ("session".to_string(), session),
+        ("token".to_string(), token),
+    ]
+}
+
+/// The table a scope's SIGNIN or SIGNUP selects or creates its record in.
+fn access_table(access: &Value) -> Option<String> {
+    let Value::Subquery(subquery) = access else {
+        return None;
+    };
+    let what = match subquery.as_ref() {
+        Subquery::Select(select) => &select.what,
+        Subquery::Create(create) => &create.what,
+        _ => return None,
+    };
+    match what.first()? {
+        Value::Table(table) => Some(table.to_string()),
+        _ => None,
+    }
+}
+
+fn object<'a>(fields: impl IntoIterator<Item = (&'a str, TypeAST)>) -> TypeAST {
+    TypeAST::Object(ObjectType {
+        fields: fields
+            .into_iter()
+            .map(|(name, ast)| {
+                let info = FieldInfo {
+                    ast,
+                    meta: FieldMetadata {
+                        original_name: name.to_string(),
+                        original_path: vec![name.to_string()],
+                        permissions: Permissions::default(),
+                        constraints: vec![],
+                        default: None,
+                        deprecated: None,
+                    },
+                };
+                (name.to_string(), info)
+            })
+            .collect(),
+    })
+}
+
+/// The type of the value at `path` within a value of type `ast`, e.g. `.name` of `$auth`,
+/// following record links through the tables of `schema`.
+///
+/// Objects without any defined fields, and values of any type, hold anything.
+pub(super) fn path_type(
+    schema: &TypeAST,
+    ast: &TypeAST,
+    path: &[Part],
+) -> Result<TypeAST, AnalysisError> {
+    let Some((part, rest)) = path.split_first() else {
+        return Ok(ast.clone());
+    };
+    match (ast, part) {
+        (TypeAST::Option(inner), _) => Ok(TypeAST::union([
+            path_type(schema, inner, path)?,
+            TypeAST::Scalar(ScalarType::Null),
+        ])),
+        (TypeAST::Union(variants), _) => Ok(TypeAST::union(
+            variants
+                .iter()
+                .map(|variant| path_type(schema, variant, path))
+                .collect::<Result<Vec<_>, _>>()?,
+        )),
+        // The id of a record is the link itself, whatever its table defines.
+        (TypeAST::Record(_), Part::Field(ident)) if ident.as_str() == "id" => {
+            path_type(schema, ast, rest)
+        }
+        (TypeAST::Record(table), _) => {
+            let TypeAST::Object(tables) = schema else {
+                return Ok(TypeAST::Scalar(ScalarType::Any));
+            };
+            match tables.fields.get(table) {
+                Some(record) => path_type(schema, &record.ast, path),
+                None => Err(AnalysisError::UnknownField(table.clone())),
+            }
+        }
+        (TypeAST::Object(obj), Part::Field(ident)) => match obj.fields.get(ident.as_str()) {
+            Some(field) => path_type(schema, &field.ast, rest),
+            None if obj.fields.is_empty() => Ok(TypeAST::Scalar(ScalarType::Any)),
+            None => Err(AnalysisError::UnknownField(ident.to_string())),
+        },
+        (TypeAST::Array(items), Part::All) => Ok(TypeAST::Array(Box::new((
+            path_type(schema, &items.0, rest)?,
+            None,
+        )))),
+        (TypeAST::Array(items), Part::Field(_)) => Ok(TypeAST::Array(Box::new((
+            path_type(schema, &items.0, path)?,
+            None,
+        )))),
+        _ => Ok(TypeAST::Scalar(ScalarType::Any)),
+    }
+}
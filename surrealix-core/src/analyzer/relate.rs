@@ -1,8 +1,8 @@
 use super::Tables;
-use crate::types::TypedQuery;
+use crate::ast::TypeAST;
 use surrealdb::sql::statements::RelateStatement;
 
-pub fn analyze_relate(tbls: &Tables, relate: &RelateStatement) -> TypedQuery {
+pub fn analyze_relate(tbls: &Tables, relate: &RelateStatement) -> TypeAST {
     // Implement relate analysis logic here
     todo!("Implement relate analysis")
 }
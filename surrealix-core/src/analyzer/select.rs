@@ -1,44 +1,109 @@
+use super::{operators::expression_type, params::path_type, Analyzer};
 use crate::{
-    ast::{FieldInfo, FieldMetadata, ObjectType, ResolverError, ScalarType, TypeAST},
+    ast::{Constraint, FieldInfo, FieldMetadata, ObjectType, ResolverError, ScalarType, TypeAST},
     errors::AnalysisError,
+    fetch::dynamic_fetch,
+    typing::shape,
 };
 use std::collections::HashMap;
 use surrealdb::sql::{
-    statements::SelectStatement, Fetchs, Field, Fields, Idiom, Idioms, Part, Permissions, Value,
+    statements::SelectStatement, Expression, Fetchs, Field, Fields, Function, Ident, Idiom, Number,
+    Operator, Part, Subquery, Table, Value, Values,
 };
 use thiserror::Error;
-pub fn analyze_select(schema: &TypeAST, stmt: &SelectStatement) -> Result<TypeAST, AnalysisError> {
+pub fn analyze_select(
+    analyzer: &Analyzer,
+    schema: &TypeAST,
+    stmt: &SelectStatement,
+) -> Result<TypeAST, AnalysisError> {
     let TypeAST::Object(schema_obj) = schema else {
         return Err(AnalysisError::UnsupportedType(format!(
             "Schema was not an object! This should not be possible. Please file a bug report."
         )));
     };
 
-    let base_type = analyze_from(&schema_obj, &stmt.what)?;
+    // Selecting from several tables yields the rows of each, e.g. `SELECT * FROM user, post`.
+    if stmt.what.len() > 1 {
+        let results = stmt
+            .what
+            .iter()
+            .map(|what| {
+                let mut stmt = stmt.clone();
+                stmt.what.0 = vec![what.clone()];
+                analyze_select(analyzer, schema, &stmt)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(union_rows(results));
+    }
+
+    let base_type = analyze_from(analyzer, &schema_obj, &stmt.what)?;
+
+    if let Some(limit) = &stmt.limit {
+        validate_count("LIMIT", &limit.0)?;
+    }
+    if let Some(start) = &stmt.start {
+        validate_count("START", &start.0)?;
+    }
+
+    // Nothing is known of rows that may be anything, nor of what is selected from them, but
+    // their WHERE clause is still checked as far as it can be. ORDER BY may sort on any field.
+    if base_type == TypeAST::Scalar(ScalarType::Any) {
+        if let Some(cond) = &stmt.cond {
+            check_condition(analyzer, schema, &base_type, &cond.0)?;
+        }
+        return Ok(match stmt.only {
+            true => base_type,
+            false => TypeAST::Array(Box::new((base_type, None))),
+        });
+    }
 
-    let mut selected_type = apply_field_selection(schema, &base_type, &stmt.expr, &stmt.omit)
-        .map_err(|e| AnalysisError::UnsupportedOperation(e.to_string()))?;
+    let grouped = stmt.group.is_some();
+    let (mut selected_type, projections) =
+        apply_field_selection(analyzer, schema, &base_type, &stmt.expr, grouped)?;
+
+    // SPLIT, GROUP and ORDER run on the selected rows, in that order, so each refers to the
+    // fields of the result as the clauses before it left them.
+    if let Some(splits) = &stmt.split {
+        if stmt.only {
+            return Err(AnalysisError::UnsupportedOperation(
+                "SPLIT yields a row for each element, so it can't select ONLY one row".to_string(),
+            ));
+        }
+        for split in splits.iter() {
+            apply_split(&mut selected_type, &split.0)?;
+        }
+    }
+    // Grouping keeps the shape of the rows, with aggregates typed by apply_field_selection. The
+    // database only allows grouped fields and aggregates in a GROUP BY, while other fields of a
+    // GROUP ALL take their value from the first row, so neither changes the type of a field.
+    if let Some(groups) = &stmt.group {
+        for group in groups.iter() {
+            result_field(&mut selected_type, &group.0)?;
+        }
+    }
+    if let Some(orders) = &stmt.order {
+        let selects_all = stmt.expr.0.iter().any(|field| matches!(field, Field::All));
+        for order in orders.0.iter().filter(|order| !order.random) {
+            check_order_field(&selected_type, &order.order, selects_all)?;
+        }
+    }
 
     if let Some(fetch) = &stmt.fetch {
         for fetch_item in &fetch.0 {
+            if let Some(param) = dynamic_fetch(fetch_item) {
+                return Err(AnalysisError::DynamicFetch(param));
+            }
             let fetched_ast = selected_type
-                .resolve_idiom(&fetch_item.0)
+                .fetched_at(&fetch_item.0, schema)
                 .map_err(|e| AnalysisError::ResolverFailure(e))?;
-            match fetched_ast {
-                TypeAST::Record(_) => {
-                    selected_type
-                        .replace_record_links(schema)
-                        .map_err(|e| AnalysisError::ResolverFailure(e))?;
-                }
+            match &*fetched_ast {
+                TypeAST::Record(_) => {}
+                TypeAST::Array(boxed) if matches!(boxed.0, TypeAST::Record(_)) => {}
                 TypeAST::Array(boxed) => {
-                    if let TypeAST::Record(_) = boxed.0 {
-                        selected_type.replace_record_links(schema)?;
-                    } else {
-                        return Err(AnalysisError::UnsupportedOperation(format!(
-                            "Unsupported fetch type: {:?}",
-                            boxed.0
-                        )));
-                    }
+                    return Err(AnalysisError::UnsupportedOperation(format!(
+                        "Unsupported fetch type: {:?}",
+                        boxed.0
+                    )));
                 }
                 _ => {
                     return Err(AnalysisError::UnsupportedOperation(format!(
@@ -47,52 +112,137 @@ pub fn analyze_select(schema: &TypeAST, stmt: &SelectStatement) -> Result<TypeAS
                     )));
                 }
             }
+            // Only the fetched field becomes the record, other links stay links.
+            fetched_ast.replace_record_links(schema)?;
         }
     }
 
-    // Step 4: Handle VALUE keyword
-    let value_type = if stmt.expr.0.len() == 1 && stmt.expr.1 {
-        // If there's only one field and VALUE keyword is used
-        match &selected_type {
-            TypeAST::Object(obj) => {
-                if let Some(field) = obj.fields.values().next() {
-                    match &field.ast {
-                        TypeAST::Array(boxed) => (*boxed).0.clone(),
-                        _ => field.ast.clone(),
-                    }
-                } else {
-                    return Err(AnalysisError::UnsupportedType(format!(
-                        "'VALUE' cannot be used on an empty object!"
-                    )));
-                }
-            }
+    // OMIT runs on the final shape, so it can reach into traversed and fetched records.
+    if let Some(omit) = &stmt.omit {
+        for idiom in &omit.0 {
+            apply_omit(&mut selected_type, &projections, idiom);
+        }
+    }
+
+    // A WHERE clause ruling out NONE guarantees those fields are in every result.
+    if let Some(cond) = &stmt.cond {
+        check_condition(analyzer, schema, &base_type, &cond.0)?;
+        for idiom in present_fields(&cond.0) {
+            narrow_present(&mut selected_type, &projections, &idiom);
+        }
+    }
+
+    // Step 4: VALUE selects each row's single field, rather than an object holding it. The
+    // field keeps its own type, so `SELECT VALUE tags` still yields an array per row.
+    let row_type = if stmt.expr.1 {
+        let TypeAST::Object(obj) = &selected_type else {
+            return Err(AnalysisError::UnsupportedType(
+                "'VALUE' cannot select from a non-table type.".to_string(),
+            ));
+        };
+        match obj.fields.values().collect::<Vec<_>>().as_slice() {
+            [field] => field.ast.clone(),
             _ => {
-                return Err(AnalysisError::UnsupportedType(format!(
-                    "'VALUE' cannot select from a non-table type."
-                )))
+                return Err(AnalysisError::UnsupportedType(
+                    "'VALUE' must select exactly one field.".to_string(),
+                ))
             }
         }
     } else {
         selected_type
     };
 
-    // Step 5: Wrap in array if not ONLY
-    let final_type = if stmt.only {
-        value_type
+    // Step 5: ONLY yields the single row itself, rather than an array of rows. GROUP ALL yields
+    // no rows at all for an empty table, in which case ONLY yields NONE.
+    let group_all = stmt.group.as_ref().is_some_and(|groups| groups.is_empty());
+    let final_type = if stmt.only && group_all {
+        TypeAST::Option(Box::new(row_type))
+    } else if stmt.only {
+        row_type
     } else {
-        TypeAST::Array(Box::new((value_type, None)))
+        TypeAST::Array(Box::new((row_type, None)))
     };
 
     Ok(final_type)
 }
 
-fn analyze_from(schema: &ObjectType, what: &[Value]) -> Result<TypeAST, AnalysisError> {
-    if let Some(Value::Table(table)) = what.first() {
+/// Analyzes a SELECT from `type::table($param)` once for each of the candidate `tables`,
+/// since which one is selected is only known at runtime.
+pub fn analyze_select_tables(
+    analyzer: &Analyzer,
+    schema: &TypeAST,
+    stmt: &SelectStatement,
+    tables: &[String],
+) -> Result<Vec<(String, TypeAST)>, AnalysisError> {
+    if tables.is_empty() {
+        return Err(missing_tables(&stmt.what));
+    }
+    tables
+        .iter()
+        .map(|table| {
+            let mut stmt = stmt.clone();
+            stmt.what = Values(vec![Value::Table(Table(table.clone()))]);
+            Ok((table.clone(), analyze_select(analyzer, schema, &stmt)?))
+        })
+        .collect()
+}
+
+/// The result of a SELECT from several tables, given the result from each: the rows of any of
+/// them, each a variant of the union.
+fn union_rows(results: Vec<TypeAST>) -> TypeAST {
+    if !results.iter().all(|ast| matches!(ast, TypeAST::Array(_))) {
+        return TypeAST::union(results);
+    }
+    let rows = results.into_iter().map(|ast| match ast {
+        TypeAST::Array(rows) => rows.0,
+        ast => ast,
+    });
+    TypeAST::Array(Box::new((TypeAST::union(rows), None)))
+}
+
+/// Whether a SELECT chooses its table at runtime, e.g. `SELECT * FROM type::table($tb)`.
+pub fn selects_dynamic_table(stmt: &SelectStatement) -> bool {
+    match stmt.what.first() {
+        Some(Value::Function(function)) => {
+            matches!(function.as_ref(), Function::Normal(name, _) if name == "type::table")
+        }
+        _ => false,
+    }
+}
+
+fn analyze_from(
+    analyzer: &Analyzer,
+    schema: &ObjectType,
+    what: &[Value],
+) -> Result<TypeAST, AnalysisError> {
+    // A parameter, e.g. one bound by a LET, selects from the records it holds. One whose type
+    // isn't known, e.g. one the caller binds, may hold any rows, which lint_query warns about.
+    if let Some(Value::Param(param)) = what.first() {
+        let Some(ast) = analyzer.param_type(param) else {
+            return Ok(TypeAST::Scalar(ScalarType::Any));
+        };
+        return param_rows(schema, ast).ok_or_else(|| {
+            AnalysisError::UnsupportedOperation(format!(
+                "Selecting from {}, which doesn't hold records",
+                param
+            ))
+        });
+    }
+
+    // A record id selects from its table, e.g. `user` in `FROM ONLY user:john`.
+    let table = match what.first() {
+        Some(Value::Table(table)) => Some(table.to_string()),
+        Some(Value::Thing(thing)) => Some(thing.tb.clone()),
+        _ => None,
+    };
+    if let Some(table) = table {
         schema
             .fields
-            .get(&table.to_string().to_lowercase())
+            .get(&table.to_lowercase())
             .map(|field_info| field_info.ast.clone())
-            .ok_or_else(|| AnalysisError::UnknownField(table.to_string()))
+            .ok_or_else(|| AnalysisError::UnknownField(table))
+    } else if let Some(Value::Function(_)) = what.first() {
+        Err(missing_tables(what))
     } else {
         Err(AnalysisError::UnsupportedOperation(
             "Unsupported FROM clause".to_string(),
@@ -100,12 +250,49 @@ fn analyze_from(schema: &ObjectType, what: &[Value]) -> Result<TypeAST, Analysis
     }
 }
 
-fn apply_field_selection(
+/// The type of the rows selected from a parameter holding `ast`: its objects, or the records
+/// it links to. Untyped values may hold any rows.
+fn param_rows(schema: &ObjectType, ast: &TypeAST) -> Option<TypeAST> {
+    match ast {
+        TypeAST::Object(_) | TypeAST::Scalar(ScalarType::Any) => Some(ast.clone()),
+        TypeAST::Record(table) => schema.fields.get(table).map(|table| table.ast.clone()),
+        TypeAST::Array(inner) => param_rows(schema, &inner.0),
+        TypeAST::Option(inner) => param_rows(schema, inner),
+        _ => None,
+    }
+}
+
+fn missing_tables(what: &[Value]) -> AnalysisError {
+    AnalysisError::UnsupportedOperation(format!(
+        "Selecting from {} requires the candidate tables, given as `tables = [...]`",
+        Values(what.to_vec())
+    ))
+}
+
+/// Checks that a literal LIMIT or START fits in a `usize`.
+///
+/// Parameters and expressions are only known at runtime, so they are left to the database.
+fn validate_count(clause: &str, value: &Value) -> Result<(), AnalysisError> {
+    let invalid = || AnalysisError::InvalidCount(clause.to_string(), value.to_string());
+    match value {
+        Value::Number(Number::Int(n)) => usize::try_from(*n).map(|_| ()).map_err(|_| invalid()),
+        Value::Number(_) | Value::Strand(_) | Value::Bool(_) | Value::Null | Value::None => {
+            Err(invalid())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The idiom each result field was projected from, keyed by its name in the result.
+type Projections = HashMap<String, Idiom>;
+
+pub(super) fn apply_field_selection(
+    analyzer: &Analyzer,
     schema: &TypeAST,
     base_type: &TypeAST,
     expr: &Fields,
-    omit: &Option<Idioms>,
-) -> Result<TypeAST, AnalysisError> {
+    grouped: bool,
+) -> Result<(TypeAST, Projections), AnalysisError> {
     let TypeAST::Object(base_obj) = base_type else {
         return Err(AnalysisError::UnsupportedType(format!(
             "Selected from a non-object type!"
@@ -121,66 +308,183 @@ fn apply_field_selection(
         .unwrap_or_else(|| "unknown".to_string());
 
     let mut result_fields = HashMap::new();
+    let mut projections = Projections::new();
 
     for field in &expr.0 {
         match field {
             Field::All => {
-                // Include all fields except those in the OMIT clause
                 for (name, field_info) in &base_obj.fields {
-                    if !is_field_omitted(name, omit) {
-                        let mut new_field_info = field_info.clone();
-                        new_field_info
-                            .meta
-                            .original_path
-                            .insert(0, table_name.clone());
-                        result_fields.insert(name.clone(), new_field_info);
-                    }
+                    let mut new_field_info = field_info.clone();
+                    new_field_info
+                        .meta
+                        .original_path
+                        .insert(0, table_name.clone());
+                    project(
+                        &mut projections,
+                        name,
+                        Idiom(vec![Part::Field(Ident::from(name.clone()))]),
+                    )?;
+                    result_fields.insert(name.clone(), new_field_info);
                 }
             }
             Field::Single { expr, alias } => match expr {
-                Value::Idiom(idiom) => {
+                // Paths into a parameter, like `$auth.name`, are typed as expressions.
+                Value::Idiom(idiom) if !matches!(idiom.first(), Some(Part::Start(_))) => {
                     let (field_name, field_ast) =
                         resolve_graph_traversal(schema, base_type, idiom)?;
 
-                    let result_name = alias.as_ref().map(|a| a.to_string()).unwrap_or_else(|| {
-                        if field_name.starts_with("->") || field_name.starts_with("<-") {
-                            field_name
-                                .split("->")
-                                .last()
-                                .unwrap_or(&field_name)
-                                .to_string()
-                        } else {
-                            field_name.clone()
-                        }
-                    });
-
-                    if !is_field_omitted(&result_name, omit) {
-                        let mut original_path = vec![table_name.clone()];
-                        original_path.extend(idiom.0.iter().map(|p| p.to_string()));
-                        let field_info = FieldInfo {
-                            ast: field_ast,
-                            meta: FieldMetadata {
-                                original_name: field_name.clone(),
-                                original_path,
-                                permissions: Permissions::default(),
-                            },
-                        };
-
-                        result_fields.insert(result_name, field_info);
-                    }
+                    let result_name = alias
+                        .as_ref()
+                        .map(|a| a.to_string())
+                        .unwrap_or_else(|| traversal_name(&field_name));
+
+                    let mut original_path = vec![table_name.clone()];
+                    original_path.extend(idiom.0.iter().map(|p| p.to_string()));
+                    let field_info = FieldInfo {
+                        ast: field_ast,
+                        meta: FieldMetadata {
+                            original_name: field_name.clone(),
+                            original_path,
+                            permissions: source_field(base_obj, idiom)
+                                .map(|field| field.meta.permissions.clone())
+                                .unwrap_or_default(),
+                            constraints: source_field(base_obj, idiom)
+                                .map(|field| field.meta.constraints.clone())
+                                .unwrap_or_default(),
+                            default: source_field(base_obj, idiom)
+                                .and_then(|field| field.meta.default.clone()),
+                            deprecated: source_field(base_obj, idiom)
+                                .and_then(|field| field.meta.deprecated.clone()),
+                        },
+                    };
+
+                    project(&mut projections, &result_name, idiom.clone())?;
+                    result_fields.insert(result_name, field_info);
                 }
-                _ => {
-                    return Err(AnalysisError::UnsupportedOperation(
-                        "Unsupported field expression".to_string(),
-                    ));
+                expr => {
+                    let typed = match expr {
+                        Value::Function(func) => analyzer
+                            .call_type(func)
+                            .or_else(|| {
+                                grouped
+                                    .then(|| aggregate_type(schema, base_type, func))
+                                    .flatten()
+                            })
+                            .or_else(|| {
+                                // Only a registered signature says what a custom function returns.
+                                matches!(func.as_ref(), Function::Custom(..)).then(|| {
+                                    Err(AnalysisError::UnsupportedOperation(format!(
+                                        "{} calls a custom function without a registered signature",
+                                        expr
+                                    )))
+                                })
+                            }),
+                        Value::Model(model) => Some(analyzer.model_call_type(model)),
+                        Value::Param(param) => analyzer.param_type(param).cloned().map(Ok),
+                        Value::Idiom(_) => Some(analyzer.analyze_expression(schema, expr)),
+                        // Statements in parentheses, like `(SELECT ...)` or `(IF ...)`, are typed
+                        // on their own, while other values are evaluated against the row.
+                        Value::Subquery(subquery)
+                            if !matches!(subquery.as_ref(), Subquery::Value(_)) =>
+                        {
+                            Some(analyzer.analyze_expression(schema, expr))
+                        }
+                        _ => None,
+                    };
+                    // Anything else, like `price - discount`, `'user'` or `string::len(name)`, is
+                    // evaluated as an expression over the row.
+                    let matched = typed
+                        .or_else(|| match_expression(base_type, expr))
+                        .unwrap_or_else(|| expression_type(analyzer, schema, base_type, expr));
+                    let result_name = alias
+                        .as_ref()
+                        .map(|a| a.to_string())
+                        .unwrap_or_else(|| unaliased_name(expr));
+                    let field_info = FieldInfo {
+                        ast: matched?,
+                        meta: FieldMetadata {
+                            original_name: result_name.clone(),
+                            original_path: vec![table_name.clone(), result_name.clone()],
+                            permissions: Default::default(),
+                            constraints: vec![],
+                            default: None,
+                            deprecated: None,
+                        },
+                    };
+
+                    let idiom = Idiom(vec![Part::Field(Ident::from(result_name.clone()))]);
+                    project(&mut projections, &result_name, idiom)?;
+                    result_fields.insert(result_name, field_info);
                 }
             },
         }
     }
 
-    Ok(TypeAST::Object(ObjectType {
-        fields: result_fields,
-    }))
+    Ok((
+        TypeAST::Object(ObjectType {
+            fields: result_fields,
+        }),
+        projections,
+    ))
+}
+
+/// The name a selected idiom gets without an alias, e.g. `user` for `->friend->user`, as it is
+/// typed against the rows of `base_type`.
+pub(crate) fn idiom_name(
+    schema: &TypeAST,
+    base_type: &TypeAST,
+    idiom: &Idiom,
+) -> Result<String, AnalysisError> {
+    let (field_name, _) = resolve_graph_traversal(schema, base_type, idiom)?;
+    Ok(traversal_name(&field_name))
+}
+
+/// The last step of a resolved graph traversal, or the path of a plain field as it is.
+fn traversal_name(field_name: &str) -> String {
+    if field_name.starts_with("->") || field_name.starts_with("<-") {
+        field_name
+            .split("->")
+            .last()
+            .unwrap_or(field_name)
+            .to_string()
+    } else {
+        field_name.to_string()
+    }
+}
+
+/// The name the database gives an unaliased expression: functions are named after the function
+/// alone, e.g. `count` for `count()`, parameters without their `$`, and anything else after
+/// itself.
+fn unaliased_name(expr: &Value) -> String {
+    match expr.to_idiom().0.as_slice() {
+        [Part::Field(ident)] => ident.0.clone(),
+        _ => expr.to_string(),
+    }
+}
+
+/// Records where a result field came from, rejecting a second field of the same name.
+///
+/// Selecting the same idiom twice (e.g. `SELECT *, name`) is allowed, as it yields the same value.
+fn project(projections: &mut Projections, name: &str, idiom: Idiom) -> Result<(), AnalysisError> {
+    match projections.get(name) {
+        Some(existing) if *existing != idiom => Err(AnalysisError::DuplicateField(
+            name.to_string(),
+            existing.to_string(),
+            idiom.to_string(),
+        )),
+        _ => {
+            projections.insert(name.to_string(), idiom);
+            Ok(())
+        }
+    }
+}
+
+/// The schema field behind a directly selected idiom, whose permissions and constraints carry over.
+fn source_field<'a>(base_obj: &'a ObjectType, idiom: &Idiom) -> Option<&'a FieldInfo> {
+    match idiom.0.as_slice() {
+        [Part::Field(ident)] => base_obj.fields.get(&ident.to_string()),
+        _ => None,
+    }
 }
 
 fn resolve_graph_traversal(
@@ -241,7 +545,11 @@ fn resolve_graph_traversal(
                 }
             }
             Part::Graph(graph) => {
-                let edge_table = &graph.what.0[0].to_string();
+                let Some(edge_table) = graph.what.0.first().map(|table| table.to_string()) else {
+                    return Err(AnalysisError::UnsupportedOperation(
+                        "Graph traversal over edges of any table".to_string(),
+                    ));
+                };
                 field_name = match graph.dir {
                     surrealdb::sql::Dir::Out => format!("->{}", edge_table),
                     surrealdb::sql::Dir::In => format!("<-{}", edge_table),
@@ -254,10 +562,10 @@ fn resolve_graph_traversal(
                 traversal_path.push(field_name.clone());
 
                 if let TypeAST::Object(schema_obj) = schema {
-                    if let Some(edge_table_info) = schema_obj.fields.get(edge_table) {
+                    if let Some(edge_table_info) = schema_obj.fields.get(&edge_table) {
                         if let TypeAST::Object(edge_obj) = &edge_table_info.ast {
                             let (relation_field, target_table) =
-                                find_relation_field(edge_obj, &graph.dir)?;
+                                find_relation_field(&edge_table, edge_obj, &graph.dir)?;
 
                             if let Some(target_table_info) = schema_obj.fields.get(&target_table) {
                                 current_type = &target_table_info.ast;
@@ -311,15 +619,14 @@ fn resolve_graph_traversal(
     Ok((traversal_path.join("->"), final_type))
 }
 
+/// The field an edge table links through in the direction of a traversal, and the table it links
+/// to, e.g. `out` and `user` for `->friend`. A table without `in` or `out`, e.g. `user` in
+/// `->friend->user`, holds the records the traversal lands on, through their `id`.
 fn find_relation_field(
+    table: &str,
     edge_obj: &ObjectType,
     dir: &surrealdb::sql::Dir,
 ) -> Result<(String, String), AnalysisError> {
-    // Handle the case when dealing with the user table
-    if edge_obj.fields.contains_key("id") {
-        return Ok(("id".to_string(), "user".to_string()));
-    }
-
     let (primary, fallback) = match dir {
         surrealdb::sql::Dir::Out => ("out", "in"),
         surrealdb::sql::Dir::In => ("in", "out"),
@@ -346,6 +653,9 @@ fn find_relation_field(
                 )))
             }
         }
+        (None, None) if edge_obj.fields.contains_key("id") => {
+            Ok(("id".to_string(), table.to_string()))
+        }
         (None, None) => Err(AnalysisError::UnknownField(format!(
             "Neither '{}' nor '{}' field found in edge object",
             primary, fallback
@@ -353,23 +663,545 @@ fn find_relation_field(
     }
 }
 
-fn is_field_omitted(field_name: &str, omit: &Option<Idioms>) -> bool {
-    omit.as_ref().map_or(false, |idioms| {
-        idioms.0.iter().any(|idiom| {
-            idiom.0.first().map_or(
-                false,
-                |part| matches!(part, Part::Field(ident) if ident.to_string() == field_name),
-            )
+/// The type of an aggregate function selected by a grouped SELECT, e.g. `math::sum(age)`, whose
+/// argument is then the array of its values across the rows of each group.
+///
+/// Math functions type from the kind of number the field holds: sums and products of integers
+/// are integers, as are the values picked out of them like their maximum, while means and other
+/// statistics are numbers whatever they are of. They fail on fields that don't hold numbers.
+///
+/// Returns `None` for other functions.
+fn aggregate_type(
+    schema: &TypeAST,
+    base_type: &TypeAST,
+    func: &Function,
+) -> Option<Result<TypeAST, AnalysisError>> {
+    let Function::Normal(name, args) = func else {
+        return None;
+    };
+    if !func.is_aggregate() {
+        return None;
+    }
+
+    let value = match args.first() {
+        Some(Value::Idiom(idiom)) => match resolve_graph_traversal(schema, base_type, idiom) {
+            Ok((_, value)) => value,
+            Err(e) => return Some(Err(e)),
+        },
+        _ => TypeAST::Scalar(ScalarType::Any),
+    };
+    let number = || TypeAST::Scalar(ScalarType::Number);
+    let kind = number_kind(&value);
+    let unknown = match &value {
+        TypeAST::Option(inner) => matches!(**inner, TypeAST::Scalar(ScalarType::Any)),
+        value => matches!(value, TypeAST::Scalar(ScalarType::Any)),
+    };
+    if name.starts_with("math::") && kind.is_none() && !unknown {
+        return Some(Err(AnalysisError::TypeMismatch(
+            func.to_string(),
+            "numbers".to_string(),
+            shape(&value),
+        )));
+    }
+    // Math functions skip NONE, so values picked out of a group are only NONE if all of its are.
+    let element = || TypeAST::Scalar(kind.clone().unwrap_or(ScalarType::Number));
+    let picked = || match value {
+        TypeAST::Option(_) => TypeAST::Option(Box::new(element())),
+        _ => element(),
+    };
+    let values = TypeAST::Array(Box::new((value.clone(), None)));
+    let ast = match name.as_str() {
+        "count" => TypeAST::Scalar(ScalarType::Integer),
+        "array::distinct" => values,
+        "array::first" | "array::last" => match values {
+            TypeAST::Array(values) => values.0,
+            values => values,
+        },
+        "array::flatten" | "array::group" => TypeAST::Array(Box::new((flattened(values), None))),
+        "math::sum" | "math::product" if kind == Some(ScalarType::Integer) => {
+            TypeAST::Scalar(ScalarType::Integer)
+        }
+        "math::max" | "math::min" | "math::mode" => picked(),
+        "math::bottom" | "math::top" => TypeAST::Array(Box::new((element(), None))),
+        "time::max" | "time::min" => TypeAST::Scalar(ScalarType::Datetime),
+        name if name.starts_with("math::") => number(),
+        _ => TypeAST::Scalar(ScalarType::Any),
+    };
+    Some(Ok(ast))
+}
+
+/// The kind of number a field holds, whether or not it may be NONE.
+fn number_kind(ast: &TypeAST) -> Option<ScalarType> {
+    match ast {
+        TypeAST::Option(inner) => number_kind(inner),
+        TypeAST::Scalar(
+            scalar @ (ScalarType::Integer | ScalarType::Float | ScalarType::Number),
+        ) => Some(scalar.clone()),
+        _ => None,
+    }
+}
+
+/// The type of the elements of `values` once the arrays among them are flattened into it.
+fn flattened(values: TypeAST) -> TypeAST {
+    let TypeAST::Array(values) = values else {
+        return values;
+    };
+    match values.0 {
+        TypeAST::Array(items) => items.0,
+        TypeAST::Option(inner) => match *inner {
+            TypeAST::Array(items) => TypeAST::Option(Box::new(items.0)),
+            inner => TypeAST::Option(Box::new(inner)),
+        },
+        value => value,
+    }
+}
+
+/// Splits the rows of the result on the array at `idiom`, so that each row holds one of its
+/// elements instead. Fields that aren't arrays are left as they are, as SurrealDB does, so a
+/// field that may or may not be an array (`array<string> | string`) is any of what each leaves.
+fn apply_split(selected: &mut TypeAST, idiom: &Idiom) -> Result<(), AnalysisError> {
+    fn split(ast: TypeAST) -> TypeAST {
+        match ast {
+            TypeAST::Array(items) => items.0,
+            TypeAST::Option(inner) => TypeAST::Option(Box::new(split(*inner))),
+            TypeAST::Union(variants) => TypeAST::union(variants.into_iter().map(split)),
+            ast => ast,
+        }
+    }
+
+    let field = result_field(selected, idiom)?;
+    *field = split(std::mem::replace(field, TypeAST::Scalar(ScalarType::Null)));
+    Ok(())
+}
+
+/// The field at `idiom` in a row of the result, as SPLIT, GROUP and ORDER pick it.
+fn result_field<'a>(
+    selected: &'a mut TypeAST,
+    idiom: &Idiom,
+) -> Result<&'a mut TypeAST, AnalysisError> {
+    fn nested<'a>(ast: &'a mut TypeAST, parts: &[Part]) -> Option<&'a mut TypeAST> {
+        match (ast, parts) {
+            (ast, []) => Some(ast),
+            (TypeAST::Option(inner), parts) => nested(inner, parts),
+            (TypeAST::Object(obj), [Part::Field(ident), rest @ ..]) => {
+                nested(&mut obj.fields.get_mut(&ident.to_string())?.ast, rest)
+            }
+            _ => None,
+        }
+    }
+    nested(selected, &idiom.0).ok_or_else(|| AnalysisError::UnknownField(idiom.to_string()))
+}
+
+/// Checks that ORDER BY sorts on a field of the rows as selected: a field of the table selected
+/// with `*`, or a field or alias of the selection, e.g. `ORDER BY rounded_balance` for
+/// `math::round(balance) AS rounded_balance`.
+///
+/// Every record has an id, whether or not its table defines one, and objects without any defined
+/// fields can hold anything, so those are sorted on freely.
+fn check_order_field(
+    selected: &TypeAST,
+    idiom: &Idiom,
+    selects_all: bool,
+) -> Result<(), AnalysisError> {
+    fn known(ast: &TypeAST, parts: &[Part]) -> bool {
+        match (ast, parts) {
+            (_, []) | (TypeAST::Scalar(ScalarType::Any), _) => true,
+            (TypeAST::Option(inner), parts) => known(inner, parts),
+            // Sorting on a field of the elements of an array sorts on the array of them.
+            (TypeAST::Array(items), parts) => known(&items.0, parts),
+            (TypeAST::Object(obj), [Part::Field(ident), rest @ ..]) => {
+                match obj.fields.get(ident.as_str()) {
+                    Some(field) => known(&field.ast, rest),
+                    None => obj.fields.is_empty(),
+                }
+            }
+            _ => false,
+        }
+    }
+
+    let record_id = matches!(idiom.0.as_slice(), [Part::Field(ident)] if ident.as_str() == "id");
+    match (selects_all && record_id) || known(selected, &idiom.0) {
+        true => Ok(()),
+        false => Err(AnalysisError::UnknownOrderField(idiom.to_string())),
+    }
+}
+
+/// Removes an omitted path from the result.
+///
+/// The path is matched against the idiom each result field was projected from, so
+/// `->friend->user.password` reaches into the field produced by `->friend->user.*`.
+/// Paths that don't resolve are ignored, as SurrealDB does.
+fn apply_omit(selected: &mut TypeAST, projections: &Projections, idiom: &Idiom) {
+    let TypeAST::Object(obj) = selected else {
+        return;
+    };
+    let Some((name, rest)) = projected_field(projections, idiom) else {
+        return;
+    };
+
+    if rest.is_empty() {
+        obj.fields.remove(name);
+    } else if let Some(field) = obj.fields.get_mut(name) {
+        omit_nested(&mut field.ast, rest);
+    }
+}
+
+/// The result field whose source idiom is the longest prefix of `idiom`, along with the rest of the path.
+fn projected_field<'a>(
+    projections: &'a Projections,
+    idiom: &'a Idiom,
+) -> Option<(&'a String, &'a [Part])> {
+    // `->friend->user.*` projects whole records, so its trailing `.*` is not part of the path.
+    let (name, source) = projections
+        .iter()
+        .map(|(name, source)| match source.0.split_last() {
+            Some((Part::All, head)) => (name, head),
+            _ => (name, source.0.as_slice()),
         })
+        .filter(|(_, source)| idiom.0.starts_with(source))
+        .max_by_key(|(_, source)| source.len())?;
+
+    Some((name, &idiom.0[source.len()..]))
+}
+
+/// Types a pattern match, i.e. a fuzzy match (`name ~ 'ann'`) or a regex (`name = /^an+/`), as
+/// a bool, checking that it matches a string.
+///
+/// Returns `None` for other values.
+fn match_expression(base_type: &TypeAST, value: &Value) -> Option<Result<TypeAST, AnalysisError>> {
+    let Value::Expression(expr) = value else {
+        return None;
+    };
+    let Expression::Binary { l, o, r } = expr.as_ref() else {
+        return None;
+    };
+    // `*~` and `?~` match each element of an array.
+    let elementwise = match o {
+        Operator::Like | Operator::NotLike => false,
+        Operator::AllLike | Operator::AnyLike => true,
+        Operator::Equal | Operator::Exact | Operator::NotEqual if matches!(r, Value::Regex(_)) => {
+            false
+        }
+        _ => return None,
+    };
+
+    // Parameters and other values are only known at runtime, as are the fields of rows that
+    // may be anything.
+    let Value::Idiom(idiom) = l else {
+        return Some(Ok(TypeAST::Scalar(ScalarType::Boolean)));
+    };
+    if *base_type == TypeAST::Scalar(ScalarType::Any) {
+        return Some(Ok(TypeAST::Scalar(ScalarType::Boolean)));
+    }
+    let matched = match base_type.resolve_idiom(idiom) {
+        Ok(matched) => matched,
+        Err(e) => return Some(Err(e.into())),
+    };
+    let matched = match matched {
+        TypeAST::Option(inner) => inner.as_ref(),
+        matched => matched,
+    };
+    let is_string = |ast: &TypeAST| {
+        matches!(
+            ast,
+            TypeAST::Scalar(ScalarType::String) | TypeAST::Scalar(ScalarType::Any)
+        )
+    };
+    let valid = match matched {
+        TypeAST::Array(inner) if elementwise => is_string(&inner.0),
+        matched => is_string(matched),
+    };
+    Some(if valid {
+        Ok(TypeAST::Scalar(ScalarType::Boolean))
+    } else {
+        Err(AnalysisError::InvalidMatch(value.to_string()))
     })
 }
 
+/// Checks a WHERE clause on rows of `base_type`: the fields it refers to, the types of its
+/// operands, its pattern matches and the literals it compares fields with.
+fn check_condition(
+    analyzer: &Analyzer,
+    schema: &TypeAST,
+    base_type: &TypeAST,
+    cond: &Value,
+) -> Result<(), AnalysisError> {
+    check_condition_fields(schema, base_type, cond)?;
+    expression_type(analyzer, schema, base_type, cond)?;
+    check_matches(base_type, cond)?;
+    check_literals(base_type, cond)
+}
+
+/// Checks the pattern matches within a WHERE clause.
+fn check_matches(base_type: &TypeAST, cond: &Value) -> Result<(), AnalysisError> {
+    if let Some(matched) = match_expression(base_type, cond) {
+        return matched.map(|_| ());
+    }
+    match cond {
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary { l, r, .. } => {
+                check_matches(base_type, l)?;
+                check_matches(base_type, r)
+            }
+            Expression::Unary { v, .. } => check_matches(base_type, v),
+            _ => Ok(()),
+        },
+        Value::Subquery(subquery) => match subquery.as_ref() {
+            Subquery::Value(value) => check_matches(base_type, value),
+            _ => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Checks that a field restricted to literals is only compared with one of them, e.g. rejecting
+/// `WHERE status = 'shiped'` when `status` is `ASSERT $value INSIDE ['pending', 'shipped']`.
+///
+/// Only values that are constant at compile time are checked, parameters are left to the database.
+fn check_literals(base_type: &TypeAST, cond: &Value) -> Result<(), AnalysisError> {
+    match cond {
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary { l, o, r } => {
+                let compared = match (l, o, r) {
+                    (
+                        Value::Idiom(idiom),
+                        Operator::Inside | Operator::NotInside,
+                        Value::Array(values),
+                    ) => Some((idiom, values.iter().collect())),
+                    (Value::Idiom(idiom), o, value) | (value, o, Value::Idiom(idiom))
+                        if matches!(o, Operator::Equal | Operator::Exact | Operator::NotEqual) =>
+                    {
+                        Some((idiom, vec![value]))
+                    }
+                    _ => None,
+                };
+                if let Some((idiom, values)) = compared {
+                    if let Some(literals) = field_literals(base_type, idiom) {
+                        for value in values.into_iter().filter_map(constant_string) {
+                            if !literals.contains(&value) {
+                                let literals: Vec<_> =
+                                    literals.iter().map(|l| format!("'{}'", l)).collect();
+                                return Err(AnalysisError::UnknownLiteral(
+                                    idiom.to_string(),
+                                    value,
+                                    literals.join(", "),
+                                ));
+                            }
+                        }
+                    }
+                }
+                check_literals(base_type, l)?;
+                check_literals(base_type, r)
+            }
+            Expression::Unary { v, .. } => check_literals(base_type, v),
+            _ => Ok(()),
+        },
+        Value::Subquery(subquery) => match subquery.as_ref() {
+            Subquery::Value(value) => check_literals(base_type, value),
+            _ => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// The literals a field's ASSERT clause restricts it to, following nested objects.
+fn field_literals<'a>(base_type: &'a TypeAST, idiom: &Idiom) -> Option<&'a [String]> {
+    let mut ast = base_type;
+    let mut literals = None;
+    for part in &idiom.0 {
+        let Part::Field(ident) = part else {
+            return None;
+        };
+        let obj = match ast {
+            TypeAST::Object(obj) => obj,
+            TypeAST::Option(inner) => match inner.as_ref() {
+                TypeAST::Object(obj) => obj,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let field = obj.fields.get(ident.as_str())?;
+        literals = field
+            .meta
+            .constraints
+            .iter()
+            .find_map(|constraint| match constraint {
+                Constraint::OneOf(literals) => Some(literals.as_slice()),
+                _ => None,
+            });
+        ast = &field.ast;
+    }
+    literals
+}
+
+/// Evaluates a value to a string if it is constant, e.g. `'ship' + 'ped'` or
+/// `string::lowercase('SHIPPED')`.
+fn constant_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Strand(s) => Some(s.0.clone()),
+        Value::Subquery(subquery) => match subquery.as_ref() {
+            Subquery::Value(value) => constant_string(value),
+            _ => None,
+        },
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary {
+                l,
+                o: Operator::Add,
+                r,
+            } => Some(constant_string(l)? + &constant_string(r)?),
+            _ => None,
+        },
+        Value::Function(func) => {
+            let args = func
+                .args()
+                .iter()
+                .map(constant_string)
+                .collect::<Option<Vec<_>>>()?;
+            match (func.name()?, args.as_slice()) {
+                ("string::concat", args) => Some(args.concat()),
+                ("string::lowercase", [s]) => Some(s.to_lowercase()),
+                ("string::uppercase", [s]) => Some(s.to_uppercase()),
+                ("string::trim", [s]) => Some(s.trim().to_string()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Checks that every field a WHERE clause refers to is defined, e.g. rejecting `nmae` in
+/// `WHERE nmae = 'bob'`, following record links through the schema.
+///
+/// Subqueries are left to their own analysis, and paths into parameters aren't fields.
+fn check_condition_fields(
+    schema: &TypeAST,
+    base_type: &TypeAST,
+    cond: &Value,
+) -> Result<(), AnalysisError> {
+    let check = |value: &Value| check_condition_fields(schema, base_type, value);
+    match cond {
+        Value::Idiom(idiom) => match idiom.first() {
+            // Every record has an id, whether or not its table defines one.
+            Some(Part::Field(ident))
+                if ident.as_str() == "id"
+                    && !matches!(base_type, TypeAST::Object(obj) if obj.fields.contains_key("id")) =>
+            {
+                Ok(())
+            }
+            Some(Part::Field(_)) => path_type(schema, base_type, &idiom.0).map(|_| ()),
+            _ => Ok(()),
+        },
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary { l, r, .. } => {
+                check(l)?;
+                check(r)
+            }
+            Expression::Unary { v, .. } => check(v),
+            _ => Ok(()),
+        },
+        Value::Subquery(subquery) => match subquery.as_ref() {
+            Subquery::Value(value) => check(value),
+            _ => Ok(()),
+        },
+        Value::Function(function) => function.args().iter().try_for_each(check),
+        Value::Model(model) => model.args.iter().try_for_each(check),
+        Value::Array(values) => values.iter().try_for_each(check),
+        _ => Ok(()),
+    }
+}
+
+/// The fields a WHERE clause requires to be present, e.g. `email` in `email != NONE AND age > 18`.
+///
+/// Only conjunctions are followed, since either side of an OR may be the one that holds.
+fn present_fields(cond: &Value) -> Vec<Idiom> {
+    match cond {
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary {
+                l,
+                o: Operator::And,
+                r,
+            } => {
+                let mut fields = present_fields(l);
+                fields.extend(present_fields(r));
+                fields
+            }
+            Expression::Binary {
+                l: Value::Idiom(idiom),
+                o: Operator::NotEqual,
+                r: Value::None | Value::Null,
+            }
+            | Expression::Binary {
+                l: Value::None | Value::Null,
+                o: Operator::NotEqual,
+                r: Value::Idiom(idiom),
+            } => vec![idiom.clone()],
+            _ => vec![],
+        },
+        Value::Subquery(subquery) => match subquery.as_ref() {
+            Subquery::Value(value) => present_fields(value),
+            _ => vec![],
+        },
+        _ => vec![],
+    }
+}
+
+/// Removes the `Option` from a field the WHERE clause guarantees, and from the objects containing it.
+fn narrow_present(selected: &mut TypeAST, projections: &Projections, idiom: &Idiom) {
+    let TypeAST::Object(obj) = selected else {
+        return;
+    };
+    let Some((name, rest)) = projected_field(projections, idiom) else {
+        return;
+    };
+    if let Some(field) = obj.fields.get_mut(name) {
+        narrow_nested(&mut field.ast, rest);
+    }
+}
+
+fn narrow_nested(ast: &mut TypeAST, parts: &[Part]) {
+    while let TypeAST::Option(inner) = ast {
+        let inner = std::mem::replace(inner.as_mut(), TypeAST::Scalar(ScalarType::Null));
+        *ast = inner;
+    }
+
+    // Arrays aren't narrowed through, as the condition only holds for some of their elements.
+    if let (TypeAST::Object(obj), [Part::Field(ident), rest @ ..]) = (ast, parts) {
+        if let Some(field) = obj.fields.get_mut(&ident.to_string()) {
+            narrow_nested(&mut field.ast, rest);
+        }
+    }
+}
+
+fn omit_nested(ast: &mut TypeAST, parts: &[Part]) {
+    match (ast, parts) {
+        (_, []) => {}
+        (TypeAST::Array(inner), [Part::All, rest @ ..]) => omit_nested(&mut inner.0, rest),
+        (TypeAST::Array(inner), parts) => omit_nested(&mut inner.0, parts),
+        (TypeAST::Option(inner), parts) => omit_nested(inner, parts),
+        (TypeAST::Union(variants), parts) => {
+            for variant in variants {
+                omit_nested(variant, parts);
+            }
+        }
+        (TypeAST::Object(obj), [Part::Field(ident), rest @ ..]) => {
+            let name = ident.to_string();
+            if rest.is_empty() {
+                obj.fields.remove(&name);
+            } else if let Some(field) = obj.fields.get_mut(&name) {
+                omit_nested(&mut field.ast, rest);
+            }
+        }
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         ast::{ScalarType, TypeAST},
         schema::analyze_schema,
+        typing::shape,
     };
     use surrealdb::sql::{parse, Statement};
 
@@ -413,7 +1245,7 @@ mod tests {
         let schema = create_test_schema();
         let stmt = parse_select("SELECT id, name, age FROM user");
 
-        let result = analyze_select(&schema, &stmt).unwrap();
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
 
         let TypeAST::Array(boxed_arr) = result else {
             panic!("Expected Array TypeAST");
@@ -429,12 +1261,37 @@ mod tests {
         assert!(obj.fields.contains_key("age"));
     }
 
+    #[test]
+    fn select_pattern_matches() {
+        let schema = create_test_schema();
+        let stmt = parse_select("SELECT name ~ 'ann' AS similar FROM user WHERE name = /^an+/");
+
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
+
+        let TypeAST::Array(boxed_arr) = result else {
+            panic!("Expected Array TypeAST");
+        };
+        let TypeAST::Object(obj) = boxed_arr.0 else {
+            panic!("Expected Object inside Array");
+        };
+        assert!(matches!(
+            obj.fields["similar"].ast,
+            TypeAST::Scalar(ScalarType::Boolean)
+        ));
+
+        let stmt = parse_select("SELECT * FROM user WHERE age = /^1/ OR address.city ~ 'ber'");
+        assert!(matches!(
+            analyze_select(&Analyzer::default(), &schema, &stmt),
+            Err(AnalysisError::InvalidMatch(_))
+        ));
+    }
+
     #[test]
     fn select_all() {
         let schema = create_test_schema();
         let stmt = parse_select("SELECT * FROM user");
 
-        let result = analyze_select(&schema, &stmt).unwrap();
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
 
         let TypeAST::Array(boxed_arr) = result else {
             panic!("Expected Array TypeAST");
@@ -458,7 +1315,7 @@ mod tests {
         let schema = create_test_schema();
         let stmt = parse_select("SELECT * FROM ONLY user");
 
-        let result = analyze_select(&schema, &stmt).unwrap();
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
 
         let TypeAST::Object(obj) = result else {
             panic!("Expected Object TypeAST");
@@ -478,7 +1335,7 @@ mod tests {
         let schema = create_test_schema();
         let stmt = parse_select("SELECT name AS full_name, age FROM user");
 
-        let result = analyze_select(&schema, &stmt).unwrap();
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
 
         let TypeAST::Array(boxed_arr) = result else {
             panic!("Expected Array TypeAST");
@@ -503,7 +1360,7 @@ mod tests {
         let schema = create_test_schema();
         let stmt = parse_select("SELECT * OMIT age FROM user");
 
-        let result = analyze_select(&schema, &stmt).unwrap();
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
 
         let TypeAST::Array(boxed_arr) = result else {
             panic!("Expected Array TypeAST");
@@ -525,11 +1382,11 @@ mod tests {
     }
 
     #[test]
-    fn select_object() {
+    fn select_omit_nested() {
         let schema = create_test_schema();
-        let stmt = parse_select("SELECT address FROM user");
+        let stmt = parse_select("SELECT * OMIT address.zip FROM user");
 
-        let result = analyze_select(&schema, &stmt).unwrap();
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
 
         let TypeAST::Array(boxed_arr) = result else {
             panic!("Expected Array TypeAST");
@@ -539,38 +1396,444 @@ mod tests {
             panic!("Expected Object inside Array");
         };
 
-        assert_eq!(obj.fields.len(), 1);
-        assert!(obj.fields.contains_key("address"));
         let TypeAST::Object(address_obj) = &obj.fields["address"].ast else {
             panic!("Expected Object TypeAST for address");
         };
         assert!(address_obj.fields.contains_key("city"));
+        assert!(!address_obj.fields.contains_key("zip"));
     }
 
     #[test]
-    fn test_select_value() {
+    fn select_omit_traversed() {
         let schema = create_test_schema();
-        let stmt = parse_select("SELECT VALUE age FROM user");
+        let stmt = parse_select(
+            "SELECT name, ->friend->user.* AS friends OMIT ->friend->user.age FROM user",
+        );
 
-        let result = analyze_select(&schema, &stmt).unwrap();
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
 
         let TypeAST::Array(boxed_arr) = result else {
             panic!("Expected Array TypeAST");
         };
 
-        let TypeAST::Scalar(scalar_type) = boxed_arr.0 else {
-            panic!("Expected Scalar TypeAST inside Array");
-        };
+        let TypeAST::Object(obj) = boxed_arr.0 else {
+            panic!("Expected Object inside Array");
+        };
+
+        let TypeAST::Array(friends_arr) = &obj.fields["friends"].ast else {
+            panic!("Expected Array TypeAST for friends");
+        };
+
+        let TypeAST::Object(friends_obj) = &friends_arr.0 else {
+            panic!("Expected Object inside Array for friends");
+        };
+
+        assert!(friends_obj.fields.contains_key("name"));
+        assert!(!friends_obj.fields.contains_key("age"));
+    }
+
+    #[test]
+    fn select_omit_fetched() {
+        let schema = create_test_schema();
+        let stmt = parse_select("SELECT * OMIT best_friend.age FROM user FETCH best_friend");
+
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
+
+        let TypeAST::Array(boxed_arr) = result else {
+            panic!("Expected Array TypeAST");
+        };
+
+        let TypeAST::Object(obj) = boxed_arr.0 else {
+            panic!("Expected Object inside Array");
+        };
+
+        let TypeAST::Object(best_friend_obj) = &obj.fields["best_friend"].ast else {
+            panic!("Expected Object TypeAST for best_friend");
+        };
+
+        assert!(best_friend_obj.fields.contains_key("name"));
+        assert!(!best_friend_obj.fields.contains_key("age"));
+    }
+
+    #[test]
+    fn select_invalid_count() {
+        let schema = create_test_schema();
+
+        let stmt = parse_select("SELECT * FROM user LIMIT -1");
+        assert!(matches!(
+            analyze_select(&Analyzer::default(), &schema, &stmt),
+            Err(AnalysisError::InvalidCount(_, _))
+        ));
+
+        let stmt = parse_select("SELECT * FROM user START 1.5");
+        assert!(matches!(
+            analyze_select(&Analyzer::default(), &schema, &stmt),
+            Err(AnalysisError::InvalidCount(_, _))
+        ));
+
+        let stmt = parse_select("SELECT * FROM user LIMIT $limit START 10");
+        assert!(analyze_select(&Analyzer::default(), &schema, &stmt).is_ok());
+    }
+
+    #[test]
+    fn select_dynamic_table() {
+        let schema = create_test_schema();
+        let stmt = parse_select("SELECT name FROM type::table($tb)");
+
+        assert!(selects_dynamic_table(&stmt));
+        assert!(analyze_select(&Analyzer::default(), &schema, &stmt).is_err());
+
+        let tables = vec!["user".to_string(), "tag".to_string()];
+        let results = analyze_select_tables(&Analyzer::default(), &schema, &stmt, &tables).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].0, "tag");
+        let TypeAST::Array(boxed_arr) = &results[1].1 else {
+            panic!("Expected Array TypeAST");
+        };
+        let TypeAST::Object(obj) = &boxed_arr.0 else {
+            panic!("Expected Object inside Array");
+        };
+        assert!(obj.fields.contains_key("name"));
+
+        let tables = vec!["missing".to_string()];
+        assert!(matches!(
+            analyze_select_tables(&Analyzer::default(), &schema, &stmt, &tables),
+            Err(AnalysisError::UnknownField(_))
+        ));
+    }
+
+    #[test]
+    fn select_duplicate_alias() {
+        let schema = create_test_schema();
+
+        let stmt = parse_select("SELECT name AS label, address.city AS label FROM user");
+        assert!(matches!(
+            analyze_select(&Analyzer::default(), &schema, &stmt),
+            Err(AnalysisError::DuplicateField(_, _, _))
+        ));
+
+        let stmt = parse_select("SELECT *, age AS name FROM user");
+        assert!(matches!(
+            analyze_select(&Analyzer::default(), &schema, &stmt),
+            Err(AnalysisError::DuplicateField(_, _, _))
+        ));
+
+        let stmt = parse_select("SELECT *, name FROM user");
+        assert!(analyze_select(&Analyzer::default(), &schema, &stmt).is_ok());
+    }
+
+    #[test]
+    fn operators_in_where_and_projections() {
+        let schema = create_test_schema();
+        let analyze = |sql: &str| analyze_select(&Analyzer::default(), &schema, &parse_select(sql));
+
+        assert_eq!(
+            shape(&analyze("SELECT age + 1 AS next, age > 18 AS adult FROM user").unwrap()),
+            "[{ adult: Boolean, next: Number }]"
+        );
+        assert!(matches!(
+            analyze("SELECT * FROM user WHERE name > 18"),
+            Err(AnalysisError::InvalidOperands(..))
+        ));
+        assert!(analyze("SELECT name * 2 AS doubled FROM user").is_err());
+    }
+
+    #[test]
+    fn expression_projections() {
+        let schema = analyze_schema(
+            parse(
+                r#"
+                DEFINE TABLE product SCHEMAFULL;
+                    DEFINE FIELD name ON product TYPE string;
+                    DEFINE FIELD stock ON product TYPE int;
+                    DEFINE FIELD price ON product TYPE float;
+                    DEFINE FIELD discount ON product TYPE float;
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let analyze = |sql: &str| analyze_select(&Analyzer::default(), &schema, &parse_select(sql));
+
+        assert_eq!(
+            shape(
+                &analyze(
+                    "SELECT stock * 2 AS double_stock, price - discount AS net, \
+                     (price - discount) * stock AS total, 'product' AS kind, 1 AS one \
+                     FROM product"
+                )
+                .unwrap()
+            ),
+            "[{ double_stock: Integer, kind: String, net: Number, one: Integer, total: Number }]"
+        );
+        assert!(matches!(
+            analyze("SELECT (name - discount) AS net FROM product"),
+            Err(AnalysisError::InvalidOperands(..))
+        ));
+    }
+
+    #[test]
+    fn function_projections() {
+        let schema = analyze_schema(
+            parse(
+                r#"
+                DEFINE TABLE account SCHEMAFULL;
+                    DEFINE FIELD balance ON account TYPE float;
+                    DEFINE FIELD rank ON account TYPE int;
+                    DEFINE FIELD scores ON account TYPE array<int>;
+                    DEFINE FIELD posts ON account TYPE array<record<post>>;
+                DEFINE TABLE post SCHEMAFULL;
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let analyze = |sql: &str| analyze_select(&Analyzer::default(), &schema, &parse_select(sql));
+
+        assert_eq!(
+            shape(
+                &analyze(
+                    "SELECT math::round(balance) AS rounded_balance, math::abs(rank) AS rank, \
+                     math::max(scores) AS best, array::len(posts) AS post_count, \
+                     array::first(posts) AS first_post FROM account"
+                )
+                .unwrap()
+            ),
+            "[{ best: Option<Integer>, first_post: Record(post), post_count: Integer, \
+             rank: Integer, rounded_balance: Number }]"
+        );
+        assert!(matches!(
+            analyze("SELECT math::round(missing) AS rounded FROM account"),
+            Err(AnalysisError::UnknownField(field)) if field == "missing"
+        ));
+    }
+
+    #[test]
+    fn unknown_where_fields() {
+        let schema = create_test_schema();
+        let analyze = |sql: &str| analyze_select(&Analyzer::default(), &schema, &parse_select(sql));
+
+        assert!(analyze(
+            "SELECT * FROM user WHERE string::len(name) > 3 AND address.city = 'x' \
+             AND best_friend.name = 'y' AND tags[WHERE name = 'a'] AND id = $id"
+        )
+        .is_ok());
+        for sql in [
+            "SELECT * FROM user WHERE nmae = 'bob'",
+            "SELECT * FROM user WHERE age > 18 AND !(address.cty = 'x')",
+            "SELECT * FROM user WHERE string::lowercase(best_friend.nmae) = 'bob'",
+        ] {
+            assert!(
+                matches!(analyze(sql), Err(AnalysisError::UnknownField(_))),
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn literal_where_values() {
+        let schema = r#"
+            DEFINE TABLE purchase SCHEMAFULL;
+                DEFINE FIELD status ON purchase TYPE string
+                    ASSERT $value INSIDE ['pending', 'shipped', 'delivered'];
+                DEFINE FIELD note ON purchase TYPE string;
+        "#;
+        let schema = analyze_schema(parse(schema).unwrap()).unwrap();
+        let analyze = |sql: &str| analyze_select(&Analyzer::default(), &schema, &parse_select(sql));
+
+        for sql in [
+            "SELECT * FROM purchase WHERE status = 'shipped' AND note = 'shiped'",
+            "SELECT * FROM purchase WHERE 'pending' != status OR status = $status",
+            "SELECT * FROM purchase WHERE status IN ['pending', 'ship' + 'ped']",
+        ] {
+            assert!(analyze(sql).is_ok(), "{}", sql);
+        }
+        for (sql, value) in [
+            ("SELECT * FROM purchase WHERE status = 'shiped'", "shiped"),
+            ("SELECT * FROM purchase WHERE !(status == 'lost')", "lost"),
+            (
+                "SELECT * FROM purchase WHERE status = string::uppercase('pending')",
+                "PENDING",
+            ),
+            (
+                "SELECT * FROM purchase WHERE status NOT IN ['pending', 'sent']",
+                "sent",
+            ),
+        ] {
+            assert!(
+                matches!(
+                    analyze(sql),
+                    Err(AnalysisError::UnknownLiteral(field, v, literals))
+                        if field == "status"
+                            && v == value
+                            && literals == "'pending', 'shipped', 'delivered'"
+                ),
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn select_narrowed_by_where() {
+        let schema = r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD email ON user TYPE option<string>;
+                DEFINE FIELD phone ON user TYPE option<string>;
+                DEFINE FIELD address ON user TYPE object;
+                    DEFINE FIELD address.city ON user TYPE option<string>;
+        "#;
+        let schema = analyze_schema(parse(schema).unwrap()).unwrap();
+        let stmt = parse_select(
+            "SELECT * FROM user WHERE email != NONE AND (address.city IS NOT NULL) AND phone = 'x'",
+        );
+
+        let TypeAST::Array(boxed_arr) =
+            analyze_select(&Analyzer::default(), &schema, &stmt).unwrap()
+        else {
+            panic!("Expected Array TypeAST");
+        };
+        let TypeAST::Object(obj) = boxed_arr.0 else {
+            panic!("Expected Object inside Array");
+        };
+
+        assert_eq!(obj.fields["email"].ast, TypeAST::Scalar(ScalarType::String));
+        assert!(matches!(obj.fields["phone"].ast, TypeAST::Option(_)));
+        let TypeAST::Object(address) = &obj.fields["address"].ast else {
+            panic!("Expected address to be an Object");
+        };
+        assert_eq!(
+            address.fields["city"].ast,
+            TypeAST::Scalar(ScalarType::String)
+        );
+
+        let stmt = parse_select("SELECT * FROM user WHERE email != NONE OR phone != NONE");
+        let TypeAST::Array(boxed_arr) =
+            analyze_select(&Analyzer::default(), &schema, &stmt).unwrap()
+        else {
+            panic!("Expected Array TypeAST");
+        };
+        let TypeAST::Object(obj) = boxed_arr.0 else {
+            panic!("Expected Object inside Array");
+        };
+        assert!(matches!(obj.fields["email"].ast, TypeAST::Option(_)));
+    }
+
+    #[test]
+    fn select_object() {
+        let schema = create_test_schema();
+        let stmt = parse_select("SELECT address FROM user");
+
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
+
+        let TypeAST::Array(boxed_arr) = result else {
+            panic!("Expected Array TypeAST");
+        };
+
+        let TypeAST::Object(obj) = boxed_arr.0 else {
+            panic!("Expected Object inside Array");
+        };
+
+        assert_eq!(obj.fields.len(), 1);
+        assert!(obj.fields.contains_key("address"));
+        let TypeAST::Object(address_obj) = &obj.fields["address"].ast else {
+            panic!("Expected Object TypeAST for address");
+        };
+        assert!(address_obj.fields.contains_key("city"));
+    }
+
+    #[test]
+    fn test_select_value() {
+        let schema = create_test_schema();
+        let stmt = parse_select("SELECT VALUE age FROM user");
+
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
+
+        let TypeAST::Array(boxed_arr) = result else {
+            panic!("Expected Array TypeAST");
+        };
+
+        let TypeAST::Scalar(scalar_type) = boxed_arr.0 else {
+            panic!("Expected Scalar TypeAST inside Array");
+        };
 
         assert!(matches!(scalar_type, ScalarType::Number));
     }
 
+    #[test]
+    fn select_value_only() {
+        let schema = create_test_schema();
+        let analyze =
+            |sql: &str| analyze_select(&Analyzer::default(), &schema, &parse_select(sql)).unwrap();
+        let is_string = |ast: &TypeAST| matches!(ast, TypeAST::Scalar(ScalarType::String));
+
+        // VALUE and ONLY
+        assert!(is_string(&analyze("SELECT VALUE name FROM ONLY user:john")));
+
+        // VALUE alone
+        let TypeAST::Array(rows) = analyze("SELECT VALUE name FROM user") else {
+            panic!("Expected Array TypeAST");
+        };
+        assert!(is_string(&rows.0));
+
+        // ONLY alone
+        let TypeAST::Object(obj) = analyze("SELECT name FROM ONLY user:john") else {
+            panic!("Expected Object TypeAST");
+        };
+        assert!(is_string(&obj.fields["name"].ast));
+
+        // Neither
+        let TypeAST::Array(rows) = analyze("SELECT name FROM user:john") else {
+            panic!("Expected Array TypeAST");
+        };
+        assert!(matches!(&rows.0, TypeAST::Object(obj) if is_string(&obj.fields["name"].ast)));
+
+        // An array field stays an array for each row.
+        let TypeAST::Array(rows) = analyze("SELECT VALUE tags FROM user") else {
+            panic!("Expected Array TypeAST");
+        };
+        assert!(matches!(rows.0, TypeAST::Array(_)));
+        assert!(matches!(
+            analyze("SELECT VALUE tags FROM ONLY user:john"),
+            TypeAST::Array(tags) if matches!(tags.0, TypeAST::Record(_))
+        ));
+    }
+
+    #[test]
+    fn select_many_tables() {
+        let schema = create_test_schema();
+        let analyze = |sql: &str| analyze_select(&Analyzer::default(), &schema, &parse_select(sql));
+
+        let TypeAST::Array(rows) = analyze("SELECT * FROM user, tag").unwrap() else {
+            panic!("Expected Array TypeAST");
+        };
+        let TypeAST::Union(variants) = &rows.0 else {
+            panic!("Expected a Union of the rows of each table");
+        };
+        assert!(matches!(
+            variants.as_slice(),
+            [TypeAST::Object(user), TypeAST::Object(tag)]
+                if user.fields.contains_key("best_friend") && tag.fields.contains_key("value")
+        ));
+
+        // Rows of the same shape are a single type.
+        assert!(matches!(
+            analyze("SELECT VALUE name FROM user, tag").unwrap(),
+            TypeAST::Array(names) if names.0 == TypeAST::Scalar(ScalarType::String)
+        ));
+        assert!(matches!(
+            analyze("SELECT * FROM user, post"),
+            Err(AnalysisError::UnknownField(table)) if table == "post"
+        ));
+    }
+
     #[test]
     fn fetch_array() {
         let schema = create_test_schema();
         let stmt = parse_select("SELECT name, tags FROM user FETCH tags");
 
-        let result = analyze_select(&schema, &stmt).unwrap();
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
 
         let TypeAST::Array(boxed_arr) = result else {
             panic!("Expected Array TypeAST");
@@ -606,7 +1869,7 @@ mod tests {
         let schema = create_test_schema();
         let stmt = parse_select("SELECT name, best_friend FROM user FETCH best_friend");
 
-        let result = analyze_select(&schema, &stmt).unwrap();
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
 
         let TypeAST::Array(boxed_arr) = result else {
             panic!("Expected Array TypeAST");
@@ -633,12 +1896,81 @@ mod tests {
         assert!(best_friend_obj.fields.contains_key("best_friend"));
     }
 
+    #[test]
+    fn fetch_leaves_other_links() {
+        let schema = create_test_schema();
+        let stmt = parse_select("SELECT name, tags, best_friend FROM user FETCH tags");
+
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
+
+        let TypeAST::Array(boxed_arr) = result else {
+            panic!("Expected Array TypeAST");
+        };
+        let TypeAST::Object(obj) = boxed_arr.0 else {
+            panic!("Expected Object inside Array");
+        };
+        assert!(matches!(
+            &obj.fields["tags"].ast,
+            TypeAST::Array(tags) if matches!(tags.0, TypeAST::Object(_))
+        ));
+        assert!(matches!(
+            &obj.fields["best_friend"].ast,
+            TypeAST::Record(table) if table == "user"
+        ));
+    }
+
+    #[test]
+    fn fetch_through_links() {
+        let schema = analyze_schema(
+            parse(
+                r#"
+                DEFINE TABLE org SCHEMAFULL;
+                    DEFINE FIELD name ON org TYPE string;
+                DEFINE TABLE user SCHEMAFULL;
+                    DEFINE FIELD org ON user TYPE option<record<org>>;
+                DEFINE TABLE post SCHEMAFULL;
+                    DEFINE FIELD author ON post TYPE option<record<user>>;
+                    DEFINE FIELD editors ON post TYPE array<record<user>>;
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let analyze = |sql: &str| {
+            shape(&analyze_select(&Analyzer::default(), &schema, &parse_select(sql)).unwrap())
+        };
+
+        assert_eq!(
+            analyze("SELECT author FROM post FETCH author.org"),
+            "[{ author: Option<{ id: Record(user), org: Option<{ id: Record(org), name: String }> }> }]"
+        );
+        assert_eq!(
+            analyze("SELECT editors FROM post FETCH editors.org"),
+            "[{ editors: [{ id: Record(user), org: Option<{ id: Record(org), name: String }> }] }]"
+        );
+        assert_eq!(
+            analyze("SELECT author FROM post FETCH author"),
+            "[{ author: Option<{ id: Record(user), org: Option<Record(org)> }> }]"
+        );
+    }
+
+    #[test]
+    fn graph_traversal_any_edge() {
+        let schema = create_test_schema();
+        let stmt = parse_select("SELECT ->?->user AS linked FROM user");
+
+        assert!(matches!(
+            analyze_select(&Analyzer::default(), &schema, &stmt),
+            Err(AnalysisError::UnsupportedOperation(_))
+        ));
+    }
+
     #[test]
     fn test_graph_traversal_out() {
         let schema = create_test_schema();
         let stmt = parse_select("SELECT name, ->friend->user.name as friend_names FROM user");
 
-        let result = analyze_select(&schema, &stmt).unwrap();
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
 
         let TypeAST::Array(boxed_arr) = result else {
             panic!("Expected Array TypeAST");
@@ -664,7 +1996,7 @@ mod tests {
         let schema = create_test_schema();
         let stmt = parse_select("SELECT name, <-friend<-user.name as follower_names FROM user");
 
-        let result = analyze_select(&schema, &stmt).unwrap();
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
 
         let TypeAST::Array(boxed_arr) = result else {
             panic!("Expected Array TypeAST");
@@ -695,7 +2027,7 @@ mod tests {
             "SELECT name, ->friend->user->friend->user.name as friend_of_friend_names FROM user",
         );
 
-        let result = analyze_select(&schema, &stmt).unwrap();
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
 
         let TypeAST::Array(boxed_arr) = result else {
             panic!("Expected Array TypeAST");
@@ -721,7 +2053,7 @@ mod tests {
         let schema = create_test_schema();
         let stmt = parse_select("SELECT name, ->friend->user.* as friends FROM user");
 
-        let result = analyze_select(&schema, &stmt).unwrap();
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
 
         let TypeAST::Array(boxed_arr) = result else {
             panic!("Expected Array TypeAST");
@@ -751,4 +2083,231 @@ mod tests {
         assert!(friends_obj.fields.contains_key("tags"));
         assert!(friends_obj.fields.contains_key("best_friend"));
     }
+
+    #[test]
+    fn split_group_and_order_fields() {
+        let schema = create_test_schema();
+        let analyze = |sql: &str| analyze_select(&Analyzer::default(), &schema, &parse_select(sql));
+
+        // Each clause picks fields of the rows as selected, aliases included.
+        for sql in [
+            "SELECT name, tags FROM user SPLIT tags GROUP BY name, tags",
+            "SELECT address.city AS city FROM user GROUP BY city ORDER BY city",
+            "SELECT name, tags FROM user SPLIT tags ORDER BY name",
+        ] {
+            assert!(analyze(sql).is_ok(), "{}", sql);
+        }
+        for sql in [
+            "SELECT name FROM user SPLIT tags",
+            "SELECT name FROM user GROUP BY age",
+        ] {
+            assert!(
+                matches!(analyze(sql), Err(AnalysisError::UnknownField(_))),
+                "{}",
+                sql
+            );
+        }
+        assert!(matches!(
+            analyze("SELECT address.city AS city FROM user GROUP BY city ORDER BY address"),
+            Err(AnalysisError::UnknownOrderField(_))
+        ));
+
+        assert!(matches!(
+            analyze("SELECT * FROM ONLY user:john SPLIT tags"),
+            Err(AnalysisError::UnsupportedOperation(_))
+        ));
+    }
+
+    #[test]
+    fn grouped_fields() {
+        let schema = create_test_schema();
+        let analyze =
+            |sql: &str| analyze_select(&Analyzer::default(), &schema, &parse_select(sql)).unwrap();
+
+        // Unaliased aggregates are named after the function, as the database names them.
+        assert_eq!(
+            shape(&analyze(
+                "SELECT name, count(), math::max(age) FROM user GROUP BY name"
+            )),
+            "[{ count: Integer, math::max: Number, name: String }]"
+        );
+        assert_eq!(
+            shape(&analyze(
+                "SELECT count(), array::group(tags) FROM ONLY user GROUP ALL"
+            )),
+            "Option<{ array::group: [Record(tag)], count: Integer }>"
+        );
+        // Other fields of a GROUP ALL hold the value of the first row.
+        assert_eq!(
+            shape(&analyze("SELECT name, count() FROM user GROUP ALL")),
+            "[{ count: Integer, name: String }]"
+        );
+    }
+
+    #[test]
+    fn grouped_aggregates() {
+        let schema = analyze_schema(
+            parse(
+                r#"
+                DEFINE TABLE sale SCHEMAFULL;
+                    DEFINE FIELD region ON sale TYPE string;
+                    DEFINE FIELD units ON sale TYPE int;
+                    DEFINE FIELD price ON sale TYPE option<float>;
+                    DEFINE FIELD note ON sale TYPE string;
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let analyze =
+            |sql: &str| analyze_select(&Analyzer::default(), &schema, &parse_select(sql)).unwrap();
+
+        assert_eq!(
+            shape(&analyze(
+                "SELECT region, math::sum(units) AS units, math::mean(units) AS average, \
+                 math::max(units) AS most, math::sum(price) AS revenue, \
+                 math::min(price) AS cheapest, math::top(units, 3) AS top \
+                 FROM sale GROUP BY region"
+            )),
+            "[{ average: Number, cheapest: Option<Float>, most: Integer, region: String, \
+             revenue: Number, top: [Integer], units: Integer }]"
+        );
+        assert_eq!(
+            shape(&analyze(
+                "SELECT math::max(units) AS most FROM ONLY sale GROUP ALL"
+            )),
+            "Option<{ most: Integer }>"
+        );
+        for sql in [
+            "SELECT math::max(note) AS most FROM sale GROUP ALL",
+            "SELECT region, math::sum(region) AS total FROM sale GROUP BY region",
+        ] {
+            assert!(
+                matches!(
+                    analyze_select(&Analyzer::default(), &schema, &parse_select(sql)),
+                    Err(AnalysisError::TypeMismatch(_, expected, _)) if expected == "numbers"
+                ),
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn order_by_fields() {
+        let schema = analyze_schema(
+            parse(
+                r#"
+                DEFINE TABLE account SCHEMAFULL;
+                    DEFINE FIELD balance ON account TYPE float;
+                    DEFINE FIELD owner ON account TYPE object;
+                        DEFINE FIELD owner.name ON account TYPE string;
+                DEFINE TABLE log SCHEMALESS;
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let analyze = |sql: &str| analyze_select(&Analyzer::default(), &schema, &parse_select(sql));
+
+        for sql in [
+            "SELECT math::round(balance) AS rounded_balance FROM account ORDER BY rounded_balance DESC",
+            "SELECT * FROM account ORDER BY owner.name, balance",
+            "SELECT * FROM account ORDER BY id",
+            "SELECT * FROM log ORDER BY created",
+        ] {
+            assert!(analyze(sql).is_ok(), "{}", sql);
+        }
+        assert!(matches!(
+            analyze("SELECT * FROM account ORDER BY owner.age"),
+            Err(AnalysisError::UnknownOrderField(field)) if field == "owner.age"
+        ));
+    }
+
+    #[test]
+    fn split_fields() {
+        let schema = analyze_schema(
+            parse(
+                r#"
+                DEFINE TABLE post SCHEMAFULL;
+                    DEFINE FIELD title ON post TYPE string;
+                    DEFINE FIELD tags ON post TYPE set<string>;
+                    DEFINE FIELD authors ON post TYPE array<string> | string;
+                    DEFINE FIELD scores ON post TYPE option<array<array<int>>>;
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let analyze =
+            |sql: &str| analyze_select(&Analyzer::default(), &schema, &parse_select(sql)).unwrap();
+
+        assert_eq!(
+            shape(&analyze(
+                "SELECT title, tags, authors, scores FROM post SPLIT tags"
+            )),
+            "[{ authors: Union([String] | String), scores: Option<[[Integer]]>, tags: String, \
+             title: String }]"
+        );
+        assert_eq!(
+            shape(&analyze(
+                "SELECT title, authors, scores FROM post SPLIT authors, scores"
+            )),
+            "[{ authors: String, scores: Option<[Integer]>, title: String }]"
+        );
+    }
+
+    #[test]
+    fn split_and_order() {
+        let schema = create_test_schema();
+        let analyze =
+            |sql: &str| analyze_select(&Analyzer::default(), &schema, &parse_select(sql)).unwrap();
+
+        assert_eq!(
+            shape(&analyze(
+                "SELECT name, tags FROM user SPLIT tags ORDER BY tags"
+            )),
+            "[{ name: String, tags: Record(tag) }]"
+        );
+        // FETCH runs on the rows once split, so it fetches the one tag of each.
+        assert_eq!(
+            shape(&analyze(
+                "SELECT tags FROM user SPLIT tags ORDER BY tags FETCH tags"
+            )),
+            "[{ tags: { id: Uuid, name: String, value: Number } }]"
+        );
+    }
+
+    #[test]
+    fn group_and_order() {
+        let schema = create_test_schema();
+        let stmt = parse_select(
+            "SELECT address.city AS city, math::mean(age) AS average, array::group(name) AS names \
+             FROM user GROUP BY city ORDER BY average DESC",
+        );
+
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
+
+        assert_eq!(
+            shape(&result),
+            "[{ average: Number, city: String, names: [String] }]"
+        );
+    }
+
+    #[test]
+    fn split_and_group() {
+        let schema = create_test_schema();
+        let stmt = parse_select(
+            "SELECT tags, array::distinct(name) AS names, count() AS total FROM user \
+             SPLIT tags GROUP BY tags ORDER BY total DESC",
+        );
+
+        let result = analyze_select(&Analyzer::default(), &schema, &stmt).unwrap();
+
+        // Rows are grouped by each of their tags once split on them.
+        assert_eq!(
+            shape(&result),
+            "[{ names: [String], tags: Record(tag), total: Integer }]"
+        );
+    }
 }
@@ -1,8 +1,8 @@
 use super::Tables;
-use crate::types::TypedQuery;
+use crate::ast::TypeAST;
 use surrealdb::sql::statements::UpdateStatement;
 
-pub fn analyze_update(tbls: &Tables, update: &UpdateStatement) -> TypedQuery {
+pub fn analyze_update(tbls: &Tables, update: &UpdateStatement) -> TypeAST {
     // Implement update analysis logic here
     todo!("Implement update analysis")
 }
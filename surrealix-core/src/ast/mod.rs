@@ -1,9 +1,17 @@
 use std::fmt;
-use std::{collections::HashMap, num::NonZeroU64};
+use std::{
+    collections::{BTreeMap, HashMap},
+    num::NonZeroU64,
+};
 use surrealdb::sql::{Fields, Idiom, Kind, Part, Permissions, Value};
 use thiserror::Error;
 
 #[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(tag = "kind", content = "type", rename_all = "snake_case")
+)]
 pub enum TypeAST {
     Scalar(ScalarType),
     Object(ObjectType),
@@ -14,6 +22,7 @@ pub enum TypeAST {
 }
 
 #[derive(Error, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ResolverError {
     #[error("The field '{0}' does not exist on: '{1}'")]
     UnknownField(String, String),
@@ -124,17 +133,86 @@ impl TypeAST {
         }
         Ok(())
     }
+
+    /// Replaces the record links at `path` with the records they link to, as FETCH does,
+    /// leaving links elsewhere in the type as they are.
+    pub fn replace_record_link_at(
+        &mut self,
+        path: &Idiom,
+        schema: &TypeAST,
+    ) -> Result<(), ResolverError> {
+        self.fetched_at(path, schema)?.replace_record_links(schema)
+    }
+
+    /// The type at `path` of a FETCH, which may be NONE.
+    ///
+    /// The links the path passes through are replaced with the records they link to, as
+    /// fetching `author.org` fetches `author` too, and the fields of an array of records are
+    /// those of each record.
+    pub fn fetched_at(
+        &mut self,
+        path: &Idiom,
+        schema: &TypeAST,
+    ) -> Result<&mut TypeAST, ResolverError> {
+        let mut current = self;
+        let mut parts = path.0.iter().peekable();
+        while let Some(part) = parts.peek() {
+            current = match (current, part) {
+                (TypeAST::Option(inner), _) => inner.as_mut(),
+                (link @ TypeAST::Record(_), _) => {
+                    link.replace_record_links(schema)?;
+                    if matches!(link, TypeAST::Record(_)) {
+                        return Err(ResolverError::InterruptedTraversal(path.to_string()));
+                    }
+                    link
+                }
+                (TypeAST::Object(obj), Part::Field(ident)) => {
+                    parts.next();
+                    match obj.fields.get_mut(ident.as_str()) {
+                        Some(field_info) => &mut field_info.ast,
+                        None => {
+                            return Err(ResolverError::UnknownField(
+                                ident.to_string(),
+                                path.to_string(),
+                            ))
+                        }
+                    }
+                }
+                (TypeAST::Array(boxed), Part::All) => {
+                    parts.next();
+                    &mut boxed.0
+                }
+                (TypeAST::Array(boxed), Part::Field(_)) => &mut boxed.0,
+                _ => return Err(ResolverError::InterruptedTraversal(path.to_string())),
+            };
+        }
+        match current {
+            TypeAST::Option(inner) => Ok(inner.as_mut()),
+            current => Ok(current),
+        }
+    }
 }
 
 impl From<Kind> for TypeAST {
     fn from(value: Kind) -> Self {
         match value {
             Kind::Object => TypeAST::Object(ObjectType::default()),
-            Kind::Record(rec) => TypeAST::Record(rec.first().unwrap().to_string()),
+            // A link to any table could be to a record of any shape.
+            Kind::Record(tables) => match tables.as_slice() {
+                [] => TypeAST::Scalar(ScalarType::Any),
+                [table] => TypeAST::Record(table.to_string()),
+                tables => TypeAST::Union(
+                    tables
+                        .iter()
+                        .map(|table| TypeAST::Record(table.to_string()))
+                        .collect(),
+                ),
+            },
             Kind::Option(inner_kind) => TypeAST::Option(Box::new(TypeAST::from(*inner_kind))),
             Kind::Set(kind, len) | Kind::Array(kind, len) => TypeAST::Array(Box::new((
                 TypeAST::from(*kind),
-                len.map(|v| NonZeroU64::new(v).expect("array length is not zero.")),
+                // `array<T, 0>` can only ever be empty, which is no more useful a bound than none.
+                len.and_then(NonZeroU64::new),
             ))),
             Kind::Either(kind) => TypeAST::Union(kind.into_iter().map(TypeAST::from).collect()),
             kind => TypeAST::Scalar(ScalarType::from(kind)),
@@ -142,7 +220,80 @@ impl From<Kind> for TypeAST {
     }
 }
 
+impl TypeAST {
+    /// The type as a SurrealQL kind, e.g. `option<array<string>>`.
+    ///
+    /// Objects are only `object`, as SurrealQL kinds don't list fields.
+    pub fn surql_kind(&self) -> String {
+        match self {
+            TypeAST::Scalar(scalar) => scalar.surql_kind().to_string(),
+            TypeAST::Object(_) => "object".to_string(),
+            TypeAST::Array(inner) => match inner.1 {
+                Some(len) => format!("array<{}, {}>", inner.0.surql_kind(), len),
+                None => format!("array<{}>", inner.0.surql_kind()),
+            },
+            TypeAST::Option(inner) => format!("option<{}>", inner.surql_kind()),
+            TypeAST::Record(table) => format!("record<{}>", table),
+            TypeAST::Union(variants) => variants
+                .iter()
+                .map(TypeAST::surql_kind)
+                .collect::<Vec<_>>()
+                .join(" | "),
+        }
+    }
+
+    /// The type that is any of `variants`, with nested unions flattened and duplicates removed.
+    ///
+    /// A single distinct variant is the type itself, and NONE among others makes the rest
+    /// optional rather than a variant of its own. A union with `Any` is `Any`.
+    pub fn union(variants: impl IntoIterator<Item = TypeAST>) -> TypeAST {
+        let mut distinct: Vec<TypeAST> = vec![];
+        let mut optional = false;
+        let mut add = |variant: TypeAST, distinct: &mut Vec<TypeAST>| match variant {
+            TypeAST::Scalar(ScalarType::Null) => optional = true,
+            TypeAST::Option(inner) => {
+                optional = true;
+                if !distinct.contains(&*inner) {
+                    distinct.push(*inner);
+                }
+            }
+            variant => {
+                if !distinct.contains(&variant) {
+                    distinct.push(variant);
+                }
+            }
+        };
+        for variant in variants {
+            match variant {
+                TypeAST::Union(nested) => nested
+                    .into_iter()
+                    .for_each(|variant| add(variant, &mut distinct)),
+                variant => add(variant, &mut distinct),
+            }
+        }
+
+        if distinct.contains(&TypeAST::Scalar(ScalarType::Any)) {
+            return TypeAST::Scalar(ScalarType::Any);
+        }
+        let ast = match distinct.len() {
+            0 => return TypeAST::Scalar(ScalarType::Null),
+            1 => distinct.remove(0),
+            _ => TypeAST::Union(distinct),
+        };
+        if optional {
+            TypeAST::Option(Box::new(ast))
+        } else {
+            ast
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum ScalarType {
     String,
     Integer,
@@ -160,6 +311,27 @@ pub enum ScalarType {
     Null,
 }
 
+impl ScalarType {
+    fn surql_kind(&self) -> &'static str {
+        match self {
+            ScalarType::String => "string",
+            ScalarType::Integer => "int",
+            ScalarType::Number => "number",
+            ScalarType::Float => "float",
+            ScalarType::Boolean => "bool",
+            ScalarType::Point => "point",
+            ScalarType::Geometry => "geometry",
+            ScalarType::Set => "set",
+            ScalarType::Datetime => "datetime",
+            ScalarType::Duration => "duration",
+            ScalarType::Bytes => "bytes",
+            ScalarType::Uuid => "uuid",
+            ScalarType::Any => "any",
+            ScalarType::Null => "null",
+        }
+    }
+}
+
 impl From<Kind> for ScalarType {
     fn from(value: Kind) -> Self {
         match value {
@@ -177,27 +349,66 @@ impl From<Kind> for ScalarType {
             Kind::Uuid => Self::Uuid,
             Kind::Point => Self::Point,
             Kind::Geometry(_) => ScalarType::Geometry,
-            _ => panic!("Cannot convert complex Kind to ScalarType"),
+            // Complex kinds are converted to a TypeAST instead, so only have a scalar type of any.
+            _ => Self::Any,
         }
     }
 }
 
 #[derive(Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ObjectType {
     pub fields: HashMap<String, FieldInfo>,
 }
 
+impl ObjectType {
+    /// The fields ordered by name, as the order of `fields` changes from one run to the next.
+    pub fn sorted_fields(&self) -> Vec<(&String, &FieldInfo)> {
+        let mut fields: Vec<_> = self.fields.iter().collect();
+        fields.sort_by_key(|(name, _)| *name);
+        fields
+    }
+}
+
 #[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FieldInfo {
     pub ast: TypeAST,
     pub meta: FieldMetadata,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FieldMetadata {
     pub original_name: String,
     pub original_path: Vec<String>,
     pub permissions: Permissions,
+    /// Constraints recognized in the field's ASSERT clause.
+    pub constraints: Vec<Constraint>,
+    /// The field's DEFAULT as JSON, if it is a literal that can be filled in client-side.
+    pub default: Option<String>,
+    /// The note of a deprecated field, whose COMMENT starts with `deprecated:`.
+    pub deprecated: Option<String>,
+}
+
+/// A predicate from a field's ASSERT clause that can also be checked client-side.
+///
+/// Assertions the analyzer doesn't recognize are left to the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Constraint {
+    /// `string::is::<check>($value)`, e.g. `email` or `url`.
+    StringIs(String),
+    /// `string::len($value) >= n`
+    MinLength(u64),
+    /// `string::len($value) <= n`
+    MaxLength(u64),
+    /// `$value INSIDE ['a', 'b']`, the literal strings the field may hold.
+    OneOf(Vec<String>),
 }
 
 impl TypeAST {
@@ -207,7 +418,7 @@ impl TypeAST {
             TypeAST::Scalar(scalar) => write!(f, "{:?}", scalar),
             TypeAST::Object(obj) => {
                 writeln!(f, "{{")?;
-                for (name, field) in &obj.fields {
+                for (name, field) in obj.sorted_fields() {
                     write!(f, "{}  {}", indent_str, name)?;
                     if matches!(field.ast, TypeAST::Option(_)) {
                         write!(f, "?: ")?;
@@ -256,7 +467,7 @@ impl fmt::Debug for TypeAST {
 impl fmt::Debug for ObjectType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ObjectType")
-            .field("fields", &self.fields)
+            .field("fields", &BTreeMap::from_iter(self.sorted_fields()))
             .finish()
     }
 }
@@ -269,3 +480,78 @@ impl fmt::Debug for FieldInfo {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surql_kinds() {
+        let kind = |kind: &str| {
+            let schema = format!("DEFINE FIELD x ON t TYPE {};", kind);
+            let query = surrealdb::sql::parse(&schema).unwrap();
+            let surrealdb::sql::Statement::Define(
+                surrealdb::sql::statements::DefineStatement::Field(field),
+            ) = &query[0]
+            else {
+                panic!("Expected a field definition");
+            };
+            TypeAST::from(field.kind.clone().unwrap()).surql_kind()
+        };
+
+        assert_eq!(kind("option<string>"), "option<string>");
+        assert_eq!(kind("array<int, 3>"), "array<int, 3>");
+        assert_eq!(kind("record<user>"), "record<user>");
+        assert_eq!(kind("string | datetime"), "string | datetime");
+        assert_eq!(kind("object"), "object");
+    }
+
+    #[test]
+    fn unions_collapse() {
+        let string = || TypeAST::Scalar(ScalarType::String);
+        let int = || TypeAST::Scalar(ScalarType::Integer);
+        let null = || TypeAST::Scalar(ScalarType::Null);
+
+        assert!(TypeAST::union([string(), string()]) == string());
+        assert!(
+            TypeAST::union([string(), TypeAST::Union(vec![int(), string()])])
+                == TypeAST::Union(vec![string(), int()])
+        );
+        assert!(
+            TypeAST::union([string(), null(), TypeAST::Option(Box::new(string()))])
+                == TypeAST::Option(Box::new(string()))
+        );
+        assert!(TypeAST::union([null(), null()]) == null());
+        assert!(
+            TypeAST::union([string(), TypeAST::Scalar(ScalarType::Any)])
+                == TypeAST::Scalar(ScalarType::Any)
+        );
+    }
+
+    #[test]
+    fn debug_orders_fields() {
+        // Each analysis hashes its fields differently, so would print them in a different order.
+        let user = || {
+            let schema = surrealdb::sql::parse(
+                r#"
+                DEFINE TABLE user SCHEMAFULL;
+                    DEFINE FIELD name ON user TYPE string;
+                    DEFINE FIELD email ON user TYPE option<string>;
+                    DEFINE FIELD age ON user TYPE int;
+                "#,
+            )
+            .unwrap();
+            let TypeAST::Object(tables) = crate::schema::analyze_schema(schema).unwrap() else {
+                panic!("Expected the tables of the schema");
+            };
+            format!("{:?}", tables.fields["user"].ast)
+        };
+
+        for _ in 0..8 {
+            assert_eq!(
+                user(),
+                "{\n  age: Integer,\n  email?: String,\n  id: Record(user),\n  name: String,\n}"
+            );
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Deterministic schema and query corpora for profiling the analyzer.
+//!
+//! The same inputs are generated on every run, so timings can be compared between builds.
+
+/// A generated schema, and SELECT statements that are valid against it.
+#[derive(Debug, Clone)]
+pub struct Corpus {
+    pub schema: String,
+    pub queries: Vec<String>,
+}
+
+/// Generates `tables` tables with `fields` fields each, linked in a ring by record links and relations.
+///
+/// Field types cycle through scalars, options, arrays, nested objects and record links, so at
+/// least six fields are generated per table to cover every kind.
+pub fn corpus(tables: usize, fields: usize) -> Corpus {
+    let tables = tables.max(1);
+    let fields = fields.max(6);
+
+    let mut schema = String::new();
+    let mut queries = vec![];
+
+    for t in 0..tables {
+        let table = format!("t{}", t);
+        let next = format!("t{}", (t + 1) % tables);
+
+        schema.push_str(&format!("DEFINE TABLE {} SCHEMAFULL;\n", table));
+        for f in 0..fields {
+            let kind = match f % 6 {
+                0 => "string".to_string(),
+                1 => "int".to_string(),
+                2 => "option<float>".to_string(),
+                3 => "array<string>".to_string(),
+                4 => "object".to_string(),
+                _ => format!("record<{}>", next),
+            };
+            schema.push_str(&format!(
+                "DEFINE FIELD f{} ON {} TYPE {};\n",
+                f, table, kind
+            ));
+            if f % 6 == 4 {
+                schema.push_str(&format!(
+                    "DEFINE FIELD f{}.a ON {} TYPE string;\n",
+                    f, table
+                ));
+                schema.push_str(&format!("DEFINE FIELD f{}.b ON {} TYPE int;\n", f, table));
+            }
+        }
+
+        schema.push_str(&format!("DEFINE TABLE rel{} SCHEMAFULL;\n", t));
+        schema.push_str(&format!(
+            "DEFINE FIELD in ON rel{} TYPE record<{}>;\n",
+            t, table
+        ));
+        schema.push_str(&format!(
+            "DEFINE FIELD out ON rel{} TYPE record<{}>;\n",
+            t, next
+        ));
+
+        queries.push(format!("SELECT * FROM {}", table));
+        queries.push(format!("SELECT f0, f1 AS renamed, f4.a FROM {}", table));
+        queries.push(format!("SELECT * OMIT f4.b FROM {} FETCH f5", table));
+        queries.push(format!(
+            "SELECT f0, ->rel{}->{}.* AS related FROM {}",
+            t, next, table
+        ));
+    }
+
+    Corpus { schema, queries }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analyzer::analyze_statement, schema::analyze_schema};
+    use surrealdb::sql::parse;
+
+    #[test]
+    fn corpus_analyzes() {
+        let corpus = corpus(4, 12);
+        let schema = analyze_schema(parse(&corpus.schema).unwrap()).unwrap();
+
+        for query in &corpus.queries {
+            let parsed = parse(query).unwrap();
+            for statement in parsed.iter() {
+                assert!(
+                    analyze_statement(&schema, statement).is_ok(),
+                    "failed to analyze: {}",
+                    query
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Canonical forms of queries, so that queries differing only in their formatting, the order of
+//! their fields or redundant aliases are recognised as the same query, e.g. by caches.
+
+use std::collections::{BTreeSet, HashSet};
+
+use surrealdb::sql::{
+    statements::{DefineStatement, SelectStatement},
+    Field, Fields, Part, Query, Statement, Value,
+};
+
+use crate::{
+    ast::{ObjectType, TypeAST},
+    errors::AnalysisError,
+    schema::analyze_schema,
+};
+
+/// Rewrites each top-level SELECT of `query` into its canonical form:
+///
+/// - Aliases naming a field after itself, like `name AS name`, are dropped.
+/// - Fields are sorted, as the order they are selected in doesn't change the result.
+/// - Selecting every field of a SCHEMAFULL table, `id` included, is written as `*`.
+///
+/// Printing the canonical query also normalizes its whitespace and keywords.
+pub fn canonicalize(schema: &Query, query: &Query) -> Result<Query, AnalysisError> {
+    let tables = match analyze_schema(schema.clone())? {
+        TypeAST::Object(tables) => tables,
+        _ => ObjectType::default(),
+    };
+    let schemafull = schemafull_tables(schema);
+
+    let mut query = query.clone();
+    for stmt in query.0 .0.iter_mut() {
+        if let Statement::Select(select) = stmt {
+            canonicalize_select(&tables, &schemafull, select);
+        }
+    }
+    Ok(query)
+}
+
+/// A hash of the canonical form of `query`, stable across builds and compiler versions, so it
+/// can key caches and registries shared between them.
+pub fn query_hash(schema: &Query, query: &Query) -> Result<u64, AnalysisError> {
+    let canonical = canonicalize(schema, query)?.to_string();
+    // FNV-1a, as std's hashers may change between releases.
+    Ok(canonical.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    }))
+}
+
+/// The tables whose last definition is SCHEMAFULL, so they hold no fields but the defined ones.
+fn schemafull_tables(schema: &Query) -> HashSet<String> {
+    let mut tables = HashSet::new();
+    for stmt in schema.iter() {
+        if let Statement::Define(DefineStatement::Table(table)) = stmt {
+            if table.full {
+                tables.insert(table.name.to_string());
+            } else {
+                tables.remove(&table.name.to_string());
+            }
+        }
+    }
+    tables
+}
+
+fn canonicalize_select(
+    tables: &ObjectType,
+    schemafull: &HashSet<String>,
+    select: &mut SelectStatement,
+) {
+    // `SELECT VALUE` has a single field, whose alias isn't part of the result.
+    if select.expr.1 {
+        return;
+    }
+
+    for field in select.expr.0.iter_mut() {
+        if let Field::Single { expr, alias } = field {
+            if alias
+                .as_ref()
+                .is_some_and(|alias| expr.to_string() == alias.to_string())
+            {
+                *alias = None;
+            }
+        }
+    }
+    select.expr.0.sort_by_key(|field| field.to_string());
+
+    let table = match select.what.as_slice() {
+        [Value::Table(table)] => table.to_string(),
+        _ => return,
+    };
+    let Some(TypeAST::Object(defined)) = tables.fields.get(&table).map(|table| &table.ast) else {
+        return;
+    };
+    if !schemafull.contains(&table) {
+        return;
+    }
+
+    let mut every_field: BTreeSet<&str> = defined.fields.keys().map(String::as_str).collect();
+    every_field.insert("id");
+    let selected: Option<BTreeSet<String>> = select
+        .expr
+        .0
+        .iter()
+        .map(|field| match field {
+            Field::Single {
+                expr: Value::Idiom(idiom),
+                alias: None,
+            } => match idiom.0.as_slice() {
+                [Part::Field(name)] => Some(name.to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    let selects_every_field = selected.is_some_and(|selected| {
+        selected.len() == every_field.len()
+            && selected
+                .iter()
+                .all(|name| every_field.contains(name.as_str()))
+    });
+    if selects_every_field {
+        select.expr = Fields::all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::sql::parse;
+
+    const SCHEMA: &str = r#"
+        DEFINE TABLE user SCHEMAFULL;
+            DEFINE FIELD name ON user TYPE string;
+            DEFINE FIELD age ON user TYPE int;
+        DEFINE TABLE post SCHEMALESS;
+            DEFINE FIELD title ON post TYPE string;
+    "#;
+
+    fn canonical(query: &str) -> String {
+        canonicalize(&parse(SCHEMA).unwrap(), &parse(query).unwrap())
+            .unwrap()
+            .to_string()
+    }
+
+    fn hash(query: &str) -> u64 {
+        query_hash(&parse(SCHEMA).unwrap(), &parse(query).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn canonical_selects() {
+        assert_eq!(
+            canonical("select age,  name AS name from user where age > 18"),
+            "SELECT age, name FROM user WHERE age > 18;"
+        );
+        assert_eq!(
+            canonical("SELECT name, age, id FROM user"),
+            "SELECT * FROM user;"
+        );
+        assert_eq!(
+            canonical("SELECT title, id FROM post"),
+            "SELECT id, title FROM post;"
+        );
+        assert_eq!(
+            canonical("SELECT VALUE name AS name FROM user"),
+            "SELECT VALUE name AS name FROM user;"
+        );
+    }
+
+    #[test]
+    fn equivalent_queries_share_a_hash() {
+        assert_eq!(
+            hash("SELECT name, age FROM user"),
+            hash("select age, name as name\n  from user")
+        );
+        assert_eq!(
+            hash("SELECT * FROM user"),
+            hash("SELECT id, age, name FROM user")
+        );
+        assert_ne!(hash("SELECT name FROM user"), hash("SELECT age FROM user"));
+    }
+}
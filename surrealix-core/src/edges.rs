@@ -0,0 +1,273 @@
+//! Graph traversals selected together with the edges they cross, e.g. each `friend` record
+//! along with the user it leads to, for relationship metadata such as `since`.
+
+use surrealdb::sql::{
+    parse, Dir, Field, Ident, Idiom, Part, Permissions, Query, Statement, Subquery, Value,
+};
+
+use crate::{
+    analyzer::select::idiom_name,
+    ast::{FieldInfo, FieldMetadata, ObjectType, TypeAST},
+};
+
+/// A result field holding a traversal together with its edges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeField {
+    /// The index of the statement selecting the field.
+    pub statement: usize,
+    /// The name of the field in the result.
+    pub name: String,
+    /// The edge table crossed, e.g. `friend` in `->friend->user`.
+    pub edge: String,
+    /// The table of the nodes, e.g. `user` in `->friend->user`.
+    pub node: String,
+    /// Whether the nodes are fetched, as they are for `->friend->user.*`, rather than their ids.
+    pub fetch_node: bool,
+}
+
+/// Rewrites each `->edge->node` or `<-edge<-node` selected by a top-level SELECT from a table
+/// of `schema` into a subquery returning `{ edge, node }` for every edge crossed, with the edge
+/// fetched, e.g. `(SELECT id AS edge, out AS node FROM $parent->friend FETCH edge) AS user`.
+/// The node is fetched too if the traversal selects its fields, as `->friend->user.*` does.
+///
+/// Fields keep the name the analyzer gives the traversal, so the original query is analyzed
+/// instead, with the returned fields then typed by [type_edges].
+pub fn include_edges(schema: &TypeAST, query: &Query) -> (Query, Vec<EdgeField>) {
+    let mut query = query.clone();
+    let mut fields = vec![];
+    let TypeAST::Object(tables) = schema else {
+        return (query, fields);
+    };
+    for (index, stmt) in query.0 .0.iter_mut().enumerate() {
+        let Statement::Select(select) = stmt else {
+            continue;
+        };
+        let Some(rows) = (match select.what.0.as_slice() {
+            [Value::Table(table)] => tables.fields.get(&table.to_lowercase()),
+            _ => None,
+        }) else {
+            continue;
+        };
+        for field in select.expr.0.iter_mut() {
+            fields.extend(include_edge(schema, &rows.ast, index, field));
+        }
+    }
+    (query, fields)
+}
+
+/// Rewrites a single field of the statement at `statement`, a SELECT from `rows`, returning it
+/// if it is a traversal.
+fn include_edge(
+    schema: &TypeAST,
+    rows: &TypeAST,
+    statement: usize,
+    field: &mut Field,
+) -> Option<EdgeField> {
+    let Field::Single {
+        expr: Value::Idiom(idiom),
+        alias,
+    } = field
+    else {
+        return None;
+    };
+    let (edge, node, fetch_node) = match idiom.0.as_slice() {
+        [Part::Graph(edge), Part::Graph(node)] => (edge, node, false),
+        [Part::Graph(edge), Part::Graph(node), Part::All] => (edge, node, true),
+        _ => return None,
+    };
+    let node_field = match (&edge.dir, &node.dir) {
+        (Dir::Out, Dir::Out) => "out",
+        (Dir::In, Dir::In) => "in",
+        _ => return None,
+    };
+    // The node is whichever record the edge links to, so it can't be filtered on its own.
+    let ([edge_table], [node_table], None) =
+        (edge.what.0.as_slice(), node.what.0.as_slice(), &node.cond)
+    else {
+        return None;
+    };
+
+    let name = match alias {
+        Some(alias) => alias.to_string(),
+        None => idiom_name(schema, rows, idiom).ok()?,
+    };
+    let fetch = match fetch_node {
+        true => "edge, node",
+        false => "edge",
+    };
+    let source = format!(
+        "SELECT id AS edge, {} AS node FROM $parent{} FETCH {}",
+        node_field, edge, fetch
+    );
+    let Some(Statement::Select(select)) = parse(&source).ok()?.0 .0.into_iter().next() else {
+        return None;
+    };
+    let edge_field = EdgeField {
+        statement,
+        name: name.clone(),
+        edge: edge_table.to_string(),
+        node: node_table.to_string(),
+        fetch_node,
+    };
+
+    *field = Field::Single {
+        expr: Value::Subquery(Box::new(Subquery::Select(select))),
+        alias: Some(Idiom(vec![Part::Field(Ident::from(name))])),
+    };
+    Some(edge_field)
+}
+
+/// Types a field rewritten by [include_edges] in the result of its statement, from an array of
+/// the nodes the traversal was typed as to an array of `{ edge, node }` objects, where nodes that
+/// weren't fetched are their ids.
+pub fn type_edges(ast: &mut TypeAST, schema: &TypeAST, field: &EdgeField) {
+    match ast {
+        TypeAST::Array(rows) => type_edges(&mut rows.0, schema, field),
+        TypeAST::Option(row) => type_edges(row, schema, field),
+        TypeAST::Object(row) => {
+            let TypeAST::Object(tables) = schema else {
+                return;
+            };
+            let (Some(selected), Some(edge)) = (
+                row.fields.get_mut(&field.name),
+                tables.fields.get(&field.edge),
+            ) else {
+                return;
+            };
+            let TypeAST::Array(nodes) = &mut selected.ast else {
+                return;
+            };
+
+            let pair = |name: &str, ast: TypeAST| {
+                let info = FieldInfo {
+                    ast,
+                    meta: FieldMetadata {
+                        original_name: name.to_string(),
+                        original_path: vec![field.name.clone(), name.to_string()],
+                        permissions: Permissions::default(),
+                        constraints: vec![],
+                        default: None,
+                        deprecated: None,
+                    },
+                };
+                (name.to_string(), info)
+            };
+            let node = match field.fetch_node {
+                true => std::mem::replace(&mut nodes.0, TypeAST::Object(ObjectType::default())),
+                false => TypeAST::Record(field.node.clone()),
+            };
+            nodes.0 = TypeAST::Object(ObjectType {
+                fields: [pair("edge", edge.ast.clone()), pair("node", node)]
+                    .into_iter()
+                    .collect(),
+            });
+        }
+        _ => {}
+    }
+}
+
+/// The edge record of an object typed by [type_edges], and its node: the record if it was
+/// fetched, or its id.
+pub fn edge_and_node(obj: &ObjectType) -> Option<(&ObjectType, &TypeAST)> {
+    if obj.fields.len() != 2 {
+        return None;
+    }
+    match (
+        obj.fields.get("edge").map(|field| &field.ast),
+        obj.fields.get("node").map(|field| &field.ast),
+    ) {
+        (Some(TypeAST::Object(edge)), Some(node @ TypeAST::Object(fields)))
+            if !edge.fields.is_empty() && !fields.fields.is_empty() =>
+        {
+            Some((edge, node))
+        }
+        (Some(TypeAST::Object(edge)), Some(node @ TypeAST::Record(_)))
+            if !edge.fields.is_empty() =>
+        {
+            Some((edge, node))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analyzer::analyze, schema::analyze_schema, typing::shape};
+
+    const SCHEMA: &str = r#"
+        DEFINE TABLE user SCHEMAFULL;
+            DEFINE FIELD id ON user TYPE record<user>;
+            DEFINE FIELD name ON user TYPE string;
+        DEFINE TABLE friend SCHEMAFULL;
+            DEFINE FIELD in ON friend TYPE record<user>;
+            DEFINE FIELD out ON friend TYPE record<user>;
+            DEFINE FIELD since ON friend TYPE datetime;
+    "#;
+
+    #[test]
+    fn traversals_with_edges() {
+        let schema = analyze_schema(parse(SCHEMA).unwrap()).unwrap();
+        let query = parse("SELECT name, ->friend->user.* AS friends FROM user").unwrap();
+
+        let (rewritten, fields) = include_edges(&schema, &query);
+
+        assert_eq!(
+            rewritten.to_string(),
+            "SELECT name, (SELECT id AS edge, out AS node FROM $parent->friend FETCH edge, node) \
+             AS friends FROM user;"
+        );
+        assert_eq!(
+            fields,
+            vec![EdgeField {
+                statement: 0,
+                name: "friends".to_string(),
+                edge: "friend".to_string(),
+                node: "user".to_string(),
+                fetch_node: true,
+            }]
+        );
+
+        let mut ast = analyze(parse(SCHEMA).unwrap(), query).unwrap().remove(0);
+        type_edges(&mut ast, &schema, &fields[0]);
+        assert_eq!(
+            shape(&ast),
+            "[{ friends: [{ edge: { id: Record(friend), in: Record(user), out: Record(user), \
+             since: Datetime }, node: { id: Record(user), name: String } }], name: String }]"
+        );
+    }
+
+    #[test]
+    fn unfetched_nodes_keep_their_ids() {
+        let schema = analyze_schema(parse(SCHEMA).unwrap()).unwrap();
+        let query = parse("SELECT ->friend->user FROM user").unwrap();
+
+        let (rewritten, fields) = include_edges(&schema, &query);
+
+        // The field is named as the analyzer names the traversal.
+        assert_eq!(
+            rewritten.to_string(),
+            "SELECT (SELECT id AS edge, out AS node FROM $parent->friend FETCH edge) AS user \
+             FROM user;"
+        );
+        let mut ast = analyze(parse(SCHEMA).unwrap(), query).unwrap().remove(0);
+        type_edges(&mut ast, &schema, &fields[0]);
+        assert_eq!(
+            shape(&ast),
+            "[{ user: [{ edge: { id: Record(friend), in: Record(user), out: Record(user), \
+             since: Datetime }, node: Record(user) }] }]"
+        );
+    }
+
+    #[test]
+    fn other_fields_are_left_alone() {
+        let schema = analyze_schema(parse(SCHEMA).unwrap()).unwrap();
+        let query =
+            parse("SELECT name, ->friend->(user WHERE name = 'a') AS friends FROM user").unwrap();
+
+        let (rewritten, fields) = include_edges(&schema, &query);
+
+        assert_eq!(rewritten, query);
+        assert!(fields.is_empty());
+    }
+}
@@ -27,9 +27,48 @@ pub enum SchemaError {
 
     #[error("Failed to load .env file: {0}")]
     DotEnvError(#[from] dotenv::Error),
+
+    #[error("Failed to expand the mixins of the schema file: {0}")]
+    MixinError(#[from] MixinError),
+
+    /// The schema embedded in an environment variable couldn't be decoded.
+    #[error("Failed to decode the schema in {0}: {1}")]
+    InvalidEmbeddedSchema(String, String),
+}
+
+/// A mixin directive of a schema file that couldn't be expanded, with the line it is on.
+#[derive(Error, Debug, PartialEq)]
+pub enum MixinError {
+    #[error(
+        "line {0}: expected `-- @mixin name(params)`, `-- @include name(args)` or `-- @end` \
+         outside of a mixin, found `{1}`"
+    )]
+    Malformed(usize, String),
+    #[error("line {0}: the mixin '{1}' is never closed with `-- @end`")]
+    Unclosed(usize, String),
+    #[error("line {0}: the parameter ${1} would hide the one SurrealQL defines")]
+    ReservedParam(usize, String),
+    #[error("line {0}: there is no mixin '{1}'")]
+    Unknown(usize, String),
+    #[error("line {0}: the mixin '{1}' takes {2} arguments, but {3} were given")]
+    Arity(usize, String, usize, usize),
+}
+
+impl MixinError {
+    /// The line of the schema file the directive is on, counting from 1.
+    pub fn line(&self) -> usize {
+        match self {
+            MixinError::Malformed(line, _)
+            | MixinError::Unclosed(line, _)
+            | MixinError::ReservedParam(line, _)
+            | MixinError::Unknown(line, _)
+            | MixinError::Arity(line, ..) => *line,
+        }
+    }
 }
 
 #[derive(Error, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum AnalysisError {
     #[error("Statement references an unknown field: {0}")]
     UnknownField(String),
@@ -37,6 +76,34 @@ pub enum AnalysisError {
     UnsupportedType(String),
     #[error("Statement performs an operation that is not supported: {0}")]
     UnsupportedOperation(String),
+    #[error("The result field '{0}' is selected from both '{1}' and '{2}'. Rename one with AS.")]
+    DuplicateField(String, String, String),
+    #[error("{0} must be a non-negative integer, but got: {1}")]
+    InvalidCount(String, String),
+    #[error("The scope filter '{1}' on '{0}' is invalid: {2}")]
+    InvalidScopeFilter(String, String, String),
+    #[error("FETCH {0} is a parameter, so it isn't known which links are fetched. Use the lenient_fetch option to type them as either.")]
+    DynamicFetch(String),
+    #[error("'{0}' matches a pattern against a value that isn't a string")]
+    InvalidMatch(String),
+    #[error("'{0}' expects {1}, but is given {2}")]
+    TypeMismatch(String, String, String),
+    #[error("'{0}' applies {1} to {2}, which it isn't defined for")]
+    InvalidOperands(String, String, String),
+    #[error("{0} takes {1} argument(s), but is given {2}")]
+    FunctionArity(String, usize, usize),
+    #[error("ORDER BY {0}, which is neither a field of the table nor a field or alias the query selects")]
+    UnknownOrderField(String),
+    #[error("{0} is compared with '{1}', which isn't one of the values it may hold: {2}")]
+    UnknownLiteral(String, String, String),
+    #[error("{0} reads the match @{1}@, which the WHERE clause doesn't make")]
+    UnknownMatchRef(String, u8),
+    #[error("{0} reads a match on {1}, which no SEARCH index covers")]
+    UnsearchableField(String, String),
+    #[error("{0} reads a match with the SEARCH index '{1}', whose analyzer '{2}' isn't defined")]
+    UndefinedAnalyzer(String, String, String),
+    #[error("{0} needs the SEARCH index '{1}' to be defined with HIGHLIGHTS")]
+    NoHighlights(String, String),
     #[error("Failure resolving a path in the schema: {0}")]
     ResolverFailure(#[from] ResolverError),
 
@@ -0,0 +1,108 @@
+//! FETCH clauses whose fields are a parameter, so which links get fetched is only known at runtime.
+
+use surrealdb::sql::{statements::SelectStatement, Fetch, Part, Query, Statement, Value};
+
+use crate::ast::TypeAST;
+
+/// The parameter a FETCH item consists of, as in `FETCH $fields`.
+pub(crate) fn dynamic_fetch(fetch: &Fetch) -> Option<String> {
+    match fetch.0 .0.first() {
+        Some(Part::Start(Value::Param(param))) => Some(param.to_string()),
+        _ => None,
+    }
+}
+
+/// Removes the parameter FETCH items from each SELECT, so the rest of the query can be analyzed.
+///
+/// Returns the indices of the statements that had any, whose links should be typed with
+/// [degrade_links].
+pub fn strip_dynamic_fetches(query: &mut Query) -> Vec<usize> {
+    let statements = &mut query.0 .0;
+    statements
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(index, stmt)| match stmt {
+            Statement::Select(select) => strip_select(select).then_some(index),
+            _ => None,
+        })
+        .collect()
+}
+
+fn strip_select(select: &mut SelectStatement) -> bool {
+    let Some(fetch) = &mut select.fetch else {
+        return false;
+    };
+    let before = fetch.0.len();
+    fetch.0.retain(|item| dynamic_fetch(item).is_none());
+    let stripped = fetch.0.len() != before;
+    if fetch.0.is_empty() {
+        select.fetch = None;
+    }
+    stripped
+}
+
+/// Types every record link in `ast` as either the link or the fetched record, which is all
+/// that is known when the fields to fetch are a parameter.
+pub fn degrade_links(ast: &mut TypeAST, schema: &TypeAST) {
+    match ast {
+        TypeAST::Object(obj) => {
+            for field in obj.fields.values_mut() {
+                degrade_links(&mut field.ast, schema);
+            }
+        }
+        TypeAST::Array(inner) => degrade_links(&mut inner.0, schema),
+        TypeAST::Option(inner) => degrade_links(inner, schema),
+        TypeAST::Union(variants) => variants
+            .iter_mut()
+            .for_each(|variant| degrade_links(variant, schema)),
+        TypeAST::Record(_) => {
+            let mut fetched = ast.clone();
+            if fetched.replace_record_links(schema).is_ok()
+                && matches!(&fetched, TypeAST::Object(obj) if !obj.fields.is_empty())
+            {
+                *ast = TypeAST::Union(vec![ast.clone(), fetched]);
+            }
+        }
+        TypeAST::Scalar(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::analyze_schema;
+    use surrealdb::sql::parse;
+
+    #[test]
+    fn degrades_links() {
+        let schema = analyze_schema(
+            parse(
+                r#"
+                DEFINE TABLE user SCHEMAFULL;
+                    DEFINE FIELD name ON user TYPE string;
+                    DEFINE FIELD friends ON user TYPE array<record<user>>;
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let TypeAST::Object(tables) = &schema else {
+            panic!("Expected the schema to be an object");
+        };
+        let mut user = tables.fields["user"].ast.clone();
+
+        degrade_links(&mut user, &schema);
+
+        let TypeAST::Object(user) = user else {
+            panic!("Expected user to be an object");
+        };
+        let TypeAST::Array(friends) = &user.fields["friends"].ast else {
+            panic!("Expected friends to be an array");
+        };
+        assert!(matches!(
+            &friends.0,
+            TypeAST::Union(variants)
+                if matches!(variants.as_slice(), [TypeAST::Record(_), TypeAST::Object(_)])
+        ));
+    }
+}
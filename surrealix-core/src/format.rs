@@ -0,0 +1,84 @@
+//! Canonical formatting of SurrealQL, with keywords in upper case and nested blocks indented,
+//! so queries stay readable and diffs of them small.
+
+use surrealdb::sql::parse;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FormatError {
+    #[error("Invalid SurrealQL syntax: {0}")]
+    InvalidSyntax(#[from] surrealdb::error::Db),
+
+    /// Comments aren't part of the parsed query, so formatting would drop them.
+    #[error("The query contains comments, which formatting would remove")]
+    HasComments,
+}
+
+/// Formats a query as the database prints it, one statement per line, each ending in `;`.
+///
+/// Formatting is idempotent, so formatted queries are left as they are.
+pub fn format_query(source: &str) -> Result<String, FormatError> {
+    if has_comments(source) {
+        return Err(FormatError::HasComments);
+    }
+    let query = parse(source)?;
+    Ok(format!("{:#}", query))
+}
+
+/// Whether `source` contains a comment (`--`, `//`, `#` or `/* */`) outside of strings and
+/// escaped identifiers.
+fn has_comments(source: &str) -> bool {
+    let mut chars = source.chars().peekable();
+    // The character closing the string or identifier being read, if any.
+    let mut closing = None;
+    while let Some(c) = chars.next() {
+        match closing {
+            Some(_) if c == '\\' => {
+                chars.next();
+            }
+            Some(end) if c == end => closing = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' | '`' => closing = Some(c),
+                '⟨' => closing = Some('⟩'),
+                '#' => return true,
+                '-' | '/' if chars.peek() == Some(&c) => return true,
+                '/' if chars.peek() == Some(&'*') => return true,
+                _ => {}
+            },
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_canonically() {
+        let formatted =
+            format_query("select name, age from user where age > 18 order by age; return 1")
+                .unwrap();
+
+        assert_eq!(
+            formatted,
+            "SELECT name, age FROM user WHERE age > 18 ORDER BY age;\nRETURN 1;"
+        );
+        assert_eq!(format_query(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn keeps_comments() {
+        assert!(matches!(
+            format_query("SELECT * FROM user -- all of them"),
+            Err(FormatError::HasComments)
+        ));
+        assert!(matches!(
+            format_query("SELECT * FROM user /* all */"),
+            Err(FormatError::HasComments)
+        ));
+        assert!(format_query(r#"SELECT * FROM user WHERE name = "-- #not a comment""#).is_ok());
+        assert!(format_query("SELECT * FROM user WHERE url = 'http://example.com'").is_ok());
+    }
+}
@@ -0,0 +1,50 @@
+//! Selecting the single row a GROUP ALL yields, rather than an array holding it.
+
+use surrealdb::sql::{Limit, Query, Statement, Value};
+
+/// The query with each top-level `SELECT ... GROUP ALL` selecting ONLY its row, e.g.
+/// `SELECT count() FROM ONLY user GROUP ALL LIMIT 1`, which yields NONE for an empty table.
+///
+/// SELECTs that already select ONLY, or have a LIMIT or START of their own, are left as they are.
+pub fn select_group_all(query: &Query) -> Query {
+    let mut query = query.clone();
+    for stmt in query.0 .0.iter_mut() {
+        let Statement::Select(select) = stmt else {
+            continue;
+        };
+        let group_all = select
+            .group
+            .as_ref()
+            .is_some_and(|groups| groups.is_empty());
+        if group_all && !select.only && select.limit.is_none() && select.start.is_none() {
+            select.only = true;
+            // ONLY rejects selecting from arrays, like those of a parameter, without a LIMIT.
+            select.limit = Some(Limit(Value::from(1)));
+        }
+    }
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::sql::parse;
+
+    #[test]
+    fn selects_only_the_group() {
+        let grouped = |sql: &str| select_group_all(&parse(sql).unwrap()).to_string();
+
+        assert_eq!(
+            grouped("SELECT count() FROM user GROUP ALL"),
+            "SELECT count() FROM ONLY user GROUP ALL LIMIT 1;"
+        );
+        assert_eq!(
+            grouped("SELECT count() FROM user GROUP ALL LIMIT 5"),
+            "SELECT count() FROM user GROUP ALL LIMIT 5;"
+        );
+        assert_eq!(
+            grouped("SELECT name, count() FROM user GROUP BY name"),
+            "SELECT name, count() FROM user GROUP BY name;"
+        );
+    }
+}
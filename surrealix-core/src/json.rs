@@ -0,0 +1,81 @@
+//! The analysis of a query as JSON, for tooling that doesn't link the Rust code generator, such
+//! as the CLI, the language server, or generators for other languages.
+
+use serde::{Serialize, Serializer};
+use surrealdb::sql::Query;
+
+use crate::{analyzer::analyze, ast::TypeAST, errors::AnalysisError};
+
+/// The type of each statement of a query, or why the query couldn't be typed.
+///
+/// Serialized as `{ "statements": [...] }` or `{ "error": { "message": ..., "detail": ... } }`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Analysis {
+    Statements(Vec<TypeAST>),
+    Error {
+        /// The error as it would be reported to a user.
+        message: String,
+        /// The error variant and its fields.
+        detail: AnalysisError,
+    },
+}
+
+impl From<Result<Vec<TypeAST>, AnalysisError>> for Analysis {
+    fn from(result: Result<Vec<TypeAST>, AnalysisError>) -> Self {
+        match result {
+            Ok(statements) => Analysis::Statements(statements),
+            Err(detail) => Analysis::Error {
+                message: detail.to_string(),
+                detail,
+            },
+        }
+    }
+}
+
+/// Analyzes `query` against `schema` as [analyze] does, with the result as JSON.
+pub fn analyze_json(schema: Query, query: Query) -> Result<serde_json::Value, serde_json::Error> {
+    serde_json::to_value(Analysis::from(analyze(schema, query)))
+}
+
+/// Serializes a value without a `Serialize` impl, e.g. an error from the database, as its message.
+pub(crate) fn display<T: std::fmt::Display, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use surrealdb::sql::parse;
+
+    const SCHEMA: &str = r#"
+        DEFINE TABLE user SCHEMAFULL;
+            DEFINE FIELD name ON user TYPE string;
+    "#;
+
+    #[test]
+    fn analysis_as_json() {
+        let json = |query: &str| analyze_json(parse(SCHEMA).unwrap(), parse(query).unwrap());
+
+        let analysis = json("SELECT name FROM user").unwrap();
+        assert_eq!(analysis["statements"][0]["kind"], "array");
+        let row = &analysis["statements"][0]["type"][0];
+        assert_eq!(row["kind"], "object");
+        assert_eq!(
+            row["type"]["fields"]["name"]["ast"],
+            json!({ "kind": "scalar", "type": "string" })
+        );
+        assert_eq!(
+            row["type"]["fields"]["name"]["meta"]["original_name"],
+            "name"
+        );
+
+        let analysis = json("SELECT * FROM post").unwrap();
+        assert!(analysis["error"]["message"].is_string());
+        assert!(analysis["error"]["detail"].is_object());
+    }
+}
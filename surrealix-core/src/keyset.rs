@@ -0,0 +1,98 @@
+//! Paging through the rows of a SELECT by their id, continuing after the last id of the previous
+//! page, rather than skipping rows with START, which gets slower the further in a page is.
+
+use surrealdb::sql::{
+    Cond, Expression, Idiom, Limit, Operator, Order, Orders, Param, Query, Statement, Subquery,
+    Value,
+};
+
+/// The parameter bound to the id a page continues after, NONE for the first page, as NONE sorts
+/// before every record.
+pub const AFTER_PARAM: &str = "surrealix_after";
+
+/// The parameter bound to the number of rows a page asks for.
+pub const PAGE_SIZE_PARAM: &str = "surrealix_page_size";
+
+/// The query paging through its rows by id, e.g. `SELECT * FROM user WHERE id >
+/// $surrealix_after AND (age > 18) ORDER BY id LIMIT $surrealix_page_size`.
+///
+/// Only a single SELECT from tables can be paged, and only if it has no order, grouping, split,
+/// limit or start of its own, which paging by id would change the meaning of.
+pub fn keyset_query(query: &Query) -> Option<Query> {
+    let mut query = query.clone();
+    let [Statement::Select(select)] = query.0 .0.as_mut_slice() else {
+        return None;
+    };
+    let from_tables = select
+        .what
+        .iter()
+        .all(|what| matches!(what, Value::Table(_)));
+    if !from_tables
+        || select.only
+        || select.order.is_some()
+        || select.group.is_some()
+        || select.split.is_some()
+        || select.limit.is_some()
+        || select.start.is_some()
+    {
+        return None;
+    }
+
+    let after = Value::Expression(Box::new(Expression::Binary {
+        l: Value::Idiom(Idiom::from("id".to_string())),
+        o: Operator::MoreThan,
+        r: Value::Param(Param::from(AFTER_PARAM)),
+    }));
+    select.cond = Some(Cond(match select.cond.take() {
+        None => after,
+        Some(existing) => Value::Expression(Box::new(Expression::Binary {
+            l: after,
+            o: Operator::And,
+            r: Value::Subquery(Box::new(Subquery::Value(existing.0))),
+        })),
+    }));
+    select.order = Some(Orders(vec![Order {
+        order: Idiom::from("id".to_string()),
+        random: false,
+        collate: false,
+        numeric: false,
+        direction: true,
+    }]));
+    select.limit = Some(Limit(Value::Param(Param::from(PAGE_SIZE_PARAM))));
+    Some(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::sql::parse;
+
+    #[test]
+    fn pages_by_id() {
+        let paged = |sql: &str| keyset_query(&parse(sql).unwrap()).map(|q| q.to_string());
+
+        assert_eq!(
+            paged("SELECT * FROM user WHERE age > 18").as_deref(),
+            Some(
+                "SELECT * FROM user WHERE id > $surrealix_after AND (age > 18) ORDER BY id \
+                 LIMIT $surrealix_page_size;"
+            )
+        );
+        assert_eq!(
+            paged("SELECT id, name FROM user").as_deref(),
+            Some(
+                "SELECT id, name FROM user WHERE id > $surrealix_after ORDER BY id \
+                 LIMIT $surrealix_page_size;"
+            )
+        );
+        for sql in [
+            "SELECT * FROM user ORDER BY name",
+            "SELECT * FROM user LIMIT 10",
+            "SELECT * FROM $users",
+            "SELECT * FROM user; SELECT * FROM post",
+            "UPDATE user SET age += 1",
+        ] {
+            assert_eq!(paged(sql), None, "{}", sql);
+        }
+    }
+}
@@ -1,4 +1,26 @@
+pub mod access;
 pub mod analyzer;
 pub mod ast;
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod canonical;
+pub mod edges;
 pub mod errors;
+pub mod fetch;
+pub mod format;
+pub mod group;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod keyset;
+pub mod limit;
+pub mod lint;
+pub mod mixin;
+pub mod params;
+pub mod permissions;
+pub mod relations;
 pub mod schema;
+pub mod scope;
+pub mod search;
+pub mod source;
+pub mod typing;
+pub mod usage;
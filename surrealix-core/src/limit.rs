@@ -0,0 +1,82 @@
+//! Capping how many rows a query returns, so the database doesn't send more than a client can
+//! hold, and the parameters LIMIT and START take.
+
+use surrealdb::sql::{Array, Function, Param, Query, Statement, Value};
+
+/// The parameter bound to the row cap of a query from `limit_rows`.
+pub const MAX_ROWS_PARAM: &str = "surrealix_max_rows";
+
+/// The query with its LIMIT capped at `$surrealix_max_rows`, if it is a single SELECT.
+///
+/// A LIMIT the query already has is kept when it is lower, as
+/// `LIMIT math::min([10, $surrealix_max_rows])`.
+pub fn limit_rows(query: &Query) -> Option<Query> {
+    let mut query = query.clone();
+    let [Statement::Select(select)] = query.0 .0.as_mut_slice() else {
+        return None;
+    };
+
+    let param = Value::Param(Param::from(MAX_ROWS_PARAM));
+    let mut limit = select.limit.take().unwrap_or_default();
+    limit.0 = match limit.0 {
+        Value::None => param,
+        existing => Value::Function(Box::new(Function::Normal(
+            "math::min".to_string(),
+            vec![Value::Array(Array::from(vec![existing, param]))],
+        ))),
+    };
+    select.limit = Some(limit);
+    Some(query)
+}
+
+/// The parameters of the LIMIT and START clauses of each SELECT, which hold row counts.
+pub fn count_params(query: &Query) -> Vec<String> {
+    let mut params: Vec<String> = query
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Statement::Select(select) => Some(select),
+            _ => None,
+        })
+        .flat_map(|select| {
+            let limit = select.limit.as_ref().map(|limit| &limit.0);
+            let start = select.start.as_ref().map(|start| &start.0);
+            limit.into_iter().chain(start)
+        })
+        .filter_map(|value| match value {
+            Value::Param(param) => Some(param.0 .0.clone()),
+            _ => None,
+        })
+        .collect();
+    params.sort();
+    params.dedup();
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::sql::parse;
+
+    #[test]
+    fn caps_selects() {
+        let limited = |sql: &str| limit_rows(&parse(sql).unwrap()).map(|q| q.to_string());
+
+        assert_eq!(
+            limited("SELECT * FROM user WHERE age > 18").as_deref(),
+            Some("SELECT * FROM user WHERE age > 18 LIMIT $surrealix_max_rows;")
+        );
+        assert_eq!(
+            limited("SELECT * FROM user LIMIT 10").as_deref(),
+            Some("SELECT * FROM user LIMIT math::min([10, $surrealix_max_rows]);")
+        );
+        assert_eq!(limited("SELECT * FROM user; SELECT * FROM post"), None);
+        assert_eq!(limited("CREATE user"), None);
+    }
+
+    #[test]
+    fn finds_count_params() {
+        let query = parse("SELECT * FROM user LIMIT $n START $offset; SELECT * FROM post LIMIT $n")
+            .unwrap();
+        assert_eq!(count_params(&query), vec!["n", "offset"]);
+    }
+}
@@ -0,0 +1,756 @@
+//! Heuristics for suspicious schemas and for queries that are likely to be slow.
+//!
+//! These are hints rather than errors: the analyzer can't know how much data a table holds,
+//! so index checks only apply to the tables configured as large.
+
+use std::{collections::HashMap, fmt};
+
+use surrealdb::sql::{
+    statements::{DefineStatement, RemoveStatement, SelectStatement},
+    Expression, Function, Idiom, Operator, Permission, Query, Statement, Subquery, Value, With,
+};
+
+use crate::{
+    analyzer::Analyzer,
+    ast::{ObjectType, ScalarType, TypeAST},
+    params::RESERVED_PARAMS,
+    schema::{analyze_schema, SchemaParseError},
+    search::search_indexes,
+};
+
+/// Field names that usually hold data not every user should read.
+const SENSITIVE_NAMES: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "ssn",
+    "api_key",
+    "apikey",
+    "private_key",
+    "credit_card",
+];
+
+/// A definition in the schema that is legal, but likely a mistake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaLint {
+    /// A field typed `any`, or without a type, so queries can't know what it holds.
+    UntypedField { table: String, field: String },
+    /// An array field whose elements (`field.*`) have no definition.
+    UntypedArray { table: String, field: String },
+    /// A record link to a table the schema doesn't define.
+    UndefinedLink {
+        table: String,
+        field: String,
+        target: String,
+    },
+    /// An edge table that doesn't define its `in` or `out` field, so it can't be traversed.
+    IncompleteEdge { table: String, missing: String },
+    /// A field that looks sensitive, but anyone who can read the table can select.
+    UnprotectedField { table: String, field: String },
+}
+
+impl fmt::Display for SchemaLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaLint::UntypedField { table, field } => write!(
+                f,
+                "{}.{} has no type, or is typed 'any', so its values are untyped in queries.",
+                table, field
+            ),
+            SchemaLint::UntypedArray { table, field } => write!(
+                f,
+                "{}.{} is an array without a definition for '{}.*', so its elements are untyped.",
+                table, field, field
+            ),
+            SchemaLint::UndefinedLink {
+                table,
+                field,
+                target,
+            } => write!(
+                f,
+                "{}.{} links to the table '{}', which isn't defined.",
+                table, field, target
+            ),
+            SchemaLint::IncompleteEdge { table, missing } => write!(
+                f,
+                "The edge table '{}' doesn't define its '{}' field, so it can't be traversed.",
+                table, missing
+            ),
+            SchemaLint::UnprotectedField { table, field } => write!(
+                f,
+                "{}.{} looks sensitive, but has FULL select permissions.",
+                table, field
+            ),
+        }
+    }
+}
+
+/// Checks every table of the schema for suspicious definitions, in table and field order.
+pub fn lint_schema(schema: &Query) -> Result<Vec<SchemaLint>, SchemaParseError> {
+    let TypeAST::Object(tables) = analyze_schema(schema.clone())? else {
+        return Ok(vec![]);
+    };
+
+    let mut names: Vec<_> = tables.fields.keys().collect();
+    names.sort();
+
+    let mut lints = vec![];
+    for table in names {
+        let TypeAST::Object(obj) = &tables.fields[table].ast else {
+            continue;
+        };
+
+        // Tables are only known to be edges by their `in` and `out` fields, so defining one
+        // side without the other is what gives an edge away.
+        let is_edge = obj.fields.contains_key("in") || obj.fields.contains_key("out");
+        if is_edge {
+            for side in ["in", "out"] {
+                if !obj.fields.contains_key(side) {
+                    lints.push(SchemaLint::IncompleteEdge {
+                        table: table.clone(),
+                        missing: side.to_string(),
+                    });
+                }
+            }
+        }
+
+        lint_fields(&tables, table, obj, "", &mut lints);
+    }
+
+    Ok(lints)
+}
+
+fn lint_fields(
+    tables: &ObjectType,
+    table: &str,
+    obj: &ObjectType,
+    prefix: &str,
+    lints: &mut Vec<SchemaLint>,
+) {
+    let mut fields: Vec<_> = obj.fields.iter().collect();
+    fields.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (name, field) in fields {
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+
+        let lower = name.to_lowercase();
+        if SENSITIVE_NAMES
+            .iter()
+            .any(|sensitive| lower.contains(sensitive))
+            && field.meta.permissions.select == Permission::Full
+        {
+            lints.push(SchemaLint::UnprotectedField {
+                table: table.to_string(),
+                field: path.clone(),
+            });
+        }
+
+        lint_type(tables, table, &path, &field.ast, lints);
+    }
+}
+
+fn lint_type(
+    tables: &ObjectType,
+    table: &str,
+    path: &str,
+    ast: &TypeAST,
+    lints: &mut Vec<SchemaLint>,
+) {
+    let (table, field) = (table.to_string(), path.to_string());
+    match ast {
+        TypeAST::Scalar(ScalarType::Any) => lints.push(SchemaLint::UntypedField { table, field }),
+        TypeAST::Array(inner) if inner.0 == TypeAST::Scalar(ScalarType::Any) => {
+            lints.push(SchemaLint::UntypedArray { table, field })
+        }
+        TypeAST::Array(inner) => lint_type(tables, &table, path, &inner.0, lints),
+        TypeAST::Option(inner) => lint_type(tables, &table, path, inner, lints),
+        TypeAST::Union(variants) => {
+            for variant in variants {
+                lint_type(tables, &table, path, variant, lints);
+            }
+        }
+        TypeAST::Record(target) if !tables.fields.contains_key(target) => {
+            lints.push(SchemaLint::UndefinedLink {
+                table,
+                field,
+                target: target.clone(),
+            })
+        }
+        TypeAST::Object(obj) => lint_fields(tables, &table, obj, path, lints),
+        TypeAST::Scalar(_) | TypeAST::Record(_) => {}
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lint {
+    /// A WHERE clause on a large table that no index can serve.
+    UnindexedFilter { table: String, fields: Vec<String> },
+    /// A string match with a leading wildcard, which can't use an index.
+    LeadingWildcard { table: String, condition: String },
+    /// A `WITH INDEX` hint naming an index the table doesn't define.
+    UnknownIndex { table: String, index: String },
+    /// A full-text match (`@@`) on a field without a SEARCH index, which the query fails without.
+    UnsearchableMatch { table: String, field: String },
+    /// A SELECT from a parameter no LET before it binds, so its rows are untyped.
+    UntypedParamRows { param: String },
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lint::UnindexedFilter { table, fields } => write!(
+                f,
+                "The WHERE clause on the large table '{}' filters on {}, none of which is indexed. This is likely a full table scan.",
+                table,
+                fields.join(", ")
+            ),
+            Lint::LeadingWildcard { table, condition } => write!(
+                f,
+                "'{}' on '{}' matches with a leading wildcard, which can't use an index.",
+                condition, table
+            ),
+            Lint::UnknownIndex { table, index } => write!(
+                f,
+                "The hinted index '{}' isn't defined on '{}', so the planner can't use it.",
+                index, table
+            ),
+            Lint::UnsearchableMatch { table, field } => write!(
+                f,
+                "{}.{} is matched with @@, which needs a SEARCH index on it with a defined ANALYZER. Without one, the query fails.",
+                table, field
+            ),
+            Lint::UntypedParamRows { param } => write!(
+                f,
+                "The rows selected from ${} are untyped, as no LET before the SELECT binds it.",
+                param
+            ),
+        }
+    }
+}
+
+/// The `WITH` clause of a SELECT, which overrides the indexes the query planner picks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexHint {
+    /// The index of the statement within the query.
+    pub statement: usize,
+    /// What the statement selects from, e.g. `user`.
+    pub table: String,
+    /// The indexes the planner is limited to, or `None` for `WITH NOINDEX`.
+    pub indexes: Option<Vec<String>>,
+}
+
+/// The index hints of the top-level SELECTs of the query.
+pub fn index_hints(query: &Query) -> Vec<IndexHint> {
+    query
+        .iter()
+        .enumerate()
+        .filter_map(|(statement, stmt)| match stmt {
+            Statement::Select(select) => {
+                let indexes = match select.with.as_ref()? {
+                    With::NoIndex => None,
+                    With::Index(indexes) => Some(indexes.clone()),
+                };
+                Some(IndexHint {
+                    statement,
+                    table: select.what.to_string(),
+                    indexes,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The indexed columns of each table, from the DEFINE INDEX statements of the schema.
+///
+/// Only the first column of a compound index is recorded, as only it can serve a filter alone.
+pub fn indexes(schema: &Query) -> HashMap<String, Vec<Idiom>> {
+    defined_indexes(schema)
+        .into_iter()
+        .map(|(table, columns)| (table, columns.into_iter().map(|(_, c)| c).collect()))
+        .collect()
+}
+
+/// The name and first column of each index of each table.
+fn defined_indexes(schema: &Query) -> HashMap<String, Vec<(String, Idiom)>> {
+    let mut indexes: HashMap<String, Vec<(String, Idiom)>> = HashMap::new();
+
+    for stmt in schema.iter() {
+        match stmt {
+            Statement::Define(DefineStatement::Index(def)) => {
+                if let Some(column) = def.cols.0.first() {
+                    indexes
+                        .entry(def.what.to_string().to_lowercase())
+                        .or_default()
+                        .push((def.name.to_string(), column.clone()));
+                }
+            }
+            Statement::Remove(RemoveStatement::Index(rm)) => {
+                if let Some(table) = indexes.get_mut(&rm.what.to_string().to_lowercase()) {
+                    table.retain(|(name, _)| *name != rm.name.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    indexes
+}
+
+/// The columns of each table's SEARCH indexes, of those whose analyzer is defined.
+fn searchable_columns(schema: &Query) -> HashMap<String, Vec<Idiom>> {
+    let mut columns: HashMap<String, Vec<Idiom>> = HashMap::new();
+    for index in search_indexes(schema) {
+        if index.analyzer_defined {
+            columns.entry(index.table).or_default().push(index.column);
+        }
+    }
+    columns
+}
+
+/// Checks every SELECT in the query for unindexed filters on `large_tables`, leading wildcards,
+/// full-text matches without a SEARCH index, hints naming indexes that don't exist and
+/// parameters of unknown type to select from.
+pub fn lint_query(schema: &Query, query: &Query, large_tables: &[String]) -> Vec<Lint> {
+    lint_query_with(&Analyzer::default(), schema, query, large_tables)
+}
+
+/// Lints a query like [lint_query], knowing the types of the parameters `analyzer` registers.
+pub(crate) fn lint_query_with(
+    analyzer: &Analyzer,
+    schema: &Query,
+    query: &Query,
+    large_tables: &[String],
+) -> Vec<Lint> {
+    let defined = defined_indexes(schema);
+    let indexes = indexes(schema);
+    let searchable = searchable_columns(schema);
+
+    let mut lints: Vec<Lint> = query
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Statement::Select(select) => Some(select),
+            _ => None,
+        })
+        .flat_map(|select| lint_select(select, &indexes, &searchable, large_tables))
+        .collect();
+
+    for hint in index_hints(query) {
+        let table = hint.table.to_lowercase();
+        let defined = defined.get(&table).map(Vec::as_slice).unwrap_or_default();
+        for index in hint.indexes.unwrap_or_default() {
+            if !defined.iter().any(|(name, _)| *name == index) {
+                lints.push(Lint::UnknownIndex {
+                    table: table.clone(),
+                    index,
+                });
+            }
+        }
+    }
+
+    // The analyzer only knows the type of parameters bound by a LET, those the schema defines
+    // or the analyzer registers, and SurrealDB's own.
+    let mut bound: Vec<String> = schema
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Statement::Define(DefineStatement::Param(param)) => Some(param.name.0.clone()),
+            _ => None,
+        })
+        .collect();
+    for stmt in query.iter() {
+        match stmt {
+            Statement::Set(set) => bound.push(set.name.clone()),
+            Statement::Select(select) => match select.what.first() {
+                Some(Value::Param(param))
+                    if !bound.contains(&param.0 .0)
+                        && analyzer.param_type(param).is_none()
+                        && !RESERVED_PARAMS.contains(&param.0.as_str()) =>
+                {
+                    lints.push(Lint::UntypedParamRows {
+                        param: param.0 .0.clone(),
+                    })
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    lints
+}
+
+fn lint_select(
+    select: &SelectStatement,
+    indexes: &HashMap<String, Vec<Idiom>>,
+    searchable: &HashMap<String, Vec<Idiom>>,
+    large_tables: &[String],
+) -> Vec<Lint> {
+    let (Some(Value::Table(table)), Some(cond)) = (select.what.first(), &select.cond) else {
+        return vec![];
+    };
+    let table = table.to_string().to_lowercase();
+    let mut lints = vec![];
+
+    leading_wildcards(&cond.0, &table, &mut lints);
+
+    let searchable = searchable
+        .get(&table)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let mut unsearchable = vec![];
+    unsearchable_matches(&cond.0, searchable, &mut unsearchable);
+    unsearchable.dedup();
+    lints.extend(
+        unsearchable
+            .into_iter()
+            .map(|field| Lint::UnsearchableMatch {
+                table: table.clone(),
+                field,
+            }),
+    );
+
+    let indexed = indexes.get(&table).map(Vec::as_slice).unwrap_or_default();
+    let is_large = large_tables.iter().any(|t| t.eq_ignore_ascii_case(&table));
+    // `WITH NOINDEX` asks for the table scan, so it isn't warned about.
+    let no_index = matches!(select.with, Some(With::NoIndex));
+    if is_large && !no_index && !uses_index(&cond.0, indexed) {
+        let mut fields = vec![];
+        filtered_fields(&cond.0, &mut fields);
+        if !fields.is_empty() {
+            fields.sort();
+            fields.dedup();
+            lints.push(Lint::UnindexedFilter { table, fields });
+        }
+    }
+
+    lints
+}
+
+/// Whether the planner could serve the condition from an index: any side of an AND,
+/// or every side of an OR.
+fn uses_index(cond: &Value, indexed: &[Idiom]) -> bool {
+    match cond {
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary {
+                l,
+                o: Operator::And,
+                r,
+            } => uses_index(l, indexed) || uses_index(r, indexed),
+            Expression::Binary {
+                l,
+                o: Operator::Or,
+                r,
+            } => uses_index(l, indexed) && uses_index(r, indexed),
+            Expression::Binary { l, r, .. } => [l, r]
+                .into_iter()
+                .any(|side| matches!(side, Value::Idiom(idiom) if indexed.contains(idiom))),
+            _ => false,
+        },
+        Value::Subquery(subquery) => match subquery.as_ref() {
+            Subquery::Value(value) => uses_index(value, indexed),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// The fields the condition matches with `@@` that no SEARCH index covers.
+fn unsearchable_matches(cond: &Value, searchable: &[Idiom], fields: &mut Vec<String>) {
+    match cond {
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary {
+                l: Value::Idiom(idiom),
+                o: Operator::Matches(_),
+                ..
+            } => {
+                if !searchable.contains(idiom) {
+                    fields.push(idiom.to_string());
+                }
+            }
+            Expression::Binary { l, r, .. } => {
+                unsearchable_matches(l, searchable, fields);
+                unsearchable_matches(r, searchable, fields);
+            }
+            Expression::Unary { v, .. } => unsearchable_matches(v, searchable, fields),
+            _ => {}
+        },
+        Value::Subquery(subquery) => {
+            if let Subquery::Value(value) = subquery.as_ref() {
+                unsearchable_matches(value, searchable, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn filtered_fields(cond: &Value, fields: &mut Vec<String>) {
+    match cond {
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary { l, r, .. } => {
+                filtered_fields(l, fields);
+                filtered_fields(r, fields);
+            }
+            Expression::Unary { v, .. } => filtered_fields(v, fields),
+            _ => {}
+        },
+        Value::Subquery(subquery) => {
+            if let Subquery::Value(value) = subquery.as_ref() {
+                filtered_fields(value, fields);
+            }
+        }
+        Value::Idiom(idiom) => fields.push(idiom.to_string()),
+        _ => {}
+    }
+}
+
+fn leading_wildcards(cond: &Value, table: &str, lints: &mut Vec<Lint>) {
+    let lint = || Lint::LeadingWildcard {
+        table: table.to_string(),
+        condition: cond.to_string(),
+    };
+
+    match cond {
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary {
+                l,
+                o: Operator::And | Operator::Or,
+                r,
+            } => {
+                leading_wildcards(l, table, lints);
+                leading_wildcards(r, table, lints);
+            }
+            Expression::Binary { l, r, .. } => {
+                if [l, r].into_iter().any(is_leading_wildcard) {
+                    lints.push(lint());
+                }
+            }
+            Expression::Unary { v, .. } => leading_wildcards(v, table, lints),
+            _ => {}
+        },
+        Value::Function(function) => match function.as_ref() {
+            Function::Normal(name, args)
+                if (name == "string::endsWith" || name == "string::contains")
+                    && matches!(args.first(), Some(Value::Idiom(_))) =>
+            {
+                lints.push(lint())
+            }
+            Function::Normal(name, args) if name == "string::matches" => {
+                if args.iter().any(is_leading_wildcard) {
+                    lints.push(lint());
+                }
+            }
+            _ => {}
+        },
+        Value::Subquery(subquery) => {
+            if let Subquery::Value(value) = subquery.as_ref() {
+                leading_wildcards(value, table, lints);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A regex or pattern string starting with `.*`, `.+` or `%`.
+fn is_leading_wildcard(value: &Value) -> bool {
+    let pattern = match value {
+        Value::Regex(regex) => regex.to_string(),
+        Value::Strand(strand) => strand.as_str().to_string(),
+        _ => return false,
+    };
+    let pattern = pattern.trim_start_matches('/');
+    pattern.starts_with(".*") || pattern.starts_with(".+") || pattern.starts_with('%')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::sql::parse;
+
+    #[test]
+    fn lints_schema() {
+        let schema = parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD password ON user TYPE string;
+                DEFINE FIELD api_token ON user TYPE string PERMISSIONS FOR select NONE;
+                DEFINE FIELD meta ON user;
+                DEFINE FIELD tags ON user TYPE array;
+                DEFINE FIELD team ON user TYPE record<team>;
+            DEFINE TABLE follows SCHEMAFULL;
+                DEFINE FIELD in ON follows TYPE record<user>;
+            "#,
+        )
+        .unwrap();
+
+        let lints = lint_schema(&schema).unwrap();
+
+        assert_eq!(
+            lints,
+            vec![
+                SchemaLint::IncompleteEdge {
+                    table: "follows".to_string(),
+                    missing: "out".to_string(),
+                },
+                SchemaLint::UntypedField {
+                    table: "user".to_string(),
+                    field: "meta".to_string(),
+                },
+                SchemaLint::UnprotectedField {
+                    table: "user".to_string(),
+                    field: "password".to_string(),
+                },
+                SchemaLint::UntypedArray {
+                    table: "user".to_string(),
+                    field: "tags".to_string(),
+                },
+                SchemaLint::UndefinedLink {
+                    table: "user".to_string(),
+                    field: "team".to_string(),
+                    target: "team".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lints_filters() {
+        let schema = parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD email ON user TYPE string;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD age ON user TYPE number;
+                DEFINE INDEX user_email ON user FIELDS email UNIQUE;
+            "#,
+        )
+        .unwrap();
+        let large = vec!["user".to_string()];
+        let lint = |query: &str| lint_query(&schema, &parse(query).unwrap(), &large);
+
+        assert!(lint("SELECT * FROM user WHERE email = 'a' AND age > 18").is_empty());
+        assert_eq!(
+            lint("SELECT * FROM user WHERE email = 'a' OR age > 18"),
+            vec![Lint::UnindexedFilter {
+                table: "user".to_string(),
+                fields: vec!["age".to_string(), "email".to_string()],
+            }]
+        );
+        assert!(matches!(
+            lint("SELECT * FROM user WHERE email = 'a' AND name = /.*son/")[..],
+            [Lint::LeadingWildcard { .. }]
+        ));
+        assert!(matches!(
+            lint("SELECT * FROM user WHERE email = 'a' AND string::endsWith(name, 'son')")[..],
+            [Lint::LeadingWildcard { .. }]
+        ));
+
+        let small = lint_query(
+            &schema,
+            &parse("SELECT * FROM user WHERE age > 18").unwrap(),
+            &[],
+        );
+        assert!(small.is_empty());
+    }
+
+    #[test]
+    fn index_hints_are_checked() {
+        let schema = parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD email ON user TYPE string;
+                DEFINE FIELD age ON user TYPE number;
+                DEFINE INDEX user_email ON user FIELDS email UNIQUE;
+            "#,
+        )
+        .unwrap();
+        let large = vec!["user".to_string()];
+        let query = parse(
+            "SELECT * FROM user WITH INDEX user_email, user_age WHERE email = 'a'; \
+             SELECT * FROM user WITH NOINDEX WHERE age > 18",
+        )
+        .unwrap();
+
+        assert_eq!(
+            index_hints(&query),
+            vec![
+                IndexHint {
+                    statement: 0,
+                    table: "user".to_string(),
+                    indexes: Some(vec!["user_email".to_string(), "user_age".to_string()]),
+                },
+                IndexHint {
+                    statement: 1,
+                    table: "user".to_string(),
+                    indexes: None,
+                },
+            ]
+        );
+        assert_eq!(
+            lint_query(&schema, &query, &large),
+            vec![Lint::UnknownIndex {
+                table: "user".to_string(),
+                index: "user_age".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn lints_unsearchable_matches() {
+        let schema = parse(
+            r#"
+            DEFINE ANALYZER simple TOKENIZERS blank FILTERS lowercase;
+            DEFINE TABLE post SCHEMAFULL;
+                DEFINE FIELD title ON post TYPE string;
+                DEFINE FIELD body ON post TYPE string;
+                DEFINE FIELD summary ON post TYPE string;
+                DEFINE INDEX post_title ON post FIELDS title SEARCH ANALYZER simple BM25;
+                DEFINE INDEX post_body ON post FIELDS body SEARCH ANALYZER missing BM25;
+            "#,
+        )
+        .unwrap();
+        let lint = |query: &str| lint_query(&schema, &parse(query).unwrap(), &[]);
+
+        assert!(lint("SELECT * FROM post WHERE title @@ 'surreal'").is_empty());
+        assert_eq!(
+            lint("SELECT * FROM post WHERE title @@ 'a' OR body @0@ 'b' OR summary @@ 'c'"),
+            vec![
+                Lint::UnsearchableMatch {
+                    table: "post".to_string(),
+                    field: "body".to_string(),
+                },
+                Lint::UnsearchableMatch {
+                    table: "post".to_string(),
+                    field: "summary".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn untyped_param_rows() {
+        let schema =
+            parse("DEFINE TABLE user SCHEMALESS; DEFINE PARAM $admins VALUE (SELECT * FROM user);")
+                .unwrap();
+        let lint = |query: &str| lint_query(&schema, &parse(query).unwrap(), &[]);
+
+        assert_eq!(
+            lint("SELECT * FROM $users; LET $users = (SELECT * FROM user); SELECT * FROM $users"),
+            vec![Lint::UntypedParamRows {
+                param: "users".to_string(),
+            }]
+        );
+        assert!(lint("SELECT * FROM $auth").is_empty());
+        assert!(lint("SELECT * FROM $admins").is_empty());
+
+        let friends = TypeAST::Array(Box::new((TypeAST::Record("user".into()), None)));
+        let analyzer = Analyzer::new().with_param("friends", friends);
+        assert!(analyzer
+            .lint_query(&schema, &parse("SELECT * FROM $friends").unwrap(), &[])
+            .is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+//! Mixins in schema files: definitions shared by several tables, written once and included
+//! into each, e.g. the timestamps every table keeps.
+//!
+//! ```surql
+//! -- @mixin owned(table, owner)
+//! DEFINE FIELD owner ON $table TYPE record<$owner>;
+//! -- @end
+//!
+//! DEFINE TABLE post SCHEMAFULL;
+//! -- @include timestamps(post)
+//! -- @include owned(post, user)
+//! ```
+//!
+//! The directives are comments to SurrealDB, so the schema file can still be imported as is.
+
+use std::collections::HashMap;
+
+use crate::{errors::MixinError, params::RESERVED_PARAMS};
+
+/// The built-in `timestamps(table)` mixin, which a schema can replace with its own.
+const TIMESTAMPS: &str = "\
+DEFINE FIELD created_at ON $table TYPE datetime VALUE $before OR time::now();
+DEFINE FIELD updated_at ON $table TYPE datetime VALUE time::now();";
+
+struct Mixin {
+    params: Vec<String>,
+    body: String,
+}
+
+enum Line<'a> {
+    Text(&'a str),
+    Include(usize, String, Vec<String>),
+}
+
+/// The schema with each `-- @include name(args)` replaced by the body of the mixin, with every
+/// `$param` of the mixin replaced by its argument, and the `-- @mixin ... -- @end` blocks
+/// defining them blanked out. Mixins may be defined anywhere in the schema, but can't include
+/// other mixins.
+pub fn expand_mixins(schema: &str) -> Result<String, MixinError> {
+    expand_mixins_mapped(schema).map(|(expanded, _)| expanded)
+}
+
+/// The schema expanded like [expand_mixins], along with the line of `schema` each line of the
+/// expansion comes from, numbered from 1: the `-- @include` line for those of a mixin's body.
+pub fn expand_mixins_mapped(schema: &str) -> Result<(String, Vec<usize>), MixinError> {
+    let mut mixins = HashMap::from([(
+        "timestamps".to_string(),
+        Mixin {
+            params: vec!["table".to_string()],
+            body: TIMESTAMPS.to_string(),
+        },
+    )]);
+
+    let mut lines = vec![];
+    let mut defining: Option<(usize, String, Mixin)> = None;
+    for (index, line) in schema.lines().enumerate() {
+        let number = index + 1;
+        let malformed = || MixinError::Malformed(number, line.trim().to_string());
+        match (directive(line), &mut defining) {
+            (Some(("mixin", text)), None) => {
+                let (name, params) = call(text).ok_or_else(malformed)?;
+                if let Some(param) = params
+                    .iter()
+                    .find(|param| RESERVED_PARAMS.contains(&param.as_str()))
+                {
+                    return Err(MixinError::ReservedParam(number, param.clone()));
+                }
+                let body = String::new();
+                defining = Some((number, name, Mixin { params, body }));
+                lines.push(Line::Text(""));
+            }
+            (Some(("include", text)), None) => {
+                let (name, args) = call(text).ok_or_else(malformed)?;
+                lines.push(Line::Include(number, name, args));
+            }
+            (Some(("end", "")), Some(_)) => {
+                if let Some((_, name, mixin)) = defining.take() {
+                    mixins.insert(name, mixin);
+                }
+                lines.push(Line::Text(""));
+            }
+            // Directives within a mixin, or an `@end` outside of one.
+            (Some(("mixin" | "include" | "end", _)), _) => return Err(malformed()),
+            (_, Some((_, _, mixin))) => {
+                mixin.body.push_str(line);
+                mixin.body.push('\n');
+                lines.push(Line::Text(""));
+            }
+            (_, None) => lines.push(Line::Text(line)),
+        }
+    }
+    if let Some((number, name, _)) = defining {
+        return Err(MixinError::Unclosed(number, name));
+    }
+
+    let mut expanded = Vec::with_capacity(lines.len());
+    let mut sources = Vec::with_capacity(lines.len());
+    for (index, line) in lines.into_iter().enumerate() {
+        match line {
+            Line::Text(text) => {
+                expanded.push(text.to_string());
+                sources.push(index + 1);
+            }
+            Line::Include(number, name, args) => {
+                let Some(mixin) = mixins.get(&name) else {
+                    return Err(MixinError::Unknown(number, name));
+                };
+                if args.len() != mixin.params.len() {
+                    return Err(MixinError::Arity(
+                        number,
+                        name,
+                        mixin.params.len(),
+                        args.len(),
+                    ));
+                }
+                let body = substitute(mixin.body.trim_end(), &mixin.params, &args);
+                sources.extend(body.split('\n').map(|_| number));
+                expanded.push(body);
+            }
+        }
+    }
+    Ok((expanded.join("\n"), sources))
+}
+
+/// The directive of a comment such as `-- @include timestamps(user)`, as its keyword and the
+/// text after it.
+fn directive(line: &str) -> Option<(&str, &str)> {
+    let rest = line
+        .trim()
+        .strip_prefix("--")?
+        .trim_start()
+        .strip_prefix('@')?;
+    let keyword = rest
+        .find(|c: char| !c.is_alphanumeric())
+        .unwrap_or(rest.len());
+    Some((&rest[..keyword], rest[keyword..].trim()))
+}
+
+/// The name and arguments of a call such as `owned(post, user)`.
+fn call(text: &str) -> Option<(String, Vec<String>)> {
+    let (name, args) = text.strip_suffix(')')?.split_once('(')?;
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    let args: Vec<_> = match args.trim() {
+        "" => vec![],
+        args => args.split(',').map(|arg| arg.trim().to_string()).collect(),
+    };
+    if args.iter().any(String::is_empty) {
+        return None;
+    }
+    Some((name.to_string(), args))
+}
+
+/// `text` with each `$param` of `params` replaced by its argument, leaving other parameters,
+/// and those merely starting with the name of one, as they are.
+fn substitute(text: &str, params: &[String], args: &[String]) -> String {
+    let mut substituted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('$') {
+        substituted.push_str(&rest[..at]);
+        let after = &rest[at + 1..];
+        let len = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        match params.iter().position(|param| *param == after[..len]) {
+            Some(index) => substituted.push_str(&args[index]),
+            None => {
+                substituted.push('$');
+                substituted.push_str(&after[..len]);
+            }
+        }
+        rest = &after[len..];
+    }
+    substituted.push_str(rest);
+    substituted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ast::TypeAST, schema::analyze_schema, typing::shape};
+
+    #[test]
+    fn expands_includes() {
+        let schema = expand_mixins(
+            r#"
+            -- @mixin owned(table, owner)
+            DEFINE FIELD owner ON $table TYPE record<$owner> ASSERT $value != NONE;
+            DEFINE FIELD owners ON $table TYPE array<record<$owner>>;
+            -- @end
+
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+            DEFINE TABLE post SCHEMAFULL;
+                -- @include timestamps(post)
+                -- @include owned(post, user)
+            "#,
+        )
+        .unwrap();
+        assert!(schema.contains("DEFINE FIELD owner ON post TYPE record<user> ASSERT $value"));
+        assert!(!schema.contains("$table"));
+
+        let tables = analyze_schema(surrealdb::sql::parse(&schema).unwrap()).unwrap();
+        let TypeAST::Object(tables) = tables else {
+            panic!("Expected the tables of the schema");
+        };
+        assert_eq!(
+            shape(&tables.fields["post"].ast),
+            "{ created_at: Datetime, id: Record(post), owner: Record(user), owners: [Record(user)], \
+             updated_at: Datetime }"
+        );
+    }
+
+    #[test]
+    fn maps_expanded_lines() {
+        let (expanded, sources) = expand_mixins_mapped(
+            "DEFINE TABLE post SCHEMAFULL;\n-- @include timestamps(post)\nDEFINE FIELD title ON post;",
+        )
+        .unwrap();
+
+        assert_eq!(expanded.lines().count(), sources.len());
+        assert_eq!(sources, vec![1, 2, 2, 3]);
+        assert_eq!(expanded.lines().last(), Some("DEFINE FIELD title ON post;"));
+    }
+
+    #[test]
+    fn invalid_directives() {
+        let expand = |schema: &str| expand_mixins(schema).unwrap_err();
+
+        assert_eq!(
+            expand("-- @include audited(user)"),
+            MixinError::Unknown(1, "audited".to_string())
+        );
+        assert_eq!(
+            expand("-- @include timestamps(user, post)"),
+            MixinError::Arity(1, "timestamps".to_string(), 1, 2)
+        );
+        assert_eq!(
+            expand("\n-- @mixin owned(table)\nDEFINE FIELD owner ON $table;"),
+            MixinError::Unclosed(2, "owned".to_string())
+        );
+        assert_eq!(
+            expand("-- @mixin checked(value)\n-- @end"),
+            MixinError::ReservedParam(1, "value".to_string())
+        );
+        assert_eq!(
+            expand("-- @include timestamps"),
+            MixinError::Malformed(1, "-- @include timestamps".to_string())
+        );
+    }
+}
@@ -0,0 +1,562 @@
+//! The parameters a query expects to be bound by its caller.
+
+use std::collections::BTreeMap;
+
+use surrealdb::sql::{
+    parse,
+    statements::{DefineStatement, IfelseStatement, OutputStatement, SelectStatement},
+    Block, Cond, Data, Expression, Field, Fields, Function, Id, Idiom, Operator, Output, Part,
+    Query, Statement, Subquery, Value,
+};
+
+use crate::ast::{Constraint, FieldInfo, TypeAST};
+
+/// Parameters SurrealQL defines itself, which are never the caller's to bind.
+pub const RESERVED_PARAMS: &[&str] = &[
+    "after", "auth", "before", "event", "input", "parent", "scope", "session", "this", "token",
+    "value",
+];
+
+/// The parameters `query` refers to that the caller binds, sorted by name.
+///
+/// Those SurrealQL defines, those the schema defines with DEFINE PARAM, and those the query
+/// binds itself with LET or FOR are left out.
+pub fn query_params(schema: &Query, query: &Query) -> Vec<String> {
+    let defined: Vec<String> = schema
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Statement::Define(DefineStatement::Param(param)) => Some(param.name.0.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let mut refs = Refs::default();
+    for stmt in query.iter() {
+        refs.statement(stmt);
+    }
+
+    let Refs { mut params, bound } = refs;
+    params.retain(|param| {
+        !bound.contains(param)
+            && !defined.contains(param)
+            && !RESERVED_PARAMS.contains(&param.as_str())
+    });
+    params.sort();
+    params.dedup();
+    params
+}
+
+/// The types of the caller's parameters that a statement compares with, or sets, a field of the
+/// table it runs on, e.g. `string` for `$name` in `WHERE name = $name`, `record<user>` for `$id`
+/// in `WHERE id = $id` and `array<int>` for `$ages` in `WHERE age IN $ages`.
+///
+/// `schema` is the schema as `analyze_schema` types it. Parameters used with fields of different
+/// types, and those used only elsewhere, are left out.
+pub fn param_types(schema: &TypeAST, query: &Query) -> BTreeMap<String, TypeAST> {
+    let mut types: BTreeMap<String, Option<TypeAST>> = BTreeMap::new();
+    let mut infer = |param: &Value, ast: Option<TypeAST>| {
+        let (Value::Param(param), Some(ast)) = (param, ast) else {
+            return;
+        };
+        types
+            .entry(param.0 .0.clone())
+            .and_modify(|existing| {
+                if existing.as_ref() != Some(&ast) {
+                    *existing = None;
+                }
+            })
+            .or_insert(Some(ast));
+    };
+
+    param_uses(query, |table, param, idiom, op| {
+        let field = field_type(schema, table, idiom);
+        let ast = match op {
+            Operator::Inside | Operator::NotInside => {
+                field.map(|field| TypeAST::Array(Box::new((field, None))))
+            }
+            _ => field,
+        };
+        infer(param, ast)
+    });
+
+    types
+        .into_iter()
+        .filter_map(|(param, ast)| Some((param, ast?)))
+        .collect()
+}
+
+/// The ASSERT constraints a value bound to each of the caller's parameters has to meet, as the
+/// parameter is set to, or checked equal with or inside, fields that have them, e.g. those of
+/// `email` for `$email` in `UPDATE user SET email = $email`.
+///
+/// Constraints apply to the items of a parameter a field is checked inside of. Parameters used
+/// with several constrained fields have to meet the constraints of each.
+pub fn param_constraints(schema: &TypeAST, query: &Query) -> BTreeMap<String, Vec<Constraint>> {
+    let mut constraints: BTreeMap<String, Vec<Constraint>> = BTreeMap::new();
+    param_uses(query, |table, param, idiom, op| {
+        let Value::Param(param) = param else {
+            return;
+        };
+        if !matches!(op, Operator::Equal | Operator::Exact | Operator::Inside) {
+            return;
+        }
+        let Some(field) = field_info(schema, table, idiom) else {
+            return;
+        };
+        if field.meta.constraints.is_empty() {
+            return;
+        }
+        let found = constraints.entry(param.0 .0.clone()).or_default();
+        for constraint in &field.meta.constraints {
+            if !found.contains(constraint) {
+                found.push(constraint.clone());
+            }
+        }
+    });
+    constraints
+}
+
+/// Calls `found` with each parameter a statement compares with, or sets, a field of the table it
+/// runs on, along with the table, the field and the operator, which is `=` for SET.
+fn param_uses(query: &Query, mut found: impl FnMut(&str, &Value, &Idiom, &Operator)) {
+    for stmt in query.iter() {
+        let (what, cond, data) = match stmt {
+            Statement::Select(select) => (&select.what.0, select.cond.as_ref(), None),
+            Statement::Create(create) => (&create.what.0, None, create.data.as_ref()),
+            Statement::Update(update) => {
+                (&update.what.0, update.cond.as_ref(), update.data.as_ref())
+            }
+            Statement::Delete(delete) => (&delete.what.0, delete.cond.as_ref(), None),
+            _ => continue,
+        };
+        let table = match what.as_slice() {
+            [Value::Table(table)] => table.0.clone(),
+            [Value::Thing(thing)] => thing.tb.clone(),
+            _ => continue,
+        };
+        if let Some(cond) = cond {
+            compared_params(&cond.0, &mut |param, idiom, op| {
+                found(&table, param, idiom, op)
+            });
+        }
+        if let Some(Data::SetExpression(sets)) = data {
+            for (idiom, op, value) in sets {
+                if *op == Operator::Equal {
+                    found(&table, value, idiom, op);
+                }
+            }
+        }
+    }
+}
+
+/// Calls `found` with each parameter a condition compares with a field, along with the field and
+/// the operator, e.g. `($ages, age, IN)` for `age IN $ages`.
+fn compared_params(cond: &Value, found: &mut impl FnMut(&Value, &Idiom, &Operator)) {
+    let Value::Expression(expr) = cond else {
+        return;
+    };
+    let Expression::Binary { l, o, r } = expr.as_ref() else {
+        return;
+    };
+    match (l, o, r) {
+        (l, Operator::And | Operator::Or, r) => {
+            compared_params(l, found);
+            compared_params(r, found);
+        }
+        (
+            Value::Idiom(idiom),
+            Operator::Equal
+            | Operator::Exact
+            | Operator::NotEqual
+            | Operator::LessThan
+            | Operator::LessThanOrEqual
+            | Operator::MoreThan
+            | Operator::MoreThanOrEqual
+            | Operator::Inside
+            | Operator::NotInside,
+            param @ Value::Param(_),
+        ) => found(param, idiom, o),
+        (
+            param @ Value::Param(_),
+            Operator::Equal | Operator::Exact | Operator::NotEqual,
+            Value::Idiom(idiom),
+        ) => found(param, idiom, o),
+        _ => {}
+    }
+}
+
+/// The type of the field of `table` at `idiom`, without NONE, or `None` if it isn't a plain path
+/// to a field the schema defines.
+fn field_type(schema: &TypeAST, table: &str, idiom: &Idiom) -> Option<TypeAST> {
+    if matches!(idiom.0.as_slice(), [Part::Field(ident)] if ident.as_str() == "id") {
+        return Some(TypeAST::Record(table.to_string()));
+    }
+    match &field_info(schema, table, idiom)?.ast {
+        TypeAST::Option(inner) => Some(inner.as_ref().clone()),
+        ast => Some(ast.clone()),
+    }
+}
+
+/// The field of `table` at `idiom`, or `None` if it isn't a plain path to a field the schema
+/// defines.
+fn field_info<'a>(schema: &'a TypeAST, table: &str, idiom: &Idiom) -> Option<&'a FieldInfo> {
+    let mut info = field(schema, table)?;
+    for part in idiom.0.iter() {
+        let Part::Field(ident) = part else {
+            return None;
+        };
+        info = field(&info.ast, ident.as_str())?;
+    }
+    Some(info)
+}
+
+/// The field `name` of an object, which may be NONE.
+fn field<'a>(ast: &'a TypeAST, name: &str) -> Option<&'a FieldInfo> {
+    match ast {
+        TypeAST::Option(inner) => field(inner, name),
+        TypeAST::Object(object) => object.fields.get(name),
+        _ => None,
+    }
+}
+
+/// The parameters a query refers to, and those it binds itself.
+#[derive(Default)]
+struct Refs {
+    params: Vec<String>,
+    bound: Vec<String>,
+}
+
+impl Refs {
+    fn statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Value(value) => self.value(value),
+            Statement::Select(select) => self.select(select),
+            Statement::Create(create) => {
+                self.values(&create.what.0);
+                self.data(create.data.as_ref());
+                self.output(create.output.as_ref());
+            }
+            Statement::Update(update) => {
+                self.values(&update.what.0);
+                self.data(update.data.as_ref());
+                self.cond(update.cond.as_ref());
+                self.output(update.output.as_ref());
+            }
+            Statement::Delete(delete) => {
+                self.values(&delete.what.0);
+                self.cond(delete.cond.as_ref());
+                self.output(delete.output.as_ref());
+            }
+            Statement::Relate(relate) => {
+                self.values([&relate.from, &relate.kind, &relate.with]);
+                self.data(relate.data.as_ref());
+                self.output(relate.output.as_ref());
+            }
+            Statement::Insert(insert) => {
+                self.value(&insert.into);
+                self.data(Some(&insert.data));
+                self.data(insert.update.as_ref());
+                self.output(insert.output.as_ref());
+            }
+            Statement::Live(live) => {
+                self.fields(&live.expr);
+                self.value(&live.what);
+                self.cond(live.cond.as_ref());
+            }
+            Statement::Set(set) => {
+                self.value(&set.what);
+                self.bound.push(set.name.clone());
+            }
+            Statement::Foreach(foreach) => {
+                self.bound.push(foreach.param.0 .0.clone());
+                self.value(&foreach.range);
+                self.block(&foreach.block);
+            }
+            Statement::Ifelse(ifelse) => self.ifelse(ifelse),
+            Statement::Output(output) => self.output_statement(output),
+            Statement::Throw(throw) => self.value(&throw.error),
+            Statement::Kill(kill) => self.value(&kill.id),
+            _ => {}
+        }
+    }
+
+    fn select(&mut self, select: &SelectStatement) {
+        self.fields(&select.expr);
+        self.values(&select.what.0);
+        self.cond(select.cond.as_ref());
+        for order in select.order.iter().flat_map(|orders| orders.0.iter()) {
+            self.idiom(&order.order);
+        }
+        if let Some(limit) = &select.limit {
+            self.value(&limit.0);
+        }
+        if let Some(start) = &select.start {
+            self.value(&start.0);
+        }
+        for fetch in select.fetch.iter().flat_map(|fetchs| fetchs.0.iter()) {
+            self.idiom(&fetch.0);
+        }
+    }
+
+    fn ifelse(&mut self, ifelse: &IfelseStatement) {
+        for (cond, then) in &ifelse.exprs {
+            self.values([cond, then]);
+        }
+        self.values(&ifelse.close);
+    }
+
+    fn output_statement(&mut self, output: &OutputStatement) {
+        self.value(&output.what);
+        for fetch in output.fetch.iter().flat_map(|fetchs| fetchs.0.iter()) {
+            self.idiom(&fetch.0);
+        }
+    }
+
+    fn fields(&mut self, fields: &Fields) {
+        for field in fields.0.iter() {
+            if let Field::Single { expr, .. } = field {
+                self.value(expr);
+            }
+        }
+    }
+
+    fn cond(&mut self, cond: Option<&Cond>) {
+        if let Some(cond) = cond {
+            self.value(&cond.0);
+        }
+    }
+
+    fn data(&mut self, data: Option<&Data>) {
+        match data {
+            Some(Data::SetExpression(sets) | Data::UpdateExpression(sets)) => {
+                for (idiom, _, value) in sets {
+                    self.idiom(idiom);
+                    self.value(value);
+                }
+            }
+            Some(
+                Data::PatchExpression(value)
+                | Data::MergeExpression(value)
+                | Data::ReplaceExpression(value)
+                | Data::ContentExpression(value)
+                | Data::SingleExpression(value),
+            ) => self.value(value),
+            Some(Data::ValuesExpression(rows)) => {
+                for (idiom, value) in rows.iter().flatten() {
+                    self.idiom(idiom);
+                    self.value(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn output(&mut self, output: Option<&Output>) {
+        if let Some(Output::Fields(fields)) = output {
+            self.fields(fields);
+        }
+    }
+
+    fn idiom(&mut self, idiom: &Idiom) {
+        for part in idiom.0.iter() {
+            match part {
+                Part::Where(value) | Part::Value(value) | Part::Start(value) => self.value(value),
+                Part::Method(_, args) => self.values(args),
+                Part::Graph(graph) => {
+                    self.fields(&graph.expr);
+                    self.cond(graph.cond.as_ref());
+                    if let Some(limit) = &graph.limit {
+                        self.value(&limit.0);
+                    }
+                    if let Some(start) = &graph.start {
+                        self.value(&start.0);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn values<'a>(&mut self, values: impl IntoIterator<Item = &'a Value>) {
+        for value in values {
+            self.value(value);
+        }
+    }
+
+    fn value(&mut self, value: &Value) {
+        match value {
+            Value::Param(param) => self.params.push(param.0 .0.clone()),
+            Value::Idiom(idiom) => self.idiom(idiom),
+            Value::Array(array) => self.values(&array.0),
+            Value::Object(object) => self.values(object.0.values()),
+            Value::Thing(thing) => self.id(&thing.id),
+            Value::Cast(cast) => self.value(&cast.1),
+            Value::Expression(expr) => match expr.as_ref() {
+                Expression::Unary { v, .. } => self.value(v),
+                Expression::Binary { l, r, .. } => self.values([l, r]),
+            },
+            Value::Function(function) => match function.as_ref() {
+                Function::Normal(_, args)
+                | Function::Custom(_, args)
+                | Function::Script(_, args) => self.values(args),
+            },
+            Value::Model(model) => self.values(&model.args),
+            Value::Subquery(subquery) => match subquery.as_ref() {
+                Subquery::Value(value) => self.value(value),
+                Subquery::Ifelse(ifelse) => self.ifelse(ifelse),
+                Subquery::Output(output) => self.output_statement(output),
+                Subquery::Select(select) => self.select(select),
+                Subquery::Create(create) => self.statement(&Statement::Create(create.clone())),
+                Subquery::Update(update) => self.statement(&Statement::Update(update.clone())),
+                Subquery::Delete(delete) => self.statement(&Statement::Delete(delete.clone())),
+                Subquery::Relate(relate) => self.statement(&Statement::Relate(relate.clone())),
+                Subquery::Insert(insert) => self.statement(&Statement::Insert(insert.clone())),
+                Subquery::Define(_) | Subquery::Remove(_) => {}
+            },
+            Value::Block(block) => self.block(block),
+            Value::Future(future) => self.block(&future.0),
+            _ => {}
+        }
+    }
+
+    fn id(&mut self, id: &Id) {
+        match id {
+            Id::Array(array) => self.values(&array.0),
+            Id::Object(object) => self.values(object.0.values()),
+            _ => {}
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        // The entries of a block can't be named outside of surrealdb, so they are walked as
+        // the statements they print as.
+        let source: String = block.iter().map(|entry| format!("{};", entry)).collect();
+        if let Ok(statements) = parse(&source) {
+            for stmt in statements.iter() {
+                self.statement(stmt);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::ScalarType;
+
+    #[test]
+    fn finds_caller_params() {
+        let schema = parse("DEFINE PARAM $max_age VALUE 120;").unwrap();
+        let params = |sql: &str| query_params(&schema, &parse(sql).unwrap());
+
+        assert_eq!(
+            params(
+                "SELECT * FROM user WHERE name = $name AND age < $max_age AND id != $auth.id \
+                 AND email = '$email' AND name = /^ann$/ LIMIT $n"
+            ),
+            vec!["n", "name"]
+        );
+        assert_eq!(
+            params("LET $adults = SELECT * FROM user WHERE age >= $age; RETURN $adults"),
+            vec!["age"]
+        );
+        assert_eq!(
+            params("FOR $user IN $users { UPDATE $user SET seen = $now }"),
+            vec!["now", "users"]
+        );
+        assert_eq!(
+            params(
+                "SELECT *, ->wrote->post[WHERE tag = $tag] AS posts FROM user \
+                 WHERE id IN (SELECT VALUE author FROM post WHERE score > $min) \
+                 AND { LET $limit = $max; RETURN $limit } > 0"
+            ),
+            vec!["max", "min", "tag"]
+        );
+    }
+
+    #[test]
+    fn infers_param_types() {
+        let schema = crate::schema::analyze_schema(
+            parse(
+                r#"
+                DEFINE TABLE user SCHEMAFULL;
+                    DEFINE FIELD name ON user TYPE string;
+                    DEFINE FIELD age ON user TYPE option<int>;
+                    DEFINE FIELD address ON user TYPE object;
+                    DEFINE FIELD address.city ON user TYPE string;
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let types = |sql: &str| param_types(&schema, &parse(sql).unwrap());
+
+        let string = TypeAST::Scalar(ScalarType::String);
+        let int = TypeAST::Scalar(ScalarType::Integer);
+        assert_eq!(
+            types(
+                "SELECT * FROM user WHERE (id = $id OR name = $name) AND age IN $ages \
+                 AND address.city = $city AND $name = name AND string::len(name) > $len"
+            ),
+            BTreeMap::from([
+                (
+                    "ages".to_string(),
+                    TypeAST::Array(Box::new((int.clone(), None)))
+                ),
+                ("city".to_string(), string.clone()),
+                ("id".to_string(), TypeAST::Record("user".to_string())),
+                ("name".to_string(), string.clone()),
+            ])
+        );
+        assert_eq!(
+            types("UPDATE user SET age = $age WHERE name = $age"),
+            BTreeMap::new()
+        );
+        assert_eq!(
+            types("CREATE user SET age = $age"),
+            BTreeMap::from([("age".to_string(), int)])
+        );
+    }
+
+    #[test]
+    fn finds_param_constraints() {
+        let schema = crate::schema::analyze_schema(
+            parse(
+                r#"
+                DEFINE TABLE user SCHEMAFULL;
+                    DEFINE FIELD email ON user TYPE string ASSERT string::is::email($value);
+                    DEFINE FIELD name ON user TYPE string ASSERT string::len($value) <= 20;
+                    DEFINE FIELD nick ON user TYPE string ASSERT string::len($value) >= 3;
+                    DEFINE FIELD bio ON user TYPE string;
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let constraints = |sql: &str| param_constraints(&schema, &parse(sql).unwrap());
+
+        assert_eq!(
+            constraints(
+                "UPDATE user SET email = $email, bio = $bio \
+                 WHERE name = $name AND nick = $name AND name < $max"
+            ),
+            BTreeMap::from([
+                (
+                    "email".to_string(),
+                    vec![Constraint::StringIs("email".to_string())]
+                ),
+                (
+                    "name".to_string(),
+                    vec![Constraint::MaxLength(20), Constraint::MinLength(3)]
+                ),
+            ])
+        );
+        assert_eq!(
+            constraints("SELECT * FROM user WHERE email IN $emails"),
+            BTreeMap::from([(
+                "emails".to_string(),
+                vec![Constraint::StringIs("email".to_string())]
+            )])
+        );
+    }
+}
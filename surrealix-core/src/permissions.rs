@@ -0,0 +1,151 @@
+use std::fmt;
+
+use surrealdb::sql::{Permission, Permissions};
+
+use crate::ast::TypeAST;
+
+/// A field in an analyzed result whose access is restricted by the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtectedField {
+    /// Dotted path of the field within the result, e.g. `address.city`.
+    pub path: String,
+    pub permissions: Permissions,
+}
+
+impl ProtectedField {
+    /// The restricted operations and their conditions, e.g. `select WHERE $auth.id = id`.
+    pub fn conditions(&self) -> Vec<String> {
+        [
+            ("select", &self.permissions.select),
+            ("create", &self.permissions.create),
+            ("update", &self.permissions.update),
+            ("delete", &self.permissions.delete),
+        ]
+        .into_iter()
+        .filter(|(_, permission)| **permission != Permission::Full)
+        .map(|(kind, permission)| format!("{} {}", kind, permission))
+        .collect()
+    }
+}
+
+impl fmt::Display for ProtectedField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.conditions().join(", "))
+    }
+}
+
+/// Lists every field of an analyzed result that is protected by non-FULL permissions.
+///
+/// Fields are reported in path order, so the output is stable between builds.
+pub fn protected_fields(ast: &TypeAST) -> Vec<ProtectedField> {
+    let mut protected = vec![];
+    collect_protected(ast, "", &mut protected);
+    protected
+}
+
+fn collect_protected(ast: &TypeAST, prefix: &str, protected: &mut Vec<ProtectedField>) {
+    match ast {
+        TypeAST::Object(obj) => {
+            let mut fields: Vec<_> = obj.fields.iter().collect();
+            fields.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, field) in fields {
+                let path = if prefix.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", prefix, name)
+                };
+                if !field.meta.permissions.is_full() {
+                    protected.push(ProtectedField {
+                        path: path.clone(),
+                        permissions: field.meta.permissions.clone(),
+                    });
+                }
+                collect_protected(&field.ast, &path, protected);
+            }
+        }
+        TypeAST::Array(inner) => collect_protected(&inner.0, prefix, protected),
+        TypeAST::Option(inner) => collect_protected(inner, prefix, protected),
+        TypeAST::Union(variants) => {
+            for variant in variants {
+                collect_protected(variant, prefix, protected);
+            }
+        }
+        TypeAST::Scalar(_) | TypeAST::Record(_) => {}
+    }
+}
+
+/// Removes every field with `PERMISSIONS NONE` for select, which a client session never receives.
+pub fn remove_unselectable(ast: &mut TypeAST) {
+    match ast {
+        TypeAST::Object(obj) => {
+            obj.fields
+                .retain(|_, field| field.meta.permissions.select != Permission::None);
+            for field in obj.fields.values_mut() {
+                remove_unselectable(&mut field.ast);
+            }
+        }
+        TypeAST::Array(inner) => remove_unselectable(&mut inner.0),
+        TypeAST::Option(inner) => remove_unselectable(inner),
+        TypeAST::Union(variants) => variants.iter_mut().for_each(remove_unselectable),
+        TypeAST::Scalar(_) | TypeAST::Record(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::analyze;
+    use surrealdb::sql::parse;
+
+    #[test]
+    fn reports_restricted_fields() {
+        let schema = parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD ssn ON user TYPE string PERMISSIONS FOR select WHERE id = $auth.id;
+                DEFINE FIELD secret ON user TYPE string PERMISSIONS NONE;
+            "#,
+        )
+        .unwrap();
+        let query = parse("SELECT name, ssn, secret FROM user").unwrap();
+
+        let analyzed = analyze(schema, query).unwrap();
+        let protected = protected_fields(&analyzed[0]);
+
+        assert_eq!(protected.len(), 2);
+        assert_eq!(protected[0].path, "secret");
+        assert_eq!(protected[0].conditions().len(), 4);
+        assert_eq!(protected[1].path, "ssn");
+        assert_eq!(protected[1].conditions().len(), 1);
+        assert!(protected[1].conditions()[0].starts_with("select WHERE"));
+    }
+
+    #[test]
+    fn removes_unselectable_fields() {
+        let schema = parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD secret ON user TYPE string PERMISSIONS NONE;
+                DEFINE FIELD token ON user TYPE string PERMISSIONS FOR select NONE, FOR update FULL;
+                DEFINE FIELD audit ON user TYPE string PERMISSIONS FOR select FULL, FOR update NONE;
+            "#,
+        )
+        .unwrap();
+        let query = parse("SELECT * FROM user").unwrap();
+
+        let mut analyzed = analyze(schema, query).unwrap().remove(0);
+        remove_unselectable(&mut analyzed);
+
+        let TypeAST::Array(inner) = analyzed else {
+            panic!("Expected Array TypeAST");
+        };
+        let TypeAST::Object(obj) = inner.0 else {
+            panic!("Expected Object inside Array");
+        };
+        let mut fields: Vec<_> = obj.fields.keys().collect();
+        fields.sort();
+        assert_eq!(fields, vec!["audit", "id", "name"]);
+    }
+}
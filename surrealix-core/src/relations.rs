@@ -0,0 +1,86 @@
+//! The edge tables of a schema, which link records of one table to another.
+
+use crate::ast::{ObjectType, TypeAST};
+
+/// An edge table whose `in` and `out` fields link records, e.g. `friend` from `user` to `user`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relation {
+    pub edge: String,
+    /// The table of the `in` side, which `RELATE from->edge->to` starts from.
+    pub from: String,
+    /// The table of the `out` side.
+    pub to: String,
+}
+
+/// Lists the edge tables among the tables of an analyzed schema, sorted by name.
+///
+/// Only edges with both sides linking to a single table are included, as that is what the
+/// traversals through them resolve to.
+pub fn relations(tables: &ObjectType) -> Vec<Relation> {
+    let mut relations: Vec<_> = tables
+        .fields
+        .iter()
+        .filter_map(|(edge, info)| {
+            let TypeAST::Object(obj) = &info.ast else {
+                return None;
+            };
+            let side = |name: &str| match obj.fields.get(name).map(|field| &field.ast) {
+                Some(TypeAST::Record(table)) => Some(table.clone()),
+                _ => None,
+            };
+            Some(Relation {
+                edge: edge.clone(),
+                from: side("in")?,
+                to: side("out")?,
+            })
+        })
+        .collect();
+
+    relations.sort_by(|a, b| a.edge.cmp(&b.edge));
+    relations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::analyze_schema;
+    use surrealdb::sql::parse;
+
+    #[test]
+    fn finds_relations() {
+        let schema = parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+            DEFINE TABLE post SCHEMAFULL;
+                DEFINE FIELD title ON post TYPE string;
+            DEFINE TABLE wrote SCHEMAFULL;
+                DEFINE FIELD in ON wrote TYPE record<user>;
+                DEFINE FIELD out ON wrote TYPE record<post>;
+            DEFINE TABLE friend SCHEMAFULL;
+                DEFINE FIELD in ON friend TYPE record<user>;
+                DEFINE FIELD out ON friend TYPE record<user>;
+                DEFINE FIELD since ON friend TYPE datetime;
+            DEFINE TABLE follows SCHEMAFULL;
+                DEFINE FIELD in ON follows TYPE record<user>;
+            "#,
+        )
+        .unwrap();
+        let TypeAST::Object(tables) = analyze_schema(schema).unwrap() else {
+            panic!("Expected the schema to be an object");
+        };
+
+        let relation = |edge: &str, from: &str, to: &str| Relation {
+            edge: edge.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        assert_eq!(
+            relations(&tables),
+            vec![
+                relation("friend", "user", "user"),
+                relation("wrote", "user", "post"),
+            ]
+        );
+    }
+}
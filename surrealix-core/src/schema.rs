@@ -1,17 +1,27 @@
 use surrealdb::sql::{
     statements::{
         DefineFieldStatement, DefineParamStatement, DefineStatement, DefineTableStatement,
+        RemoveFieldStatement, RemoveStatement, RemoveTableStatement,
     },
-    Kind, Query, Statement,
+    Expression, Function, Kind, Number, Operator, Permissions, Query, Statement, Strand, Subquery,
+    Value,
 };
 use thiserror::Error;
 
-use crate::ast::{FieldInfo, FieldMetadata, ObjectType, ScalarType, TypeAST};
+use crate::{
+    analyzer::{operators::binary_type, Analyzer},
+    ast::{Constraint, FieldInfo, FieldMetadata, ObjectType, ScalarType, TypeAST},
+};
 
 #[derive(Error, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum SchemaParseError {
     #[error("Invalid SurrealQL syntax: {0}")]
-    InvalidSyntax(#[from] surrealdb::error::Db),
+    InvalidSyntax(
+        #[from]
+        #[cfg_attr(feature = "serde", serde(serialize_with = "crate::json::display"))]
+        surrealdb::error::Db,
+    ),
 
     #[error("Reference to non-existent table: {0}")]
     NonExistentTableReference(String),
@@ -29,7 +39,19 @@ pub enum SchemaParseError {
 /// Provided a schema, generates a [TypeAST] encompassing all of the type info in the schema.
 ///
 /// The returned [TypeAST] will *always* be an object, with the names of the tables as identifiers.
+///
+/// REMOVE statements are applied in order, so schemas exported from a live database
+/// (which may drop and redefine tables or fields) produce the final state of the schema.
+/// ALTER statements are not part of SurrealQL 1.x, and are rejected by the parser.
 pub fn analyze_schema(schema: Query) -> Result<TypeAST, SchemaParseError> {
+    analyze_schema_with(&Analyzer::default(), schema)
+}
+
+/// Analyzes a schema like [analyze_schema], with the custom kinds and functions of `analyzer`.
+pub(crate) fn analyze_schema_with(
+    analyzer: &Analyzer,
+    schema: Query,
+) -> Result<TypeAST, SchemaParseError> {
     let mut ast = TypeAST::Object(ObjectType::default());
 
     let mut field_definitions = vec![];
@@ -38,6 +60,12 @@ pub fn analyze_schema(schema: Query) -> Result<TypeAST, SchemaParseError> {
         match stmt {
             Statement::Define(DefineStatement::Field(def)) => field_definitions.push(def),
             Statement::Define(def) => apply_definition(def, &mut ast)?,
+            Statement::Remove(RemoveStatement::Table(rm)) => {
+                remove_table(rm, &mut ast, &mut field_definitions)
+            }
+            Statement::Remove(RemoveStatement::Field(rm)) => {
+                remove_field(rm, &mut field_definitions)
+            }
             _ => (),
         }
     }
@@ -49,12 +77,66 @@ pub fn analyze_schema(schema: Query) -> Result<TypeAST, SchemaParseError> {
     });
 
     for definition in field_definitions {
-        apply_field_definition(definition, &mut ast)?;
+        apply_field_definition(analyzer, definition, &mut ast)?;
     }
+    add_record_ids(&mut ast);
 
     Ok(ast)
 }
 
+/// Gives each table that defines fields the `id` every record has, unless it defines its own.
+///
+/// Tables without any defined fields are left empty, as their records hold anything.
+fn add_record_ids(ast: &mut TypeAST) {
+    let TypeAST::Object(schema) = ast else {
+        return;
+    };
+    for (table_name, table) in schema.fields.iter_mut() {
+        let TypeAST::Object(obj) = &mut table.ast else {
+            continue;
+        };
+        if obj.fields.is_empty() {
+            continue;
+        }
+        obj.fields
+            .entry("id".to_string())
+            .or_insert_with(|| FieldInfo {
+                ast: TypeAST::Record(table_name.clone()),
+                meta: FieldMetadata {
+                    original_name: "id".to_string(),
+                    original_path: vec![table_name.clone(), "id".to_string()],
+                    permissions: Permissions::default(),
+                    constraints: vec![],
+                    default: None,
+                    deprecated: None,
+                },
+            });
+    }
+}
+
+/// Drops a table, along with any of its fields defined so far.
+fn remove_table<'a>(
+    rm: &RemoveTableStatement,
+    ast: &mut TypeAST,
+    field_definitions: &mut Vec<&'a DefineFieldStatement>,
+) {
+    let table_name = rm.name.to_string().to_lowercase();
+    if let TypeAST::Object(schema) = ast {
+        schema
+            .fields
+            .retain(|name, _| name.to_lowercase() != table_name);
+    }
+    field_definitions.retain(|def| def.what.as_str().to_lowercase() != table_name);
+}
+
+/// Drops the definitions of a field, and of any fields nested within it, made so far.
+fn remove_field(rm: &RemoveFieldStatement, field_definitions: &mut Vec<&DefineFieldStatement>) {
+    let table_name = rm.what.as_str().to_lowercase();
+    field_definitions.retain(|def| {
+        def.what.as_str().to_lowercase() != table_name || !def.name.0.starts_with(&rm.name.0)
+    });
+}
+
 /// Applies the specified table definition to an existing AST.
 fn apply_definition(def: &DefineStatement, ast: &mut TypeAST) -> Result<(), SchemaParseError> {
     match def {
@@ -93,6 +175,9 @@ fn apply_table_definition(
             original_name: table_name.clone(),
             original_path: vec![table_name.clone()],
             permissions: table_def.permissions.clone(),
+            constraints: vec![],
+            default: None,
+            deprecated: None,
         },
     };
 
@@ -101,6 +186,7 @@ fn apply_table_definition(
 }
 
 fn apply_field_definition(
+    analyzer: &Analyzer,
     field_def: &DefineFieldStatement,
     ast: &mut TypeAST,
 ) -> Result<(), SchemaParseError> {
@@ -116,10 +202,14 @@ fn apply_field_definition(
         .get_mut(&table_name)
         .ok_or_else(|| SchemaParseError::NonExistentTableReference(field_def.what.to_string()))?;
 
-    let parts = &field_def.name.0;
+    let Some((last, parents)) = field_def.name.0.split_last() else {
+        return Err(SchemaParseError::Unknown(
+            "Field definition without a name".to_string(),
+        ));
+    };
     let mut current_path = vec![table_name.clone()];
 
-    for part in &parts[..parts.len() - 1] {
+    for part in parents {
         match part {
             surrealdb::sql::Part::Field(ident) => {
                 let field_name = ident.to_string();
@@ -135,6 +225,9 @@ fn apply_field_definition(
                                     original_name: field_name.clone(),
                                     original_path: current_path.clone(),
                                     permissions: field_def.permissions.clone(),
+                                    constraints: vec![],
+                                    default: None,
+                                    deprecated: None,
                                 },
                             });
                     }
@@ -149,21 +242,21 @@ fn apply_field_definition(
         }
     }
 
-    let field_type = field_def
-        .kind
-        .as_ref()
-        .map_or(TypeAST::Scalar(ScalarType::Any), |kind| {
-            TypeAST::from(kind.clone())
-        });
+    let field_type = match (&field_def.kind, &field_def.value) {
+        (Some(kind), _) => analyzer.kind_type(kind),
+        (None, Some(value)) => value_type(analyzer, value),
+        (None, None) => TypeAST::Scalar(ScalarType::Any),
+    };
 
-    match parts.last().unwrap() {
+    match last {
         surrealdb::sql::Part::All => {
             if let TypeAST::Array(obj) = &mut curr.ast {
                 let ast = &mut (*obj).0;
                 *ast = field_type;
             } else {
                 return Err(SchemaParseError::NonArrayStarSelector(
-                    parts
+                    field_def
+                        .name
                         .iter()
                         .map(|p| p.to_string())
                         .collect::<Vec<_>>()
@@ -176,11 +269,9 @@ fn apply_field_definition(
             current_path.push(field_name.clone());
             if let TypeAST::Object(obj) = &mut curr.ast {
                 let new_field = FieldInfo {
-                    ast: if field_def
-                        .kind
-                        .as_ref()
-                        .map_or(false, |k| matches!(k, Kind::Array(_, _)))
-                    {
+                    ast: if field_def.kind.as_ref().map_or(false, |k| {
+                        matches!(k, Kind::Array(_, _)) && !analyzer.maps_kind(k)
+                    }) {
                         TypeAST::Array(Box::new((TypeAST::Scalar(ScalarType::Any), None)))
                     } else {
                         field_type
@@ -189,6 +280,13 @@ fn apply_field_definition(
                         original_name: field_name.clone(),
                         original_path: current_path,
                         permissions: field_def.permissions.clone(),
+                        constraints: field_def
+                            .assert
+                            .as_ref()
+                            .map(parse_constraints)
+                            .unwrap_or_default(),
+                        default: field_def.default.as_ref().and_then(literal_default),
+                        deprecated: field_def.comment.as_ref().and_then(deprecation),
                     },
                 };
                 obj.fields.insert(field_name, new_field);
@@ -204,6 +302,165 @@ fn apply_field_definition(
     Ok(())
 }
 
+/// The note of a COMMENT marking a field as deprecated, like `"deprecated: use full_name"`.
+///
+/// A bare `"deprecated"` gives an empty note.
+fn deprecation(comment: &Strand) -> Option<String> {
+    const PREFIX: &str = "deprecated";
+
+    let comment = comment.as_str().trim();
+    if !comment.get(..PREFIX.len())?.eq_ignore_ascii_case(PREFIX) {
+        return None;
+    }
+    let note = match &comment[PREFIX.len()..] {
+        "" => "",
+        rest => rest.strip_prefix(':')?,
+    };
+    Some(note.trim().to_string())
+}
+
+/// A DEFAULT as JSON, if it is a literal rather than something only the database can
+/// compute, like `time::now()`.
+fn literal_default(value: &Value) -> Option<String> {
+    fn is_literal(value: &Value) -> bool {
+        match value {
+            Value::None
+            | Value::Null
+            | Value::Bool(_)
+            | Value::Number(_)
+            | Value::Strand(_)
+            | Value::Duration(_)
+            | Value::Datetime(_)
+            | Value::Uuid(_) => true,
+            Value::Array(array) => array.iter().all(is_literal),
+            Value::Object(object) => object.values().all(is_literal),
+            _ => false,
+        }
+    }
+    is_literal(value).then(|| value.clone().into_json().to_string())
+}
+
+/// Infers the type of a VALUE clause, for fields without a TYPE.
+///
+/// `$value` and other parameters could be anything, so only literals, functions and operators
+/// give the field a concrete type.
+pub(crate) fn value_type(analyzer: &Analyzer, value: &Value) -> TypeAST {
+    match value {
+        Value::Strand(_) => TypeAST::Scalar(ScalarType::String),
+        Value::Number(Number::Int(_)) => TypeAST::Scalar(ScalarType::Integer),
+        Value::Number(Number::Float(_)) => TypeAST::Scalar(ScalarType::Float),
+        Value::Number(_) => TypeAST::Scalar(ScalarType::Number),
+        Value::Bool(_) => TypeAST::Scalar(ScalarType::Boolean),
+        Value::Datetime(_) => TypeAST::Scalar(ScalarType::Datetime),
+        Value::Duration(_) => TypeAST::Scalar(ScalarType::Duration),
+        Value::Uuid(_) => TypeAST::Scalar(ScalarType::Uuid),
+        Value::Function(func) => {
+            let args = func
+                .args()
+                .iter()
+                .map(|arg| value_type(analyzer, arg))
+                .collect();
+            analyzer.function_type(func, args)
+        }
+        Value::Model(model) => analyzer.model_type(model),
+        Value::Subquery(subquery) => match subquery.as_ref() {
+            Subquery::Value(value) => value_type(analyzer, value),
+            _ => TypeAST::Scalar(ScalarType::Any),
+        },
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Unary {
+                o: Operator::Not, ..
+            } => TypeAST::Scalar(ScalarType::Boolean),
+            Expression::Unary {
+                o: Operator::Neg,
+                v,
+            } => value_type(analyzer, v),
+            // Operands that can't be combined are left to the database to reject.
+            Expression::Binary { l, o, r } => {
+                binary_type(value, &value_type(analyzer, l), o, &value_type(analyzer, r))
+                    .unwrap_or(TypeAST::Scalar(ScalarType::Any))
+            }
+            _ => TypeAST::Scalar(ScalarType::Any),
+        },
+        _ => TypeAST::Scalar(ScalarType::Any),
+    }
+}
+
+/// Extracts the constraints we recognize from an ASSERT clause.
+///
+/// Only conjunctions (`AND`) are followed, since a constraint under `OR` doesn't always hold.
+fn parse_constraints(assert: &Value) -> Vec<Constraint> {
+    match assert {
+        Value::Function(func) => match (func.as_ref(), func.args()) {
+            (Function::Normal(name, _), [Value::Param(param)]) if param.0 .0 == "value" => name
+                .strip_prefix("string::is::")
+                .or_else(|| name.strip_prefix("is::"))
+                .map(|check| vec![Constraint::StringIs(check.to_string())])
+                .unwrap_or_default(),
+            _ => vec![],
+        },
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary {
+                l,
+                o: Operator::And,
+                r,
+            } => {
+                let mut constraints = parse_constraints(l);
+                constraints.extend(parse_constraints(r));
+                constraints
+            }
+            Expression::Binary { l, o, r } => parse_length_constraint(l, o, r)
+                .or_else(|| parse_literals_constraint(l, o, r))
+                .map(|c| vec![c])
+                .unwrap_or_default(),
+            _ => vec![],
+        },
+        _ => vec![],
+    }
+}
+
+/// Recognizes `string::len($value) <op> n`.
+fn parse_length_constraint(l: &Value, o: &Operator, r: &Value) -> Option<Constraint> {
+    let Value::Function(func) = l else {
+        return None;
+    };
+    if func.name() != Some("string::len")
+        || !matches!(func.args(), [Value::Param(param)] if param.0 .0 == "value")
+    {
+        return None;
+    }
+    let Value::Number(Number::Int(n)) = r else {
+        return None;
+    };
+    let n = u64::try_from(*n).ok()?;
+
+    match o {
+        Operator::MoreThanOrEqual => Some(Constraint::MinLength(n)),
+        Operator::MoreThan => Some(Constraint::MinLength(n + 1)),
+        Operator::LessThanOrEqual => Some(Constraint::MaxLength(n)),
+        Operator::LessThan => Some(Constraint::MaxLength(n.checked_sub(1)?)),
+        _ => None,
+    }
+}
+
+/// Recognizes `$value INSIDE ['a', 'b']`, or `['a', 'b'] CONTAINS $value`, over string literals.
+fn parse_literals_constraint(l: &Value, o: &Operator, r: &Value) -> Option<Constraint> {
+    let is_value = |v: &Value| matches!(v, Value::Param(param) if param.0 .0 == "value");
+    let literals = match (l, o, r) {
+        (l, Operator::Inside, Value::Array(literals)) if is_value(l) => literals,
+        (Value::Array(literals), Operator::Contain, r) if is_value(r) => literals,
+        _ => return None,
+    };
+    let literals = literals
+        .iter()
+        .map(|literal| match literal {
+            Value::Strand(s) => Some(s.0.clone()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(Constraint::OneOf(literals))
+}
+
 fn apply_param_definition(
     param_def: &DefineParamStatement,
     ast: &mut TypeAST,
@@ -301,6 +558,205 @@ mod tests {
         panic!("Union type not found in AST");
     }
 
+    #[test]
+    fn test_assert_constraints() {
+        let schema = r#"
+            DEFINE TABLE user SCHEMAFULL;
+            DEFINE FIELD email ON user TYPE string ASSERT string::is::email($value);
+            DEFINE FIELD name ON user TYPE string
+                ASSERT string::len($value) >= 3 AND string::len($value) < 20;
+            DEFINE FIELD bio ON user TYPE string ASSERT $value != NONE;
+            DEFINE FIELD role ON user TYPE string ASSERT $value INSIDE ['admin', 'member'];
+            DEFINE FIELD theme ON user TYPE string ASSERT ['dark', 'light'] CONTAINS $value;
+            DEFINE FIELD level ON user TYPE int ASSERT $value IN [1, 2, 3];
+        "#;
+
+        let ast = analyze_schema(parse(schema).unwrap()).unwrap();
+        let TypeAST::Object(schema) = ast else {
+            panic!("Schema is not an object");
+        };
+        let TypeAST::Object(user) = &schema.fields["user"].ast else {
+            panic!("Table is not an object");
+        };
+
+        assert_eq!(
+            user.fields["email"].meta.constraints,
+            vec![Constraint::StringIs("email".to_string())]
+        );
+        assert_eq!(
+            user.fields["name"].meta.constraints,
+            vec![Constraint::MinLength(3), Constraint::MaxLength(19)]
+        );
+        assert!(user.fields["bio"].meta.constraints.is_empty());
+        assert_eq!(
+            user.fields["role"].meta.constraints,
+            vec![Constraint::OneOf(vec![
+                "admin".to_string(),
+                "member".to_string()
+            ])]
+        );
+        assert_eq!(
+            user.fields["theme"].meta.constraints,
+            vec![Constraint::OneOf(vec![
+                "dark".to_string(),
+                "light".to_string()
+            ])]
+        );
+        assert!(user.fields["level"].meta.constraints.is_empty());
+    }
+
+    #[test]
+    fn test_value_types() {
+        let schema = r#"
+            DEFINE TABLE product SCHEMAFULL;
+            DEFINE FIELD price ON product VALUE math::round($value, 2);
+            DEFINE FIELD slug ON product VALUE string::lowercase($value);
+            DEFINE FIELD label ON product VALUE "sku-" + string::uppercase($value);
+            DEFINE FIELD tags ON product VALUE string::words($value);
+            DEFINE FIELD count ON product VALUE 1 + 2;
+            DEFINE FIELD code ON product VALUE "sku-" + 1;
+            DEFINE FIELD updated ON product VALUE time::now();
+            DEFINE FIELD raw ON product VALUE $value;
+        "#;
+
+        let ast = analyze_schema(parse(schema).unwrap()).unwrap();
+        let TypeAST::Object(schema) = ast else {
+            panic!("Schema is not an object");
+        };
+        let TypeAST::Object(product) = &schema.fields["product"].ast else {
+            panic!("Table is not an object");
+        };
+        let field = |name: &str| product.fields[name].ast.clone();
+
+        assert!(matches!(
+            field("price"),
+            TypeAST::Scalar(ScalarType::Number)
+        ));
+        assert!(matches!(field("slug"), TypeAST::Scalar(ScalarType::String)));
+        assert!(matches!(
+            field("label"),
+            TypeAST::Scalar(ScalarType::String)
+        ));
+        assert!(matches!(
+            field("tags"),
+            TypeAST::Array(inner) if matches!(inner.0, TypeAST::Scalar(ScalarType::String))
+        ));
+        assert!(matches!(
+            field("count"),
+            TypeAST::Scalar(ScalarType::Integer)
+        ));
+        assert!(matches!(field("code"), TypeAST::Scalar(ScalarType::Any)));
+        assert!(matches!(
+            field("updated"),
+            TypeAST::Scalar(ScalarType::Datetime)
+        ));
+        assert!(matches!(field("raw"), TypeAST::Scalar(ScalarType::Any)));
+    }
+
+    #[test]
+    fn test_literal_defaults() {
+        let schema = r#"
+            DEFINE TABLE user SCHEMAFULL;
+            DEFINE FIELD role ON user TYPE string DEFAULT "member";
+            DEFINE FIELD tags ON user TYPE array<string> DEFAULT ["new", "unverified"];
+            DEFINE FIELD joined ON user TYPE datetime DEFAULT time::now();
+            DEFINE FIELD name ON user TYPE string;
+        "#;
+
+        let ast = analyze_schema(parse(schema).unwrap()).unwrap();
+        let TypeAST::Object(schema) = ast else {
+            panic!("Schema is not an object");
+        };
+        let TypeAST::Object(user) = &schema.fields["user"].ast else {
+            panic!("Table is not an object");
+        };
+        let default = |name: &str| user.fields[name].meta.default.clone();
+
+        assert_eq!(default("role").as_deref(), Some(r#""member""#));
+        assert_eq!(default("tags").as_deref(), Some(r#"["new","unverified"]"#));
+        assert_eq!(default("joined"), None);
+        assert_eq!(default("name"), None);
+    }
+
+    #[test]
+    fn test_deprecated_fields() {
+        let schema = r#"
+            DEFINE TABLE user SCHEMAFULL;
+            DEFINE FIELD nickname ON user TYPE string COMMENT "Deprecated: use display_name";
+            DEFINE FIELD legacy_id ON user TYPE string COMMENT "deprecated";
+            DEFINE FIELD display_name ON user TYPE string COMMENT "deprecatedness is a word";
+        "#;
+
+        let ast = analyze_schema(parse(schema).unwrap()).unwrap();
+        let TypeAST::Object(schema) = ast else {
+            panic!("Schema is not an object");
+        };
+        let TypeAST::Object(user) = &schema.fields["user"].ast else {
+            panic!("Table is not an object");
+        };
+        let deprecated = |name: &str| user.fields[name].meta.deprecated.clone();
+
+        assert_eq!(deprecated("nickname").as_deref(), Some("use display_name"));
+        assert_eq!(deprecated("legacy_id").as_deref(), Some(""));
+        assert_eq!(deprecated("display_name"), None);
+    }
+
+    #[test]
+    fn test_record_links_to_any_table() {
+        let schema = r#"
+            DEFINE TABLE user SCHEMAFULL;
+            DEFINE FIELD owner ON user TYPE record;
+            DEFINE FIELD pet ON user TYPE record<cat | dog>;
+        "#;
+
+        let ast = analyze_schema(parse(schema).unwrap()).unwrap();
+        let TypeAST::Object(schema) = ast else {
+            panic!("Schema is not an object");
+        };
+        let TypeAST::Object(user) = &schema.fields["user"].ast else {
+            panic!("Table is not an object");
+        };
+
+        assert!(matches!(
+            user.fields["owner"].ast,
+            TypeAST::Scalar(ScalarType::Any)
+        ));
+        assert!(matches!(
+            &user.fields["pet"].ast,
+            TypeAST::Union(variants)
+                if matches!(variants.as_slice(), [TypeAST::Record(cat), TypeAST::Record(dog)]
+                    if cat == "cat" && dog == "dog")
+        ));
+    }
+
+    #[test]
+    fn test_remove_statements() {
+        let schema = r#"
+            DEFINE TABLE user SCHEMAFULL;
+            DEFINE FIELD name ON user TYPE string;
+            DEFINE FIELD address ON user TYPE object;
+            DEFINE FIELD address.city ON user TYPE string;
+            DEFINE TABLE legacy SCHEMAFULL;
+            DEFINE FIELD value ON legacy TYPE string;
+            REMOVE FIELD address ON user;
+            REMOVE TABLE legacy;
+            DEFINE FIELD age ON user TYPE int;
+        "#;
+
+        let ast = analyze_schema(parse(schema).unwrap()).unwrap();
+        let TypeAST::Object(schema) = ast else {
+            panic!("Schema is not an object");
+        };
+        assert!(!schema.fields.contains_key("legacy"));
+
+        let TypeAST::Object(user) = &schema.fields["user"].ast else {
+            panic!("Table is not an object");
+        };
+        assert!(user.fields.contains_key("name"));
+        assert!(user.fields.contains_key("age"));
+        assert!(!user.fields.contains_key("address"));
+    }
+
     // #[test]
     // fn test_missing_parent_object() {
     //     let schema = r#"
@@ -0,0 +1,192 @@
+//! Predicates added to every SELECT on a table, so soft-delete or tenancy rules can't be
+//! forgotten in a query.
+
+use surrealdb::sql::{
+    statements::SelectStatement, Cond, Expression, Idiom, Operator, Part, Query, Statement,
+    Subquery, Table, Value,
+};
+
+use crate::{
+    ast::{ObjectType, TypeAST},
+    errors::AnalysisError,
+    schema::analyze_schema,
+};
+
+/// A predicate such as `archived = false`, on one table or on every table selected from.
+#[derive(Debug, Clone)]
+pub struct ScopeFilter {
+    pub table: Option<String>,
+    pub predicate: String,
+}
+
+/// Adds the filters that apply to each top-level SELECT to its WHERE clause, e.g.
+/// `SELECT * FROM user WHERE (archived = false) AND (age > 18)`.
+///
+/// Every field a filter refers to must exist on the table it is applied to. SELECTs from
+/// anything but a named table or record are left as they are.
+pub fn scope_query(
+    schema: &Query,
+    query: &Query,
+    filters: &[ScopeFilter],
+) -> Result<Query, AnalysisError> {
+    if filters.is_empty() {
+        return Ok(query.clone());
+    }
+    let TypeAST::Object(tables) = analyze_schema(schema.clone())? else {
+        return Ok(query.clone());
+    };
+
+    let mut query = query.clone();
+    for stmt in query.0 .0.iter_mut() {
+        if let Statement::Select(select) = stmt {
+            scope_select(&tables, select, filters)?;
+        }
+    }
+    Ok(query)
+}
+
+fn scope_select(
+    tables: &ObjectType,
+    select: &mut SelectStatement,
+    filters: &[ScopeFilter],
+) -> Result<(), AnalysisError> {
+    let table = match select.what.first() {
+        Some(Value::Table(table)) => table.to_string().to_lowercase(),
+        Some(Value::Thing(thing)) => thing.tb.to_lowercase(),
+        _ => return Ok(()),
+    };
+
+    let applicable = filters.iter().filter(|filter| {
+        filter
+            .table
+            .as_ref()
+            .map_or(true, |t| t.to_lowercase() == table)
+    });
+    for filter in applicable {
+        let predicate = parse_predicate(tables, &table, &filter.predicate)?;
+        select.cond = Some(Cond(match select.cond.take() {
+            None => predicate,
+            Some(existing) => Value::Expression(Box::new(Expression::Binary {
+                l: parenthesized(predicate),
+                o: Operator::And,
+                r: parenthesized(existing.0),
+            })),
+        }));
+    }
+    Ok(())
+}
+
+/// Parses the predicate as a WHERE clause on `table`, checking the fields it refers to.
+fn parse_predicate(
+    tables: &ObjectType,
+    table: &str,
+    predicate: &str,
+) -> Result<Value, AnalysisError> {
+    let invalid = |reason: String| {
+        AnalysisError::InvalidScopeFilter(table.to_string(), predicate.to_string(), reason)
+    };
+
+    let select = format!("SELECT * FROM {} WHERE {}", Table::from(table), predicate);
+    let parsed = surrealdb::sql::parse(&select).map_err(|e| invalid(e.to_string()))?;
+    let cond = match parsed.0 .0.as_slice() {
+        [Statement::Select(SelectStatement {
+            cond: Some(cond), ..
+        })] => cond.0.clone(),
+        _ => return Err(invalid("expected a single condition".to_string())),
+    };
+
+    let Some(TypeAST::Object(obj)) = tables.fields.get(table).map(|info| &info.ast) else {
+        return Err(invalid(format!("the table '{}' is not defined", table)));
+    };
+    let mut idioms = vec![];
+    idioms_in(&cond, &mut idioms);
+    for idiom in idioms {
+        if let Some(Part::Field(field)) = idiom.0.first() {
+            if !obj.fields.contains_key(&field.to_string()) && field.to_string() != "id" {
+                return Err(invalid(format!("'{}' has no field '{}'", table, field)));
+            }
+        }
+    }
+    Ok(cond)
+}
+
+fn parenthesized(value: Value) -> Value {
+    Value::Subquery(Box::new(Subquery::Value(value)))
+}
+
+fn idioms_in(value: &Value, idioms: &mut Vec<Idiom>) {
+    match value {
+        Value::Idiom(idiom) => idioms.push(idiom.clone()),
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary { l, r, .. } => {
+                idioms_in(l, idioms);
+                idioms_in(r, idioms);
+            }
+            Expression::Unary { v, .. } => idioms_in(v, idioms),
+            _ => {}
+        },
+        Value::Subquery(subquery) => {
+            if let Subquery::Value(value) = subquery.as_ref() {
+                idioms_in(value, idioms);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::sql::parse;
+
+    fn schema() -> Query {
+        parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD age ON user TYPE number;
+                DEFINE FIELD archived ON user TYPE bool;
+            DEFINE TABLE post SCHEMAFULL;
+                DEFINE FIELD title ON post TYPE string;
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn scopes_selects() {
+        let filters = vec![ScopeFilter {
+            table: Some("user".to_string()),
+            predicate: "archived = false".to_string(),
+        }];
+        let query = parse(
+            "SELECT name FROM user WHERE age > 18 OR age < 5; SELECT * FROM user; SELECT * FROM post;",
+        )
+        .unwrap();
+
+        let scoped = scope_query(&schema(), &query, &filters).unwrap();
+
+        assert_eq!(
+            scoped.to_string(),
+            "SELECT name FROM user WHERE (archived = false) AND (age > 18 OR age < 5);\n\
+             SELECT * FROM user WHERE archived = false;\n\
+             SELECT * FROM post;"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let filters = vec![ScopeFilter {
+            table: None,
+            predicate: "archived = false".to_string(),
+        }];
+        let query = parse("SELECT * FROM post").unwrap();
+
+        let result = scope_query(&schema(), &query, &filters);
+
+        assert!(matches!(
+            result,
+            Err(AnalysisError::InvalidScopeFilter(table, ..)) if table == "post"
+        ));
+    }
+}
@@ -0,0 +1,247 @@
+//! Full-text search: the SEARCH indexes a schema defines, and the `search::*` functions that
+//! read the matches a query makes with them.
+//!
+//! ```surql
+//! SELECT title, search::score(1) AS score FROM post WHERE title @1@ 'surreal';
+//! ```
+//!
+//! SurrealDB only fails such a query when it runs, if the match isn't made or no index with a
+//! defined analyzer covers the field, so these are checked when the query is built.
+
+use surrealdb::sql::{
+    statements::{DefineStatement, RemoveStatement, SelectStatement},
+    Expression, Field, Function, Idiom, Index, Number, Operator, Query, Statement, Subquery, Value,
+};
+
+use crate::errors::AnalysisError;
+
+/// A SEARCH index on a column of a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchIndex {
+    pub name: String,
+    pub table: String,
+    pub column: Idiom,
+    pub analyzer: String,
+    /// Whether the schema defines the analyzer, without which the index can't be built.
+    pub analyzer_defined: bool,
+    /// Whether the index is defined with HIGHLIGHTS, which `search::highlight` and
+    /// `search::offsets` need.
+    pub highlights: bool,
+}
+
+/// The SEARCH indexes of the schema, in the order they are defined, without those removed.
+pub fn search_indexes(schema: &Query) -> Vec<SearchIndex> {
+    let mut analyzers = vec![];
+    let mut indexes: Vec<SearchIndex> = vec![];
+
+    for stmt in schema.iter() {
+        match stmt {
+            Statement::Define(DefineStatement::Analyzer(def)) => {
+                analyzers.push(def.name.to_string())
+            }
+            Statement::Define(DefineStatement::Index(def)) => {
+                if let Index::Search(params) = &def.index {
+                    indexes.extend(def.cols.0.iter().map(|column| SearchIndex {
+                        name: def.name.to_string(),
+                        table: def.what.to_string().to_lowercase(),
+                        column: column.clone(),
+                        analyzer: params.az.to_string(),
+                        analyzer_defined: false,
+                        highlights: params.hl,
+                    }));
+                }
+            }
+            Statement::Remove(RemoveStatement::Index(rm)) => {
+                let table = rm.what.to_string().to_lowercase();
+                indexes.retain(|index| index.table != table || index.name != rm.name.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    // An analyzer may be defined after the indexes using it.
+    for index in &mut indexes {
+        index.analyzer_defined = analyzers.contains(&index.analyzer);
+    }
+    indexes
+}
+
+/// Checks that each `search::score`, `search::highlight` and `search::offsets` call in the
+/// top-level SELECTs refers to a match its WHERE clause makes, e.g. `@1@` for
+/// `search::score(1)`, on a field a SEARCH index with a defined analyzer covers.
+///
+/// Calls whose match reference isn't a literal are left to the database.
+pub fn check_search_calls(schema: &Query, query: &Query) -> Result<(), AnalysisError> {
+    let indexes = search_indexes(schema);
+    for stmt in query.iter() {
+        if let Statement::Select(select) = stmt {
+            check_select(&indexes, select)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_select(indexes: &[SearchIndex], select: &SelectStatement) -> Result<(), AnalysisError> {
+    let mut calls = vec![];
+    for field in select.expr.0.iter() {
+        if let Field::Single { expr, .. } = field {
+            search_calls(expr, &mut calls);
+        }
+    }
+    if calls.is_empty() {
+        return Ok(());
+    }
+
+    let table = match select.what.first() {
+        Some(Value::Table(table)) => table.to_string().to_lowercase(),
+        Some(Value::Thing(thing)) => thing.tb.to_lowercase(),
+        _ => return Ok(()),
+    };
+    let mut matches = vec![];
+    if let Some(cond) = &select.cond {
+        matched_fields(&cond.0, &mut matches);
+    }
+
+    for (call, name, match_ref) in calls {
+        let Some((_, field)) = matches.iter().find(|(r, _)| *r == match_ref) else {
+            return Err(AnalysisError::UnknownMatchRef(call.to_string(), match_ref));
+        };
+        let Some(index) = indexes
+            .iter()
+            .find(|index| index.table == table && index.column == *field)
+        else {
+            return Err(AnalysisError::UnsearchableField(
+                call.to_string(),
+                format!("{}.{}", table, field),
+            ));
+        };
+        if !index.analyzer_defined {
+            return Err(AnalysisError::UndefinedAnalyzer(
+                call.to_string(),
+                index.name.clone(),
+                index.analyzer.clone(),
+            ));
+        }
+        if name != "search::score" && !index.highlights {
+            return Err(AnalysisError::NoHighlights(
+                call.to_string(),
+                index.name.clone(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The `search::*` calls within a value, with the function's name and the literal match
+/// reference it is given.
+fn search_calls<'a>(value: &'a Value, calls: &mut Vec<(&'a Value, &'a str, u8)>) {
+    match value {
+        Value::Function(function) => {
+            if let Function::Normal(name, args) = function.as_ref() {
+                let match_ref = match name.as_str() {
+                    "search::score" | "search::offsets" => args.first(),
+                    "search::highlight" => args.get(2),
+                    _ => None,
+                };
+                if let Some(Value::Number(Number::Int(n))) = match_ref {
+                    if let Ok(n) = u8::try_from(*n) {
+                        calls.push((value, name.as_str(), n));
+                    }
+                }
+            }
+            for arg in function.args() {
+                search_calls(arg, calls);
+            }
+        }
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary { l, r, .. } => {
+                search_calls(l, calls);
+                search_calls(r, calls);
+            }
+            Expression::Unary { v, .. } => search_calls(v, calls),
+            _ => {}
+        },
+        Value::Subquery(subquery) => {
+            if let Subquery::Value(value) = subquery.as_ref() {
+                search_calls(value, calls);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The numbered matches a WHERE clause makes, e.g. `(1, title)` for `title @1@ 'surreal'`.
+fn matched_fields(cond: &Value, matches: &mut Vec<(u8, Idiom)>) {
+    match cond {
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary {
+                l: Value::Idiom(idiom),
+                o: Operator::Matches(Some(match_ref)),
+                ..
+            } => matches.push((*match_ref, idiom.clone())),
+            Expression::Binary { l, r, .. } => {
+                matched_fields(l, matches);
+                matched_fields(r, matches);
+            }
+            Expression::Unary { v, .. } => matched_fields(v, matches),
+            _ => {}
+        },
+        Value::Subquery(subquery) => {
+            if let Subquery::Value(value) = subquery.as_ref() {
+                matched_fields(value, matches);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::sql::parse;
+
+    #[test]
+    fn search_calls_are_checked() {
+        let schema = parse(
+            r#"
+            DEFINE ANALYZER simple TOKENIZERS blank FILTERS lowercase;
+            DEFINE TABLE post SCHEMAFULL;
+                DEFINE FIELD title ON post TYPE string;
+                DEFINE FIELD body ON post TYPE string;
+                DEFINE FIELD summary ON post TYPE string;
+                DEFINE FIELD tags ON post TYPE string;
+                DEFINE INDEX post_title ON post FIELDS title SEARCH ANALYZER simple BM25 HIGHLIGHTS;
+                DEFINE INDEX post_body ON post FIELDS body SEARCH ANALYZER simple BM25;
+                DEFINE INDEX post_tags ON post FIELDS tags SEARCH ANALYZER missing BM25;
+            "#,
+        )
+        .unwrap();
+        let check = |sql: &str| check_search_calls(&schema, &parse(sql).unwrap());
+
+        assert!(check(
+            "SELECT search::score(1) + search::score(2) AS score, \
+             search::highlight('<b>', '</b>', 1) AS title FROM post \
+             WHERE title @1@ 'surreal' OR body @2@ 'surreal'"
+        )
+        .is_ok());
+        assert!(check("SELECT search::score($ref) AS score FROM post").is_ok());
+
+        assert!(matches!(
+            check("SELECT search::score(2) AS score FROM post WHERE title @1@ 'surreal'"),
+            Err(AnalysisError::UnknownMatchRef(_, 2))
+        ));
+        assert!(matches!(
+            check("SELECT search::score(1) AS score FROM post WHERE summary @1@ 'surreal'"),
+            Err(AnalysisError::UnsearchableField(_, field)) if field == "post.summary"
+        ));
+        assert!(matches!(
+            check("SELECT search::score(1) AS score FROM post WHERE tags @1@ 'surreal'"),
+            Err(AnalysisError::UndefinedAnalyzer(_, index, analyzer))
+                if index == "post_tags" && analyzer == "missing"
+        ));
+        assert!(matches!(
+            check("SELECT search::offsets(1) AS offsets FROM post WHERE body @1@ 'surreal'"),
+            Err(AnalysisError::NoHighlights(_, index)) if index == "post_body"
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+//! Schemas embedded in the environment rather than read from a file, for builds without access
+//! to the filesystem, e.g. hermetic CI builds.
+//!
+//! The schema is looked up in order of precedence, each with the suffix of a schema version,
+//! e.g. `_V2`, if there is one:
+//!
+//! 1. `SURREALIX_SCHEMA`, the text of the schema itself.
+//! 2. `SURREALIX_SCHEMA_BASE64`, the schema as base64, for CI secrets that can't hold newlines.
+//! 3. `SURREALIX_SCHEMA_PATH`, the file the schema is read from, which the caller resolves.
+
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+
+use crate::errors::SchemaError;
+
+/// The variable holding the text of the schema.
+pub const SCHEMA_VAR: &str = "SURREALIX_SCHEMA";
+
+/// The variable holding the schema as base64.
+pub const SCHEMA_BASE64_VAR: &str = "SURREALIX_SCHEMA_BASE64";
+
+/// The variable holding the path of the schema file.
+pub const SCHEMA_PATH_VAR: &str = "SURREALIX_SCHEMA_PATH";
+
+/// The schema embedded in the variables `lookup` finds, with `suffix` appended to their names,
+/// or `None` if neither is set and the schema should be read from `SURREALIX_SCHEMA_PATH`.
+pub fn embedded_schema(
+    suffix: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Option<String>, SchemaError> {
+    let var = format!("{}{}", SCHEMA_VAR, suffix);
+    if let Some(text) = lookup(&var) {
+        return Ok(Some(text));
+    }
+
+    let var = format!("{}{}", SCHEMA_BASE64_VAR, suffix);
+    let Some(encoded) = lookup(&var) else {
+        return Ok(None);
+    };
+    let bytes = decode_base64(&encoded)
+        .map_err(|reason| SchemaError::InvalidEmbeddedSchema(var.clone(), reason))?;
+    let text = String::from_utf8(bytes).map_err(|_| {
+        SchemaError::InvalidEmbeddedSchema(var, "the schema isn't UTF-8 text".to_string())
+    })?;
+    Ok(Some(text))
+}
+
+/// Decoding that accepts base64 with or without padding.
+const INDIFFERENT_PADDING: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+
+/// Standard base64, with `+` and `/`.
+const STANDARD: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, INDIFFERENT_PADDING);
+
+/// URL-safe base64, with `-` and `_`.
+const URL_SAFE: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, INDIFFERENT_PADDING);
+
+/// Decodes standard or URL-safe base64, with or without padding, ignoring whitespace such as
+/// the line breaks of wrapped output.
+fn decode_base64(encoded: &str) -> Result<Vec<u8>, String> {
+    let digits: String = encoded.chars().filter(|c| !c.is_whitespace()).collect();
+    let engine = match digits.contains(['-', '_']) {
+        true => &URL_SAFE,
+        false => &STANDARD,
+    };
+    engine.decode(digits).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn embedded_schemas() {
+        let embedded = |vars: &[(&str, &str)], suffix: &str| {
+            let vars: HashMap<_, _> = vars.iter().copied().collect();
+            embedded_schema(suffix, |var| vars.get(var).map(|value| value.to_string()))
+        };
+
+        assert_eq!(
+            embedded(
+                &[
+                    ("SURREALIX_SCHEMA", "DEFINE TABLE user;"),
+                    ("SURREALIX_SCHEMA_BASE64", "REVGSU5FIFRBQkxFIHBvc3Q7"),
+                ],
+                ""
+            )
+            .unwrap()
+            .as_deref(),
+            Some("DEFINE TABLE user;")
+        );
+        assert_eq!(
+            embedded(
+                &[("SURREALIX_SCHEMA_BASE64_V2", "REVGSU5FIFRBQkxF\nIHBvc3Q7")],
+                "_V2"
+            )
+            .unwrap()
+            .as_deref(),
+            Some("DEFINE TABLE post;")
+        );
+        assert_eq!(
+            embedded(&[("SURREALIX_SCHEMA_BASE64", "LS0gY2Fmw6kK")], "")
+                .unwrap()
+                .as_deref(),
+            Some("-- café\n")
+        );
+        assert_eq!(
+            embedded(&[("SURREALIX_SCHEMA_BASE64", "Pz8_")], "")
+                .unwrap()
+                .as_deref(),
+            Some("???")
+        );
+        assert_eq!(
+            embedded(&[("SURREALIX_SCHEMA", "DEFINE TABLE user;")], "_V2").unwrap(),
+            None
+        );
+        for invalid in ["/w==", "REVGS!", "REVGS"] {
+            assert!(matches!(
+                embedded(&[("SURREALIX_SCHEMA_BASE64", invalid)], ""),
+                Err(SchemaError::InvalidEmbeddedSchema(var, _)) if var == "SURREALIX_SCHEMA_BASE64"
+            ));
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Golden tests of the analyzer: the type a query is expected to have against a schema.
+//!
+//! Types are compared by their [shape], a one-line notation such as
+//! `[{ age: Number, name: Option<String> }]`, so expectations stay short and don't depend on
+//! the order fields were defined in.
+
+use crate::{analyzer::analyze, ast::TypeAST};
+
+/// The notation types are written in for [assert_typing].
+///
+/// Scalars are written by name, objects as `{ a: T, b: U }` with their fields sorted, arrays as
+/// `[T]` or `[T; n]`, and the rest as `Option<T>`, `Record(table)` and `Union(A | B)`.
+pub fn shape(ast: &TypeAST) -> String {
+    match ast {
+        TypeAST::Scalar(scalar) => format!("{:?}", scalar),
+        TypeAST::Object(obj) if obj.fields.is_empty() => "{}".to_string(),
+        TypeAST::Object(obj) => {
+            let mut fields: Vec<_> = obj
+                .fields
+                .iter()
+                .map(|(name, field)| format!("{}: {}", name, shape(&field.ast)))
+                .collect();
+            fields.sort();
+            format!("{{ {} }}", fields.join(", "))
+        }
+        TypeAST::Array(inner) => match inner.1 {
+            Some(len) => format!("[{}; {}]", shape(&inner.0), len),
+            None => format!("[{}]", shape(&inner.0)),
+        },
+        TypeAST::Option(inner) => format!("Option<{}>", shape(inner)),
+        TypeAST::Record(table) => format!("Record({})", table),
+        TypeAST::Union(variants) => format!(
+            "Union({})",
+            variants.iter().map(shape).collect::<Vec<_>>().join(" | ")
+        ),
+    }
+}
+
+/// Analyzes `query` against `schema`, both SurrealQL, and asserts its [shape] is `expected`.
+///
+/// The shapes of a query with several statements are separated by `; `.
+///
+/// # Panics
+///
+/// If either fails to parse or analyze, or the query has a different shape.
+#[track_caller]
+pub fn assert_typing(schema: &str, query: &str, expected: &str) {
+    let schema = surrealdb::sql::parse(schema)
+        .unwrap_or_else(|err| panic!("The schema failed to parse: {}", err));
+    let parsed = surrealdb::sql::parse(query)
+        .unwrap_or_else(|err| panic!("'{}' failed to parse: {}", query, err));
+    let types = analyze(schema, parsed)
+        .unwrap_or_else(|err| panic!("'{}' failed to analyze: {}", query, err));
+
+    let actual = types.iter().map(shape).collect::<Vec<_>>().join("; ");
+    assert_eq!(actual, expected, "'{}' has an unexpected type", query);
+}
+
+/// Generates a `#[test]` for each query, asserting its type against the schema with
+/// [assert_typing](crate::typing::assert_typing).
+///
+/// ```
+/// surrealix_core::typing_tests! {
+///     schema = "DEFINE TABLE user SCHEMAFULL; DEFINE FIELD name ON user TYPE string;";
+///
+///     select_names: "SELECT name FROM user" => "[{ name: String }]",
+///     select_one_name: "SELECT VALUE name FROM ONLY user:john" => "String",
+/// }
+/// ```
+#[macro_export]
+macro_rules! typing_tests {
+    (
+        schema = $schema:expr;
+        $($name:ident: $query:expr => $expected:expr),* $(,)?
+    ) => {
+        $(
+            #[test]
+            fn $name() {
+                $crate::typing::assert_typing($schema, $query, $expected);
+            }
+        )*
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    const SCHEMA: &str = r#"
+        DEFINE TABLE user SCHEMAFULL;
+            DEFINE FIELD name ON user TYPE string;
+            DEFINE FIELD nickname ON user TYPE option<string>;
+            DEFINE FIELD tags ON user TYPE array;
+                DEFINE FIELD tags.* ON user TYPE string;
+            DEFINE FIELD best_friend ON user TYPE record<user>;
+    "#;
+
+    crate::typing_tests! {
+        schema = SCHEMA;
+
+        select_all: "SELECT * FROM user"
+            => "[{ best_friend: Record(user), id: Record(user), name: String, nickname: Option<String>, tags: [String] }]",
+        select_value: "SELECT VALUE tags FROM ONLY user:john" => "[String]",
+        select_fetch: "SELECT best_friend FROM ONLY user:john FETCH best_friend"
+            => "{ best_friend: { best_friend: Record(user), id: Record(user), name: String, nickname: Option<String>, tags: [String] } }",
+        several_statements: "SELECT name FROM user; SELECT VALUE nickname FROM user"
+            => "[{ name: String }]; [Option<String>]",
+    }
+}
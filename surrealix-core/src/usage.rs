@@ -0,0 +1,279 @@
+//! Finds the parts of a schema that no query references.
+//!
+//! References are found syntactically, from the idioms a SELECT projects, filters, orders,
+//! groups, splits and fetches by. A field counts as used when it, an object containing it,
+//! or a field nested within it is referenced.
+
+use std::collections::{HashMap, HashSet};
+
+use surrealdb::sql::{
+    statements::SelectStatement, Expression, Field, Function, Idiom, Part, Query, Statement,
+    Subquery, Value,
+};
+
+use crate::{
+    ast::{ObjectType, TypeAST},
+    schema::{analyze_schema, SchemaParseError},
+};
+
+/// A table, or a field within one, that no query references.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Unused {
+    Table(String),
+    /// The dotted path of the field within its table, e.g. `address.city`.
+    Field(String, String),
+}
+
+impl std::fmt::Display for Unused {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Unused::Table(table) => write!(f, "table '{}'", table),
+            Unused::Field(table, field) => write!(f, "field '{}.{}'", table, field),
+        }
+    }
+}
+
+/// The references made by a set of queries.
+#[derive(Default)]
+struct References {
+    tables: HashSet<String>,
+    /// Tables whose every field is referenced, e.g. by `SELECT *` or FETCH.
+    whole_tables: HashSet<String>,
+    fields: HashMap<String, HashSet<String>>,
+}
+
+/// Lists the tables and fields of `schema` that none of `queries` reference, sorted by name.
+///
+/// Record ids are always returned, so `id` fields are never reported.
+pub fn unused(schema: &Query, queries: &[Query]) -> Result<Vec<Unused>, SchemaParseError> {
+    let TypeAST::Object(tables) = analyze_schema(schema.clone())? else {
+        return Ok(vec![]);
+    };
+
+    let mut references = References::default();
+    for stmt in queries.iter().flat_map(|query| query.iter()) {
+        if let Statement::Select(select) = stmt {
+            reference_select(&tables, select, &mut references);
+        }
+    }
+
+    let mut unused = vec![];
+    for (table, table_info) in &tables.fields {
+        if !references.tables.contains(table) {
+            unused.push(Unused::Table(table.clone()));
+            continue;
+        }
+        if references.whole_tables.contains(table) {
+            continue;
+        }
+
+        let used = references.fields.get(table).cloned().unwrap_or_default();
+        let mut paths = vec![];
+        if let TypeAST::Object(obj) = &table_info.ast {
+            field_paths(obj, "", &mut paths);
+        }
+        for path in paths {
+            let is_used = path == "id"
+                || used.iter().any(|used| {
+                    used == &path
+                        || path.starts_with(&format!("{}.", used))
+                        || used.starts_with(&format!("{}.", path))
+                });
+            if !is_used {
+                unused.push(Unused::Field(table.clone(), path));
+            }
+        }
+    }
+
+    unused.sort();
+    Ok(unused)
+}
+
+fn field_paths(obj: &ObjectType, prefix: &str, paths: &mut Vec<String>) {
+    for (name, field) in &obj.fields {
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        let mut ast = &field.ast;
+        while let TypeAST::Array(inner) = ast {
+            ast = &inner.0;
+        }
+        if let TypeAST::Object(nested) = ast {
+            field_paths(nested, &path, paths);
+        }
+        paths.push(path);
+    }
+}
+
+fn reference_select(tables: &ObjectType, select: &SelectStatement, refs: &mut References) {
+    let table = match select.what.first() {
+        Some(Value::Table(table)) => table.to_string().to_lowercase(),
+        Some(Value::Thing(thing)) => thing.tb.to_lowercase(),
+        _ => return,
+    };
+    refs.tables.insert(table.clone());
+
+    let mut idioms = vec![];
+    for field in select.expr.0.iter() {
+        match field {
+            Field::All => {
+                refs.whole_tables.insert(table.clone());
+            }
+            Field::Single { expr, .. } => idioms_in(expr, &mut idioms),
+        }
+    }
+    if let Some(cond) = &select.cond {
+        idioms_in(&cond.0, &mut idioms);
+    }
+    idioms.extend(
+        select
+            .order
+            .iter()
+            .flat_map(|o| o.0.iter().map(|o| o.order.clone())),
+    );
+    idioms.extend(
+        select
+            .group
+            .iter()
+            .flat_map(|g| g.0.iter().map(|g| g.0.clone())),
+    );
+    idioms.extend(
+        select
+            .split
+            .iter()
+            .flat_map(|s| s.0.iter().map(|s| s.0.clone())),
+    );
+    idioms.extend(select.omit.iter().flat_map(|o| o.0.iter().cloned()));
+
+    for idiom in &idioms {
+        reference_idiom(&table, idiom, refs);
+    }
+
+    // Fetched records are returned whole.
+    for fetch in select.fetch.iter().flat_map(|f| f.0.iter()) {
+        reference_idiom(&table, &fetch.0, refs);
+        if let Some(target) = linked_table(tables, &table, &fetch.0) {
+            refs.tables.insert(target.clone());
+            refs.whole_tables.insert(target);
+        }
+    }
+}
+
+/// Marks the fields along an idiom, and the tables of any graph traversal within it.
+fn reference_idiom(table: &str, idiom: &Idiom, refs: &mut References) {
+    let path: Vec<String> = idiom
+        .0
+        .iter()
+        .map_while(|part| match part {
+            Part::Field(ident) => Some(ident.to_string()),
+            _ => None,
+        })
+        .collect();
+    if !path.is_empty() {
+        refs.fields
+            .entry(table.to_string())
+            .or_default()
+            .insert(path.join("."));
+    }
+
+    for part in &idiom.0 {
+        if let Part::Graph(graph) = part {
+            for edge in graph.what.0.iter() {
+                refs.tables.insert(edge.to_string().to_lowercase());
+            }
+        }
+    }
+}
+
+/// The table a field of `table` links to, looking through arrays and options.
+fn linked_table(tables: &ObjectType, table: &str, idiom: &Idiom) -> Option<String> {
+    let mut ast = &tables.fields.get(table)?.ast;
+    for part in &idiom.0 {
+        ast = match (ast, part) {
+            (TypeAST::Object(obj), Part::Field(ident)) => &obj.fields.get(&ident.to_string())?.ast,
+            (TypeAST::Array(inner), Part::All) => &inner.0,
+            _ => return None,
+        };
+    }
+    loop {
+        ast = match ast {
+            TypeAST::Array(inner) => &inner.0,
+            TypeAST::Option(inner) => inner,
+            TypeAST::Record(target) => return Some(target.clone()),
+            _ => return None,
+        };
+    }
+}
+
+fn idioms_in(value: &Value, idioms: &mut Vec<Idiom>) {
+    match value {
+        Value::Idiom(idiom) => idioms.push(idiom.clone()),
+        Value::Expression(expr) => match expr.as_ref() {
+            Expression::Binary { l, r, .. } => {
+                idioms_in(l, idioms);
+                idioms_in(r, idioms);
+            }
+            Expression::Unary { v, .. } => idioms_in(v, idioms),
+            _ => {}
+        },
+        Value::Function(function) => match function.as_ref() {
+            Function::Normal(_, args) | Function::Custom(_, args) => {
+                args.iter().for_each(|arg| idioms_in(arg, idioms))
+            }
+            _ => {}
+        },
+        Value::Subquery(subquery) => {
+            if let Subquery::Value(value) = subquery.as_ref() {
+                idioms_in(value, idioms);
+            }
+        }
+        Value::Array(array) => array.iter().for_each(|value| idioms_in(value, idioms)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::sql::parse;
+
+    #[test]
+    fn finds_unused_schema() {
+        let schema = parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD id ON user TYPE string;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD age ON user TYPE number;
+                DEFINE FIELD nickname ON user TYPE string;
+                DEFINE FIELD address ON user TYPE object;
+                    DEFINE FIELD address.city ON user TYPE string;
+                    DEFINE FIELD address.zip ON user TYPE string;
+                DEFINE FIELD tags ON user TYPE array;
+                    DEFINE FIELD tags.* ON user TYPE record<tag>;
+            DEFINE TABLE tag SCHEMAFULL;
+                DEFINE FIELD name ON tag TYPE string;
+            DEFINE TABLE audit SCHEMAFULL;
+                DEFINE FIELD event ON audit TYPE string;
+            "#,
+        )
+        .unwrap();
+        let queries = vec![
+            parse("SELECT name, address.city FROM user WHERE age > 18").unwrap(),
+            parse("SELECT tags FROM user ORDER BY name FETCH tags").unwrap(),
+        ];
+
+        let unused = unused(&schema, &queries).unwrap();
+
+        assert_eq!(
+            unused,
+            vec![
+                Unused::Table("audit".to_string()),
+                Unused::Field("user".to_string(), "address.zip".to_string()),
+                Unused::Field("user".to_string(), "nickname".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use std::{
+    collections::HashMap,
+    env,
+    path::{Path, PathBuf},
+};
+use surrealdb::sql::{parse, Statement};
+use surrealix_core::{
+    analyzer::{
+        analyze_statement,
+        completion::{complete, CompletionKind},
+    },
+    ast::TypeAST,
+    errors::SchemaError,
+    mixin::{expand_mixins, expand_mixins_mapped},
+    schema::analyze_schema,
+};
+use tokio::sync::RwLock;
+use tower_lsp::{
+    jsonrpc::Result,
+    lsp_types::{
+        CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams,
+        CompletionResponse, Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams,
+        DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
+        InitializeParams, InitializeResult, InitializedParams, MessageType, Position, Range,
+        ServerCapabilities, ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+    },
+    Client, LanguageServer,
+};
+
+use crate::document::{error_position, offset_at, position_at, selected_table, statement_spans};
+
+/// Serves diagnostics and completions for `.surql` files.
+///
+/// The schema is found the same way the macros find it, through `SURREALIX_SCHEMA_PATH` in the
+/// workspace's `.env`, so the editor reports the same errors as the compiler.
+pub struct Backend {
+    client: Client,
+    root: RwLock<Option<PathBuf>>,
+    schema_path: RwLock<Option<PathBuf>>,
+    schema: RwLock<Option<TypeAST>>,
+    documents: RwLock<HashMap<Url, String>>,
+}
+
+impl Backend {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            root: RwLock::new(None),
+            schema_path: RwLock::new(None),
+            schema: RwLock::new(None),
+            documents: RwLock::new(HashMap::new()),
+        }
+    }
+
+    async fn reload_schema(&self) {
+        let Some(root) = self.root.read().await.clone() else {
+            return;
+        };
+
+        let path = match schema_path(&root) {
+            Ok(path) => path,
+            Err(e) => return self.warn(e.to_string()).await,
+        };
+
+        let schema = std::fs::read_to_string(&path)
+            .map_err(|e| SchemaError::FileReadError(e).to_string())
+            .and_then(|text| parse_schema(&text));
+        *self.schema_path.write().await = Some(path);
+
+        match schema {
+            Ok(schema) => *self.schema.write().await = Some(schema),
+            Err(e) => self.warn(e).await,
+        }
+    }
+
+    async fn warn(&self, message: String) {
+        self.client
+            .log_message(MessageType::WARNING, format!("surrealix: {}", message))
+            .await
+    }
+
+    async fn is_schema(&self, uri: &Url) -> bool {
+        match (&*self.schema_path.read().await, uri.to_file_path()) {
+            (Some(schema_path), Ok(path)) => *schema_path == path,
+            _ => false,
+        }
+    }
+
+    async fn check(&self, uri: Url, text: &str, version: Option<i32>) {
+        let diagnostics = if self.is_schema(&uri).await {
+            check_schema(text)
+        } else {
+            match &*self.schema.read().await {
+                Some(schema) => check_query(schema, text),
+                None => vec![],
+            }
+        };
+
+        self.client
+            .publish_diagnostics(uri, diagnostics, version)
+            .await;
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        #[allow(deprecated)]
+        let root = params
+            .workspace_folders
+            .and_then(|folders| folders.into_iter().next())
+            .map(|folder| folder.uri)
+            .or(params.root_uri)
+            .and_then(|uri| uri.to_file_path().ok());
+        *self.root.write().await = root;
+
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                    TextDocumentSyncKind::FULL,
+                )),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(vec![".".to_string(), ">".to_string()]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            server_info: Some(ServerInfo {
+                name: env!("CARGO_PKG_NAME").to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+        })
+    }
+
+    async fn initialized(&self, _: InitializedParams) {
+        self.reload_schema().await;
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        let document = params.text_document;
+        self.check(document.uri.clone(), &document.text, Some(document.version))
+            .await;
+        self.documents
+            .write()
+            .await
+            .insert(document.uri, document.text);
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        // Changes are always sent in full, as advertised in `initialize`.
+        let Some(change) = params.content_changes.into_iter().last() else {
+            return;
+        };
+        let document = params.text_document;
+        self.check(document.uri.clone(), &change.text, Some(document.version))
+            .await;
+        self.documents
+            .write()
+            .await
+            .insert(document.uri, change.text);
+    }
+
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        if !self.is_schema(&params.text_document.uri).await {
+            return;
+        }
+
+        // Queries in every open document may be affected by a schema change.
+        self.reload_schema().await;
+        let documents = self.documents.read().await.clone();
+        for (uri, text) in documents {
+            self.check(uri, &text, None).await;
+        }
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.documents.write().await.remove(&uri);
+        self.client.publish_diagnostics(uri, vec![], None).await;
+    }
+
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let position = params.text_document_position;
+        let documents = self.documents.read().await;
+        let schema = self.schema.read().await;
+        let (Some(text), Some(schema)) = (documents.get(&position.text_document.uri), &*schema)
+        else {
+            return Ok(None);
+        };
+
+        let cursor = offset_at(text, position.position);
+        let Some(span) = statement_spans(text)
+            .into_iter()
+            .find(|span| span.start <= cursor && cursor <= span.end)
+        else {
+            return Ok(None);
+        };
+
+        let statement = &text[span.clone()];
+        let Some(table) = selected_table(statement) else {
+            return Ok(None);
+        };
+
+        let items = complete(schema, table, statement, cursor - span.start)
+            .into_iter()
+            .map(|completion| CompletionItem {
+                label: completion.label,
+                kind: Some(match completion.kind {
+                    CompletionKind::Field => CompletionItemKind::FIELD,
+                    CompletionKind::Edge | CompletionKind::Table => CompletionItemKind::CLASS,
+                }),
+                ..Default::default()
+            })
+            .collect();
+
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+}
+
+/// Finds the schema named by `SURREALIX_SCHEMA_PATH`, relative to the workspace root.
+fn schema_path(root: &Path) -> std::result::Result<PathBuf, SchemaError> {
+    let from_dotenv = dotenv::from_path_iter(root.join(".env"))
+        .ok()
+        .and_then(|mut vars| {
+            vars.find_map(|var| match var {
+                Ok((key, value)) if key == "SURREALIX_SCHEMA_PATH" => Some(value),
+                _ => None,
+            })
+        });
+
+    let path = from_dotenv
+        .or_else(|| env::var("SURREALIX_SCHEMA_PATH").ok())
+        .ok_or_else(|| SchemaError::EnvVarNotSet("SURREALIX_SCHEMA_PATH".to_string()))?;
+
+    Ok(root.join(path.trim_start_matches("./")))
+}
+
+fn parse_schema(text: &str) -> std::result::Result<TypeAST, String> {
+    let text = expand_mixins(text).map_err(|e| SchemaError::from(e).to_string())?;
+    let query = parse(&text).map_err(|e| e.to_string())?;
+    analyze_schema(query).map_err(|e| e.to_string())
+}
+
+fn check_schema(text: &str) -> Vec<Diagnostic> {
+    let (expanded, sources) = match expand_mixins_mapped(text) {
+        Ok(expanded) => expanded,
+        Err(e) => {
+            let position = Position::new(e.line().saturating_sub(1) as u32, 0);
+            return vec![error(Range::new(position, position), e.to_string())];
+        }
+    };
+    match parse(&expanded) {
+        Err(e) => {
+            let mut diagnostic = parse_error(&e.to_string());
+            // Errors are found in the expanded schema, so are moved to the line they come from,
+            // which for an error within a mixin is the line including it.
+            let line = diagnostic.range.start.line as usize;
+            if let Some(&source) = sources.get(line) {
+                let included = text.lines().nth(source - 1) != expanded.lines().nth(line);
+                let column = if included {
+                    0
+                } else {
+                    diagnostic.range.start.character
+                };
+                let position = Position::new(source as u32 - 1, column);
+                diagnostic.range = Range::new(position, position);
+            }
+            vec![diagnostic]
+        }
+        Ok(query) => match analyze_schema(query) {
+            Ok(_) => vec![],
+            Err(e) => vec![error(Range::default(), e.to_string())],
+        },
+    }
+}
+
+/// Analyzes each SELECT in the document, as `build_query!` would.
+fn check_query(schema: &TypeAST, text: &str) -> Vec<Diagnostic> {
+    let query = match parse(text) {
+        Ok(query) => query,
+        Err(e) => return vec![parse_error(&e.to_string())],
+    };
+
+    let spans = statement_spans(text);
+    query
+        .iter()
+        .enumerate()
+        .filter(|(_, statement)| matches!(statement, Statement::Select(_)))
+        .filter_map(|(i, statement)| {
+            let e = analyze_statement(schema, statement).err()?;
+            let range = spans.get(i).map_or(Range::default(), |span| {
+                let statement = &text[span.clone()];
+                let start = span.start + statement.len() - statement.trim_start().len();
+                Range::new(position_at(text, start), position_at(text, span.end))
+            });
+            Some(error(range, e.to_string()))
+        })
+        .collect()
+}
+
+fn parse_error(message: &str) -> Diagnostic {
+    let position = error_position(message).unwrap_or_default();
+    error(Range::new(position, position), message.to_string())
+}
+
+fn error(range: Range, message: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("surrealix".to_string()),
+        message,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,141 @@
+use std::ops::Range;
+use tower_lsp::lsp_types::Position;
+
+/// Converts an LSP position, counted in UTF-16 code units, into a byte offset into `text`.
+pub fn offset_at(text: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return text.len(),
+        }
+    }
+
+    let mut units = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        if units >= position.character as usize || c == '\n' {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+/// Converts a byte offset into `text` into an LSP position.
+pub fn position_at(text: &str, offset: usize) -> Position {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let character = before[line_start..]
+        .chars()
+        .map(char::len_utf16)
+        .sum::<usize>();
+    Position::new(line as u32, character as u32)
+}
+
+/// Splits a query into the byte ranges of its statements.
+///
+/// SurrealDB's AST doesn't keep source locations, so statements are found by their `;`
+/// separators, skipping over strings, comments and blocks.
+pub fn statement_spans(text: &str) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    let mut start = 0;
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), '\\') => {
+                chars.next();
+            }
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, '#') => skip_line(&mut chars),
+            (None, '-' | '/') if chars.peek().map(|(_, next)| *next) == Some(c) => {
+                skip_line(&mut chars)
+            }
+            (None, '{' | '(' | '[') => depth += 1,
+            (None, '}' | ')' | ']') => depth = depth.saturating_sub(1),
+            (None, ';') if depth == 0 => {
+                spans.push(start..i);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    spans.push(start..text.len());
+    spans.retain(|span| !text[span.clone()].trim().is_empty());
+    spans
+}
+
+fn skip_line(chars: &mut impl Iterator<Item = (usize, char)>) {
+    for (_, c) in chars.by_ref() {
+        if c == '\n' {
+            break;
+        }
+    }
+}
+
+/// The table a statement selects from, e.g. `user` in `SELECT * FROM ONLY user:1`.
+pub fn selected_table(statement: &str) -> Option<&str> {
+    let mut words = statement
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty());
+
+    words.find(|word| word.eq_ignore_ascii_case("FROM"))?;
+    words.find(|word| !word.eq_ignore_ascii_case("ONLY"))
+}
+
+/// The location of a SurrealDB parse error, which is rendered as `--> [line:column]`.
+pub fn error_position(message: &str) -> Option<Position> {
+    let rest = &message[message.find("--> [")? + 5..];
+    let (line, column) = rest[..rest.find(']')?].split_once(':')?;
+    let line = line.trim().parse::<u32>().ok()?;
+    let column = column.trim().parse::<u32>().ok()?;
+    Some(Position::new(
+        line.saturating_sub(1),
+        column.saturating_sub(1),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_round_trip() {
+        let text = "SELECT *\nFROM ⟨user⟩;";
+        let offset = text.find("user").unwrap();
+        let position = position_at(text, offset);
+
+        assert_eq!(position, Position::new(1, 6));
+        assert_eq!(offset_at(text, position), offset);
+    }
+
+    #[test]
+    fn splits_statements() {
+        let text = "SELECT * FROM user WHERE name = 'a;b';\n-- comment;\nIF true { RETURN 1; };";
+        let statements: Vec<_> = statement_spans(text)
+            .into_iter()
+            .map(|span| text[span].trim())
+            .collect();
+
+        assert_eq!(
+            statements,
+            vec![
+                "SELECT * FROM user WHERE name = 'a;b'",
+                "-- comment;\nIF true { RETURN 1; }"
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_selected_table() {
+        assert_eq!(selected_table("SELECT name FROM ONLY user:1"), Some("user"));
+        assert_eq!(selected_table("select ->friend from user"), Some("user"));
+        assert_eq!(selected_table("SELECT name"), None);
+    }
+}
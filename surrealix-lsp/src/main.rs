@@ -0,0 +1,13 @@
+use tower_lsp::{LspService, Server};
+
+mod backend;
+mod document;
+
+#[tokio::main]
+async fn main() {
+    let stdin = tokio::io::stdin();
+    let stdout = tokio::io::stdout();
+
+    let (service, socket) = LspService::new(backend::Backend::new);
+    Server::new(stdin, stdout, socket).serve(service).await;
+}
@@ -1,25 +1,89 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use convert_case::{Case, Casing};
+use convert_case::{Boundary, Case, Casing};
 use proc_macro::TokenStream;
-use proc_macro2::{Ident, TokenStream as TokenStream2};
+use proc_macro2::{Ident, Literal, Span, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
-use surrealdb::sql::Query;
+use surrealdb::sql::{Idiom, Param, Query, Statement, Table, Value};
 use surrealix_core::{
-    analyzer::analyze,
-    ast::{ObjectType, ScalarType, TypeAST},
+    access::{ddl_statements, is_read_only, read_tables, writing_statements},
+    analyzer::{analyze_tables, StatementType},
+    ast::{Constraint, FieldMetadata, ObjectType, ScalarType, TypeAST},
+    canonical::query_hash,
+    edges::{edge_and_node, include_edges, type_edges},
     errors,
+    fetch::{degrade_links, strip_dynamic_fetches},
+    group::select_group_all,
+    keyset::{keyset_query, AFTER_PARAM, PAGE_SIZE_PARAM},
+    limit::{count_params, limit_rows, MAX_ROWS_PARAM},
+    lint::{index_hints, lint_query},
+    params::{param_constraints, param_types, query_params},
+    permissions::{protected_fields, remove_unselectable},
+    relations::{relations, Relation},
+    schema::analyze_schema,
+    scope::scope_query,
+    search::check_search_calls,
 };
+use syn::ext::IdentExt;
 use thiserror::Error;
+use unicode_ident::{is_xid_continue, is_xid_start};
 
-use super::parser::BuildQueryInput;
+use super::{
+    naming::TypeNaming,
+    parser::{BuildQueryInput, BuildQueryOptions, DynamicObjects, TypeNames},
+};
+
+/// The canonical table models that results refer to in `shared_types` mode.
+struct SharedModels<'a> {
+    path: &'a syn::Path,
+    tables: ObjectType,
+    naming: &'a TypeNaming,
+}
+
+impl SharedModels<'_> {
+    /// The canonical model for `obj`, if it is a complete record of a table.
+    fn model_of(&self, obj: &ObjectType) -> Option<TokenStream2> {
+        let (table, _) = self
+            .tables
+            .fields
+            .iter()
+            .find(|(_, table)| matches!(&table.ast, TypeAST::Object(model) if model == obj))?;
+        Some(self.model(table))
+    }
+
+    fn model(&self, table: &str) -> TokenStream2 {
+        let path = self.path;
+        let name = self.naming.table(table);
+        quote! { #path::#name }
+    }
+}
 
 #[derive(Debug, Error)]
 pub enum QueryBuilderError {
     #[error("The specified SurrealQL is invalid: {0}")]
     ParseError(#[from] surrealdb::error::Db),
-    #[error("Failed to analyze the query")]
+    #[error("Failed to analyze the query: {0}")]
     AnalysisError(#[from] errors::AnalysisError),
+    #[error("The query is marked read_only, but this statement writes: {0}")]
+    WritesWhenReadOnly(String),
+    #[error("This statement changes the schema, which requires the allow_ddl option: {0}")]
+    DdlNotAllowed(String),
+    #[error("Handlers can only be generated for queries of a single statement that isn't LIVE")]
+    UnsupportedHandler,
+    #[error("The route's parameter '{0}' isn't a parameter of the query")]
+    UnknownRouteParam(String),
+    #[error(
+        "The query's parameter '{0}' isn't a parameter of the route, so the handler can't bind it"
+    )]
+    UnboundQueryParam(String),
+    #[error(
+        "Keyset pagination needs a single SELECT from tables that selects the id of its records, \
+         without ORDER BY, GROUP, SPLIT, LIMIT or START of its own"
+    )]
+    UnsupportedKeyset,
+    /// `encrypt(...)` or `transform(...)` fields the query doesn't select, reported at each path.
+    #[error("{0}")]
+    UnknownCodecField(syn::Error),
 }
 
 pub fn generate_code(
@@ -27,46 +91,515 @@ pub fn generate_code(
     schema: Query,
 ) -> Result<TokenStream, QueryBuilderError> {
     let query_str = input.query.value();
-    let parsed_query = surrealdb::sql::parse(&query_str)?;
+    let mut parsed_query = select_group_all(&scope_query(
+        &schema,
+        &surrealdb::sql::parse(&query_str)?,
+        &input.options.scope_filters,
+    )?);
+    // Traversals run with their edges, but are typed from the query as written.
+    let (executed_query, edge_fields) = if input.options.with_edges {
+        let schema_ast = analyze_schema(schema.clone()).map_err(errors::AnalysisError::from)?;
+        include_edges(&schema_ast, &parsed_query)
+    } else {
+        (parsed_query.clone(), vec![])
+    };
+    let scoped_query = executed_query.to_string();
+    let query_hash = query_hash(&schema, &executed_query)?;
+
+    if !input.options.allow_ddl {
+        if let Some(stmt) = ddl_statements(&parsed_query).first() {
+            return Err(QueryBuilderError::DdlNotAllowed(stmt.to_string()));
+        }
+    }
+
+    let naming = type_naming(schema.clone(), &input.options)?;
+    let models = match &input.options.shared_types {
+        Some(path) => match analyze_table_models(schema.clone(), &input.options)? {
+            TypeAST::Object(tables) => Some(SharedModels {
+                path,
+                tables,
+                naming: &naming,
+            }),
+            _ => None,
+        },
+        None => None,
+    };
+    let models = models.as_ref();
+
+    let reads_only = if input.options.read_only {
+        if let Some(stmt) = writing_statements(&parsed_query).first() {
+            return Err(QueryBuilderError::WritesWhenReadOnly(stmt.to_string()));
+        }
+        true
+    } else {
+        is_read_only(&parsed_query)
+    };
+    let access = match reads_only {
+        true => quote! { surrealix::QueryAccess::Read },
+        false => quote! { surrealix::QueryAccess::Write },
+    };
+
+    check_search_calls(&schema, &parsed_query)?;
+    let mut warnings = lint_query(&schema, &parsed_query, &input.options.large_tables)
+        .iter()
+        .map(|lint| generate_warning(&lint.to_string()))
+        .collect::<Vec<_>>();
+
+    let index_hints = index_hints(&parsed_query).into_iter().map(|hint| {
+        let statement = hint.statement;
+        let table = &hint.table;
+        let indexes = match &hint.indexes {
+            Some(indexes) => quote! { Some(&[#(#indexes),*]) },
+            None => quote! { None },
+        };
+        quote! {
+            surrealix::IndexHint {
+                statement: #statement,
+                table: #table,
+                indexes: #indexes,
+            }
+        }
+    });
+
+    let tables = read_tables(&parsed_query);
+    let limited_query = limit_rows(&executed_query).map(|query| query.to_string());
+    let count_params = count_params(&parsed_query);
+    let query_params = query_params(&schema, &parsed_query);
+    let schema_types = analyze_schema(schema.clone()).map_err(errors::AnalysisError::from)?;
+    let param_types = param_types(&schema_types, &parsed_query);
+    let param_constraints = param_constraints(&schema_types, &parsed_query);
+    let keyset_query = match input.options.keyset {
+        true => Some(
+            keyset_query(&executed_query)
+                .ok_or(QueryBuilderError::UnsupportedKeyset)?
+                .to_string(),
+        ),
+        false => None,
+    };
+
+    let dynamic_fetches = if input.options.lenient_fetch {
+        strip_dynamic_fetches(&mut parsed_query)
+    } else {
+        vec![]
+    };
+    let schema_ast = if dynamic_fetches.is_empty() && edge_fields.is_empty() {
+        None
+    } else {
+        Some(analyze_schema(schema.clone()).map_err(errors::AnalysisError::from)?)
+    };
+
+    let mut analyzed = analyze_tables(schema, parsed_query, &input.options.tables)?;
+    if let Some(schema_ast) = &schema_ast {
+        for index in dynamic_fetches {
+            if let Some(StatementType::Fixed(ast)) = analyzed.get_mut(index) {
+                degrade_links(ast, schema_ast);
+            }
+        }
+    }
+    if let Some(schema_ast) = &schema_ast {
+        for field in &edge_fields {
+            if let Some(StatementType::Fixed(ast)) = analyzed.get_mut(field.statement) {
+                type_edges(ast, schema_ast, field);
+            }
+        }
+    }
+    if input.options.drop_unselectable {
+        for statement in &mut analyzed {
+            match statement {
+                StatementType::Fixed(ast) | StatementType::Live(ast) => remove_unselectable(ast),
+                StatementType::PerTable(results) => results
+                    .iter_mut()
+                    .for_each(|(_, ast)| remove_unselectable(ast)),
+            }
+        }
+    }
+
+    // Pages continue after the id of their last row, so the rows must have one.
+    if keyset_query.is_some() {
+        let paged = match analyzed.as_slice() {
+            [StatementType::Fixed(TypeAST::Array(rows))] => {
+                matches!(&rows.0, TypeAST::Object(obj) if root_table(obj).is_some())
+            }
+            _ => false,
+        };
+        if !paged {
+            return Err(QueryBuilderError::UnsupportedKeyset);
+        }
+    }
 
-    let analyzed = analyze(schema, parsed_query)?;
+    // A misspelt or unselected path would otherwise leave the field silently unencrypted.
+    let mut sources = vec![];
+    for statement in &analyzed {
+        match statement {
+            StatementType::Fixed(ast) | StatementType::Live(ast) => {
+                selected_sources(ast, &mut sources)
+            }
+            StatementType::PerTable(results) => results
+                .iter()
+                .for_each(|(_, ast)| selected_sources(ast, &mut sources)),
+        }
+    }
+    let unmatched = input
+        .options
+        .codec_fields
+        .iter()
+        .filter(|field| !sources.iter().any(|source| field.matches(source)))
+        .map(|field| {
+            syn::Error::new(
+                field.span,
+                format!("'{}' matches no field the query selects", field.path),
+            )
+        })
+        .reduce(|mut errors, error| {
+            errors.combine(error);
+            errors
+        });
+    if let Some(errors) = unmatched {
+        return Err(QueryBuilderError::UnknownCodecField(errors));
+    }
+
+    let mut deprecated = vec![];
+    for statement in &analyzed {
+        match statement {
+            StatementType::Fixed(ast) | StatementType::Live(ast) => {
+                deprecated_fields(ast, "", &mut deprecated)
+            }
+            StatementType::PerTable(results) => results
+                .iter()
+                .for_each(|(_, ast)| deprecated_fields(ast, "", &mut deprecated)),
+        }
+    }
+    deprecated.sort();
+    deprecated.dedup();
+    warnings.extend(deprecated.iter().map(|(path, note)| {
+        let message = match note.as_str() {
+            "" => format!("The query selects '{}', which is deprecated.", path),
+            note => format!(
+                "The query selects '{}', which is deprecated: {}",
+                path, note
+            ),
+        };
+        generate_warning(&message)
+    }));
 
     let mut type_definitions = Vec::new();
     let mut type_aliases = Vec::new();
     let mut generated_types = HashMap::new();
+    // The type of the results of a query of a single statement, for the handler serving them.
+    let mut response_type = None;
 
-    for (index, ast) in analyzed.iter().enumerate() {
-        let (type_name, type_def) = generate_type_definition(ast, &mut generated_types);
-        type_definitions.extend(type_def);
-
+    for (index, statement) in analyzed.iter().enumerate() {
         let alias_name = if analyzed.len() == 1 {
             format_ident!("QueryResult")
         } else {
             format_ident!("QueryResult{}", index + 1)
         };
 
-        let alias = quote! {
-            pub type #alias_name = #type_name;
+        let ast = match statement {
+            StatementType::Fixed(ast) => ast,
+            // The statement itself returns the id of the live query, so the result is the
+            // stream of its notifications instead.
+            StatementType::Live(ast) => {
+                let (type_name, type_def) = generate_type_definition(
+                    ast,
+                    &input.options,
+                    &naming,
+                    models,
+                    0,
+                    &mut generated_types,
+                );
+                type_definitions.extend(type_def);
+                type_aliases.push(quote! {
+                    pub type #alias_name = surrealix::live::LiveStream<#type_name>;
+                });
+                // Notifications are matched to the records they change by id.
+                if let TypeAST::Object(obj) = ast {
+                    if root_table(obj).is_some() {
+                        let reduce_name = match analyzed.len() {
+                            1 => format_ident!("reduce"),
+                            _ => format_ident!("reduce{}", index + 1),
+                        };
+                        let reduce_tests =
+                            generate_reduce_tests(obj, &type_name, &reduce_name, &input.options);
+                        type_aliases.push(quote! {
+                            /// Applies a notification of the live query, or the change it makes,
+                            /// to the records it has matched so far, as `surrealix::live::reduce`
+                            /// does.
+                            pub fn #reduce_name(
+                                state: &mut Vec<#type_name>,
+                                change: impl Into<surrealix::live::Change<#type_name>>,
+                            ) {
+                                surrealix::live::reduce(state, change)
+                            }
+
+                            #reduce_tests
+                        });
+                    }
+                }
+                continue;
+            }
+            StatementType::PerTable(results) => {
+                response_type = Some(quote! { #alias_name });
+                type_aliases.push(generate_table_enum(
+                    &alias_name,
+                    results,
+                    &input.options,
+                    &naming,
+                    models,
+                    &mut type_definitions,
+                    &mut generated_types,
+                ));
+                continue;
+            }
+        };
+        let (type_name, type_def) = generate_type_definition(
+            ast,
+            &input.options,
+            &naming,
+            models,
+            0,
+            &mut generated_types,
+        );
+        type_definitions.extend(type_def);
+        response_type = Some(type_name.clone());
+
+        let report = if input.options.permissions_report {
+            generate_permissions_report(&alias_name, ast)
+        } else {
+            quote! {}
         };
-        type_aliases.push(alias);
+        type_aliases.push(quote! {
+            #report
+            pub type #alias_name = #type_name;
+        });
     }
 
-    let module_name = format_ident!("adult_users");
-    let alias_name = format_ident!("AdultUsers");
+    let (module_name, alias_name) = match input.options.schema_version {
+        Some(v) => (
+            format_ident!("adult_users_v{}", v),
+            format_ident!("AdultUsersV{}", v),
+        ),
+        None => (format_ident!("adult_users"), format_ident!("AdultUsers")),
+    };
+
+    let takes = analyzed
+        .iter()
+        .enumerate()
+        .map(|(index, statement)| match statement {
+            StatementType::Live(_) => quote! { surrealix::live::stream(&mut response, #index)? },
+            _ => quote! { surrealix::execute::take(&mut response, #index)? },
+        });
+    let (result_type, take_results) = if analyzed.len() == 1 {
+        (quote! { #module_name::QueryResult }, quote! { #(#takes)* })
+    } else {
+        let types = (1..=analyzed.len()).map(|n| format_ident!("QueryResult{}", n));
+        (
+            quote! { (#(#module_name::#types,)*) },
+            quote! { (#(#takes,)*) },
+        )
+    };
+
+    // Only a single SELECT of many rows can be capped.
+    let limited = match (&limited_query, analyzed.as_slice()) {
+        (Some(limited_query), [StatementType::Fixed(TypeAST::Array(_))]) => Some(limited_query),
+        _ => None,
+    };
+    let limited_const = limited.map(|limited_query| {
+        quote! {
+            /// The query with its LIMIT capped at the row limit of `execute_limited`.
+            pub const LIMITED_QUERY: &str = #limited_query;
+        }
+    });
+    let execute_limited = limited.map(|_| {
+        quote! {
+            /// Runs the query, returning no more than `options.max_rows` rows and whether there
+            /// were more. The cap is applied by the database, so the rest are never sent.
+            pub async fn execute_limited<C: surrealix::surrealdb::Connection>(
+                db: &surrealix::surrealdb::Surreal<C>,
+                options: impl Into<surrealix::ExecuteOptions>,
+            ) -> Result<
+                surrealix::Truncated<<#module_name::QueryResult as IntoIterator>::Item>,
+                surrealix::Error,
+            > {
+                let options = options.into();
+                let max_rows = options.max_rows;
+                surrealix::execute::run(&options, async {
+                    let mut response = match max_rows {
+                        Some(max) => {
+                            db.query(#module_name::LIMITED_QUERY)
+                                .bind((#MAX_ROWS_PARAM, max.saturating_add(1)))
+                                .await?
+                        }
+                        None => db.query(#module_name::QUERY).await?,
+                    };
+                    surrealix::execute::take_rows(&mut response, 0, max_rows)
+                })
+                .await
+            }
+        }
+    });
+
+    let keyset_const = keyset_query.as_ref().map(|keyset_query| {
+        quote! {
+            /// The query paging through its rows by id, for `execute_page`.
+            pub const KEYSET_QUERY: &str = #keyset_query;
+        }
+    });
+    let execute_page = keyset_query.as_ref().map(|_| {
+        quote! {
+            /// Runs the query for a page of at most `page_size` rows ordered by id, continuing
+            /// after the cursor of the previous page, or from the first row without one.
+            pub async fn execute_page<C: surrealix::surrealdb::Connection>(
+                db: &surrealix::surrealdb::Surreal<C>,
+                after: Option<&surrealix::Cursor<<#module_name::QueryResult as IntoIterator>::Item>>,
+                page_size: usize,
+                options: impl Into<surrealix::ExecuteOptions>,
+            ) -> Result<
+                surrealix::Page<<#module_name::QueryResult as IntoIterator>::Item>,
+                surrealix::Error,
+            > {
+                surrealix::execute::run(&options.into(), async {
+                    let mut response = db
+                        .query(#module_name::KEYSET_QUERY)
+                        .bind((#AFTER_PARAM, after.map(surrealix::Cursor::record)))
+                        .bind((#PAGE_SIZE_PARAM, page_size.saturating_add(1)))
+                        .await?;
+                    surrealix::keyset::take_page(&mut response, 0, page_size)
+                })
+                .await
+            }
+        }
+    });
+
+    // The results of a LIVE SELECT are a stream, which can't be cached.
+    let memoizable = (!analyzed
+        .iter()
+        .any(|statement| matches!(statement, StatementType::Live(_))))
+    .then(|| {
+        quote! {
+            impl surrealix::Memoizable for #alias_name {
+                type Output = #result_type;
+                const QUERY: &'static str = #module_name::QUERY;
+                const QUERY_HASH: u64 = #module_name::QUERY_HASH;
+                const TABLES: &'static [&'static str] = #module_name::TABLES;
+
+                fn take(
+                    mut response: surrealix::surrealdb::Response,
+                ) -> Result<Self::Output, surrealix::Error> {
+                    Ok(#take_results)
+                }
+            }
+        }
+    });
+
+    let params_struct = generate_params_struct(&query_params, &count_params, |name| {
+        let (ty, bind) = param_type(param_types.get(name), &naming, models);
+        let constraints = param_constraints.get(name).map_or(&[][..], Vec::as_slice);
+        let validation = validation_attributes(param_types.get(name), constraints);
+        (ty, quote! { #bind #validation })
+    });
+    let execute_with = params_struct.as_ref().map(|_| {
+        quote! {
+            /// Runs the query with its parameters bound, like `execute`.
+            pub async fn execute_with<C: surrealix::surrealdb::Connection>(
+                db: &surrealix::surrealdb::Surreal<C>,
+                params: #module_name::Params,
+                options: impl Into<surrealix::ExecuteOptions>,
+            ) -> Result<#result_type, surrealix::Error> {
+                surrealix::execute::run(&options.into(), async {
+                    let mut response = db.query(#module_name::QUERY).bind(params).await?;
+                    Ok(#take_results)
+                })
+                .await
+            }
+        }
+    });
+
+    let handler = match &input.options.axum_route {
+        Some(route) => Some(generate_handler(
+            route,
+            response_type.filter(|_| analyzed.len() == 1).as_ref(),
+            &query_params,
+            |name| {
+                path_param_type(
+                    name,
+                    &count_params,
+                    &param_types,
+                    &param_constraints,
+                    &naming,
+                    models,
+                )
+            },
+            reads_only,
+            &take_results,
+        )?),
+        None => None,
+    };
 
     let generated_code = quote! {
         pub struct #alias_name;
 
         impl #alias_name {
-            pub fn execute() -> Result<QueryResult, surrealix::Error> {
-                // Implementation of execute method
-                todo!("Implement execute method")
+            /// Whether the query only reads, so it can run on a read replica.
+            pub const ACCESS: surrealix::QueryAccess = #access;
+
+            /// Runs the query, giving up with `surrealix::Error::Cancelled` once the deadline
+            /// passes or the cancellation token is cancelled.
+            pub async fn execute<C: surrealix::surrealdb::Connection>(
+                db: &surrealix::surrealdb::Surreal<C>,
+                options: impl Into<surrealix::ExecuteOptions>,
+            ) -> Result<#result_type, surrealix::Error> {
+                surrealix::execute::run(&options.into(), async {
+                    let mut response = db.query(#module_name::QUERY).await?;
+                    Ok(#take_results)
+                })
+                .await
+            }
+
+            /// Runs the query on the connection the router picks for it: a read replica if the
+            /// query only reads, otherwise the primary.
+            pub async fn execute_routed<C: surrealix::surrealdb::Connection>(
+                router: &surrealix::Router<C>,
+                options: impl Into<surrealix::ExecuteOptions>,
+            ) -> Result<#result_type, surrealix::Error> {
+                Self::execute(router.route(Self::ACCESS), options).await
             }
+
+            #execute_with
+
+            #execute_limited
+
+            #execute_page
         }
 
+        #memoizable
+
+        #(#warnings)*
+
         pub mod #module_name {
             use super::*;
 
+            /// The query as it is executed, including any scope filters.
+            pub const QUERY: &str = #scoped_query;
+
+            /// A hash of the query's canonical form, which queries differing only in formatting,
+            /// field order or redundant aliases share, e.g. to key a cache of results on.
+            pub const QUERY_HASH: u64 = #query_hash;
+
+            /// The index hints given by the WITH clauses of the query's SELECTs.
+            pub const INDEX_HINTS: &[surrealix::IndexHint] = &[#(#index_hints),*];
+
+            /// The tables the query reads from, whose changes may change its results.
+            pub const TABLES: &[&str] = &[#(#tables),*];
+
+            #limited_const
+
+            #keyset_const
+
+            #params_struct
+
+            #handler
+
             #(#type_definitions)*
 
             #(#type_aliases)*
@@ -76,99 +609,2676 @@ pub fn generate_code(
     Ok(generated_code.into())
 }
 
-fn generate_type_definition(
-    ast: &TypeAST,
-    generated_types: &mut HashMap<String, TokenStream2>,
-) -> (TokenStream2, Vec<TokenStream2>) {
+/// Generates `Params`, the parameters of the query as a struct `execute_with` binds, so a set
+/// of them can be built once and logged, persisted or sent to another service as a unit.
+///
+/// Parameters holding row counts are `usize`, others are any value. The struct derives the
+/// validation crates' `Validate` for the `garde` and `validator` features, checking the
+/// parameters against the ASSERT constraints of the fields they are set to or checked with.
+fn generate_params_struct(
+    query_params: &[String],
+    count_params: &[String],
+    param_type: impl Fn(&str) -> (TokenStream2, TokenStream2),
+) -> Option<TokenStream2> {
+    if query_params.is_empty() {
+        return None;
+    }
+    let fields = query_params.iter().map(|name| {
+        let field = to_valid_rust_identifier(name);
+        let (ty, attrs) = match count_params.contains(name) {
+            true => (quote! { usize }, validation_attributes(None, &[])),
+            false => param_type(name),
+        };
+        quote! {
+            #[serde(rename = #name)] #attrs
+            pub #field: #ty,
+        }
+    });
+    let validation_derives = validation_derives();
+    Some(quote! {
+        /// The parameters of the query, for `execute_with`.
+        #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+        #validation_derives
+        pub struct Params {
+            #(#fields)*
+        }
+    })
+}
+
+/// The Rust type of a query parameter of type `ast`, as `param_types` infers it from the fields
+/// it is compared with, and the serde attribute binding it as that type.
+///
+/// Record ids are bound as records rather than strings. Parameters of other types, which don't
+/// bind as themselves from JSON, or whose type isn't known, are `serde_json::Value`.
+fn param_type(
+    ast: Option<&TypeAST>,
+    naming: &TypeNaming,
+    models: Option<&SharedModels>,
+) -> (TokenStream2, TokenStream2) {
+    let scalar = |ast: &TypeAST| match ast {
+        TypeAST::Scalar(
+            scalar @ (ScalarType::String
+            | ScalarType::Integer
+            | ScalarType::Number
+            | ScalarType::Float
+            | ScalarType::Boolean),
+        ) => Some(scalar_type_to_rust_type(scalar)),
+        _ => None,
+    };
     match ast {
-        TypeAST::Object(obj) => generate_object_definition(obj, generated_types),
-        TypeAST::Array(inner) => {
-            let (inner_type, inner_defs) = generate_type_definition(&inner.0, generated_types);
-            (quote! { Vec<#inner_type> }, inner_defs)
+        Some(TypeAST::Record(table)) => {
+            let link = link_type(table, naming, models);
+            (
+                link,
+                quote! { #[serde(serialize_with = "surrealix::types::serialize_thing")] },
+            )
         }
-        TypeAST::Option(inner) => {
-            let (inner_type, inner_defs) = generate_type_definition(inner, generated_types);
-            (quote! { Option<#inner_type> }, inner_defs)
+        Some(TypeAST::Array(items)) => match &items.0 {
+            TypeAST::Record(table) => {
+                let link = link_type(table, naming, models);
+                (
+                    quote! { Vec<#link> },
+                    quote! { #[serde(serialize_with = "surrealix::types::serialize_things")] },
+                )
+            }
+            items => match scalar(items) {
+                Some(item) => (quote! { Vec<#item> }, quote! {}),
+                None => (quote! { serde_json::Value }, quote! {}),
+            },
+        },
+        Some(ast) => (
+            scalar(ast).unwrap_or_else(|| quote! { serde_json::Value }),
+            quote! {},
+        ),
+        None => (quote! { serde_json::Value }, quote! {}),
+    }
+}
+
+/// Generates an Axum handler serving the results of the query as JSON at `route`, along with
+/// `ROUTE`, the route in Axum's syntax, and `route()`, the handler on the method serving it.
+///
+/// Each path parameter is bound to the query parameter of the same name, with the type and
+/// attributes `path_param` gives it, so every other parameter of the query is an error. The
+/// database is taken from the router's state. Queries that only read are served on GET, others
+/// on POST.
+fn generate_handler(
+    route: &str,
+    response_type: Option<&TokenStream2>,
+    query_params: &[String],
+    path_param: impl Fn(&str) -> (TokenStream2, TokenStream2),
+    reads_only: bool,
+    take_results: &TokenStream2,
+) -> Result<TokenStream2, QueryBuilderError> {
+    let Some(response_type) = response_type else {
+        return Err(QueryBuilderError::UnsupportedHandler);
+    };
+    let params = route_params(route);
+    if let Some(param) = params.iter().find(|param| !query_params.contains(param)) {
+        return Err(QueryBuilderError::UnknownRouteParam(param.clone()));
+    }
+    if let Some(param) = query_params.iter().find(|param| !params.contains(param)) {
+        return Err(QueryBuilderError::UnboundQueryParam(param.clone()));
+    }
+    let method = match reads_only {
+        true => format_ident!("get"),
+        false => format_ident!("post"),
+    };
+    let axum_route = axum_route(route);
+
+    let (params_struct, path, bind) = if params.is_empty() {
+        (None, None, None)
+    } else {
+        let fields = params.iter().map(|name| {
+            let field = to_valid_rust_identifier(name);
+            let (ty, attrs) = path_param(name);
+            quote! { #[serde(rename = #name)] #attrs pub #field: #ty }
+        });
+        let into_params = cfg!(feature = "utoipa").then(|| {
+            quote! {
+                #[derive(utoipa::IntoParams)]
+                #[into_params(parameter_in = Path)]
+            }
+        });
+        let validation_derives = validation_derives();
+        (
+            Some(quote! {
+                /// The path parameters of `handler`, bound to the query parameters of the same names.
+                #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+                #validation_derives
+                #into_params
+                pub struct PathParams {
+                    #(#fields,)*
+                }
+            }),
+            Some(quote! {
+                surrealix::axum::extract::Path(params): surrealix::axum::extract::Path<PathParams>,
+            }),
+            Some(quote! { .bind(params) }),
+        )
+    };
+    let openapi = cfg!(feature = "utoipa").then(|| {
+        let params = params_struct.as_ref().map(|_| quote! { params(PathParams), });
+        quote! {
+            #[utoipa::path(#method, path = #route, #params responses((status = 200, body = #response_type)))]
         }
-        TypeAST::Scalar(scalar) => (scalar_type_to_rust_type(scalar), vec![]),
-        TypeAST::Record(table) => {
-            let type_name = format_ident!("{}", table.to_case(Case::Pascal));
-            (quote! { RecordLink<#type_name> }, vec![])
+    });
+
+    Ok(quote! {
+        /// The route `handler` serves, in Axum's syntax.
+        pub const ROUTE: &str = #axum_route;
+
+        #params_struct
+
+        /// Runs the query for a request, responding with its results as JSON.
+        #openapi
+        pub async fn handler<C: surrealix::surrealdb::Connection>(
+            surrealix::axum::extract::State(db): surrealix::axum::extract::State<
+                surrealix::surrealdb::Surreal<C>,
+            >,
+            #path
+        ) -> Result<surrealix::axum::Json<QueryResult>, surrealix::Error> {
+            let mut response = db.query(QUERY)#bind.await?;
+            Ok(surrealix::axum::Json(#take_results))
+        }
+
+        /// `handler` on the method it is served on, for `Router::route(ROUTE, route())`.
+        pub fn route<C, S>() -> surrealix::axum::routing::MethodRouter<S>
+        where
+            C: surrealix::surrealdb::Connection,
+            S: Clone + Send + Sync + 'static,
+            surrealix::surrealdb::Surreal<C>: surrealix::axum::extract::FromRef<S>,
+        {
+            surrealix::axum::routing::#method(handler::<C>)
+        }
+    })
+}
+
+/// The Rust type of a path parameter bound to the query parameter `name`, and its attributes:
+/// a `usize` if it counts rows, the type `param_types` infers for it if it is a record id or a
+/// scalar, and otherwise the string it arrives as. Strings are validated against the
+/// constraints `param_constraints` finds for them.
+fn path_param_type(
+    name: &str,
+    count_params: &[String],
+    param_types: &BTreeMap<String, TypeAST>,
+    param_constraints: &BTreeMap<String, Vec<Constraint>>,
+    naming: &TypeNaming,
+    models: Option<&SharedModels>,
+) -> (TokenStream2, TokenStream2) {
+    let skip = validation_attributes(None, &[]);
+    if count_params.iter().any(|param| param == name) {
+        return (quote! { usize }, skip);
+    }
+    match param_types.get(name) {
+        Some(ast @ (TypeAST::Record(_) | TypeAST::Scalar(_))) => {
+            let (ty, bind) = param_type(Some(ast), naming, models);
+            let docs = cfg!(feature = "utoipa")
+                .then(|| json_form_type(ast, false))
+                .flatten()
+                .map(|ty| quote! { #[param(value_type = #ty)] });
+            let constraints = param_constraints.get(name).map_or(&[][..], Vec::as_slice);
+            let validation = validation_attributes(Some(ast), constraints);
+            (ty, quote! { #bind #docs #validation })
         }
-        TypeAST::Union(_) => (quote! { serde_json::Value }, vec![]),
+        _ => (quote! { String }, skip),
     }
 }
 
-fn generate_object_definition(
-    obj: &ObjectType,
-    generated_types: &mut HashMap<String, TokenStream2>,
-) -> (TokenStream2, Vec<TokenStream2>) {
-    let mut type_definitions = Vec::new();
-    let type_name = generate_object_name(obj);
+/// The parameters of a route, e.g. `["id"]` for `/users/{id}`.
+fn route_params(route: &str) -> Vec<String> {
+    route
+        .split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+        .map(String::from)
+        .collect()
+}
 
-    if let Some(existing_def) = generated_types.get(&type_name.to_string()) {
-        return (existing_def.clone(), type_definitions);
+/// A route in Axum's syntax, e.g. `/users/:id` for `/users/{id}`.
+fn axum_route(route: &str) -> String {
+    route
+        .split('/')
+        .map(
+            |segment| match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some(param) => format!(":{}", param),
+                None => segment.to_string(),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Generates the canonical model of every table, which `shared_types` invocations refer to.
+pub fn generate_table_models(
+    schema: Query,
+    options: &BuildQueryOptions,
+) -> Result<TokenStream, QueryBuilderError> {
+    let TypeAST::Object(tables) = analyze_table_models(schema.clone(), options)? else {
+        return Ok(TokenStream::new());
+    };
+    let naming =
+        TypeNaming::new(options.type_names, tables.clone()).with_version(options.schema_version);
+
+    let mut type_definitions = Vec::new();
+    let mut generated_types = HashMap::new();
+    for (table, table_info) in &tables.fields {
+        match &table_info.ast {
+            TypeAST::Object(obj) if !obj.fields.is_empty() => {
+                let (_, defs) = generate_object_definition(
+                    obj,
+                    options,
+                    &naming,
+                    None,
+                    0,
+                    &mut generated_types,
+                );
+                type_definitions.extend(defs);
+                type_definitions.extend(generate_upsert_many(table, obj, &naming));
+                type_definitions.push(generate_count_and_exists(
+                    table, obj, &naming, &schema, options,
+                )?);
+            }
+            // Tables without defined fields can hold anything.
+            _ => {
+                let name = naming.table(table);
+                let map_type = dynamic_object_type(options.dynamic_objects);
+                type_definitions.push(quote! { pub type #name = #map_type; });
+            }
+        }
     }
 
-    let fields = obj.fields.iter().map(|(name, field_info)| {
-        let field_name = format_ident!("{}", name);
-        let (field_type, mut field_defs) =
-            generate_type_definition(&field_info.ast, generated_types);
-        type_definitions.append(&mut field_defs);
-        quote! { pub #field_name: #field_type }
+    let relation_methods = relations(&tables).into_iter().filter_map(|relation| {
+        let name = options.relation_name(&relation.edge);
+        generate_relation_methods(&relation, name, &tables, &naming)
     });
 
-    let type_def = quote! {
-        #[derive(Debug, serde::Serialize, serde::Deserialize)]
-        pub struct #type_name {
-            #(#fields,)*
+    Ok(quote! {
+        #(#type_definitions)*
+        #(#relation_methods)*
+    }
+    .into())
+}
+
+/// How many records `upsert_many` sends in one statement.
+const UPSERT_CHUNK_SIZE: usize = 1000;
+
+/// Generates `upsert_many` on a table's model, which inserts records or updates the ones whose
+/// id already exists, using `INSERT ... ON DUPLICATE KEY UPDATE`.
+///
+/// Records are matched by their `id`, so the models of tables without defined fields, which
+/// hold anything, get no method.
+fn generate_upsert_many(
+    table: &str,
+    obj: &ObjectType,
+    naming: &TypeNaming,
+) -> Option<TokenStream2> {
+    if !obj.fields.contains_key("id") {
+        return None;
+    }
+    let name = naming.table(table);
+
+    let mut fields: Vec<_> = obj.fields.keys().filter(|field| *field != "id").collect();
+    fields.sort();
+    let updates: Vec<_> = fields
+        .into_iter()
+        .map(|field| {
+            let field = Idiom::from(field.as_str()).to_string();
+            format!("{} = $input.{}", field, field)
+        })
+        .collect();
+    // A table with only ids has nothing to update.
+    let insert = if updates.is_empty() {
+        format!("INSERT IGNORE INTO {} $records", Table::from(table))
+    } else {
+        format!(
+            "INSERT INTO {} $records ON DUPLICATE KEY UPDATE {}",
+            Table::from(table),
+            updates.join(", ")
+        )
+    };
+
+    Some(quote! {
+        impl #name {
+            /// Inserts `records`, replacing the fields of those whose id already exists, and
+            /// returns them as stored. Large batches are sent in chunks, each in a statement of
+            /// its own, so a failing chunk leaves the earlier ones written.
+            pub async fn upsert_many<C: surrealix::surrealdb::Connection>(
+                db: &surrealix::surrealdb::Surreal<C>,
+                records: &[Self],
+            ) -> Result<Vec<Self>, surrealix::surrealdb::Error> {
+                let mut stored = Vec::with_capacity(records.len());
+                for chunk in records.chunks(#UPSERT_CHUNK_SIZE) {
+                    let mut response = db.query(#insert).bind(("records", chunk)).await?;
+                    stored.extend(response.take::<Vec<Self>>(0)?);
+                }
+                Ok(stored)
+            }
         }
+    })
+}
+
+/// Generates `count` on a table's model, counting the records whose fields equal those set in
+/// a filter (`User::count(&db, json!({ "active": true }))`), and `exists`, checking for the record
+/// of an id (`User::exists(&db, id)`).
+///
+/// Both queries are scoped like any other SELECT on the table, and analyzed against the schema.
+fn generate_count_and_exists(
+    table: &str,
+    obj: &ObjectType,
+    naming: &TypeNaming,
+    schema: &Query,
+    options: &BuildQueryOptions,
+) -> Result<TokenStream2, QueryBuilderError> {
+    let name = naming.table(table);
+    let table_name = Table::from(table).to_string();
+
+    let mut fields: Vec<_> = obj.fields.keys().filter(|field| *field != "id").collect();
+    fields.sort();
+    // Fields the filter doesn't set are NONE in it, and count every record.
+    let predicates: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let field = Idiom::from(field.as_str()).to_string();
+            format!(
+                "($filter.{} = NONE OR {} = $filter.{})",
+                field, field, field
+            )
+        })
+        .collect();
+    let cond = match predicates.is_empty() {
+        true => String::new(),
+        false => format!(" WHERE {}", predicates.join(" AND ")),
     };
+    let count = analyzed_select(
+        schema,
+        &format!("SELECT count() FROM {}{} GROUP ALL", table_name, cond),
+        options,
+    )?
+    .to_string();
+    let count_doc = format!(
+        " The number of `{}` records whose fields equal those set in `filter`, e.g. `()` to \
+         count them all. Filtering on fields the table doesn't have fails.",
+        table
+    );
 
-    type_definitions.push(type_def.clone());
-    generated_types.insert(type_name.to_string(), quote! { #type_name });
+    let mut exists = analyzed_select(
+        schema,
+        &format!("SELECT 1 FROM ONLY {}", table_name),
+        options,
+    )?;
+    // Analyzed from the table, then run on the record alone rather than a scan.
+    if let Some(Statement::Select(select)) = exists.0 .0.first_mut() {
+        select.what.0 = vec![Value::Param(Param::from("record"))];
+    }
+    let exists = exists.to_string();
+    let exists_doc = format!(" Whether the `{}` record `id` exists.", table);
 
-    (quote! { #type_name }, type_definitions)
+    Ok(quote! {
+        impl #name {
+            #[doc = #count_doc]
+            pub async fn count<C: surrealix::surrealdb::Connection>(
+                db: &surrealix::surrealdb::Surreal<C>,
+                filter: impl serde::Serialize,
+            ) -> Result<u64, surrealix::surrealdb::Error> {
+                let filter = surrealix::filter::field_filter(&[#(#fields),*], filter)?;
+                let counted: Option<std::collections::HashMap<String, u64>> = db
+                    .query(#count)
+                    .bind(("filter", filter))
+                    .await?
+                    .take(0)?;
+                Ok(counted.and_then(|row| row.get("count").copied()).unwrap_or(0))
+            }
+
+            #[doc = #exists_doc]
+            pub async fn exists<C: surrealix::surrealdb::Connection>(
+                db: &surrealix::surrealdb::Surreal<C>,
+                id: impl Into<surrealix::RecordId<Self>>,
+            ) -> Result<bool, surrealix::surrealdb::Error> {
+                let id = id.into();
+                let found: Option<std::collections::HashMap<String, i64>> = db
+                    .query(#exists)
+                    .bind(("record", surrealix::surrealdb::sql::Thing::from(&id)))
+                    .await?
+                    .take(0)?;
+                Ok(found.is_some())
+            }
+        }
+    })
 }
 
-fn generate_object_name(obj: &ObjectType) -> Ident {
-    let path = obj
-        .fields
-        .values()
-        .next()
-        .map(|field| field.meta.original_path.clone())
-        .unwrap_or_else(|| vec!["Unknown".to_string()]);
-
-    let name = if path.len() > 1 {
-        if path[0] == path[1] {
-            // This is the root object, just use the table name
-            path[0].clone()
-        } else {
-            // For nested objects, use all segments except the last one
-            path[..path.len() - 1].join("_")
+/// A SELECT on a table as the models run it: scoped, selecting ONLY the row of a GROUP ALL,
+/// and checked against the schema.
+fn analyzed_select(
+    schema: &Query,
+    sql: &str,
+    options: &BuildQueryOptions,
+) -> Result<Query, QueryBuilderError> {
+    let query = select_group_all(&scope_query(
+        schema,
+        &surrealdb::sql::parse(sql)?,
+        &options.scope_filters,
+    )?);
+    analyze_tables(schema.clone(), query.clone(), &options.tables)?;
+    Ok(query)
+}
+
+/// Generates methods on the model of a relation's `from` table for traversing the relation
+/// (`user.friend(&db)`), creating edges (`user.add_friend(&db, other, content)`) and deleting
+/// them (`user.remove_friend(&db, other)`), named after `name`, the edge table's or the one the
+/// `relation` option gives it.
+///
+/// Tables without defined fields hold anything and get no model, so they get no methods either.
+fn generate_relation_methods(
+    relation: &Relation,
+    name: &str,
+    tables: &ObjectType,
+    naming: &TypeNaming,
+) -> Option<TokenStream2> {
+    let from = match &tables.fields.get(&relation.from)?.ast {
+        TypeAST::Object(obj) if !obj.fields.is_empty() => naming.table(&relation.from),
+        _ => return None,
+    };
+    tables.fields.get(&relation.to)?;
+    let to = naming.table(&relation.to);
+    let edge = naming.table(&relation.edge);
+
+    let list_name = to_valid_rust_identifier(name);
+    let add_name = to_valid_rust_identifier(&format!("add_{}", name));
+    let remove_name = to_valid_rust_identifier(&format!("remove_{}", name));
+
+    let edge_table = Table::from(relation.edge.as_str()).to_string();
+    let to_table = Table::from(relation.to.as_str()).to_string();
+    let select = format!("SELECT * FROM $record->{}->{}", edge_table, to_table);
+    let relate = format!("RELATE $from->{}->$to CONTENT $content", edge_table);
+    let delete = format!("DELETE $from->{} WHERE out = $to RETURN BEFORE", edge_table);
+    let list_doc = format!(
+        " The `{}` records this one links to, i.e. `->{}->{}`.",
+        relation.to, relation.edge, relation.to
+    );
+    let add_doc = format!(
+        " Links this record to `to` with a new `{}` edge holding `content`.",
+        relation.edge
+    );
+    let remove_doc = format!(
+        " Deletes every `{}` edge from this record to `to`, returning the deleted edges.",
+        relation.edge
+    );
+
+    Some(quote! {
+        impl #from {
+            #[doc = #list_doc]
+            pub async fn #list_name<C: surrealix::surrealdb::Connection>(
+                &self,
+                db: &surrealix::surrealdb::Surreal<C>,
+            ) -> Result<Vec<#to>, surrealix::surrealdb::Error> {
+                db.query(#select)
+                    .bind(("record", surrealix::surrealdb::sql::Thing::from(&self.id.0)))
+                    .await?
+                    .take(0)
+            }
+
+            #[doc = #add_doc]
+            pub async fn #add_name<C: surrealix::surrealdb::Connection>(
+                &self,
+                db: &surrealix::surrealdb::Surreal<C>,
+                to: impl Into<surrealix::RecordId<#to>>,
+                content: impl serde::Serialize,
+            ) -> Result<Option<#edge>, surrealix::surrealdb::Error> {
+                let to = to.into();
+                db.query(#relate)
+                    .bind(("from", surrealix::surrealdb::sql::Thing::from(&self.id.0)))
+                    .bind(("to", surrealix::surrealdb::sql::Thing::from(&to)))
+                    .bind(("content", content))
+                    .await?
+                    .take(0)
+            }
+
+            #[doc = #remove_doc]
+            pub async fn #remove_name<C: surrealix::surrealdb::Connection>(
+                &self,
+                db: &surrealix::surrealdb::Surreal<C>,
+                to: impl Into<surrealix::RecordId<#to>>,
+            ) -> Result<Vec<#edge>, surrealix::surrealdb::Error> {
+                let to = to.into();
+                db.query(#delete)
+                    .bind(("from", surrealix::surrealdb::sql::Thing::from(&self.id.0)))
+                    .bind(("to", surrealix::surrealdb::sql::Thing::from(&to)))
+                    .await?
+                    .take(0)
+            }
         }
-    } else {
-        "Unknown".to_string()
+    })
+}
+
+/// Generates a `fields` module with a constant for the name of every table and field,
+/// e.g. `fields::USER` and `fields::user::ADDRESS_CITY`.
+pub fn generate_field_constants(schema: Query) -> Result<TokenStream, QueryBuilderError> {
+    let TypeAST::Object(tables) = analyze_schema(schema).map_err(errors::AnalysisError::from)?
+    else {
+        return Ok(TokenStream::new());
     };
 
-    format_ident!("{}", name.to_case(Case::Pascal))
+    let mut table_names: Vec<_> = tables.fields.keys().collect();
+    table_names.sort();
+    let (table_consts, mut errors) = unique_constants(&table_names, "fields", constant_name);
+    let (modules, module_errors) =
+        unique_constants(&table_names, "fields", to_valid_rust_identifier);
+    errors.extend(module_errors);
+
+    let table_modules = table_consts.into_iter().filter_map(|(table_const, table)| {
+        let (module_name, _) = modules.iter().find(|(_, name)| *name == table)?;
+        let mut paths = vec![];
+        field_paths(&tables.fields[table].ast, "", &mut paths);
+        paths.sort();
+        let (fields, field_errors) =
+            unique_constants(&paths, &format!("fields::{}", module_name), constant_name);
+        let fields = fields
+            .into_iter()
+            .map(|(const_name, path)| quote! { pub const #const_name: &str = #path; });
+
+        Some(quote! {
+            pub const #table_const: &str = #table;
+
+            pub mod #module_name {
+                #(#field_errors)*
+                #(#fields)*
+            }
+        })
+    });
+
+    Ok(quote! {
+        /// The names of the tables and fields in the schema.
+        pub mod fields {
+            #(#errors)*
+            #(#table_modules)*
+        }
+    }
+    .into())
 }
 
-fn scalar_type_to_rust_type(scalar_type: &ScalarType) -> TokenStream2 {
-    match scalar_type {
-        ScalarType::String => quote! { String },
-        ScalarType::Integer => quote! { i64 },
-        ScalarType::Number => quote! { f64 },
-        ScalarType::Float => quote! { f32 },
-        ScalarType::Boolean => quote! { bool },
-        ScalarType::Point => quote! { Point },
-        ScalarType::Geometry => quote! { Geometry },
-        ScalarType::Set => quote! { std::collections::HashSet<String> },
-        ScalarType::Datetime => quote! { chrono::DateTime<chrono::Utc> },
-        ScalarType::Duration => quote! { std::time::Duration },
-        ScalarType::Bytes => quote! { Vec<u8> },
-        ScalarType::Uuid => quote! { uuid::Uuid },
-        ScalarType::Any => quote! { serde_json::Value },
-        ScalarType::Null => quote! { () },
+/// The dotted path of every field within `ast`, including those of objects nested in arrays.
+fn field_paths(ast: &TypeAST, prefix: &str, paths: &mut Vec<String>) {
+    match ast {
+        TypeAST::Object(obj) => {
+            for (name, field) in &obj.fields {
+                let path = if prefix.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", prefix, name)
+                };
+                field_paths(&field.ast, &path, paths);
+                paths.push(path);
+            }
+        }
+        TypeAST::Array(inner) => field_paths(&inner.0, prefix, paths),
+        TypeAST::Option(inner) => field_paths(inner, prefix, paths),
+        _ => {}
+    }
+}
+
+/// The identifier of each of `names` within `module`, with a compile error for every name whose
+/// identifier is already taken by an earlier one, e.g. `address_city` after `address.city`.
+fn unique_constants<'a, S: AsRef<str>>(
+    names: &'a [S],
+    module: &str,
+    ident: impl Fn(&str) -> Ident,
+) -> (Vec<(Ident, &'a str)>, Vec<TokenStream2>) {
+    let mut idents: Vec<(Ident, &str)> = Vec::new();
+    let mut errors = Vec::new();
+    for name in names.iter().map(AsRef::as_ref) {
+        let name_ident = ident(name);
+        match idents.iter().find(|(existing, _)| *existing == name_ident) {
+            Some((_, first)) => {
+                let message = format!(
+                    "'{}' and '{}' both map to `{}::{}`. Rename one in the schema.",
+                    first, name, module, name_ident
+                );
+                errors.push(syn::Error::new(Span::call_site(), message).to_compile_error());
+            }
+            None => idents.push((name_ident, name)),
+        }
+    }
+    (idents, errors)
+}
+
+/// The constant for a table or field path, e.g. `ADDRESS_CITY` for `address.city`.
+fn constant_name(path: &str) -> Ident {
+    let name = to_valid_rust_identifier(&path.replace('.', "_"));
+    format_ident!("{}", name.unraw().to_string().to_uppercase())
+}
+
+/// The tables of the schema, as the canonical models represent them.
+fn analyze_table_models(
+    schema: Query,
+    options: &BuildQueryOptions,
+) -> Result<TypeAST, QueryBuilderError> {
+    let mut tables = analyze_schema(schema).map_err(errors::AnalysisError::from)?;
+    if let (true, TypeAST::Object(tables)) = (options.drop_unselectable, &mut tables) {
+        // Only the fields within each table, as the tables themselves carry table permissions.
+        for table in tables.fields.values_mut() {
+            remove_unselectable(&mut table.ast);
+        }
+    }
+    Ok(tables)
+}
+
+/// The naming of generated types, which for `TypeNames::Hash` depends on the table models.
+fn type_naming(
+    schema: Query,
+    options: &BuildQueryOptions,
+) -> Result<TypeNaming, QueryBuilderError> {
+    let tables = match options.type_names {
+        TypeNames::Hash => match analyze_table_models(schema, options)? {
+            TypeAST::Object(tables) => tables,
+            _ => ObjectType::default(),
+        },
+        _ => ObjectType::default(),
+    };
+    Ok(TypeNaming::new(options.type_names, tables).with_version(options.schema_version))
+}
+
+/// Generates the result of a SELECT from `type::table($param)`, or a row of a SELECT from
+/// several tables, with a variant for each table.
+///
+/// Rows that select their `id` carry their table in it, and are decoded by it. Whole results are
+/// instead tried in order, as their rows are unknown until the table is.
+fn generate_table_enum(
+    enum_name: &Ident,
+    results: &[(String, TypeAST)],
+    options: &BuildQueryOptions,
+    naming: &TypeNaming,
+    models: Option<&SharedModels>,
+    type_definitions: &mut Vec<TokenStream2>,
+    generated_types: &mut HashMap<String, TokenStream2>,
+) -> TokenStream2 {
+    let mut variants = Vec::new();
+    let mut tables = Vec::new();
+    let mut decoders = Vec::new();
+    for (table, ast) in results {
+        let (type_name, type_def) =
+            generate_type_definition(ast, options, naming, models, 0, generated_types);
+        type_definitions.extend(type_def);
+
+        let variant = format_ident!("{}", table.to_case(Case::Pascal));
+        variants.push(quote! { #variant(#type_name) });
+        tables.push(quote! { Self::#variant(_) => #table });
+        decoders.push(quote! { #table => serde_json::from_value(row).map(Self::#variant) });
+    }
+
+    let by_id = results
+        .iter()
+        .all(|(_, ast)| matches!(ast, TypeAST::Object(obj) if root_table(obj).is_some()));
+    let utoipa_derives = cfg!(feature = "utoipa").then(|| quote! { #[derive(utoipa::ToSchema)] });
+    let (doc, derives, deserialize) = if by_id {
+        let deserialize = quote! {
+            impl<'de> serde::Deserialize<'de> for #enum_name {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let row = <serde_json::Value as serde::Deserialize>::deserialize(deserializer)?;
+                    let decoded = match surrealix::decode::row_table(&row)
+                        .map_err(serde::de::Error::custom)?
+                        .as_str()
+                    {
+                        #(#decoders,)*
+                        table => {
+                            return Err(serde::de::Error::custom(format_args!(
+                                "a row of the unexpected table '{}'",
+                                table
+                            )))
+                        }
+                    };
+                    decoded.map_err(serde::de::Error::custom)
+                }
+            }
+        };
+        (
+            "Rows are decoded by the table of their `id`.",
+            quote! { #[derive(Debug, serde::Serialize)] },
+            deserialize,
+        )
+    } else {
+        (
+            "Variants are tried in order when decoding, so a result decodes as the first table \
+             whose rows it matches.",
+            quote! { #[derive(Debug, serde::Serialize, serde::Deserialize)] },
+            quote! {},
+        )
+    };
+    quote! {
+        /// The result, or row, of whichever of the tables it was selected from.
+        ///
+        #[doc = #doc]
+        #derives
+        #utoipa_derives
+        #[serde(untagged)]
+        pub enum #enum_name {
+            #(#variants,)*
+        }
+
+        impl #enum_name {
+            /// The table the result was selected from.
+            pub fn table(&self) -> &'static str {
+                match self {
+                    #(#tables,)*
+                }
+            }
+        }
+
+        #deserialize
+    }
+}
+
+/// Emits a compiler warning at the macro invocation.
+///
+/// Proc macros can't emit warnings on stable, so this uses a deprecated item instead.
+fn generate_warning(message: &str) -> TokenStream2 {
+    quote! {
+        const _: () = {
+            #[deprecated(note = #message)]
+            struct Warning;
+            let _ = Warning;
+        };
+    }
+}
+
+/// The deprecated fields of a result, as `(path, note)` pairs like `("best_friend.nickname", ...)`.
+fn deprecated_fields(ast: &TypeAST, prefix: &str, fields: &mut Vec<(String, String)>) {
+    match ast {
+        TypeAST::Object(obj) => {
+            for (name, field) in &obj.fields {
+                let path = match prefix {
+                    "" => name.clone(),
+                    prefix => format!("{}.{}", prefix, name),
+                };
+                if let Some(note) = &field.meta.deprecated {
+                    fields.push((path.clone(), note.clone()));
+                }
+                deprecated_fields(&field.ast, &path, fields);
+            }
+        }
+        TypeAST::Array(inner) => deprecated_fields(&inner.0, prefix, fields),
+        TypeAST::Option(inner) => deprecated_fields(inner, prefix, fields),
+        TypeAST::Union(variants) => variants
+            .iter()
+            .for_each(|variant| deprecated_fields(variant, prefix, fields)),
+        TypeAST::Scalar(_) | TypeAST::Record(_) => {}
+    }
+}
+
+/// The table and path each field of a result was selected from, as `codec_of` matches them.
+fn selected_sources<'a>(ast: &'a TypeAST, sources: &mut Vec<&'a [String]>) {
+    match ast {
+        TypeAST::Object(obj) => {
+            for field in obj.fields.values() {
+                sources.push(&field.meta.original_path);
+                selected_sources(&field.ast, sources);
+            }
+        }
+        TypeAST::Array(inner) => selected_sources(&inner.0, sources),
+        TypeAST::Option(inner) => selected_sources(inner, sources),
+        TypeAST::Union(variants) => variants
+            .iter()
+            .for_each(|variant| selected_sources(variant, sources)),
+        TypeAST::Scalar(_) | TypeAST::Record(_) => {}
+    }
+}
+
+/// Documents the protected fields of a query result on its alias, and exposes them as a constant
+/// (`QUERY_RESULT_PERMISSIONS`) of `(path, conditions)` pairs for tooling and tests.
+fn generate_permissions_report(alias_name: &Ident, ast: &TypeAST) -> TokenStream2 {
+    let protected = protected_fields(ast);
+    let const_name = format_ident!(
+        "{}_PERMISSIONS",
+        alias_name.to_string().to_case(Case::UpperSnake)
+    );
+
+    let mut docs = vec![if protected.is_empty() {
+        "No selected fields are protected by non-FULL permissions.".to_string()
+    } else {
+        "Fields protected by non-FULL permissions:".to_string()
+    }];
+    docs.extend(protected.iter().map(|field| format!("- `{}`", field)));
+
+    let entries = protected.iter().map(|field| {
+        let path = &field.path;
+        let conditions = field.conditions().join(", ");
+        quote! { (#path, #conditions) }
+    });
+
+    quote! {
+        #[doc = "Permissions report for the query result."]
+        pub const #const_name: &[(&str, &str)] = &[#(#entries),*];
+
+        #(#[doc = #docs])*
+    }
+}
+
+/// Generates the Rust type for `ast`, where `depth` is how many objects it is nested within.
+fn generate_type_definition(
+    ast: &TypeAST,
+    options: &BuildQueryOptions,
+    naming: &TypeNaming,
+    models: Option<&SharedModels>,
+    depth: usize,
+    generated_types: &mut HashMap<String, TokenStream2>,
+) -> (TokenStream2, Vec<TokenStream2>) {
+    match ast {
+        // `TYPE object` without any defined fields can hold arbitrary keys.
+        TypeAST::Object(obj) if obj.fields.is_empty() => {
+            (dynamic_object_type(options.dynamic_objects), vec![])
+        }
+        TypeAST::Object(obj) => {
+            let (type_name, defs) =
+                generate_object_definition(obj, options, naming, models, depth, generated_types);
+            if should_box(obj, options, depth) {
+                (quote! { Box<#type_name> }, defs)
+            } else {
+                (type_name, defs)
+            }
+        }
+        TypeAST::Array(inner) => {
+            // Vec elements already live on the heap, so they are never boxed.
+            let (inner_type, inner_defs) = match &inner.0 {
+                TypeAST::Object(obj) if !obj.fields.is_empty() => {
+                    generate_object_definition(obj, options, naming, models, depth, generated_types)
+                }
+                inner => {
+                    generate_type_definition(inner, options, naming, models, depth, generated_types)
+                }
+            };
+            (quote! { Vec<#inner_type> }, inner_defs)
+        }
+        TypeAST::Option(inner) => {
+            let (inner_type, inner_defs) =
+                generate_type_definition(inner, options, naming, models, depth, generated_types);
+            (quote! { Option<#inner_type> }, inner_defs)
+        }
+        TypeAST::Scalar(scalar) => (scalar_type_to_rust_type(scalar), vec![]),
+        TypeAST::Record(table) => (link_type(table, naming, models), vec![]),
+        TypeAST::Union(variants) => match variants.as_slice() {
+            // A link that may have been fetched, from a FETCH of a parameter.
+            [TypeAST::Record(_), fetched @ TypeAST::Object(_)] => {
+                let (fetched_type, defs) = generate_type_definition(
+                    fetched,
+                    options,
+                    naming,
+                    models,
+                    depth,
+                    generated_types,
+                );
+                (quote! { surrealix::Link<#fetched_type> }, defs)
+            }
+            // The rows of a SELECT from several tables, e.g. `FROM user, post`.
+            variants => match table_rows(variants) {
+                Some(rows) => {
+                    let tables: Vec<_> = rows.iter().map(|(table, _)| table.as_str()).collect();
+                    let enum_name = naming.name(&tables.join("_or_"), Some(ast));
+                    if let Some(existing) = generated_types.get(&enum_name.to_string()) {
+                        return (existing.clone(), vec![]);
+                    }
+                    let mut defs = Vec::new();
+                    let enum_def = generate_table_enum(
+                        &enum_name,
+                        &rows,
+                        options,
+                        naming,
+                        models,
+                        &mut defs,
+                        generated_types,
+                    );
+                    defs.push(enum_def);
+                    generated_types.insert(enum_name.to_string(), quote! { #enum_name });
+                    (quote! { #enum_name }, defs)
+                }
+                None => (quote! { serde_json::Value }, vec![]),
+            },
+        },
+    }
+}
+
+/// The table of each variant of a union, if each is a record of a different table that selects
+/// its `id`, which tells the rows apart when decoding.
+fn table_rows(variants: &[TypeAST]) -> Option<Vec<(String, TypeAST)>> {
+    let mut rows: Vec<(String, TypeAST)> = Vec::new();
+    for variant in variants {
+        let TypeAST::Object(obj) = variant else {
+            return None;
+        };
+        let table = root_table(obj)?;
+        if rows.iter().any(|(existing, _)| *existing == table) {
+            return None;
+        }
+        rows.push((table, variant.clone()));
+    }
+    Some(rows)
+}
+
+/// Whether a nested object is past the configured `box_depth` or `box_size`.
+fn should_box(obj: &ObjectType, options: &BuildQueryOptions, depth: usize) -> bool {
+    depth > 0
+        && (options.box_depth.map_or(false, |max| depth > max)
+            || options.box_size.map_or(false, |max| inline_size(obj) > max))
+}
+
+/// The type of an unfetched link to a record of `table`.
+fn link_type(table: &str, naming: &TypeNaming, models: Option<&SharedModels>) -> TokenStream2 {
+    let type_name = match models {
+        Some(models) => models.model(table),
+        None => {
+            let name = naming.table(table);
+            quote! { #name }
+        }
+    };
+    quote! { surrealix::RecordLink<#type_name> }
+}
+
+/// The number of fields stored inline in an object, including those of nested objects.
+fn inline_size(obj: &ObjectType) -> usize {
+    obj.fields
+        .values()
+        .map(|field| match &field.ast {
+            TypeAST::Object(nested) => 1 + inline_size(nested),
+            TypeAST::Option(inner) => match inner.as_ref() {
+                TypeAST::Object(nested) => 1 + inline_size(nested),
+                _ => 1,
+            },
+            _ => 1,
+        })
+        .sum()
+}
+
+fn generate_object_definition(
+    obj: &ObjectType,
+    options: &BuildQueryOptions,
+    naming: &TypeNaming,
+    models: Option<&SharedModels>,
+    depth: usize,
+    generated_types: &mut HashMap<String, TokenStream2>,
+) -> (TokenStream2, Vec<TokenStream2>) {
+    if let Some(model) = models.and_then(|models| models.model_of(obj)) {
+        return (model, vec![]);
+    }
+    if let Some((edge, node)) = edge_and_node(obj) {
+        let (edge_type, mut defs) =
+            generate_object_definition(edge, options, naming, models, depth, generated_types);
+        let (node_type, node_defs) =
+            generate_type_definition(node, options, naming, models, depth, generated_types);
+        defs.extend(node_defs);
+        return (
+            quote! { surrealix::EdgeAndNode<#edge_type, #node_type> },
+            defs,
+        );
+    }
+
+    let mut type_definitions = Vec::new();
+    let type_name = naming.object(obj);
+
+    if let Some(existing_def) = generated_types.get(&type_name.to_string()) {
+        return (existing_def.clone(), type_definitions);
+    }
+
+    // Table records get a dedicated id type, so ids of different tables aren't interchangeable.
+    let id_type = root_table(obj).map(|table| {
+        let id_name = format_ident!("{}Id", type_name);
+        if !generated_types.contains_key(&id_name.to_string()) {
+            let kind = IdKind::of(obj);
+            type_definitions.push(generate_id_definition(&id_name, &type_name, &table, kind));
+            generated_types.insert(id_name.to_string(), quote! { #id_name });
+        }
+        quote! { #id_name }
+    });
+
+    // Distinct result fields can still map to the same Rust field, e.g. `userName` and `user_name`.
+    let mut names: Vec<_> = obj.fields.keys().collect();
+    names.sort();
+    let mut field_names: Vec<(Ident, &String)> = Vec::new();
+    for name in names {
+        let field_name = to_valid_rust_identifier(name);
+        match field_names
+            .iter()
+            .find(|(existing, _)| *existing == field_name)
+        {
+            Some((_, first)) => {
+                let message = format!(
+                    "The fields '{}' and '{}' of `{}` both map to the Rust field `{}`. Rename one with AS.",
+                    first, name, type_name, field_name
+                );
+                type_definitions
+                    .push(syn::Error::new(Span::call_site(), message).to_compile_error());
+            }
+            None => field_names.push((field_name, name)),
+        }
+    }
+
+    let columns: Vec<_> = field_names
+        .iter()
+        .map(|(field_name, name)| (field_name.clone(), name.to_string()))
+        .collect();
+
+    // Fields read when deserializing, and how each is filled in from them.
+    let mut stored_fields = Vec::new();
+    let mut filled_fields = Vec::new();
+    let mut has_defaults = false;
+    let fields = field_names.into_iter().map(|(field_name, name)| {
+        let field_info = &obj.fields[name];
+        let constraints = &field_info.meta.constraints;
+        let codec = options.codec_of(&field_info.meta.original_path);
+        let newtype = match &id_type {
+            Some(id_type) if name == "id" => Some((id_type.clone(), vec![])),
+            _ if codec.is_some() => codec.map(|codec| (codec_type(&field_info.ast, codec), vec![])),
+            _ if options.constrained_types && !constraints.is_empty() => {
+                generate_constrained_field(
+                    &field_info.ast,
+                    field_info
+                        .meta
+                        .original_path
+                        .first()
+                        .map_or("", String::as_str),
+                    &type_name,
+                    name,
+                    constraints,
+                    naming,
+                    generated_types,
+                )
+            }
+            _ => None,
+        };
+        let is_newtype = newtype.is_some();
+        let (field_type, mut field_defs) = newtype.unwrap_or_else(|| {
+            generate_type_definition(
+                &field_info.ast,
+                options,
+                naming,
+                models,
+                depth + 1,
+                generated_types,
+            )
+        });
+        type_definitions.append(&mut field_defs);
+
+        match &field_info.meta.default {
+            Some(json) if options.lenient_defaults && name != "id" && codec.is_none() => {
+                has_defaults = true;
+                stored_fields.push(quote! {
+                    #[serde(rename = #name, default)] #field_name: Option<#field_type>
+                });
+                filled_fields.push(quote! {
+                    #field_name: match stored.#field_name {
+                        Some(value) => value,
+                        None => surrealix::decode::schema_default(#json).map_err(|err| {
+                            serde::de::Error::custom(format_args!(
+                                "The schema DEFAULT of '{}' doesn't fit its type: {}",
+                                #name, err
+                            ))
+                        })?,
+                    }
+                });
+            }
+            _ => {
+                let codec = codec.map(|codec| codec_deserialize_attribute(&field_info.ast, codec));
+                stored_fields.push(quote! {
+                    #[serde(rename = #name)] #codec #field_name: #field_type
+                });
+                filled_fields.push(quote! { #field_name: stored.#field_name });
+            }
+        }
+
+        // Reading a deprecated field warns where it is read, not only where it is selected.
+        let deprecated = field_info
+            .meta
+            .deprecated
+            .as_deref()
+            .map(|note| match note {
+                "" => quote! { #[deprecated] },
+                note => quote! { #[deprecated(note = #note)] },
+            });
+
+        let archive = archive_attribute(&field_info.ast, is_newtype);
+        let schema = match codec {
+            Some(codec) => codec_attributes(&field_info.ast, codec),
+            None => {
+                let specta = specta_attribute(&field_info.ast, is_newtype);
+                let utoipa = utoipa_attribute(&field_info.ast, is_newtype);
+                quote! { #specta #utoipa }
+            }
+        };
+        // The original name is always kept, as the identifier may differ from it in ways
+        // serde's case conversions can't undo.
+        quote! {
+            #[serde(rename = #name)] #archive #schema #deprecated pub #field_name: #field_type
+        }
+    });
+    let fields: Vec<_> = fields.collect();
+
+    let archive_derives = if cfg!(feature = "rkyv") {
+        quote! {
+            #[derive(surrealix::rkyv::Archive, surrealix::rkyv::Serialize, surrealix::rkyv::Deserialize)]
+            #[rkyv(crate = surrealix::rkyv)]
+        }
+    } else {
+        quote! {}
+    };
+
+    let specta_derives = if cfg!(feature = "specta") {
+        quote! {
+            #[derive(surrealix::specta::Type)]
+            #[specta(crate = surrealix::specta)]
+        }
+    } else {
+        quote! {}
+    };
+
+    // utoipa is the user's own dependency, so it is referred to directly.
+    let utoipa_derives = cfg!(feature = "utoipa").then(|| quote! { #[derive(utoipa::ToSchema)] });
+
+    let non_exhaustive = options.non_exhaustive.then(|| quote! { #[non_exhaustive] });
+    let deny_unknown_fields = options
+        .deny_unknown_fields
+        .then(|| quote! { #[serde(deny_unknown_fields)] });
+
+    // Schema DEFAULTs are filled in by a `Deserialize` of its own, which reports a default that
+    // doesn't fit its field as a deserialization error.
+    let deserialize_derive = (!has_defaults).then(|| quote! { serde::Deserialize });
+    let type_def = quote! {
+        #[derive(Debug, serde::Serialize, #deserialize_derive)]
+        #archive_derives
+        #specta_derives
+        #utoipa_derives
+        #non_exhaustive
+        #deny_unknown_fields
+        pub struct #type_name {
+            #(#fields,)*
+        }
+    };
+
+    type_definitions.push(type_def.clone());
+    type_definitions.push(generate_type_info(obj, &type_name, &columns));
+    if let Some(id_type) = &id_type {
+        type_definitions.push(quote! {
+            impl surrealix::HasId for #type_name {
+                type Id = #id_type;
+
+                fn id(&self) -> &#id_type {
+                    &self.id
+                }
+            }
+        });
+    }
+    if has_defaults {
+        let stored_name = format_ident!("{}Stored", type_name);
+        type_definitions.push(quote! {
+            impl<'de> serde::Deserialize<'de> for #type_name {
+                #[allow(deprecated)]
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    #[derive(serde::Deserialize)]
+                    #deny_unknown_fields
+                    struct #stored_name {
+                        #(#stored_fields,)*
+                    }
+
+                    let stored = <#stored_name as serde::Deserialize>::deserialize(deserializer)?;
+                    Ok(Self {
+                        #(#filled_fields,)*
+                    })
+                }
+            }
+        });
+    }
+    if cfg!(feature = "diff") {
+        type_definitions.push(quote! {
+            impl #type_name {
+                /// The fields that changed from `self` to `other`, grouped by record id.
+                pub fn diff(&self, other: &Self) -> Vec<surrealix::diff::FieldChange> {
+                    surrealix::diff::diff(self, other)
+                }
+            }
+        });
+    }
+    if cfg!(feature = "cli") {
+        type_definitions.push(generate_table_impls(&type_name, &columns));
+    }
+    generated_types.insert(type_name.to_string(), quote! { #type_name });
+
+    (quote! { #type_name }, type_definitions)
+}
+
+/// The `TYPE_INFO` constant of a result struct, describing its fields at runtime.
+fn generate_type_info(
+    obj: &ObjectType,
+    type_name: &Ident,
+    columns: &[(Ident, String)],
+) -> TokenStream2 {
+    let name = type_name.to_string();
+    let table = match root_table(obj) {
+        Some(table) => quote! { Some(#table) },
+        None => quote! { None },
+    };
+    let fields = columns.iter().map(|(field_name, name)| {
+        let field = &obj.fields[name];
+        let rust_name = field_name.unraw().to_string();
+        let kind = field.ast.surql_kind();
+        let source = &field.meta.original_path;
+        let deprecated = match &field.meta.deprecated {
+            Some(note) => quote! { Some(#note) },
+            None => quote! { None },
+        };
+        quote! {
+            surrealix::FieldTypeInfo {
+                name: #name,
+                rust_name: #rust_name,
+                kind: #kind,
+                source: &[#(#source),*],
+                deprecated: #deprecated,
+            }
+        }
+    });
+    quote! {
+        impl #type_name {
+            pub const TYPE_INFO: surrealix::TypeInfo = surrealix::TypeInfo {
+                name: #name,
+                table: #table,
+                fields: &[#(#fields),*],
+            };
+        }
+    }
+}
+
+/// `Tabled` and `Display` for a result struct, with a column for each field.
+fn generate_table_impls(type_name: &Ident, columns: &[(Ident, String)]) -> TokenStream2 {
+    let headers = columns.iter().map(|(_, name)| name);
+    let cells = columns.iter().map(|(field_name, _)| field_name);
+    quote! {
+        impl surrealix::table::Tabled for #type_name {
+            const HEADERS: &'static [&'static str] = &[#(#headers),*];
+
+            #[allow(deprecated)]
+            fn fields(&self) -> Vec<String> {
+                vec![#(surrealix::table::cell(&self.#cells)),*]
+            }
+        }
+
+        impl std::fmt::Display for #type_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Display::fmt(&surrealix::table::table(std::slice::from_ref(self)), f)
+            }
+        }
+    }
+}
+
+/// The `#[rkyv(with = ...)]` attribute for a field that rkyv can't archive natively.
+///
+/// `newtype` marks fields using a generated id or constrained type, which are archived as JSON.
+fn archive_attribute(ast: &TypeAST, newtype: bool) -> TokenStream2 {
+    if !cfg!(feature = "rkyv") {
+        return quote! {};
+    }
+    match archive_wrapper(ast, newtype) {
+        Some(wrapper) => quote! { #[rkyv(with = #wrapper)] },
+        None => quote! {},
+    }
+}
+
+fn archive_wrapper(ast: &TypeAST, newtype: bool) -> Option<TokenStream2> {
+    match ast {
+        TypeAST::Array(boxed) => archive_wrapper(&boxed.0, newtype)
+            .map(|wrapper| quote! { surrealix::rkyv::with::Map<#wrapper> }),
+        TypeAST::Option(inner) => archive_wrapper(inner, newtype)
+            .map(|wrapper| quote! { surrealix::rkyv::with::Map<#wrapper> }),
+        TypeAST::Object(obj) if obj.fields.is_empty() => {
+            Some(quote! { surrealix::archive::AsJson })
+        }
+        TypeAST::Object(_) => None,
+        _ if newtype => Some(quote! { surrealix::archive::AsJson }),
+        TypeAST::Scalar(
+            ScalarType::String
+            | ScalarType::Integer
+            | ScalarType::Number
+            | ScalarType::Float
+            | ScalarType::Boolean
+            | ScalarType::Set
+            | ScalarType::Bytes
+            | ScalarType::Null,
+        ) => None,
+        _ => Some(quote! { surrealix::archive::AsJson }),
+    }
+}
+
+/// The `#[derive(...)]`s of `garde::Validate` and `validator::Validate` on the structs of a
+/// query's parameters, for the `garde` and `validator` features.
+///
+/// The validation crates are the user's own dependencies, so they are referred to directly.
+fn validation_derives() -> TokenStream2 {
+    let garde = cfg!(feature = "garde").then(|| quote! { #[derive(garde::Validate)] });
+    let validator = cfg!(feature = "validator").then(|| quote! { #[derive(validator::Validate)] });
+    quote! { #garde #validator }
+}
+
+/// The `#[garde(...)]` and `#[validate(...)]` attributes checking a parameter of type `ast`, if
+/// it is known, against the ASSERT constraints `param_constraints` finds for it.
+///
+/// Only strings are checked, and with garde also the strings of an array, which a parameter
+/// holds when a field is checked inside of it. garde requires a rule for every field, so the
+/// others are skipped explicitly.
+fn validation_attributes(ast: Option<&TypeAST>, constraints: &[Constraint]) -> TokenStream2 {
+    let string = TypeAST::Scalar(ScalarType::String);
+    let (garde_rules, validator_rules) = match ast {
+        Some(ast) if *ast == string => (garde_rules(constraints), validator_rules(constraints)),
+        Some(TypeAST::Array(items)) if items.0 == string => {
+            match garde_rules(constraints).as_slice() {
+                [] => (vec![], vec![]),
+                rules => (vec![quote! { inner(#(#rules),*) }], vec![]),
+            }
+        }
+        _ => (vec![], vec![]),
+    };
+
+    let garde = cfg!(feature = "garde").then(|| match garde_rules.as_slice() {
+        [] => quote! { #[garde(skip)] },
+        rules => quote! { #[garde(#(#rules),*)] },
+    });
+    let validator = (cfg!(feature = "validator") && !validator_rules.is_empty())
+        .then(|| quote! { #[validate(#(#validator_rules),*)] });
+    quote! { #garde #validator }
+}
+
+/// The garde rules for the constraints it has an equivalent of.
+fn garde_rules(constraints: &[Constraint]) -> Vec<TokenStream2> {
+    let mut rules: Vec<_> = constraints
+        .iter()
+        .filter_map(|constraint| match constraint {
+            Constraint::StringIs(check) => match check.as_str() {
+                "alphanum" => Some(quote! { alphanumeric }),
+                "ascii" => Some(quote! { ascii }),
+                "email" => Some(quote! { email }),
+                "url" => Some(quote! { url }),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    // `string::len` counts characters, as garde's `chars` mode does.
+    rules.extend(length_bounds(constraints).map(|bounds| quote! { length(chars, #bounds) }));
+    rules
+}
+
+/// The validator rules for the constraints it has an equivalent of.
+fn validator_rules(constraints: &[Constraint]) -> Vec<TokenStream2> {
+    let mut rules: Vec<_> = constraints
+        .iter()
+        .filter_map(|constraint| match constraint {
+            Constraint::StringIs(check) => match check.as_str() {
+                "email" => Some(quote! { email }),
+                "url" => Some(quote! { url }),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    rules.extend(length_bounds(constraints).map(|bounds| quote! { length(#bounds) }));
+    rules
+}
+
+/// The `min = n, max = m` arguments of a length rule, if the constraints bound the length.
+fn length_bounds(constraints: &[Constraint]) -> Option<TokenStream2> {
+    let min = constraints.iter().find_map(|constraint| match constraint {
+        Constraint::MinLength(n) => Some(Literal::u64_unsuffixed(*n)),
+        _ => None,
+    });
+    let max = constraints.iter().find_map(|constraint| match constraint {
+        Constraint::MaxLength(n) => Some(Literal::u64_unsuffixed(*n)),
+        _ => None,
+    });
+    match (min, max) {
+        (Some(min), Some(max)) => Some(quote! { min = #min, max = #max }),
+        (Some(min), None) => Some(quote! { min = #min }),
+        (None, Some(max)) => Some(quote! { max = #max }),
+        (None, None) => None,
+    }
+}
+
+/// The type of a field passing through `codec`, its value in place of the stored one.
+fn codec_type(ast: &TypeAST, codec: &syn::Path) -> TokenStream2 {
+    match ast {
+        TypeAST::Option(_) => quote! { Option<<#codec as surrealix::FieldCodec>::Value> },
+        _ => quote! { <#codec as surrealix::FieldCodec>::Value },
+    }
+}
+
+/// The serde attributes passing a field through `codec`, along with its specta type, which is
+/// that of the stored value the field is sent as, and its utoipa type.
+fn codec_attributes(ast: &TypeAST, codec: &syn::Path) -> TokenStream2 {
+    let (suffix, stored, default) = codec_fns(ast, codec);
+    let codec = quote! { #codec }.to_string();
+    let serialize = format!("surrealix::codec::serialize{}::<{}, _>", suffix, codec);
+    let deserialize = format!("surrealix::codec::deserialize{}::<{}, _>", suffix, codec);
+    let specta = cfg!(feature = "specta").then(|| quote! { #[specta(type = #stored)] });
+    // utoipa can't name the stored type through the codec, so it is left undocumented.
+    let utoipa = cfg!(feature = "utoipa").then(|| quote! { #[schema(value_type = Value)] });
+    quote! {
+        #[serde(#default serialize_with = #serialize, deserialize_with = #deserialize)]
+        #specta
+        #utoipa
+    }
+}
+
+/// The serde attribute reading a field through `codec`, for a struct that is only deserialized.
+fn codec_deserialize_attribute(ast: &TypeAST, codec: &syn::Path) -> TokenStream2 {
+    let (suffix, _, default) = codec_fns(ast, codec);
+    let codec = quote! { #codec }.to_string();
+    let deserialize = format!("surrealix::codec::deserialize{}::<{}, _>", suffix, codec);
+    quote! { #[serde(#default deserialize_with = #deserialize)] }
+}
+
+/// The suffix of the `surrealix::codec` functions for a field, its stored type, and whether it
+/// may be missing.
+fn codec_fns(ast: &TypeAST, codec: &syn::Path) -> (&'static str, TokenStream2, TokenStream2) {
+    match ast {
+        TypeAST::Option(_) => (
+            "_option",
+            quote! { Option<<#codec as surrealix::FieldCodec>::Stored> },
+            quote! { default, },
+        ),
+        _ => (
+            "",
+            quote! { <#codec as surrealix::FieldCodec>::Stored },
+            quote! {},
+        ),
+    }
+}
+
+/// The `#[specta(type = ...)]` attribute for a field whose Rust type specta doesn't know.
+///
+/// Such fields are exported as the type of their JSON form, e.g. record ids and datetimes as
+/// strings. `newtype` marks fields using a generated id or constrained type, which are strings.
+fn specta_attribute(ast: &TypeAST, newtype: bool) -> TokenStream2 {
+    if !cfg!(feature = "specta") {
+        return quote! {};
+    }
+    match json_form_type(ast, newtype) {
+        Some(ty) => quote! { #[specta(type = #ty)] },
+        None => quote! {},
+    }
+}
+
+/// The `#[schema(value_type = ...)]` attribute for a field whose Rust type utoipa doesn't know,
+/// which is documented as its JSON form like it is exported to specta.
+fn utoipa_attribute(ast: &TypeAST, newtype: bool) -> TokenStream2 {
+    if !cfg!(feature = "utoipa") {
+        return quote! {};
+    }
+    match json_form_type(ast, newtype) {
+        Some(ty) => quote! { #[schema(value_type = #ty)] },
+        None => quote! {},
+    }
+}
+
+/// The type of the JSON form of a field, for fields whose Rust type isn't known to schema
+/// generators, or `None` if the field's own type is.
+fn json_form_type(ast: &TypeAST, newtype: bool) -> Option<TokenStream2> {
+    match ast {
+        TypeAST::Array(boxed) => json_form_type(&boxed.0, newtype).map(|ty| quote! { Vec<#ty> }),
+        TypeAST::Option(inner) => json_form_type(inner, newtype).map(|ty| quote! { Option<#ty> }),
+        TypeAST::Object(_) => None,
+        _ if newtype => Some(quote! { String }),
+        TypeAST::Record(_) => Some(quote! { String }),
+        TypeAST::Scalar(ScalarType::Datetime | ScalarType::Duration) => Some(quote! { String }),
+        TypeAST::Scalar(ScalarType::Point | ScalarType::Geometry) => {
+            Some(quote! { serde_json::Value })
+        }
+        TypeAST::Scalar(_) => None,
+        // Links that may have been fetched are either an id or the record.
+        TypeAST::Union(_) => Some(quote! { serde_json::Value }),
+    }
+}
+
+/// Generates unit tests for a live query's reducer, which decode two records of the query and
+/// check that notifications of them are reconciled with the state by id.
+///
+/// Records are made up from the types of their fields, so queries with fields whose values can't
+/// be, like geometries or fields decoded by a codec or constraint, get no tests.
+fn generate_reduce_tests(
+    obj: &ObjectType,
+    type_name: &Ident,
+    reduce_name: &Ident,
+    options: &BuildQueryOptions,
+) -> Option<TokenStream2> {
+    let table = root_table(obj)?;
+    let keys = IdKind::of(obj).sample_keys();
+    let record = |key: &str| {
+        let mut fields = vec![];
+        for (name, field) in obj.fields.iter() {
+            let value = match name.as_str() {
+                "id" => {
+                    let id = format!("{}:{}", table, key);
+                    quote! { #id }
+                }
+                _ => sample_value(&field.ast, &field.meta, options)?,
+            };
+            fields.push(quote! { #name: #value });
+        }
+        Some(quote! { serde_json::json!({ #(#fields),* }) })
+    };
+    let (first, second) = (record(keys[0])?, record(keys[1])?);
+    let tests_name = format_ident!("{}_tests", reduce_name);
+
+    Some(quote! {
+        #[cfg(test)]
+        mod #tests_name {
+            use super::*;
+            use surrealix::{live::Notification, HasId};
+
+            fn records() -> (#type_name, #type_name) {
+                (
+                    surrealix::decode::decode(#first).unwrap(),
+                    surrealix::decode::decode(#second).unwrap(),
+                )
+            }
+
+            #[test]
+            fn reconciles_notifications_by_id() {
+                let mut state = Vec::new();
+                let (first, second) = records();
+                #reduce_name(&mut state, Notification::Create(first));
+                #reduce_name(&mut state, Notification::Update(second));
+                let (first, _) = records();
+                #reduce_name(&mut state, Notification::Update(first));
+                let ids = |state: &Vec<#type_name>| {
+                    state.iter().map(|record| record.id().clone()).collect::<Vec<_>>()
+                };
+                let (first, second) = records();
+                assert_eq!(ids(&state), vec![first.id().clone(), second.id().clone()]);
+
+                #reduce_name(&mut state, Notification::Delete(first));
+                assert_eq!(ids(&state), vec![second.id().clone()]);
+                #reduce_name(&mut state, Notification::Delete(second));
+                assert!(state.is_empty());
+            }
+        }
+    })
+}
+
+/// A JSON value decoding as a field of `ast`, for generated tests, if there is an obvious one.
+fn sample_value(
+    ast: &TypeAST,
+    meta: &FieldMetadata,
+    options: &BuildQueryOptions,
+) -> Option<TokenStream2> {
+    if options.codec_of(&meta.original_path).is_some()
+        || (options.constrained_types && !meta.constraints.is_empty())
+    {
+        return None;
+    }
+    let value = match ast {
+        TypeAST::Option(_) | TypeAST::Scalar(ScalarType::Null | ScalarType::Any) => {
+            quote! { null }
+        }
+        TypeAST::Scalar(ScalarType::String) => quote! { "" },
+        TypeAST::Scalar(ScalarType::Integer | ScalarType::Number) => quote! { 0 },
+        TypeAST::Scalar(ScalarType::Float) => quote! { 0.0 },
+        TypeAST::Scalar(ScalarType::Boolean) => quote! { false },
+        TypeAST::Scalar(ScalarType::Datetime) => quote! { "1970-01-01T00:00:00Z" },
+        TypeAST::Scalar(ScalarType::Duration) => quote! { "1s" },
+        TypeAST::Scalar(ScalarType::Uuid) => quote! { "00000000-0000-0000-0000-000000000000" },
+        TypeAST::Scalar(ScalarType::Bytes | ScalarType::Set) => quote! { [] },
+        TypeAST::Record(table) => {
+            let id = format!("{}:sample", table);
+            quote! { #id }
+        }
+        // Arrays of a fixed length can't be empty.
+        TypeAST::Array(inner) if inner.1.is_none() => quote! { [] },
+        TypeAST::Object(obj) if !obj.fields.is_empty() => {
+            // Fetched records decode their `id` through the table's id newtype.
+            let table = root_table(obj);
+            let mut fields = vec![];
+            for (name, field) in obj.fields.iter() {
+                let value = match &table {
+                    Some(table) if name == "id" => {
+                        let id = format!("{}:{}", table, IdKind::of(obj).sample_keys()[0]);
+                        quote! { #id }
+                    }
+                    _ => sample_value(&field.ast, &field.meta, options)?,
+                };
+                fields.push(quote! { #name: #value });
+            }
+            quote! { { #(#fields),* } }
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Returns the table an object was selected from, if it is a full record of that table.
+///
+/// Only records carrying an `id` are considered, as that is where the id newtype is used.
+fn root_table(obj: &ObjectType) -> Option<String> {
+    let id = obj.fields.get("id")?;
+    match id.meta.original_path.as_slice() {
+        [table, same, _] if table == same => Some(table.clone()),
+        [table, _] => Some(table.clone()),
+        _ => None,
+    }
+}
+
+/// The representation of a table's record keys, as declared by the type of its `id` field.
+enum IdKind {
+    Uuid,
+    Number,
+    /// Plain string keys, which also covers ULIDs.
+    String,
+}
+
+impl IdKind {
+    fn of(obj: &ObjectType) -> Self {
+        match obj.fields.get("id").map(|field| &field.ast) {
+            Some(TypeAST::Scalar(ScalarType::Uuid)) => IdKind::Uuid,
+            Some(TypeAST::Scalar(ScalarType::Integer | ScalarType::Number)) => IdKind::Number,
+            _ => IdKind::String,
+        }
+    }
+
+    /// Two distinct keys of this kind, as they are written in a record id.
+    fn sample_keys(&self) -> [&'static str; 2] {
+        match self {
+            IdKind::Uuid => [
+                "⟨00000000-0000-0000-0000-000000000001⟩",
+                "⟨00000000-0000-0000-0000-000000000002⟩",
+            ],
+            IdKind::Number => ["1", "2"],
+            IdKind::String => ["first", "second"],
+        }
+    }
+}
+
+/// Generates the id newtype for a table model, e.g. `pub struct UserId(RecordId<User>)`.
+fn generate_id_definition(
+    id_name: &Ident,
+    model_name: &Ident,
+    table: &str,
+    kind: IdKind,
+) -> TokenStream2 {
+    let (key_type, constructor, key_desc) = match kind {
+        IdKind::Uuid => (quote! { uuid::Uuid }, format_ident!("from_uuid"), "uuid"),
+        IdKind::Number => (quote! { u64 }, format_ident!("from_number"), "number"),
+        IdKind::String => (quote! { String }, format_ident!("from_string"), "string"),
+    };
+
+    quote! {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize)]
+        #[serde(transparent)]
+        pub struct #id_name(pub surrealix::RecordId<#model_name>);
+
+        impl #id_name {
+            pub const TABLE: &'static str = #table;
+
+            pub fn #constructor(key: #key_type) -> Self {
+                Self(surrealix::RecordId::new(Self::TABLE, key))
+            }
+
+            /// The key of this record, in the representation declared by the schema.
+            pub fn key(&self) -> Result<#key_type, surrealix::RecordIdError> {
+                self.0.key().to_string().parse().map_err(|_| {
+                    surrealix::RecordIdError::InvalidKey(self.to_string(), #key_desc.to_string())
+                })
+            }
+        }
+
+        impl std::str::FromStr for #id_name {
+            type Err = surrealix::RecordIdError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let id = Self(surrealix::RecordId::parse_in(Self::TABLE, s)?);
+                id.key()?;
+                Ok(id)
+            }
+        }
+
+        impl std::fmt::Display for #id_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        /// Accepts both the `table:key` string and the `Thing` object, as which one a result
+        /// holds depends on the transport.
+        impl<'de> serde::Deserialize<'de> for #id_name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let id = <surrealix::RecordId<#model_name> as serde::Deserialize>::deserialize(
+                    deserializer,
+                )?;
+                let id = Self(id.in_table(Self::TABLE).map_err(serde::de::Error::custom)?);
+                id.key().map_err(serde::de::Error::custom)?;
+                Ok(id)
+            }
+        }
+
+        impl From<#id_name> for surrealix::RecordId<#model_name> {
+            fn from(id: #id_name) -> Self {
+                id.0
+            }
+        }
+    }
+}
+
+/// Generates a validating newtype for a string field with recognized ASSERT constraints.
+///
+/// A lone `string::is::*` check produces a name shared within the field's `table` (`UserEmail`),
+/// anything else is named after the field. Returns `None` for non-string fields, which keep their
+/// plain type.
+fn generate_constrained_field(
+    ast: &TypeAST,
+    table: &str,
+    owner: &Ident,
+    field_name: &str,
+    constraints: &[Constraint],
+    naming: &TypeNaming,
+    generated_types: &mut HashMap<String, TokenStream2>,
+) -> Option<(TokenStream2, Vec<TokenStream2>)> {
+    if let TypeAST::Option(inner) = ast {
+        let (inner_type, defs) = generate_constrained_field(
+            inner,
+            table,
+            owner,
+            field_name,
+            constraints,
+            naming,
+            generated_types,
+        )?;
+        return Some((quote! { Option<#inner_type> }, defs));
+    }
+    if !matches!(ast, TypeAST::Scalar(ScalarType::String)) {
+        return None;
+    }
+
+    let type_name = match constraints {
+        [Constraint::StringIs(check)] => naming.name(&format!("{}_{}", table, check), None),
+        _ => format_ident!("{}{}", owner, field_name.to_case(Case::Pascal)),
+    };
+    if generated_types.contains_key(&type_name.to_string()) {
+        return Some((quote! { #type_name }, vec![]));
+    }
+    generated_types.insert(type_name.to_string(), quote! { #type_name });
+
+    let checks = constraints.iter().map(|constraint| {
+        let (check, description) = match constraint {
+            Constraint::StringIs(check) => (
+                quote! { surrealix::constraints::string_is(#check, &value) },
+                format!("string::is::{}($value)", check),
+            ),
+            Constraint::MinLength(n) => (
+                quote! { value.chars().count() as u64 >= #n },
+                format!("string::len($value) >= {}", n),
+            ),
+            Constraint::MaxLength(n) => (
+                quote! { value.chars().count() as u64 <= #n },
+                format!("string::len($value) <= {}", n),
+            ),
+            Constraint::OneOf(literals) => (
+                quote! { [#(#literals),*].contains(&value.as_str()) },
+                format!(
+                    "$value INSIDE [{}]",
+                    literals
+                        .iter()
+                        .map(|literal| format!("'{}'", literal))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ),
+        };
+        quote! {
+            if !(#check) {
+                return Err(surrealix::constraints::ConstraintError(#description.to_string()));
+            }
+        }
+    });
+
+    let type_def = quote! {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+        #[serde(try_from = "String", into = "String")]
+        pub struct #type_name(String);
+
+        impl #type_name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl TryFrom<String> for #type_name {
+            type Error = surrealix::constraints::ConstraintError;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                #(#checks)*
+                Ok(Self(value))
+            }
+        }
+
+        impl From<#type_name> for String {
+            fn from(value: #type_name) -> Self {
+                value.0
+            }
+        }
+
+        impl AsRef<str> for #type_name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl std::fmt::Display for #type_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+
+    Some((quote! { #type_name }, vec![type_def]))
+}
+
+/// The Rust identifier for a result field, in snake case and escaped if it is a keyword.
+///
+/// SurrealDB allows any name in backticks, so characters that can't appear in an identifier
+/// become `_`, and a name that can't start one (e.g. `1st`) is prefixed with `_`.
+fn to_valid_rust_identifier(name: &str) -> Ident {
+    // Digits don't start a new word, so `address2` stays as it is.
+    let boundaries = [
+        Boundary::Underscore,
+        Boundary::Hyphen,
+        Boundary::Space,
+        Boundary::LowerUpper,
+        Boundary::Acronym,
+    ];
+    let mut field_name: String = name
+        .with_boundaries(&boundaries)
+        .to_case(Case::Snake)
+        .chars()
+        .map(|c| if is_xid_continue(c) { c } else { '_' })
+        .collect();
+    if field_name.trim_matches('_').is_empty() {
+        field_name = format!("field{}", field_name);
+    } else if !field_name.starts_with(|c: char| c == '_' || is_xid_start(c)) {
+        field_name.insert(0, '_');
+    }
+
+    match field_name.as_str() {
+        // The few keywords that can't be raw identifiers.
+        "self" | "super" | "crate" | "Self" => format_ident!("{}_", field_name),
+        _ if syn::parse_str::<Ident>(&field_name).is_err() => {
+            Ident::new_raw(&field_name, Span::call_site())
+        }
+        _ => format_ident!("{}", field_name),
+    }
+}
+
+fn dynamic_object_type(kind: DynamicObjects) -> TokenStream2 {
+    match kind {
+        DynamicObjects::IndexMap => quote! { surrealix::IndexMap<String, serde_json::Value> },
+        DynamicObjects::HashMap => {
+            quote! { std::collections::HashMap<String, serde_json::Value> }
+        }
+        DynamicObjects::BTreeMap => {
+            quote! { std::collections::BTreeMap<String, serde_json::Value> }
+        }
+        DynamicObjects::Json => quote! { serde_json::Map<String, serde_json::Value> },
+    }
+}
+
+fn scalar_type_to_rust_type(scalar_type: &ScalarType) -> TokenStream2 {
+    match scalar_type {
+        ScalarType::String => quote! { String },
+        ScalarType::Integer => quote! { i64 },
+        ScalarType::Number => quote! { f64 },
+        ScalarType::Float => quote! { f32 },
+        ScalarType::Boolean => quote! { bool },
+        ScalarType::Point => quote! { surrealix::types::Point },
+        ScalarType::Geometry => quote! { surrealix::types::Geometry },
+        ScalarType::Set => quote! { std::collections::HashSet<String> },
+        ScalarType::Datetime => quote! { surrealix::types::DateTime },
+        ScalarType::Duration => quote! { surrealix::types::Duration },
+        ScalarType::Bytes => quote! { Vec<u8> },
+        ScalarType::Uuid => quote! { uuid::Uuid },
+        ScalarType::Any => quote! { serde_json::Value },
+        ScalarType::Null => quote! { () },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build_query::parser::OptionsInput;
+    use proptest::prelude::*;
+
+    #[test]
+    fn exotic_field_names() {
+        let ident = |name: &str| to_valid_rust_identifier(name).to_string();
+
+        assert_eq!(ident("userName"), "user_name");
+        assert_eq!(ident("first name"), "first_name");
+        assert_eq!(ident("1st"), "_1st");
+        assert_eq!(ident("type"), "r#type");
+        assert_eq!(ident("self"), "self_");
+        assert_eq!(ident("address2"), "address2");
+        assert_eq!(ident("-"), "field");
+        assert_eq!(ident("prénom"), "prénom");
+    }
+
+    #[test]
+    fn codec_fields() {
+        let OptionsInput(options) = syn::parse_str(
+            r#"encrypt(ssn, user.card.number) = "crate::Sealed", transform(tags) = "crate::Tags""#,
+        )
+        .unwrap();
+        let codec = |source: &[&str]| {
+            let source: Vec<_> = source.iter().map(|part| part.to_string()).collect();
+            options
+                .codec_of(&source)
+                .map(|codec| quote! { #codec }.to_string())
+        };
+
+        assert_eq!(codec(&["user", "ssn"]).as_deref(), Some("crate :: Sealed"));
+        assert_eq!(codec(&["admin", "ssn"]).as_deref(), Some("crate :: Sealed"));
+        assert_eq!(
+            codec(&["user", "card", "number"]).as_deref(),
+            Some("crate :: Sealed")
+        );
+        assert_eq!(codec(&["admin", "card", "number"]), None);
+        assert_eq!(codec(&["post", "tags"]).as_deref(), Some("crate :: Tags"));
+    }
+
+    #[test]
+    fn unmatched_codec_fields_fail_to_compile() {
+        let schema = surrealdb::sql::parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD ssn ON user TYPE string;
+            "#,
+        )
+        .unwrap();
+        let generate = |input: &str| {
+            let input: BuildQueryInput = syn::parse_str(input).unwrap();
+            generate_code(input, schema.clone())
+        };
+
+        let Err(QueryBuilderError::UnknownCodecField(errors)) = generate(
+            r#"Users, encrypt(ssn, user.snn, name) = "crate::Sealed", "SELECT name FROM user""#,
+        ) else {
+            panic!("Expected the unmatched codec fields to be rejected");
+        };
+        let messages: Vec<_> = errors.into_iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "'ssn' matches no field the query selects",
+                "'user.snn' matches no field the query selects",
+            ]
+        );
+    }
+
+    #[test]
+    fn validation_rules() {
+        let constraints = [
+            Constraint::StringIs("email".to_string()),
+            Constraint::StringIs("semver".to_string()),
+            Constraint::MinLength(3),
+            Constraint::MaxLength(64),
+        ];
+        let rules = |rules: Vec<TokenStream2>| {
+            rules
+                .iter()
+                .map(|rule| rule.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            rules(garde_rules(&constraints)),
+            vec!["email", "length (chars , min = 3 , max = 64)"]
+        );
+        assert_eq!(
+            rules(validator_rules(&constraints)),
+            vec!["email", "length (min = 3 , max = 64)"]
+        );
+        assert!(garde_rules(&[Constraint::StringIs("uuid".to_string())]).is_empty());
+
+        let string = TypeAST::Scalar(ScalarType::String);
+        let strings = TypeAST::Array(Box::new((string.clone(), None)));
+        let attributes = validation_attributes(Some(&string), &constraints).to_string();
+        assert_eq!(
+            attributes.contains("# [garde (email , length (chars , min = 3 , max = 64))]"),
+            cfg!(feature = "garde")
+        );
+        assert_eq!(
+            attributes.contains("# [validate (email , length (min = 3 , max = 64))]"),
+            cfg!(feature = "validator")
+        );
+        let attributes = validation_attributes(Some(&strings), &constraints).to_string();
+        assert_eq!(
+            attributes.contains("# [garde (inner (email , length (chars , min = 3 , max = 64)))]"),
+            cfg!(feature = "garde")
+        );
+        assert!(!attributes.contains("validate"));
+        assert_eq!(
+            validation_attributes(None, &constraints).to_string(),
+            match cfg!(feature = "garde") {
+                true => "# [garde (skip)]",
+                false => "",
+            }
+        );
+    }
+
+    #[test]
+    fn constrained_type_names() {
+        let naming = TypeNaming::new(TypeNames::default(), ObjectType::default());
+        let mut generated_types = HashMap::new();
+        let ast = TypeAST::Scalar(ScalarType::String);
+        let email = [Constraint::StringIs("email".to_string())];
+        let mut name = |table: &str, owner: &str, field: &str, constraints: &[Constraint]| {
+            let owner = format_ident!("{}", owner);
+            generate_constrained_field(
+                &ast,
+                table,
+                &owner,
+                field,
+                constraints,
+                &naming,
+                &mut generated_types,
+            )
+            .map(|(ty, _)| ty.to_string())
+        };
+
+        // The same check is one type within a table, and a type of its own in every other.
+        assert_eq!(name("user", "User", "email", &email).unwrap(), "UserEmail");
+        assert_eq!(name("user", "User", "backup", &email).unwrap(), "UserEmail");
+        assert_eq!(name("org", "Org", "email", &email).unwrap(), "OrgEmail");
+        assert_eq!(
+            name("user", "User", "nick_name", &[Constraint::MaxLength(16)]).unwrap(),
+            "UserNickName"
+        );
+        assert!(generated_types.contains_key("OrgEmail"));
+        assert!(!generated_types.contains_key("Email"));
+    }
+
+    #[test]
+    fn rows_of_many_tables() {
+        let schema = surrealdb::sql::parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+            DEFINE TABLE post SCHEMAFULL;
+                DEFINE FIELD name ON post TYPE string;
+                DEFINE FIELD title ON post TYPE string;
+            "#,
+        )
+        .unwrap();
+        let TypeAST::Object(tables) = analyze_schema(schema.clone()).unwrap() else {
+            panic!("Expected the schema to be an object");
+        };
+        let naming = TypeNaming::new(TypeNames::default(), tables);
+
+        let generate = |query: &str| {
+            let query = surrealdb::sql::parse(query).unwrap();
+            let analyzed = analyze_tables(schema.clone(), query, &[]).unwrap();
+            let [StatementType::Fixed(ast)] = analyzed.as_slice() else {
+                panic!("Expected a single statement");
+            };
+            let (type_name, defs) = generate_type_definition(
+                ast,
+                &BuildQueryOptions::default(),
+                &naming,
+                None,
+                0,
+                &mut HashMap::new(),
+            );
+            (type_name.to_string(), quote! { #(#defs)* }.to_string())
+        };
+
+        // Rows that select their id are decoded by its table, as `decode` tests with such a row.
+        for query in [
+            "SELECT * FROM user, post",
+            "SELECT id, name FROM user, post",
+        ] {
+            let (type_name, defs) = generate(query);
+            assert_eq!(type_name, "Vec < UserOrPost >");
+            assert!(defs.contains("pub enum UserOrPost { User (User) , Post (Post) , }"));
+            assert!(defs.contains("Self :: Post (_) => \"post\""));
+            assert!(defs.contains("surrealix :: decode :: row_table (& row)"));
+            assert!(
+                defs.contains("\"post\" => serde_json :: from_value (row) . map (Self :: Post)")
+            );
+            assert!(defs.contains("# [derive (Debug , serde :: Serialize)]"));
+        }
+
+        // Without the id, rows with the same fields can't be told apart.
+        let (type_name, defs) = generate("SELECT name FROM user, post");
+        assert_eq!(type_name, "Vec < serde_json :: Value >");
+        assert!(!defs.contains("enum"));
+    }
+
+    #[test]
+    fn id_newtypes() {
+        let schema = surrealdb::sql::parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+            DEFINE TABLE post SCHEMAFULL;
+                DEFINE FIELD author ON post TYPE record<user>;
+            "#,
+        )
+        .unwrap();
+        let query = surrealdb::sql::parse("SELECT * FROM post FETCH author").unwrap();
+        let analyzed = analyze_tables(schema.clone(), query, &[]).unwrap();
+        let [StatementType::Fixed(ast)] = analyzed.as_slice() else {
+            panic!("Expected a single statement");
+        };
+        let TypeAST::Object(tables) = analyze_schema(schema).unwrap() else {
+            panic!("Expected the schema to be an object");
+        };
+        let naming = TypeNaming::new(TypeNames::default(), tables);
+
+        let (_, defs) = generate_type_definition(
+            ast,
+            &BuildQueryOptions::default(),
+            &naming,
+            None,
+            0,
+            &mut HashMap::new(),
+        );
+        let defs = quote! { #(#defs)* }.to_string();
+
+        // Each table's records get an id of their own, checked against the table when parsed.
+        assert!(defs.contains("pub struct PostId (pub surrealix :: RecordId < Post >) ;"));
+        assert!(defs.contains("pub struct UserId (pub surrealix :: RecordId < User >) ;"));
+        assert!(defs.contains("id : PostId"));
+        assert!(defs.contains("id : UserId"));
+        assert!(defs.contains("impl std :: str :: FromStr for UserId"));
+        assert!(defs.contains("surrealix :: RecordId :: parse_in (Self :: TABLE , s)"));
+        assert!(defs.contains("pub const TABLE : & 'static str = \"user\" ;"));
+    }
+
+    #[test]
+    fn dynamic_objects() {
+        let schema = surrealdb::sql::parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD settings ON user TYPE object;
+            "#,
+        )
+        .unwrap();
+        let query = surrealdb::sql::parse("SELECT settings FROM user").unwrap();
+        let analyzed = analyze_tables(schema, query, &[]).unwrap();
+        let [StatementType::Fixed(ast)] = analyzed.as_slice() else {
+            panic!("Expected a single statement");
+        };
+        let naming = TypeNaming::new(TypeNames::default(), ObjectType::default());
+        let defs = |options: &str| {
+            let OptionsInput(options) = syn::parse_str(options).unwrap();
+            let (_, defs) =
+                generate_type_definition(ast, &options, &naming, None, 0, &mut HashMap::new());
+            quote! { #(#defs)* }.to_string()
+        };
+
+        // Objects without defined keys keep the keys the database returns, in its order.
+        assert!(
+            defs("").contains("settings : surrealix :: IndexMap < String , serde_json :: Value >")
+        );
+        assert!(defs(r#"dynamic_objects = "btreemap""#).contains(
+            "settings : std :: collections :: BTreeMap < String , serde_json :: Value >"
+        ));
+        assert!(defs(r#"dynamic_objects = "json""#)
+            .contains("settings : serde_json :: Map < String , serde_json :: Value >"));
+        assert!(syn::parse_str::<OptionsInput>(r#"dynamic_objects = "vec""#).is_err());
+    }
+
+    #[test]
+    fn archive_wrappers() {
+        let wrapper = |ast: &TypeAST, newtype| archive_wrapper(ast, newtype).map(|w| w.to_string());
+        let string = TypeAST::Scalar(ScalarType::String);
+        let datetime = TypeAST::Scalar(ScalarType::Datetime);
+
+        // Types rkyv archives natively stay as they are, anything else is archived as JSON.
+        assert_eq!(wrapper(&string, false), None);
+        assert_eq!(
+            wrapper(&datetime, false).unwrap(),
+            "surrealix :: archive :: AsJson"
+        );
+        assert_eq!(
+            wrapper(&string, true).unwrap(),
+            "surrealix :: archive :: AsJson"
+        );
+        assert_eq!(
+            wrapper(
+                &TypeAST::Array(Box::new((TypeAST::Option(Box::new(datetime)), None))),
+                false
+            )
+            .unwrap(),
+            "surrealix :: rkyv :: with :: Map < surrealix :: rkyv :: with :: Map < \
+             surrealix :: archive :: AsJson > >"
+        );
+        assert_eq!(
+            wrapper(&TypeAST::Object(ObjectType::default()), false),
+            None
+        );
+        assert_eq!(
+            archive_attribute(&string, true).is_empty(),
+            !cfg!(feature = "rkyv")
+        );
+    }
+
+    #[test]
+    fn relation_methods() {
+        let schema = surrealdb::sql::parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+            DEFINE TABLE post SCHEMAFULL;
+                DEFINE FIELD title ON post TYPE string;
+            DEFINE TABLE wrote SCHEMAFULL;
+                DEFINE FIELD in ON wrote TYPE record<user>;
+                DEFINE FIELD out ON wrote TYPE record<post>;
+            "#,
+        )
+        .unwrap();
+        let TypeAST::Object(tables) = analyze_schema(schema).unwrap() else {
+            panic!("Expected the schema to be an object");
+        };
+        let naming = TypeNaming::new(TypeNames::default(), tables.clone());
+        let [wrote] = relations(&tables).try_into().unwrap();
+        let methods = |options: &BuildQueryOptions| {
+            let name = options.relation_name(&wrote.edge);
+            generate_relation_methods(&wrote, name, &tables, &naming)
+                .unwrap()
+                .to_string()
+        };
+
+        // Edge tables are named as they are, not pluralized.
+        let methods_of_wrote = methods(&BuildQueryOptions::default());
+        assert!(methods_of_wrote.contains("impl User"));
+        assert!(methods_of_wrote.contains("pub async fn wrote <"));
+        assert!(methods_of_wrote.contains("pub async fn add_wrote <"));
+        assert!(methods_of_wrote.contains("pub async fn remove_wrote <"));
+        assert!(!methods_of_wrote.contains("wrotes"));
+
+        let options = BuildQueryOptions {
+            relation_names: vec![("wrote".to_string(), "posts".to_string())],
+            ..Default::default()
+        };
+        let methods_of_posts = methods(&options);
+        assert!(methods_of_posts.contains("pub async fn posts <"));
+        assert!(methods_of_posts.contains("pub async fn add_posts <"));
+        assert!(methods_of_posts.contains("pub async fn remove_posts <"));
+        assert!(methods_of_posts.contains("SELECT * FROM $record->wrote->post"));
+    }
+
+    #[test]
+    fn reducer_tests_decode_sample_records() {
+        let schema = surrealdb::sql::parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD joined ON user TYPE datetime;
+                DEFINE FIELD best_friend ON user TYPE option<record<user>>;
+                DEFINE FIELD home ON user TYPE geometry<point>;
+                DEFINE FIELD phone ON user TYPE record<device>;
+            DEFINE TABLE device SCHEMAFULL;
+                DEFINE FIELD id ON device TYPE uuid;
+                DEFINE FIELD label ON device TYPE string;
+            "#,
+        )
+        .unwrap();
+        let tests = |query: &str| {
+            let analyzed =
+                analyze_tables(schema.clone(), surrealdb::sql::parse(query).unwrap(), &[]).unwrap();
+            let [StatementType::Live(TypeAST::Object(obj))] = analyzed.as_slice() else {
+                panic!("Expected a single LIVE SELECT");
+            };
+            generate_reduce_tests(
+                obj,
+                &format_ident!("User"),
+                &format_ident!("reduce"),
+                &BuildQueryOptions::default(),
+            )
+            .map(|tests| tests.to_string())
+        };
+
+        let tests = tests("LIVE SELECT id, name, joined, best_friend FROM user").unwrap();
+        assert!(tests.contains("mod reduce_tests"));
+        assert!(tests.contains("\"id\" : \"user:first\""));
+        assert!(tests.contains("\"id\" : \"user:second\""));
+        assert!(tests.contains("\"joined\" : \"1970-01-01T00:00:00Z\""));
+        assert!(tests.contains("\"best_friend\" : null"));
+        assert!(tests.contains("reduce (& mut state , Notification :: Delete (first))"));
+
+        // Fetched records get an id of their table, with a key of the kind its id newtype parses.
+        let tests = tests("LIVE SELECT id, phone FROM user FETCH phone").unwrap();
+        assert!(tests.contains(
+            "\"phone\" : { \"id\" : \"device:⟨00000000-0000-0000-0000-000000000001⟩\" , \"label\" : \"\" }"
+        ));
+
+        // A point has no obvious value to make up.
+        assert_eq!(tests("LIVE SELECT * FROM user"), None);
+    }
+
+    #[test]
+    fn schema_defaults_fail_deserialization() {
+        let schema = surrealdb::sql::parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD tags ON user TYPE array<string> DEFAULT ["new"];
+            "#,
+        )
+        .unwrap();
+        let query = surrealdb::sql::parse("SELECT name, tags FROM user").unwrap();
+        let analyzed = analyze_tables(schema, query, &[]).unwrap();
+        let [StatementType::Fixed(TypeAST::Array(rows))] = analyzed.as_slice() else {
+            panic!("Expected a single SELECT");
+        };
+        let OptionsInput(options) = syn::parse_str("lenient_defaults").unwrap();
+        let naming = TypeNaming::new(TypeNames::default(), ObjectType::default());
+
+        let (_, defs) =
+            generate_type_definition(&rows.0, &options, &naming, None, 0, &mut HashMap::new());
+        let defs = quote! { #(#defs)* }.to_string();
+        assert!(defs.contains("# [derive (Debug , serde :: Serialize ,)]"));
+        assert!(defs.contains("impl < 'de > serde :: Deserialize < 'de > for"));
+        assert!(defs.contains("# [serde (rename = \"tags\" , default)] tags : Option <"));
+        assert!(defs.contains("surrealix :: decode :: schema_default ("));
+        assert!(defs.contains("serde :: de :: Error :: custom"));
+    }
+
+    #[test]
+    fn upsert_many_updates_every_field() {
+        let schema = surrealdb::sql::parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD id ON user TYPE record<user>;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD age ON user TYPE int;
+            DEFINE TABLE log SCHEMAFULL;
+                DEFINE FIELD message ON log TYPE string;
+            "#,
+        )
+        .unwrap();
+        let TypeAST::Object(tables) = analyze_schema(schema).unwrap() else {
+            panic!("Expected the schema to be an object");
+        };
+        let naming = TypeNaming::new(TypeNames::default(), tables.clone());
+        let upsert = |table: &str| {
+            let TypeAST::Object(obj) = &tables.fields[table].ast else {
+                panic!("Expected {} to be an object", table);
+            };
+            generate_upsert_many(table, obj, &naming).map(|tokens| tokens.to_string())
+        };
+
+        let user = upsert("user").unwrap();
+        assert!(user.contains(
+            "INSERT INTO user $records ON DUPLICATE KEY UPDATE age = $input.age, name = $input.name"
+        ));
+        // Every record has an id, whether or not its table defines one.
+        assert!(upsert("log")
+            .unwrap()
+            .contains("INSERT INTO log $records ON DUPLICATE KEY UPDATE message = $input.message"));
+    }
+
+    #[test]
+    fn count_and_exists_queries() {
+        let schema = surrealdb::sql::parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD id ON user TYPE record<user>;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD archived ON user TYPE bool;
+            DEFINE TABLE log SCHEMAFULL;
+                DEFINE FIELD message ON log TYPE string;
+            "#,
+        )
+        .unwrap();
+        let mut options = BuildQueryOptions::default();
+        options
+            .scope_filters
+            .push(surrealix_core::scope::ScopeFilter {
+                table: Some("user".to_string()),
+                predicate: "archived = false".to_string(),
+            });
+        let TypeAST::Object(tables) = analyze_schema(schema.clone()).unwrap() else {
+            panic!("Expected the schema to be an object");
+        };
+        let naming = TypeNaming::new(TypeNames::default(), tables.clone());
+        let methods = |table: &str| {
+            let TypeAST::Object(obj) = &tables.fields[table].ast else {
+                panic!("Expected {} to be an object", table);
+            };
+            generate_count_and_exists(table, obj, &naming, &schema, &options)
+                .unwrap()
+                .to_string()
+        };
+
+        let user = methods("user");
+        assert!(user.contains(
+            "SELECT count() FROM ONLY user WHERE (archived = false) AND (($filter.archived = NONE \
+             OR archived = $filter.archived) AND ($filter.name = NONE OR name = $filter.name)) \
+             GROUP ALL LIMIT 1"
+        ));
+        assert!(user.contains("SELECT 1 FROM ONLY $record WHERE archived = false"));
+        let log = methods("log");
+        assert!(log.contains("pub async fn count"));
+        assert!(log.contains("pub async fn exists"));
+    }
+
+    #[test]
+    fn types_of_json_forms() {
+        let ty = |ast: &TypeAST, newtype| json_form_type(ast, newtype).map(|ty| ty.to_string());
+        let scalar = |scalar| TypeAST::Scalar(scalar);
+
+        assert_eq!(ty(&scalar(ScalarType::String), false), None);
+        assert_eq!(
+            ty(&scalar(ScalarType::String), true).as_deref(),
+            Some("String")
+        );
+        assert_eq!(
+            ty(
+                &TypeAST::Option(Box::new(scalar(ScalarType::Datetime))),
+                false
+            )
+            .as_deref(),
+            Some("Option < String >")
+        );
+        assert_eq!(
+            ty(
+                &TypeAST::Array(Box::new((TypeAST::Record("user".into()), None))),
+                false
+            )
+            .as_deref(),
+            Some("Vec < String >")
+        );
+    }
+
+    #[test]
+    fn handler_routes() {
+        assert_eq!(route_params("/users/{id}/posts/{n}"), vec!["id", "n"]);
+        assert_eq!(axum_route("/users/{id}/posts/{n}"), "/users/:id/posts/:n");
+
+        let schema = surrealdb::sql::parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD age ON user TYPE int;
+            "#,
+        )
+        .unwrap();
+        let schema_types = analyze_schema(schema.clone()).unwrap();
+        let naming = TypeNaming::new(TypeNames::default(), ObjectType::default());
+        let take = quote! { surrealix::execute::take(&mut response, 0usize)? };
+        let handler = |route, query: &str| {
+            let query = surrealdb::sql::parse(query).unwrap();
+            let count_params = count_params(&query);
+            let param_types = param_types(&schema_types, &query);
+            let param_constraints = param_constraints(&schema_types, &query);
+            generate_handler(
+                route,
+                Some(&quote! { Vec<User> }),
+                &query_params(&schema, &query),
+                |name| {
+                    path_param_type(
+                        name,
+                        &count_params,
+                        &param_types,
+                        &param_constraints,
+                        &naming,
+                        None,
+                    )
+                },
+                true,
+                &take,
+            )
+            .map(|handler| handler.to_string())
+        };
+
+        let user = handler(
+            "/users/{id}/{age}/{n}",
+            "SELECT * FROM user WHERE id = $id AND age > $age LIMIT $n;",
+        )
+        .unwrap();
+        assert!(user.contains("pub n : usize"));
+        assert!(user.contains("pub age : i64"));
+        assert!(user.contains(
+            "# [serde (serialize_with = \"surrealix::types::serialize_thing\")] \
+             pub id : surrealix :: RecordLink < User >"
+        ));
+        assert!(user.contains("db . query (QUERY) . bind (params)"));
+        assert!(user.contains("routing :: get (handler :: < C >)"));
+
+        let untyped = handler("/users/{id}", "SELECT * FROM $id;").unwrap();
+        assert!(untyped.contains("pub id : String"));
+        assert!(matches!(
+            handler("/users/{identity}", "SELECT * FROM $id;"),
+            Err(QueryBuilderError::UnknownRouteParam(param)) if param == "identity"
+        ));
+        assert!(matches!(
+            handler("/users/{name}", "SELECT * FROM user;"),
+            Err(QueryBuilderError::UnknownRouteParam(param)) if param == "name"
+        ));
+        assert!(matches!(
+            handler("/users", "SELECT * FROM user WHERE name = $name;"),
+            Err(QueryBuilderError::UnboundQueryParam(param)) if param == "name"
+        ));
+    }
+
+    #[test]
+    fn params_structs() {
+        let naming = TypeNaming::new(TypeNames::default(), ObjectType::default());
+        let types = HashMap::from([
+            ("name", TypeAST::Scalar(ScalarType::String)),
+            ("at", TypeAST::Scalar(ScalarType::Datetime)),
+            ("author", TypeAST::Record("user".to_string())),
+            (
+                "tags",
+                TypeAST::Array(Box::new((TypeAST::Scalar(ScalarType::String), None))),
+            ),
+        ]);
+        let params = generate_params_struct(
+            &["at", "author", "n", "name", "tags", "type"].map(String::from),
+            &["n".to_string()],
+            |name| param_type(types.get(name), &naming, None),
+        )
+        .unwrap()
+        .to_string();
+        assert!(params.contains("serde :: Serialize , serde :: Deserialize"));
+        assert!(params.contains("# [serde (rename = \"n\")] pub n : usize ,"));
+        assert!(params.contains("pub name : String ,"));
+        assert!(params.contains("pub tags : Vec < String > ,"));
+        assert!(params.contains("pub at : serde_json :: Value ,"));
+        assert!(params.contains("pub r#type : serde_json :: Value ,"));
+        assert!(params.contains(
+            "# [serde (serialize_with = \"surrealix::types::serialize_thing\")] \
+             pub author : surrealix :: RecordLink < User > ,"
+        ));
+        assert!(generate_params_struct(&[], &[], |_| unreachable!()).is_none());
+    }
+
+    #[test]
+    fn field_constants() {
+        let schema = surrealdb::sql::parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD userName ON user TYPE string;
+                DEFINE FIELD address ON user TYPE object;
+                    DEFINE FIELD address.city ON user TYPE string;
+            "#,
+        )
+        .unwrap();
+        let TypeAST::Object(tables) = analyze_schema(schema).unwrap() else {
+            panic!("Expected the schema to be an object");
+        };
+
+        let mut paths = vec![];
+        field_paths(&tables.fields["user"].ast, "", &mut paths);
+        paths.sort();
+        let constants: Vec<_> = paths
+            .iter()
+            .map(|path| constant_name(path).to_string())
+            .collect();
+
+        assert_eq!(paths, vec!["address", "address.city", "userName"]);
+        assert_eq!(constants, vec!["ADDRESS", "ADDRESS_CITY", "USER_NAME"]);
+
+        let paths = ["address.city", "address_city", "user_name", "userName"];
+        let (constants, errors) = unique_constants(&paths, "fields::user", constant_name);
+        let constants: Vec<_> = constants
+            .iter()
+            .map(|(constant, path)| (constant.to_string(), *path))
+            .collect();
+        assert_eq!(
+            constants,
+            vec![
+                ("ADDRESS_CITY".to_string(), "address.city"),
+                ("USER_NAME".to_string(), "user_name")
+            ]
+        );
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].to_string().contains(
+            "'address.city' and 'address_city' both map to `fields::user::ADDRESS_CITY`"
+        ));
+    }
+
+    #[test]
+    fn finds_deprecated_fields() {
+        let schema = surrealdb::sql::parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD nickname ON user TYPE string COMMENT "deprecated: use name";
+                DEFINE FIELD best_friend ON user TYPE record<user>;
+            "#,
+        )
+        .unwrap();
+        let query =
+            surrealdb::sql::parse("SELECT nickname, best_friend FROM user FETCH best_friend")
+                .unwrap();
+        let analyzed = surrealix_core::analyzer::analyze(schema, query).unwrap();
+
+        let mut fields = vec![];
+        deprecated_fields(&analyzed[0], "", &mut fields);
+        fields.sort();
+
+        assert_eq!(
+            fields,
+            vec![
+                ("best_friend.nickname".to_string(), "use name".to_string()),
+                ("nickname".to_string(), "use name".to_string()),
+            ]
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn any_field_name_generates_a_valid_struct(name in "\\PC*") {
+            let field_name = to_valid_rust_identifier(&name);
+            let item = quote! {
+                pub struct Result {
+                    #[serde(rename = #name)]
+                    pub #field_name: String,
+                }
+            };
+
+            let parsed = syn::parse2::<syn::ItemStruct>(item);
+            prop_assert!(parsed.is_ok(), "{:?} gave the field `{}`", name, field_name);
+        }
     }
 }
@@ -2,4 +2,5 @@ use proc_macro::TokenStream;
 use syn::parse_macro_input;
 
 pub(crate) mod generator;
+pub(crate) mod naming;
 pub(crate) mod parser;
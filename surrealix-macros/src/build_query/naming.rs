@@ -0,0 +1,198 @@
+//! Names of the generated types, shared by every generator so they agree on them.
+
+use std::env;
+
+use convert_case::{Case, Casing};
+use proc_macro2::Ident;
+use quote::format_ident;
+use surrealix_core::ast::{ObjectType, TypeAST};
+
+use super::parser::TypeNames;
+
+pub struct TypeNaming {
+    scheme: TypeNames,
+    /// The crate being compiled, in Pascal case, which `TypeNames::Qualified` prefixes names with.
+    prefix: String,
+    /// The full model of every table, which record links are named after.
+    tables: ObjectType,
+    /// Appended to every name, e.g. `V2` for types generated from a versioned schema.
+    suffix: String,
+}
+
+impl TypeNaming {
+    /// `tables` only matters to `TypeNames::Hash`, where a record link's name depends on the
+    /// fields of the linked table.
+    pub fn new(scheme: TypeNames, tables: ObjectType) -> Self {
+        let prefix = env::var("CARGO_CRATE_NAME")
+            .or_else(|_| env::var("CARGO_PKG_NAME"))
+            .unwrap_or_default()
+            .to_case(Case::Pascal);
+        Self {
+            scheme,
+            prefix,
+            tables,
+            suffix: String::new(),
+        }
+    }
+
+    /// Suffixes every name with the schema version, if there is one.
+    pub fn with_version(mut self, version: Option<u32>) -> Self {
+        self.suffix = version.map(|v| format!("V{}", v)).unwrap_or_default();
+        self
+    }
+
+    /// The name of a type derived from `base`, e.g. `user_address`.
+    ///
+    /// `shape` is the data the type holds, which `TypeNames::Hash` suffixes the name with a hash
+    /// of. Types that don't depend on a shape, like validating newtypes, keep their plain name.
+    pub fn name(&self, base: &str, shape: Option<&TypeAST>) -> Ident {
+        let name = format!("{}{}", base.to_case(Case::Pascal), self.suffix);
+        match (self.scheme, shape) {
+            (TypeNames::Path, _) => format_ident!("{}", name),
+            (TypeNames::Qualified, _) => format_ident!("{}{}", self.prefix, name),
+            (TypeNames::Hash, None) => format_ident!("{}", name),
+            (TypeNames::Hash, Some(shape)) => format_ident!("{}{:08x}", name, shape_hash(shape)),
+        }
+    }
+
+    /// The name of an object type, after the path of the fields within it.
+    ///
+    /// A record selected from a table is named after the table (`User`), and a nested object
+    /// after its path (`UserAddress`).
+    pub fn object(&self, obj: &ObjectType) -> Ident {
+        let path = obj
+            .fields
+            .values()
+            .next()
+            .map(|field| field.meta.original_path.clone())
+            .unwrap_or_else(|| vec!["Unknown".to_string()]);
+
+        let base = if path.len() > 1 {
+            if path[0] == path[1] {
+                // This is the root object, just use the table name
+                path[0].clone()
+            } else {
+                // For nested objects, use all segments except the last one
+                path[..path.len() - 1].join("_")
+            }
+        } else {
+            "Unknown".to_string()
+        };
+
+        self.name(&base, Some(&TypeAST::Object(obj.clone())))
+    }
+
+    /// The name of a table's full model, as record links to the table refer to it.
+    pub fn table(&self, table: &str) -> Ident {
+        let shape = self
+            .tables
+            .fields
+            .get(table)
+            .map(|table| &table.ast)
+            .filter(|ast| matches!(ast, TypeAST::Object(obj) if !obj.fields.is_empty()));
+        self.name(table, shape)
+    }
+}
+
+/// A hash of the fields and types within `ast`, stable across builds and compiler versions.
+///
+/// Field order doesn't matter, and record links only contribute their table, so a type's
+/// name doesn't depend on how the tables it links to are defined.
+fn shape_hash(ast: &TypeAST) -> u32 {
+    let mut shape = String::new();
+    describe_shape(ast, &mut shape);
+
+    // FNV-1a, as std's hashers may change between releases.
+    let hash = shape.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+    (hash ^ (hash >> 32)) as u32
+}
+
+fn describe_shape(ast: &TypeAST, shape: &mut String) {
+    match ast {
+        TypeAST::Scalar(scalar) => shape.push_str(&format!("{:?}", scalar)),
+        TypeAST::Object(obj) => {
+            let mut names: Vec<_> = obj.fields.keys().collect();
+            names.sort();
+            shape.push('{');
+            for name in names {
+                shape.push_str(&format!("{:?}:", name));
+                describe_shape(&obj.fields[name].ast, shape);
+                shape.push(',');
+            }
+            shape.push('}');
+        }
+        TypeAST::Array(inner) => {
+            shape.push('[');
+            describe_shape(&inner.0, shape);
+            if let Some(len) = inner.1 {
+                shape.push_str(&format!(";{}", len));
+            }
+            shape.push(']');
+        }
+        TypeAST::Option(inner) => {
+            shape.push('?');
+            describe_shape(inner, shape);
+        }
+        TypeAST::Record(table) => shape.push_str(&format!("record<{}>", table)),
+        TypeAST::Union(variants) => {
+            shape.push('(');
+            for variant in variants {
+                describe_shape(variant, shape);
+                shape.push('|');
+            }
+            shape.push(')');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealix_core::schema::analyze_schema;
+
+    #[test]
+    fn hashed_names_follow_the_shape() {
+        let schema = surrealdb::sql::parse(
+            r#"
+            DEFINE TABLE user SCHEMAFULL;
+                DEFINE FIELD name ON user TYPE string;
+                DEFINE FIELD age ON user TYPE number;
+            DEFINE TABLE admin SCHEMAFULL;
+                DEFINE FIELD name ON admin TYPE string;
+                DEFINE FIELD age ON admin TYPE string;
+            "#,
+        )
+        .unwrap();
+        let TypeAST::Object(tables) = analyze_schema(schema).unwrap() else {
+            panic!("Expected the schema to be an object");
+        };
+        let naming = TypeNaming::new(TypeNames::Hash, tables.clone());
+        let model = |table: &str| match &tables.fields[table].ast {
+            TypeAST::Object(obj) => obj.clone(),
+            _ => panic!("Expected {} to be an object", table),
+        };
+
+        let user = naming.object(&model("user"));
+        assert!(user.to_string().starts_with("User"));
+        assert_eq!(user, naming.table("user"));
+        assert_eq!(user, naming.object(&model("user")));
+
+        // The same path with different fields gets a different name.
+        let mut partial = model("user");
+        partial.fields.remove("age");
+        assert_ne!(naming.object(&partial), user);
+
+        // Types that don't hold a shape are named as usual.
+        assert_eq!(naming.name("email", None), "Email");
+
+        // Every name carries the schema version.
+        let versioned = TypeNaming::new(TypeNames::Path, tables.clone()).with_version(Some(2));
+        assert_eq!(versioned.table("user"), "UserV2");
+        assert_eq!(
+            TypeNaming::new(TypeNames::Path, tables).table("admin"),
+            "Admin"
+        );
+    }
+}
@@ -1,35 +1,276 @@
+use proc_macro2::Span;
+use surrealix_core::scope::ScopeFilter;
 use syn::{
+    bracketed, parenthesized,
     parse::{Parse, ParseStream},
-    Ident, LitStr, Result as SynResult, Token,
+    punctuated::Punctuated,
+    Error, Ident, Lit, LitStr, Path, Result as SynResult, Token,
 };
 
 pub struct BuildQueryInput {
     pub name: Ident,
     pub aliases: Vec<(Ident, String)>,
+    pub options: BuildQueryOptions,
     pub query: LitStr,
 }
 
+/// Codegen options supplied before the query string, either as a bare flag (`validate,`)
+/// or as a value (`name = 3,` / `name = [a, b],`).
+#[derive(Default)]
+pub struct BuildQueryOptions {
+    /// Generate validating newtypes for fields with recognized ASSERT constraints.
+    pub constrained_types: bool,
+    /// Document which selected fields are protected by non-FULL permissions.
+    pub permissions_report: bool,
+    /// Box nested objects more than this many levels below the result (`0` boxes all of them).
+    pub box_depth: Option<usize>,
+    /// Box nested objects with more than this many inline fields, counting their own nested objects.
+    pub box_size: Option<usize>,
+    /// The map type used for `TYPE object` fields that have no defined keys.
+    pub dynamic_objects: DynamicObjects,
+    /// Mark generated structs `#[non_exhaustive]`, so other crates can't rely on their exact fields.
+    pub non_exhaustive: bool,
+    /// Reject fields the query didn't select when decoding, rather than ignoring them.
+    pub deny_unknown_fields: bool,
+    /// The tables a `type::table($param)` in FROM may refer to, each becoming a variant of the result.
+    pub tables: Vec<String>,
+    /// The module holding the canonical table models from `table_models!()`, e.g. `"crate::models"`.
+    /// Fetched records and record links then use those models rather than generating their own.
+    pub shared_types: Option<Path>,
+    /// Leave out fields with `PERMISSIONS NONE` for select, which the database never returns.
+    pub drop_unselectable: bool,
+    /// Tables big enough that filtering them without an index deserves a warning.
+    pub large_tables: Vec<String>,
+    /// How generated types are named, so those of different queries or crates don't collide.
+    pub type_names: TypeNames,
+    /// Predicates added to SELECTs, e.g. `archived = false`. Filters from the invocation apply
+    /// to every table, those from the environment to a single one.
+    pub scope_filters: Vec<ScopeFilter>,
+    /// Route the query to read replicas even though it isn't only SELECTs, e.g. because it calls
+    /// a custom function that only reads. Queries of nothing but SELECTs are read-only anyway.
+    pub read_only: bool,
+    /// Fill in fields missing from a result with their schema DEFAULT, for records written
+    /// before the field was added. Only literal defaults can be filled in.
+    pub lenient_defaults: bool,
+    /// The version of the schema to generate types from, loaded from `SURREALIX_SCHEMA_PATH_V<n>`.
+    /// Generated names are suffixed with it (`UserV2`), so types of several versions can coexist.
+    pub schema_version: Option<u32>,
+    /// Allow statements that change the schema, like DEFINE or REMOVE, e.g. in admin tools.
+    /// Without it they are a compile error, so a query can't change the schema by accident.
+    pub allow_ddl: bool,
+    /// Accept `FETCH $fields`, typing every record link the SELECT returns as `Link<T>`, either
+    /// the link or the fetched record. Without it, a parameter in FETCH is a compile error.
+    pub lenient_fetch: bool,
+    /// Select `->edge->node` traversals with the edge records they cross, as
+    /// `Vec<EdgeAndNode<Edge, Node>>`, for relationship metadata such as when a friendship began.
+    pub with_edges: bool,
+    /// Generate `execute_page`, paging through the rows of a single SELECT by their id with a
+    /// `surrealix::Cursor`, rather than with LIMIT and START, which slow down deep into a table.
+    pub keyset: bool,
+    /// Fields passed through a `surrealix::FieldCodec` when (de)serialized, e.g. to encrypt them
+    /// client-side, from `encrypt(ssn, user.card) = "crate::Sealed"`.
+    pub codec_fields: Vec<CodecField>,
+    /// Generate an Axum handler serving the query's results as JSON at this route, e.g.
+    /// `"/users/{id}"`, whose path parameters are bound to the query parameters of the same name.
+    pub axum_route: Option<String>,
+    /// The names `table_models!()` gives the methods of edge tables in place of the tables' own,
+    /// e.g. `posts()`, `add_posts()` and `remove_posts()` for `wrote`, from
+    /// `relation(wrote) = "posts"`.
+    pub relation_names: Vec<(String, String)>,
+}
+
+/// A field marked with `encrypt(...)` or `transform(...)`, and the codec it passes through.
+pub struct CodecField {
+    /// The path of the field in its table, e.g. `address.city`, or prefixed with the table to
+    /// mark the field of that table only, e.g. `user.ssn`.
+    pub path: String,
+    pub codec: Path,
+    /// Where the path was written, for the error when it matches no field of the query.
+    pub span: Span,
+}
+
+impl CodecField {
+    /// Whether this marks a field coming from `source`, the table and path it was selected from.
+    pub fn matches(&self, source: &[String]) -> bool {
+        self.path == source.join(".") || self.path == source.get(1..).unwrap_or_default().join(".")
+    }
+}
+
+impl BuildQueryOptions {
+    /// The codec of a field coming from `source`, the table and path it was selected from.
+    pub fn codec_of(&self, source: &[String]) -> Option<&Path> {
+        self.codec_fields
+            .iter()
+            .find(|field| field.matches(source))
+            .map(|field| &field.codec)
+    }
+
+    /// The name of the methods traversing the edge table `edge`: the one given to it, or else
+    /// the table's own.
+    pub fn relation_name<'a>(&'a self, edge: &'a str) -> &'a str {
+        self.relation_names
+            .iter()
+            .find(|(table, _)| table == edge)
+            .map_or(edge, |(_, name)| name)
+    }
+}
+
+/// Map types available for objects with arbitrary keys, selected with `dynamic_objects = "..."`.
+#[derive(Default, Clone, Copy)]
+pub enum DynamicObjects {
+    /// `IndexMap`, keeping keys in the order the database returned them.
+    #[default]
+    IndexMap,
+    HashMap,
+    BTreeMap,
+    /// `serde_json::Map`, for passing the object on as JSON.
+    Json,
+}
+
+/// Naming schemes for generated types, selected with `type_names = "..."`.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum TypeNames {
+    /// Named after the path of the data, e.g. `UserAddress`.
+    #[default]
+    Path,
+    /// The path, prefixed with the crate's name, e.g. `MyAppUserAddress`.
+    Qualified,
+    /// The path, suffixed with a hash of the type's fields, e.g. `UserAddress1f2e3d4c`.
+    /// Types with the same name then always have the same fields.
+    Hash,
+}
+
+pub enum OptionValue {
+    Flag,
+    Lit(Lit),
+    List(Vec<Ident>),
+    /// `(a, b.c) = value`, for options applying to particular fields.
+    Fields(Vec<(String, Span)>, Lit),
+}
+
+impl BuildQueryOptions {
+    pub(crate) fn apply(&mut self, name: &Ident, value: OptionValue) -> SynResult<()> {
+        match (name.to_string().as_str(), value) {
+            ("constrained_types", OptionValue::Flag) => self.constrained_types = true,
+            ("permissions_report", OptionValue::Flag) => self.permissions_report = true,
+            ("non_exhaustive", OptionValue::Flag) => self.non_exhaustive = true,
+            ("deny_unknown_fields", OptionValue::Flag) => self.deny_unknown_fields = true,
+            ("drop_unselectable", OptionValue::Flag) => self.drop_unselectable = true,
+            ("read_only", OptionValue::Flag) => self.read_only = true,
+            ("lenient_defaults", OptionValue::Flag) => self.lenient_defaults = true,
+            ("allow_ddl", OptionValue::Flag) => self.allow_ddl = true,
+            ("lenient_fetch", OptionValue::Flag) => self.lenient_fetch = true,
+            ("with_edges", OptionValue::Flag) => self.with_edges = true,
+            ("keyset", OptionValue::Flag) => self.keyset = true,
+            ("box_depth", OptionValue::Lit(Lit::Int(n))) => {
+                self.box_depth = Some(n.base10_parse()?)
+            }
+            ("box_size", OptionValue::Lit(Lit::Int(n))) => self.box_size = Some(n.base10_parse()?),
+            ("schema_version", OptionValue::Lit(Lit::Int(n))) => {
+                self.schema_version = Some(n.base10_parse()?)
+            }
+            ("shared_types", OptionValue::Lit(Lit::Str(path))) => {
+                self.shared_types = Some(path.parse()?)
+            }
+            ("large_tables", OptionValue::List(tables)) => self
+                .large_tables
+                .extend(tables.iter().map(|table| table.to_string())),
+            ("tables", OptionValue::List(tables)) => {
+                self.tables = tables.iter().map(|table| table.to_string()).collect()
+            }
+            ("encrypt" | "transform", OptionValue::Fields(fields, Lit::Str(codec))) => {
+                let codec: Path = codec.parse()?;
+                self.codec_fields
+                    .extend(fields.into_iter().map(|(path, span)| CodecField {
+                        path,
+                        codec: codec.clone(),
+                        span,
+                    }))
+            }
+            ("relation", OptionValue::Fields(tables, Lit::Str(name))) => {
+                for (table, span) in tables {
+                    if table.contains('.') {
+                        return Err(Error::new(span, "Expected the name of an edge table"));
+                    }
+                    self.relation_names.push((table, name.value()));
+                }
+            }
+            ("axum", OptionValue::Lit(Lit::Str(route))) => {
+                if !cfg!(feature = "axum") {
+                    return Err(Error::new(
+                        route.span(),
+                        "Generating handlers requires surrealix's \"axum\" feature",
+                    ));
+                }
+                if !route.value().starts_with('/') {
+                    return Err(Error::new(
+                        route.span(),
+                        "Expected a route starting with '/'",
+                    ));
+                }
+                self.axum_route = Some(route.value())
+            }
+            ("dynamic_objects", OptionValue::Lit(Lit::Str(kind))) => {
+                self.dynamic_objects =
+                    match kind.value().as_str() {
+                        "indexmap" => DynamicObjects::IndexMap,
+                        "hashmap" => DynamicObjects::HashMap,
+                        "btreemap" => DynamicObjects::BTreeMap,
+                        "json" => DynamicObjects::Json,
+                        _ => return Err(Error::new(
+                            kind.span(),
+                            "Expected one of \"indexmap\", \"hashmap\", \"btreemap\" or \"json\"",
+                        )),
+                    }
+            }
+            ("scope_filter", OptionValue::Lit(Lit::Str(predicate))) => {
+                self.scope_filters.push(ScopeFilter {
+                    table: None,
+                    predicate: predicate.value(),
+                })
+            }
+            ("type_names", OptionValue::Lit(Lit::Str(scheme))) => {
+                self.type_names = match scheme.value().as_str() {
+                    "path" => TypeNames::Path,
+                    "qualified" => TypeNames::Qualified,
+                    "hash" => TypeNames::Hash,
+                    _ => {
+                        return Err(Error::new(
+                            scheme.span(),
+                            "Expected one of \"path\", \"qualified\" or \"hash\"",
+                        ))
+                    }
+                }
+            }
+            _ => {
+                return Err(Error::new(
+                    name.span(),
+                    format!(
+                        "Unknown option '{}', or it was given the wrong kind of value",
+                        name
+                    ),
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Parse for BuildQueryInput {
     fn parse(input: ParseStream) -> SynResult<Self> {
         let name: Ident = input.parse()?;
         input.parse::<Token![,]>()?;
 
         let mut aliases = Vec::new();
+        let mut options = BuildQueryOptions::default();
         while !input.peek(LitStr) {
-            let alias: Ident = input.parse()?;
-            input.parse::<Token![=>]>()?;
-            let mut path = String::new();
-            loop {
-                let ident: Ident = input.parse()?;
-                path.push_str(&ident.to_string());
-                if input.peek(Token![.]) {
-                    input.parse::<Token![.]>()?;
-                    path.push('.');
-                } else {
-                    break;
-                }
+            let ident: Ident = input.parse()?;
+            if input.peek(Token![=>]) {
+                input.parse::<Token![=>]>()?;
+                aliases.push((ident, parse_path(input)?));
+            } else {
+                options.apply(&ident, parse_option_value(input)?)?;
             }
-            aliases.push((alias, path));
             input.parse::<Token![,]>()?;
         }
 
@@ -38,11 +279,75 @@ impl Parse for BuildQueryInput {
         Ok(BuildQueryInput {
             name,
             aliases,
+            options,
             query,
         })
     }
 }
 
+/// The options of a macro that takes nothing else, e.g. `table_models!(schema_version = 2)`.
+pub struct OptionsInput(pub BuildQueryOptions);
+
+impl Parse for OptionsInput {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let mut options = BuildQueryOptions::default();
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            options.apply(&ident, parse_option_value(input)?)?;
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(OptionsInput(options))
+    }
+}
+
+/// The value after an option's name: `= 3`, `= [a, b]`, `(a, b.c) = value`, or nothing for
+/// a flag.
+fn parse_option_value(input: ParseStream) -> SynResult<OptionValue> {
+    if input.peek(syn::token::Paren) {
+        let content;
+        parenthesized!(content in input);
+        let fields =
+            Punctuated::<(String, Span), Token![,]>::parse_terminated_with(&content, |input| {
+                let span = input.span();
+                Ok((parse_path(input)?, span))
+            })?;
+        input.parse::<Token![=]>()?;
+        return Ok(OptionValue::Fields(
+            fields.into_iter().collect(),
+            input.parse()?,
+        ));
+    }
+    if !input.peek(Token![=]) {
+        return Ok(OptionValue::Flag);
+    }
+    input.parse::<Token![=]>()?;
+    if input.peek(syn::token::Bracket) {
+        let content;
+        bracketed!(content in input);
+        let list = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+        Ok(OptionValue::List(list.into_iter().collect()))
+    } else {
+        Ok(OptionValue::Lit(input.parse()?))
+    }
+}
+
+fn parse_path(input: ParseStream) -> SynResult<String> {
+    let mut path = String::new();
+    loop {
+        let ident: Ident = input.parse()?;
+        path.push_str(&ident.to_string());
+        if input.peek(Token![.]) {
+            input.parse::<Token![.]>()?;
+            path.push('.');
+        } else {
+            break;
+        }
+    }
+    Ok(path)
+}
+
 // #[proc_macro]
 // pub fn build_query(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 //     let BuildQueryInput {
@@ -0,0 +1,77 @@
+use std::env;
+
+use proc_macro2::Span;
+use syn::{Error, Ident, Lit, LitStr, Result as SynResult};
+
+use surrealix_core::scope::ScopeFilter;
+
+use crate::build_query::parser::{BuildQueryOptions, OptionValue};
+
+/// Flag options applied to every invocation, e.g. `SURREALIX_CODEGEN_FLAGS=non_exhaustive`.
+///
+/// Like the schema path, this is read from the project's `.env`, so the schema must be loaded first.
+const CODEGEN_FLAGS: &str = "SURREALIX_CODEGEN_FLAGS";
+
+/// Tables that every invocation treats as `large_tables`, e.g. `SURREALIX_LARGE_TABLES=event,log`.
+const LARGE_TABLES: &str = "SURREALIX_LARGE_TABLES";
+
+/// The `type_names` scheme of every invocation, e.g. `SURREALIX_TYPE_NAMES=hash`.
+///
+/// `table_models!()` and the queries using its models must agree on names, so this is best set
+/// here, where it also takes precedence over the option of a single invocation.
+const TYPE_NAMES: &str = "SURREALIX_TYPE_NAMES";
+
+/// Prefix of the scope filter for a table, e.g. `SURREALIX_SCOPE_FILTER_USER=archived = false`.
+const SCOPE_FILTER_PREFIX: &str = "SURREALIX_SCOPE_FILTER_";
+
+pub fn apply_global_options(options: &mut BuildQueryOptions) -> SynResult<()> {
+    for flag in env_list(CODEGEN_FLAGS)? {
+        options.apply(&flag, OptionValue::Flag)?;
+    }
+
+    let tables = env_list(LARGE_TABLES)?;
+    if !tables.is_empty() {
+        let name = Ident::new("large_tables", Span::call_site());
+        options.apply(&name, OptionValue::List(tables))?;
+    }
+
+    if let Ok(scheme) = env::var(TYPE_NAMES) {
+        let name = Ident::new("type_names", Span::call_site());
+        let scheme = LitStr::new(scheme.trim(), Span::call_site());
+        options.apply(&name, OptionValue::Lit(Lit::Str(scheme)))?;
+    }
+
+    let mut scope_filters: Vec<_> = env::vars()
+        .filter_map(|(var, predicate)| {
+            let table = var.strip_prefix(SCOPE_FILTER_PREFIX)?.to_lowercase();
+            Some(ScopeFilter {
+                table: Some(table),
+                predicate,
+            })
+        })
+        .collect();
+    // The environment has no order, but the generated query should be the same on every build.
+    scope_filters.sort_by(|a, b| a.table.cmp(&b.table));
+    options.scope_filters.extend(scope_filters);
+    Ok(())
+}
+
+/// Reads a comma-separated list of identifiers from the environment.
+fn env_list(var: &str) -> SynResult<Vec<Ident>> {
+    let Ok(list) = env::var(var) else {
+        return Ok(vec![]);
+    };
+
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            syn::parse_str::<Ident>(item).map_err(|_| {
+                Error::new(
+                    Span::call_site(),
+                    format!("{} contains an invalid name: '{}'", var, item),
+                )
+            })
+        })
+        .collect()
+}
@@ -1,2 +1,4 @@
+pub(crate) mod config;
+pub(crate) mod registry;
 pub(crate) mod schema_loader;
 pub(crate) mod type_checker;
@@ -0,0 +1,182 @@
+use std::{
+    collections::BTreeSet,
+    env, fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use surrealdb::sql::{parse, Query};
+use surrealix_core::canonical::query_hash;
+
+/// The directory `build_query!` records its queries in, relative to the crate, e.g.
+/// `SURREALIX_QUERY_REGISTRY=.surrealix`. Tooling such as `surrealix unused` reads it back.
+///
+/// Like the schema path, this is read from the project's `.env`, so the schema must be loaded first.
+const QUERY_REGISTRY: &str = "SURREALIX_QUERY_REGISTRY";
+
+/// The directory within the registry listing the queries each target recorded when it last compiled.
+const TARGETS: &str = "targets";
+
+/// The queries recorded by this compilation, as the macros of a crate all run in one rustc process.
+static RECORDED: Mutex<BTreeSet<u64>> = Mutex::new(BTreeSet::new());
+
+/// Records a query in the registry, if one is configured.
+///
+/// Each query is stored by the hash of its canonical form, so recording it again on every build,
+/// or recording a query that only differs in formatting, is a no-op. Every query is recorded again
+/// whenever its crate compiles, so the first query a compilation records evicts the entries no
+/// target recorded when it last compiled, which are those of removed queries.
+pub fn record_query(schema: &Query, query: &str) -> io::Result<()> {
+    let (Ok(registry), Ok(manifest_dir)) =
+        (env::var(QUERY_REGISTRY), env::var("CARGO_MANIFEST_DIR"))
+    else {
+        return Ok(());
+    };
+
+    // Queries that don't parse or analyze fail to build, so there's nothing to record.
+    let Some(hash) = parse(query)
+        .ok()
+        .and_then(|parsed| query_hash(schema, &parsed).ok())
+    else {
+        return Ok(());
+    };
+
+    let dir = PathBuf::from(manifest_dir).join(registry.trim_start_matches("./"));
+    let mut recorded = RECORDED.lock().unwrap_or_else(PoisonError::into_inner);
+    if recorded.is_empty() {
+        evict_removed(&dir)?;
+    }
+    if !recorded.insert(hash) {
+        return Ok(());
+    }
+
+    // The target lists the query before it is written, so no other target evicts it meanwhile.
+    let targets = dir.join(TARGETS);
+    fs::create_dir_all(&targets)?;
+    let listed: String = recorded
+        .iter()
+        .map(|hash| format!("{:016x}\n", hash))
+        .collect();
+    let staged = targets.join(format!("{}.tmp", target()));
+    fs::write(&staged, listed)?;
+    fs::rename(staged, targets.join(target()))?;
+
+    let path = dir.join(format!("query-{:016x}.surql", hash));
+    if path.exists() {
+        return Ok(());
+    }
+    fs::write(path, query)
+}
+
+/// Removes the entries of `dir` that no target lists.
+///
+/// Registries from before targets were listed are left as they are, rather than emptied.
+fn evict_removed(dir: &Path) -> io::Result<()> {
+    // The entries are read before the targets, which list each query before it is written.
+    let entries: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter_map(|path| entry_hash(&path).map(|hash| (path, hash)))
+            .collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    let targets = match fs::read_dir(dir.join(TARGETS)) {
+        Ok(targets) => targets,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let mut listed = None;
+    for target in targets {
+        let path = target?.path();
+        if path.extension().map_or(false, |ext| ext == "tmp") {
+            continue;
+        }
+        let hashes = fs::read_to_string(path)?;
+        listed.get_or_insert_with(BTreeSet::new).extend(
+            hashes
+                .lines()
+                .filter_map(|hash| u64::from_str_radix(hash, 16).ok()),
+        );
+    }
+    let Some(listed) = listed else {
+        return Ok(());
+    };
+
+    for (path, hash) in entries {
+        if listed.contains(&hash) {
+            continue;
+        }
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// The hash of a registry entry, e.g. `query-00000000000000ff.surql`.
+fn entry_hash(path: &Path) -> Option<u64> {
+    let name = path.file_name()?.to_str()?;
+    let hash = name.strip_prefix("query-")?.strip_suffix(".surql")?;
+    u64::from_str_radix(hash, 16).ok()
+}
+
+/// The target being compiled, as a binary can share its crate name with the library, and the
+/// library's unit tests record more queries than the library itself.
+fn target() -> String {
+    let mut target = env::var("CARGO_CRATE_NAME").unwrap_or_default();
+    if env::var_os("CARGO_BIN_NAME").is_some() {
+        target.push_str("-bin");
+    }
+    // Macros run within rustc, whose arguments say whether the crate is compiled as tests.
+    if env::args().any(|arg| arg == "--test") {
+        target.push_str("-test");
+    }
+    target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_unlisted_entries() {
+        let dir = env::temp_dir().join(format!("surrealix-registry-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join(TARGETS)).unwrap();
+        for hash in [1u64, 2, 3] {
+            fs::write(
+                dir.join(format!("query-{:016x}.surql", hash)),
+                "SELECT * FROM user",
+            )
+            .unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        // Without any listed target, the registry predates them and is kept whole.
+        evict_removed(&dir).unwrap();
+        assert!(dir.join(format!("query-{:016x}.surql", 2)).exists());
+
+        fs::write(dir.join(TARGETS).join("app"), format!("{:016x}\n", 1)).unwrap();
+        fs::write(dir.join(TARGETS).join("app-test"), format!("{:016x}\n", 3)).unwrap();
+        evict_removed(&dir).unwrap();
+
+        let mut remaining: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        remaining.sort();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            remaining,
+            vec![
+                "notes.txt",
+                "query-0000000000000001.surql",
+                "query-0000000000000003.surql",
+                "targets"
+            ]
+        );
+    }
+}
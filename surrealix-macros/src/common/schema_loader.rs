@@ -1,5 +1,9 @@
 use std::{env, path::PathBuf};
-use surrealix_core::errors::SchemaError;
+use surrealix_core::{
+    errors::SchemaError,
+    mixin::expand_mixins,
+    source::{embedded_schema, SCHEMA_PATH_VAR},
+};
 use thiserror::Error;
 
 fn load_env() -> Result<(), SchemaError> {
@@ -12,12 +16,24 @@ fn load_env() -> Result<(), SchemaError> {
     Ok(())
 }
 
-pub fn load_schema() -> Result<String, SchemaError> {
-    load_env()?;
+/// Loads the schema from `SURREALIX_SCHEMA_PATH`, or for a `schema_version` from the path
+/// configured for that version, e.g. `SURREALIX_SCHEMA_PATH_V2`, with its mixins expanded.
+///
+/// A schema embedded in `SURREALIX_SCHEMA` or `SURREALIX_SCHEMA_BASE64` takes precedence over
+/// the file, so builds without access to it can still be type checked.
+pub fn load_schema(version: Option<u32>) -> Result<String, SchemaError> {
+    // A missing `.env` is fine when the schema is embedded in the environment instead.
+    let loaded_env = load_env();
 
+    let suffix = version.map_or(String::new(), |version| format!("_V{}", version));
+    if let Some(schema) = embedded_schema(&suffix, |var| env::var(var).ok())? {
+        return Ok(expand_mixins(&schema)?);
+    }
+    loaded_env?;
+
+    let var = format!("{}{}", SCHEMA_PATH_VAR, suffix);
     // Fallback to schema file in debug mode, or primary method in release mode
-    let path = env::var("SURREALIX_SCHEMA_PATH")
-        .map_err(|_| SchemaError::EnvVarNotSet("SURREALIX_SCHEMA_PATH".to_string()))?;
+    let path = env::var(&var).map_err(|_| SchemaError::EnvVarNotSet(var))?;
 
     let path = if path.starts_with("./") || !path.starts_with('/') {
         let manifest_dir = env::var("CARGO_MANIFEST_DIR")
@@ -29,5 +45,6 @@ pub fn load_schema() -> Result<String, SchemaError> {
         PathBuf::from(path)
     };
 
-    std::fs::read_to_string(path).map_err(SchemaError::FileReadError)
+    let schema = std::fs::read_to_string(path).map_err(SchemaError::FileReadError)?;
+    Ok(expand_mixins(&schema)?)
 }
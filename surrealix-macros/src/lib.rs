@@ -9,25 +9,87 @@ mod query;
 
 #[proc_macro]
 pub fn build_query(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as build_query::parser::BuildQueryInput);
+    let mut input = parse_macro_input!(input as build_query::parser::BuildQueryInput);
 
-    let schema = match common::schema_loader::load_schema() {
+    let parsed_schema = match load_schema(&mut input.options) {
         Ok(schema) => schema,
-        Err(e) => {
-            return syn::Error::new(proc_macro2::Span::call_site(), e.to_string())
-                .to_compile_error()
-                .into()
-        }
+        Err(e) => return e.to_compile_error().into(),
     };
 
-    let Ok(parsed_schema) = surrealdb::sql::parse(&schema) else {
-        //We know its an error so this unwrap is okay.
-        let error = surrealdb::sql::parse(&schema).err().unwrap();
-
-        return syn::Error::new(proc_macro2::Span::call_site(), error.to_string())
+    let query_span = input.query.span();
+    if let Err(e) = common::registry::record_query(&parsed_schema, &input.query.value()) {
+        let message = format!("Failed to record the query in the registry: {}", e);
+        return syn::Error::new(query_span, message)
             .to_compile_error()
             .into();
+    }
+
+    build_query::generator::generate_code(input, parsed_schema).unwrap_or_else(|e| match e {
+        build_query::generator::QueryBuilderError::UnknownCodecField(errors) => {
+            errors.to_compile_error().into()
+        }
+        e => syn::Error::new(query_span, e).to_compile_error().into(),
+    })
+}
+
+/// Generates a model for every table in the schema, for `build_query!` invocations with
+/// `shared_types` to share.
+///
+/// The models use the global options, so every invocation agrees on their shape. Options can
+/// also be given directly, e.g. `table_models!(schema_version = 2)` for the models of another
+/// schema version, which `build_query!` invocations with the same version refer to.
+///
+/// Models with an `id` also get async methods for each edge table starting from them, e.g.
+/// `user.friend(&db)`, `user.add_friend(&db, other, content)` and
+/// `user.remove_friend(&db, other)` for `friend: user -> user`. The methods are named after the
+/// edge table, or as the `relation` option names them, e.g. `relation(friend) = "friends"`.
+///
+/// Models of tables with fields get `User::count(&db, filter)`, counting the records whose fields
+/// equal those set in `filter`, and models with an `id` get `User::exists(&db, id)`.
+#[proc_macro]
+pub fn table_models(input: TokenStream) -> TokenStream {
+    let build_query::parser::OptionsInput(mut options) =
+        parse_macro_input!(input as build_query::parser::OptionsInput);
+
+    let parsed_schema = match load_schema(&mut options) {
+        Ok(schema) => schema,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    build_query::generator::generate_table_models(parsed_schema, &options).unwrap_or_else(|e| {
+        syn::Error::new(proc_macro2::Span::call_site(), e)
+            .to_compile_error()
+            .into()
+    })
+}
+
+/// Generates a `fields` module holding the name of every table and field in the schema, e.g.
+/// `fields::user::ADDRESS_CITY`, for building queries and ORDER BY clauses at runtime.
+#[proc_macro]
+pub fn schema_fields(_input: TokenStream) -> TokenStream {
+    let mut options = build_query::parser::BuildQueryOptions::default();
+
+    let parsed_schema = match load_schema(&mut options) {
+        Ok(schema) => schema,
+        Err(e) => return e.to_compile_error().into(),
     };
 
-    build_query::generator::generate_code(input, parsed_schema).unwrap()
+    build_query::generator::generate_field_constants(parsed_schema).unwrap_or_else(|e| {
+        syn::Error::new(proc_macro2::Span::call_site(), e)
+            .to_compile_error()
+            .into()
+    })
+}
+
+/// Loads and parses the schema, then applies the global options from the same `.env`.
+fn load_schema(
+    options: &mut build_query::parser::BuildQueryOptions,
+) -> syn::Result<surrealdb::sql::Query> {
+    let schema = common::schema_loader::load_schema(options.schema_version)
+        .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e.to_string()))?;
+
+    common::config::apply_global_options(options)?;
+
+    surrealdb::sql::parse(&schema)
+        .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e.to_string()))
 }